@@ -0,0 +1,110 @@
+/*!
+ * Cost of reporting events on the calling thread — `cargo bench -p hawk`.
+ *
+ * Events go to an in-process collector through the real worker and
+ * transport; the queue is flushed between batches, outside the measured
+ * time, so that no event is dropped for lack of room. Figures measured
+ * on one machine, and how to compare a change with its base, are in the
+ * documentation of `hawk::estimated_overhead()`.
+ */
+
+use std::hint::black_box;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use hawk_test::MockCollector;
+
+/// Events sent between two flushes — well within the queue's capacity.
+const BATCH: u64 = 50;
+
+/// Token of the benchmark client: integration ID `bench`.
+const TOKEN: &str = "eyJpbnRlZ3JhdGlvbklkIjoiYmVuY2giLCJzZWNyZXQiOiJzIn0=";
+
+/**
+ * Initializes the SDK once, against an in-process collector, with panics
+ * captured but not printed.
+ */
+fn collector() -> &'static MockCollector {
+    static COLLECTOR: OnceLock<MockCollector> = OnceLock::new();
+
+    COLLECTOR.get_or_init(|| {
+        let collector = MockCollector::start().expect("failed to start the collector");
+        std::panic::set_hook(Box::new(|_| {}));
+        std::mem::forget(hawk::init(hawk::Options {
+            token: TOKEN.into(),
+            collector_endpoint: Some(collector.endpoint()),
+            ..Default::default()
+        }));
+        collector
+    })
+}
+
+/**
+ * Times `iters` calls of `report` in batches, flushing in between.
+ */
+fn measure(iters: u64, mut report: impl FnMut()) -> Duration {
+    let collector = collector();
+    let mut total = Duration::ZERO;
+    let mut left = iters;
+    while left > 0 {
+        let batch = left.min(BATCH);
+        let started = Instant::now();
+        for _ in 0..batch {
+            report();
+        }
+        total += started.elapsed();
+        left -= batch;
+
+        hawk::flush();
+        collector.clear();
+    }
+    total
+}
+
+/// Recurses `depth` frames deep before calling `f`, for a realistic stack.
+#[inline(never)]
+fn nested(depth: usize, f: &mut dyn FnMut()) {
+    if depth == 0 {
+        f();
+    } else {
+        nested(black_box(depth - 1), f);
+    }
+}
+
+fn bench_capture(c: &mut Criterion) {
+    collector();
+
+    c.bench_function("send", |b| {
+        b.iter_custom(|iters| measure(iters, || hawk::send("payment declined")))
+    });
+
+    c.bench_function("send/50 frames", |b| {
+        b.iter_custom(|iters| measure(iters, || nested(50, &mut || hawk::send("payment declined"))))
+    });
+
+    c.bench_function("capture!", |b| {
+        b.iter_custom(|iters| {
+            measure(iters, || {
+                let order = black_box("A-17");
+                hawk::capture!(hawk::Level::Warn, "payment declined for {}", order);
+            })
+        })
+    });
+
+    c.bench_function("capture_error", |b| {
+        let error = "abc".parse::<i32>().unwrap_err();
+        b.iter_custom(|iters| measure(iters, || hawk::capture_error(&error)))
+    });
+
+    c.bench_function("panic hook", |b| {
+        b.iter_custom(|iters| {
+            measure(iters, || {
+                let _ = std::panic::catch_unwind(|| panic!("payment declined"));
+            })
+        })
+    });
+}
+
+criterion_group!(benches, bench_capture);
+criterion_main!(benches);
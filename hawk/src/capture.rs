@@ -0,0 +1,195 @@
+/*!
+ * Formatted events — `hawk::capture!()`.
+ *
+ * `capture!(Level::Warn, "failed to sync {} items for {}", n, user_id)`
+ * sends an event titled with the formatted message, of type `"warning"`.
+ * The call site is the top frame of its backtrace, and every formatting
+ * argument is kept, structured, under `extra` in the context, keyed by its
+ * source text: `{"extra": {"n": 3, "user_id": "u-42"}}`.
+ *
+ * Arguments are kept as JSON when they implement `Serialize`, as their
+ * `Display` text otherwise, then as their `Debug` text. Only positional
+ * arguments are supported; variables captured inline (`"{n} items"`) are
+ * formatted but not kept.
+ */
+
+use std::fmt::{Debug, Display};
+use std::panic::Location;
+
+use hawk_core::{EventData, Level, CATCHER_VERSION};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+/**
+ * Sends an event with a formatted title, keeping the formatting arguments
+ * as structured context.
+ *
+ * ```ignore
+ * hawk::capture!(hawk::Level::Warn, "failed to sync {} items for {}", n, user_id);
+ * ```
+ */
+#[macro_export]
+macro_rules! capture {
+    /*
+     * Binds each argument once, in order. Every step of the recursion is
+     * its own expansion, so each `__hawk_arg` is a distinct variable.
+     */
+    (@bind ($level:expr, $fmt:literal) [$($name:expr => $bound:ident)*] $arg:expr, $($rest:expr,)*) => {{
+        let __hawk_arg = &$arg;
+        $crate::capture!(@bind ($level, $fmt) [$($name => $bound)* ::std::stringify!($arg) => __hawk_arg] $($rest,)*)
+    }};
+    (@bind ($level:expr, $fmt:literal) [$($name:expr => $bound:ident)*]) => {{
+        #[allow(unused_imports)]
+        use $crate::__private::{DebugExtra as _, DisplayExtra as _, SerializeExtra as _};
+        if $crate::accepts_events() {
+            $crate::__private::capture_message(
+                $level,
+                ::std::format!($fmt $(, $bound)*),
+                ::std::vec![$(($name, (&&&$crate::__private::Extra($bound)).hawk_extra())),*],
+            )
+        }
+    }};
+    ($level:expr, $fmt:literal $(, $arg:expr)* $(,)?) => {
+        $crate::capture!(@bind ($level, $fmt) [] $($arg,)*)
+    };
+}
+
+// ---------------------------------------------------------------------------
+// Argument conversion
+// ---------------------------------------------------------------------------
+
+/*
+ * `capture!` calls `(&&&Extra(arg)).hawk_extra()`: method lookup tries
+ * `SerializeExtra` first, then `DisplayExtra` and `DebugExtra`, each one
+ * reference further away.
+ */
+
+/// A formatting argument of `capture!`.
+pub struct Extra<'a, T: ?Sized>(pub &'a T);
+
+/// Arguments kept as JSON.
+pub trait SerializeExtra {
+    fn hawk_extra(&self) -> Value;
+}
+
+impl<T: Serialize + ?Sized> SerializeExtra for &&Extra<'_, T> {
+    fn hawk_extra(&self) -> Value {
+        serde_json::to_value(self.0).unwrap_or(Value::Null)
+    }
+}
+
+/// Arguments kept as their `Display` text.
+pub trait DisplayExtra {
+    fn hawk_extra(&self) -> Value;
+}
+
+impl<T: Display + ?Sized> DisplayExtra for &Extra<'_, T> {
+    fn hawk_extra(&self) -> Value {
+        Value::String(self.0.to_string())
+    }
+}
+
+/// Arguments kept as their `Debug` text.
+pub trait DebugExtra {
+    fn hawk_extra(&self) -> Value;
+}
+
+impl<T: Debug + ?Sized> DebugExtra for Extra<'_, T> {
+    fn hawk_extra(&self) -> Value {
+        Value::String(format!("{:?}", self.0))
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Sending
+// ---------------------------------------------------------------------------
+
+/**
+ * Sends the event of a `capture!` call, with the caller's location as the
+ * top frame.
+ */
+#[track_caller]
+pub fn capture_message(level: Level, title: String, extra: Vec<(&'static str, Value)>) {
+    if !hawk_core::accepts_events() {
+        return;
+    }
+
+    let context = (!extra.is_empty()).then(|| {
+        let extra: Map<String, Value> = extra
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect();
+        serde_json::json!({ "extra": extra })
+    });
+
+    let event = EventData {
+        title,
+        event_type: Some(level.as_str().to_string()),
+        backtrace: Some(vec![hawk_core::frame_from_location(Location::caller())]),
+        context,
+        breadcrumbs: None,
+        addons: None,
+        fingerprint: None,
+        user: None,
+        catcher_version: CATCHER_VERSION.into(),
+    };
+    hawk_core::capture_event_with_backtrace(event, hawk_core::Backtrace::new_unresolved());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    struct Opaque;
+
+    impl Debug for Opaque {
+        fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+            f.write_str("Opaque")
+        }
+    }
+
+    /**
+     * Verifies the title, level, call-site frame, and that arguments are
+     * evaluated once and kept in the richest available form.
+     */
+    #[test]
+    fn test_capture_macro() {
+        let user_id = String::from("u-42");
+        let error = std::io::Error::other("disk full");
+        let mut calls = 0;
+
+        let events = hawk_test::with_captured_events(|| {
+            crate::capture!(
+                Level::Warn,
+                "failed to sync {} items for {}: {} {:?}",
+                {
+                    calls += 1;
+                    3
+                },
+                user_id,
+                error,
+                Opaque,
+            );
+        });
+
+        assert_eq!(calls, 1);
+        let event = &events[0];
+        assert_eq!(
+            event.title,
+            "failed to sync 3 items for u-42: disk full Opaque"
+        );
+        assert_eq!(event.event_type.as_deref(), Some("warning"));
+        let frames = event.backtrace.as_ref().unwrap();
+        assert!(frames[0].file.as_deref().unwrap().ends_with("capture.rs"));
+        assert!(frames[1..]
+            .iter()
+            .all(|frame| { frame.function.as_deref() != Some("hawk::capture::capture_message") }));
+
+        let extra = &event.context.as_ref().unwrap()["extra"];
+        assert_eq!(extra["user_id"], json!("u-42"));
+        assert_eq!(extra["error"], json!("disk full"));
+        assert_eq!(extra["Opaque"], json!("Opaque"));
+        assert_eq!(extra.as_object().unwrap().len(), 4);
+    }
+}
@@ -0,0 +1,87 @@
+/*!
+ * Structured context — `hawk::context!{}`.
+ *
+ * `context!{ "order_id" => order.id, "retries" => retries }` builds a JSON
+ * object for `send_with_context()`, `with_context()`, or the `context` of
+ * an `EventData`. Values are anything `Serialize`, including nested
+ * `context!{}` objects.
+ *
+ * Keys are checked at compile time: each must be a string literal, and a
+ * key given twice is an error (`unreachable pattern`) instead of silently
+ * overwriting the first value.
+ */
+
+use serde::Serialize;
+use serde_json::Value;
+
+/**
+ * Builds a `serde_json::Value` object from `"key" => value` pairs.
+ *
+ * ```ignore
+ * hawk::send_with_context("payment declined", hawk::context!{
+ *     "order_id" => order.id,
+ *     "retries" => retries,
+ * });
+ * ```
+ */
+#[macro_export]
+macro_rules! context {
+    ($($key:literal => $value:expr),* $(,)?) => {{
+        #[deny(unreachable_patterns)]
+        let _ = match "" {
+            $($key => {})*
+            _ => {}
+        };
+
+        #[allow(unused_mut)]
+        let mut __hawk_context = $crate::__private::Map::new();
+        $(
+            __hawk_context.insert(::std::string::String::from($key), $crate::__private::context_value(&$value));
+        )*
+        $crate::__private::Value::Object(__hawk_context)
+    }};
+}
+
+/**
+ * Converts a `context!` value to JSON; `null` if it cannot be represented
+ * (e.g. a map with non-string keys).
+ */
+pub fn context_value(value: &(impl Serialize + ?Sized)) -> Value {
+    serde_json::to_value(value).unwrap_or(Value::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    /**
+     * Verifies that values of several types, nested objects, and trailing
+     * commas are accepted, and that the event carries the context.
+     */
+    #[test]
+    fn test_context_macro() {
+        let retries = 3;
+        let context = crate::context! {
+            "order_id" => "A-17",
+            "retries" => retries,
+            "items" => vec![1, 2],
+            "user" => crate::context! { "id" => 7 },
+        };
+        assert_eq!(
+            context,
+            json!({ "order_id": "A-17", "retries": 3, "items": [1, 2], "user": { "id": 7 } })
+        );
+        assert_eq!(crate::context! {}, json!({}));
+
+        let events = hawk_test::with_captured_events(|| {
+            crate::send_with_context("declined", context.clone())
+        });
+        let mut captured = events[0].context.clone().unwrap();
+        assert!(captured
+            .as_object_mut()
+            .unwrap()
+            .remove("process")
+            .is_some());
+        assert_eq!(captured, context);
+    }
+}
@@ -0,0 +1,153 @@
+/*!
+ * Background job monitoring — `hawk::monitor_job()`.
+ *
+ * Scheduled tasks fail silently: nobody watches a cron job's output, and a
+ * nightly import that stopped working shows up as missing data days
+ * later. `monitor_job()` runs a job and:
+ *
+ * 1. Records a breadcrumb when the job starts and when it succeeds, with
+ *    its duration.
+ * 2. Tags every event sent while it runs — panics, `hawk::send()`, log
+ *    and tracing integrations — with `{"job": {"name": ...}}`.
+ * 3. Sends an event when the job returns `Err`, with its status and
+ *    duration under `job`.
+ * 4. Reports a panic with the same context (through the panic hook, or
+ *    here if the hook is not installed), then resumes it.
+ */
+
+use std::fmt::Display;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::{Duration, Instant};
+
+use hawk_core::{Breadcrumb, EventData, CATCHER_VERSION};
+use serde_json::{json, Value};
+
+/// Breadcrumb category of job start / end records.
+const JOB_CATEGORY: &str = "job";
+
+/**
+ * Outcome of a monitored job: `()` always succeeds, a `Result` fails with
+ * its error.
+ */
+pub trait JobOutcome {
+    /// The failure to report, if the job failed.
+    fn failure(&self) -> Option<String>;
+}
+
+impl JobOutcome for () {
+    fn failure(&self) -> Option<String> {
+        None
+    }
+}
+
+impl<T, E: Display> JobOutcome for Result<T, E> {
+    fn failure(&self) -> Option<String> {
+        self.as_ref().err().map(ToString::to_string)
+    }
+}
+
+/**
+ * Runs `job` as the background job `name`, reporting its start, its
+ * success or failure with the duration, and any panic or error inside it.
+ * Returns what the job returned; panics are resumed.
+ *
+ * ```ignore
+ * hawk::monitor_job("nightly-import", || {
+ *     import_orders()?;
+ *     Ok::<_, ImportError>(())
+ * })?;
+ * ```
+ */
+pub fn monitor_job<R: JobOutcome>(name: &str, job: impl FnOnce() -> R) -> R {
+    let context = json!({ "job": { "name": name } });
+
+    hawk_core::add_breadcrumb(job_breadcrumb(name, "started", None));
+    let started = Instant::now();
+
+    let result = hawk_core::with_context(context, || panic::catch_unwind(AssertUnwindSafe(job)));
+    let duration = started.elapsed();
+
+    match result {
+        Ok(outcome) => {
+            match outcome.failure() {
+                Some(error) => {
+                    hawk_core::capture_event(job_event(
+                        format!("{error} [job: {name}]"),
+                        "error",
+                        name,
+                        "failed",
+                        duration,
+                    ));
+                }
+                None => {
+                    hawk_core::add_breadcrumb(job_breadcrumb(name, "succeeded", Some(duration)))
+                }
+            }
+            outcome
+        }
+        Err(payload) => {
+            if !hawk_panic::is_installed() {
+                let message = hawk_panic::payload_message(payload.as_ref());
+                hawk_core::capture_event(job_event(
+                    format!("panic: {message} [job: {name}]"),
+                    hawk_core::panic_type(&message),
+                    name,
+                    "panicked",
+                    duration,
+                ));
+            }
+            panic::resume_unwind(payload)
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Internal helpers
+// ---------------------------------------------------------------------------
+
+/**
+ * Builds the breadcrumb for a job status change.
+ */
+fn job_breadcrumb(name: &str, status: &str, duration: Option<Duration>) -> Breadcrumb {
+    Breadcrumb {
+        category: Some(JOB_CATEGORY.to_string()),
+        data: Some(job_data(name, status, duration)),
+        ..Breadcrumb::at(hawk_core::now_millis(), format!("job {name} {status}"))
+    }
+}
+
+/**
+ * Builds the event for a failed or panicked job. The stack at this point
+ * is the caller's, not the failure site's, so no backtrace is attached.
+ */
+fn job_event(
+    title: String,
+    event_type: &str,
+    name: &str,
+    status: &str,
+    duration: Duration,
+) -> EventData {
+    EventData {
+        title,
+        event_type: Some(event_type.to_string()),
+        backtrace: None,
+        context: Some(json!({ "job": job_data(name, status, Some(duration)) })),
+        breadcrumbs: None,
+        addons: None,
+        fingerprint: None,
+        user: None,
+        catcher_version: CATCHER_VERSION.into(),
+    }
+}
+
+/**
+ * `{"name": ..., "status": ..., "duration_ms": ...}`, without a duration
+ * for a job that has just started.
+ */
+fn job_data(name: &str, status: &str, duration: Option<Duration>) -> Value {
+    let mut data = json!({ "name": name, "status": status });
+    if let Some(duration) = duration {
+        data["duration_ms"] = json!(duration.as_secs_f64() * 1000.0);
+    }
+    data
+}
@@ -35,23 +35,279 @@
  *     hawk::send("something happened");
  * }
  * ```
+ *
+ * # Handled errors
+ *
+ * `hawk::capture_error(&err)` reports any `std::error::Error` with its type
+ * name and source chain. An `io::Error` is typed by its `ErrorKind` and
+ * carries its OS error code and errno name;
+ * `hawk::capture_io_error_at(&err, path)` adds the path involved as
+ * `io.path`, leaving the title alone so that events group across files, and
+ * `hawk::capture_error_for_user(&err, user)` the user affected by this one
+ * event — as `hawk::event(..).user(user)` does for built events.
+ *
+ * # Formatted events
+ *
+ * `hawk::capture!(hawk::Level::Warn, "failed to sync {} items for {}", n, user_id)`
+ * sends an event titled with the formatted message, with the call site as
+ * its top frame and the arguments, structured, under `extra` in its
+ * context.
+ *
+ * `hawk::send_with_context(message, hawk::context!{ "order_id" => id })`
+ * attaches context to a single event; `context!{}` checks at compile time
+ * that keys are string literals and not repeated.
+ *
+ * `hawk::capture_json(title, json!({...}))` sends a structured business
+ * event (type `"message"`, no backtrace) instead of an error.
+ * `hawk::capture_report(name, &payload)` sends a report — a config
+ * snapshot, feature-flag state — as type `"report"`, apart from errors.
+ *
+ * `let _phase = hawk::tag_scope("phase", "migration")` tags every event
+ * until the guard is dropped, then restores the tag's previous value.
+ *
+ * `hawk::event(title)` builds any other event fluently — `.level()`,
+ * `.user()`, `.context()`, `.fingerprint()`, `.capture_backtrace()` —
+ * and sends it with `.capture()`.
+ *
+ * # Performance
+ *
+ * `hawk::start_transaction(name, op)` times a unit of work; spans started
+ * from it with `start_child(op, description)` time its parts. Handles
+ * finish when dropped, and the finished transaction is sent with its
+ * spans through the same pipeline as errors.
+ *
+ * `hawk::time_block!("load_orders", { ... })` times a block as a span of
+ * the transaction bound to the thread (or as its own transaction), and
+ * reports it if it evaluates to `Err` or panics.
+ *
+ * # Distributed tracing
+ *
+ * Run each incoming request inside
+ * `hawk::TraceContext::continue_from(header).run(...)`, where `header` is
+ * the request's `hawk-trace` header, and forward
+ * `hawk::current_trace_header()` on outgoing requests. Events from every
+ * service handling the request then share a `trace.trace_id` in their
+ * context, and each links to its caller through `trace.parent_id`.
+ *
+ * # Metrics
+ *
+ * `hawk::metrics::increment("orders.failed", 1, &[("region", "eu")])`,
+ * `gauge()`, and `distribution()` record custom metrics. Values are
+ * aggregated in memory and sent every 10 seconds as one payload, so they
+ * are cheap to record on hot paths.
+ *
+ * # Background jobs
+ *
+ * Wrap scheduled tasks in `hawk::monitor_job("nightly-import", || ...)`:
+ * their start and success are recorded as breadcrumbs, and an `Err` or a
+ * panic is reported with the job name and duration. Every event sent
+ * while the job runs is tagged with the job name.
+ *
+ * # Health checks
+ *
+ * `hawk::status()` tells whether the SDK is initialized, the collector
+ * endpoint, the queue depth, the last delivery error and when it
+ * happened, and whether the worker is alive — for health endpoints and
+ * admin panels. With several `destinations`, each one's delivery state is
+ * listed in `status().destinations`. `hawk::pending_events()` alone tells how many events are
+ * waiting to be sent.
+ *
+ * # Overhead
+ *
+ * `hawk::estimated_overhead()` counts the events reported and the time
+ * they cost the reporting threads and the worker, to check the SDK's cost
+ * in production; figures measured by `cargo bench -p hawk` are documented
+ * there.
+ *
+ * # Browser (wasm32-unknown-unknown)
+ *
+ * The same API works in the browser: events are sent with `fetch()` right
+ * after the current task yields (or immediately on `flush()`), and panics
+ * are printed to the console and reported with the JavaScript stack.
+ *
+ * # WASI (wasm32-wasip2)
+ *
+ * Components running in wasmtime, Spin, etc. send events through
+ * `wasi:http/outgoing-handler`. There are no threads: each event is sent
+ * synchronously from the call that captured it.
+ *
+ * # Tokio
+ *
+ * With the `tokio` feature, `hawk::spawn` / `hawk::spawn_named` replace
+ * `tokio::spawn` and tag task panics with the task name and ID.
+ *
+ * # anyhow
+ *
+ * With the `anyhow` feature, `hawk::capture_anyhow(&err)` reports an
+ * `anyhow::Error` with its full context chain and, if anyhow captured one,
+ * the backtrace from where the error was created.
+ *
+ * # eyre
+ *
+ * With the `eyre` feature, `hawk::eyre::install()` (or `install_with()`
+ * around a color-eyre hook) reports every `eyre::Report` printed with
+ * `{:?}` — including the one returned from `main` — with its full text.
+ *
+ * # log
+ *
+ * With the `log` feature, `hawk::log::init` installs a `log` backend that
+ * turns `error!` records into events and lower levels into breadcrumbs.
+ *
+ * # tracing
+ *
+ * With the `tracing` feature, add `hawk::tracing::HawkLayer` to a
+ * `tracing_subscriber` registry: `ERROR` events become Hawk events carrying
+ * the span hierarchy, lower levels become breadcrumbs.
+ *
+ * # slog
+ *
+ * With the `slog` feature, add `hawk::slog::HawkDrain` to the root drain
+ * (e.g. through `slog::Duplicate`): error records become events with their
+ * key-value pairs as context, lower levels become breadcrumbs.
+ *
+ * # SQL
+ *
+ * With the `sqlx` feature, add `hawk::sql::SqlxLayer` to a
+ * `tracing_subscriber` registry; with the `diesel` feature, call
+ * `hawk::sql::install_diesel()`. Executed statements become breadcrumbs —
+ * normalized, without parameter values — and failed queries become events
+ * tagged with the statement fingerprint.
+ *
+ * # Smaller builds
+ *
+ * The `backtrace`, `breadcrumbs`, and `context` features are on by
+ * default. Built with `default-features = false` (adding back only what is
+ * needed), the corresponding code is compiled out and the APIs stay as
+ * no-ops: events carry no stack frames (panics keep their location),
+ * `add_breadcrumb` records nothing, and `with_context`, `tag_scope`, and
+ * the `run()` of `TraceContext` / `RequestContext` attach nothing. Without
+ * `backtrace`, the crate of that name is not a dependency either, and
+ * native crash reports carry no frames.
+ *
+ * `default-features = false` also drops the TLS backend: add back
+ * `rustls` or `native-tls`, or `init` rejects `https://` endpoints —
+ * the default one included.
+ *
+ * # Rayon
+ *
+ * With the `rayon` feature, build thread pools through
+ * `hawk::rayon::build_pool` / `hawk::rayon::build_global` to tag panics
+ * with the pool name and worker index.
  */
 
+#![forbid(unsafe_code)]
+
+use std::fmt;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::time::Duration;
+
+mod capture;
+mod context;
+mod job;
+mod timing;
 
 // ---------------------------------------------------------------------------
 // Re-exports from hawk_core — the public surface area
 // ---------------------------------------------------------------------------
 
 pub use hawk_core::{
-    BacktraceFrame, EventData, Guard, HawkEvent, CATCHER_VERSION,
-    send, capture_event, flush,
+    accepts_events, add_breadcrumb, capture_batch, capture_error, capture_error_for_user,
+    capture_event, capture_io_error_at, capture_json, capture_report, clear_breadcrumbs,
+    current_trace_header, estimated_overhead, event, flush, is_crash_looping, log_tail_enabled,
+    now_millis, pending_events, record_log_line, send, send_default_pii, send_with_context,
+    start_span, start_transaction, status, tag_scope, with_context, Addons, Attachment,
+    BacktraceFrame, Breadcrumb, Clock, Destination, DestinationStatus, EventBuilder, EventData,
+    Guard, HawkEvent, Level, Overhead, PrintEvents, RequestContext, SdkStatus, Shadow, Span,
+    SpanData, SystemClock, TagGuard, TraceContext, Transaction, TransactionData, User,
+    WireEncoding, CATCHER_VERSION, DEFAULT_SCRUB_KEYS, TRACE_HEADER,
 };
 
+pub use job::{monitor_job, JobOutcome};
+
+/// Support for `time_block!`, `capture!`, and `context!`; not public API.
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::capture::{capture_message, DebugExtra, DisplayExtra, Extra, SerializeExtra};
+    pub use crate::context::context_value;
+    pub use crate::timing::{AnyOutcome, BlockTimer, ResultOutcome};
+    pub use serde_json::{Map, Value};
+}
+
+pub use hawk_crash::HawkAlloc;
+
+/// Custom counters, gauges, and distributions, aggregated and sent
+/// periodically.
+pub mod metrics {
+    pub use hawk_core::metrics::{distribution, gauge, increment};
+}
+
+/// Panic hook control: suppression, uninstalling, custom payload types,
+/// capture from an application-owned hook, event types of std's panics.
+pub mod panic {
+    pub use hawk_core::panic_type;
+    pub use hawk_panic::{capture, register_payload_extractor, suppress, uninstall};
+}
+
+#[cfg(feature = "tokio")]
+pub use hawk_tokio::{spawn, spawn_named};
+
+#[cfg(feature = "anyhow")]
+pub use hawk_anyhow::capture_anyhow;
+
+/// eyre report handler (`eyre` feature).
+#[cfg(feature = "eyre")]
+pub mod eyre {
+    pub use hawk_eyre::{install, install_with, HawkHandler};
+}
+
+/// `log` crate backend (`log` feature).
+#[cfg(feature = "log")]
+pub mod log {
+    pub use hawk_log::{init, HawkLogger, LoggerOptions};
+}
+
+/// `tracing-subscriber` layer (`tracing` feature).
+#[cfg(feature = "tracing")]
+pub mod tracing {
+    pub use hawk_tracing::{HawkLayer, LayerOptions};
+}
+
+/// slog drain (`slog` feature).
+#[cfg(feature = "slog")]
+pub mod slog {
+    pub use hawk_slog::{DrainOptions, HawkDrain};
+}
+
+/// SQL query breadcrumbs and query errors (`sqlx` / `diesel` features).
+#[cfg(any(feature = "sqlx", feature = "diesel"))]
+pub mod sql {
+    pub use hawk_sql::{
+        capture_query_error, fingerprint, normalize_sql, record_query, CaptureQueryError,
+    };
+
+    #[cfg(feature = "sqlx")]
+    pub use hawk_sql::SqlxLayer;
+    #[cfg(feature = "diesel")]
+    pub use hawk_sql::{install_diesel, DieselInstrumentation};
+}
+
+/// Rayon thread pool integration (`rayon` feature).
+#[cfg(feature = "rayon")]
+pub mod rayon {
+    pub use hawk_rayon::{build_global, build_pool};
+}
+
 // ---------------------------------------------------------------------------
 // Options
 // ---------------------------------------------------------------------------
 
+/// Grouping callback of `Options::group_by`.
+type GroupBy = Arc<dyn Fn(&EventData) -> Option<Vec<String>> + Send + Sync>;
+
+/// Dormancy callback of `Options::on_dormant`.
+type OnDormant = Arc<dyn Fn(Duration, &str) + Send + Sync>;
+
 /**
  * Configuration for the Hawk SDK.
  *
@@ -59,6 +315,49 @@ pub use hawk_core::{
  * All optional fields have sensible defaults:
  * - `catch_panics` = `true`
  * - `before_send` = `None`
+ * - `group_by` = `None`
+ * - `dedupe_window` = `None`
+ * - `sample_rate` = `1.0`
+ * - `collector_endpoint` = `None` (derived from the token)
+ * - `destinations` = empty
+ * - `shadow` = `None`
+ * - `validate_connection` = `false`
+ * - `wire_encoding` = `WireEncoding::Json`
+ * - `on_rate_limit` = `None`
+ * - `on_dormant` = `None`
+ * - `max_queue_bytes` = `16 MiB`
+ * - `clock` = `None` (the system clock)
+ * - `event_id_generator` = `None` (random UUIDs)
+ * - `in_app_include` = empty
+ * - `max_backtrace_frames` = `100`
+ * - `include_addresses` = `false`
+ * - `workspace_root` = `None`
+ * - `max_breadcrumbs` = `20`
+ * - `log_tail_bytes` = `0` (disabled)
+ * - `state_file` = `None` (uptime only)
+ * - `startup_crash_window` = 5 s
+ * - `startup_crash_threshold` = `2`
+ * - `fatal_event_dir` = `None`
+ * - `spool_key` = `None` (plaintext)
+ * - `max_string_length` = `8192`
+ * - `max_context_depth` = `16`
+ * - `max_context_nodes` = `5000`
+ * - `scrub_data` = `true`
+ * - `scrub_keys` = `DEFAULT_SCRUB_KEYS`
+ * - `scrub_env_allowlist` = empty
+ * - `scrub_patterns` = empty
+ * - `scrub_headers` = empty
+ * - `context_allowlist` = `None`
+ * - `send_default_pii` = `false`
+ * - `print_events` = `PrintEvents::Off`
+ * - `flush_on_panic` = `false` (always on with `panic = "abort"`)
+ * - `thread_inventory` = `false`
+ * - `ignore_panic_modules` = `[]`
+ * - `catch_crashes` = `false`
+ * - `crash_dir` = `None` (`<temp dir>/hawk-crashes/<integration>-<exe>`)
+ * - `minidumps` = `true`
+ * - `flush_on_exit` = `true`
+ * - `flush_on_shutdown_signal` = `false` (`shutdown-signals` feature)
  */
 pub struct Options {
     /// The base64-encoded integration token from your Hawk project settings.
@@ -76,6 +375,223 @@ pub struct Options {
     ///
     /// If the callback panics, the original event is sent unchanged.
     pub before_send: Option<Arc<dyn Fn(EventData) -> Option<EventData> + Send + Sync>>,
+
+    /// Optional callback computing the grouping key (`fingerprint`) of
+    /// each event that has none, e.g. the title with IDs stripped. Events
+    /// with equal fingerprints are grouped together.
+    pub group_by: Option<GroupBy>,
+
+    /// Drop events identical to one sent within this window (same
+    /// fingerprint, or same type and title), attaching the number dropped
+    /// to the next occurrence. For retry loops failing the same way.
+    pub dedupe_window: Option<Duration>,
+
+    /// Share of events sent, from `0.0` to `1.0`, for high-volume apps.
+    /// Sampled-out events cost no backtrace and are never built.
+    pub sample_rate: f64,
+
+    /// URL to send events to instead of the collector derived from the
+    /// token: a self-hosted collector, a proxy, or a mock collector in
+    /// integration tests.
+    pub collector_endpoint: Option<String>,
+
+    /// Other projects every event is delivered to as well, e.g. a
+    /// company-wide aggregate next to the team's project. Each one fails
+    /// and is rate limited independently.
+    pub destinations: Vec<Destination>,
+
+    /// Mirror a fraction of events to a secondary collector, e.g. a
+    /// staging instance being evaluated, without affecting the others.
+    pub shadow: Option<Shadow>,
+
+    /// Check at `init()` that the collector endpoint can be reached, and
+    /// fail if it cannot (`try_init()` returns the error, `init()` prints
+    /// it), instead of losing the first events silently. Blocks `init()`
+    /// for up to 5 s; skipped on wasm targets.
+    pub validate_connection: bool,
+
+    /// Wire encoding of payloads: MessagePack (`msgpack` feature) or CBOR
+    /// (`cbor` feature) with collectors that advertise it, JSON otherwise.
+    pub wire_encoding: WireEncoding,
+
+    /// Called once when the collector starts rate limiting the client,
+    /// with the time until events are sent again (they are dropped
+    /// meanwhile) — to capture less until then.
+    pub on_rate_limit: Option<Arc<dyn Fn(Duration) + Send + Sync>>,
+
+    /// Called once when the collector reports the project disabled or over
+    /// its quota, with the time until events are sent again and the
+    /// reason — events are dropped cheaply meanwhile.
+    pub on_dormant: Option<OnDormant>,
+
+    /// Maximum size, in bytes of JSON, of the events waiting to be sent;
+    /// events beyond it are dropped. `0` disables the limit.
+    pub max_queue_bytes: usize,
+
+    /// Source of the timestamps of breadcrumbs, transactions, and
+    /// metrics, e.g. a fixed clock for deterministic payloads in tests.
+    pub clock: Option<Arc<dyn Clock>>,
+
+    /// Generates the ID of each event (`addons.eventId`) instead of a
+    /// random UUID, e.g. ULIDs that sort by time downstream.
+    pub event_id_generator: Option<Arc<dyn Fn() -> String + Send + Sync>>,
+
+    /// Module path prefixes whose backtrace frames are always marked as
+    /// application code (`in_app`). Other frames are classified by file path.
+    pub in_app_include: Vec<String>,
+
+    /// Maximum number of frames kept per backtrace (topmost frames win).
+    /// Defaults to `100`.
+    pub max_backtrace_frames: usize,
+
+    /// Attach raw instruction addresses and module offsets to frames,
+    /// for offline re-symbolization of stripped binaries.
+    /// Defaults to `false`.
+    pub include_addresses: bool,
+
+    /// Absolute project path on the build machine, shown as `<workspace>`
+    /// in frame file paths. Pass `Some(env!("CARGO_MANIFEST_DIR").into())`.
+    pub workspace_root: Option<String>,
+
+    /// Number of most recent breadcrumbs (`hawk::add_breadcrumb`) attached
+    /// to each event. `0` disables breadcrumbs. Defaults to `20`.
+    pub max_breadcrumbs: usize,
+
+    /// Bytes of recent log lines (from the `log` and `tracing`
+    /// integrations) kept and attached to fatal events only, under
+    /// `log_tail` in their context. `0` disables it.
+    pub log_tail_bytes: usize,
+
+    /// File keeping the restart history across runs, sent under `process`
+    /// in the context with the uptime: earlier runs, how the previous one
+    /// ended, and how many in a row crashed. Must persist across restarts.
+    pub state_file: Option<PathBuf>,
+
+    /// Time after `init()` within which a run ending uncleanly — a panic,
+    /// a crash, a kill — counts as a startup crash. Needs `state_file`.
+    pub startup_crash_window: Duration,
+
+    /// Consecutive runs ending in a startup crash that make a crash loop:
+    /// their fatal events get `startup_crash: true`, those left by earlier
+    /// runs are delivered before `init()` returns, and
+    /// `is_crash_looping()` turns true. `0` turns detection off. Needs
+    /// `state_file`.
+    pub startup_crash_threshold: u32,
+
+    /// Directory fatal events are written to until sent, so that those a
+    /// crash or an abort kept from being delivered are sent at the next
+    /// `init()`, flagged `previous_run`. Must persist across restarts.
+    pub fatal_event_dir: Option<PathBuf>,
+
+    /// 256-bit key that fatal events on disk and crash records are
+    /// encrypted with, e.g. kept in the OS keyring; files written with
+    /// another key are discarded. Turns off `minidumps`. Not supported on
+    /// wasm targets.
+    pub spool_key: Option<[u8; 32]>,
+
+    /// Maximum length in characters of every string in an event; longer
+    /// ones are cut and marked `"...[truncated]"`. `0` disables the limit.
+    /// Defaults to `8192`.
+    pub max_string_length: usize,
+
+    /// Maximum nesting depth of event context and breadcrumb data; deeper
+    /// values are replaced with `"...[truncated]"`. `0` disables the
+    /// limit. Defaults to `16`.
+    pub max_context_depth: usize,
+
+    /// Maximum number of values in event context and breadcrumb data; the
+    /// rest is replaced with a `"...[truncated]"` marker. `0` disables the
+    /// limit. Defaults to `5000`.
+    pub max_context_nodes: usize,
+
+    /// Replace values stored under `scrub_keys` in event context and
+    /// breadcrumb data, and card numbers, bearer tokens, and email
+    /// addresses in event strings, with `"[Filtered]"`. Defaults to `true`.
+    pub scrub_data: bool,
+
+    /// Sensitive keys, matched as whole words of a key or at its end,
+    /// ignoring case, `-`, and `_`.
+    /// Defaults to `DEFAULT_SCRUB_KEYS`: password, secret, token,
+    /// authorization, cookie, api_key.
+    pub scrub_keys: Vec<String>,
+
+    /// Variables kept in environment snapshots (context objects under
+    /// `env` / `environment` keys) although their names look like
+    /// credentials: `*_KEY`, `*_SECRET`, `*_TOKEN`, `AWS_*` are redacted
+    /// otherwise. Empty by default.
+    pub scrub_env_allowlist: Vec<String>,
+
+    /// Regexes whose matches are replaced with `"[Filtered]"` in event
+    /// titles, context strings, and breadcrumbs — applied even with
+    /// `scrub_data` off. An invalid pattern makes `init()` fail.
+    pub scrub_patterns: Vec<String>,
+
+    /// Request headers scrubbed in `RequestContext` (names kept, values
+    /// replaced), in addition to `Authorization`, `Cookie`, and
+    /// `Set-Cookie`. Empty by default.
+    pub scrub_headers: Vec<String>,
+
+    /// Strict mode for "deny by default" data policies: when set, only
+    /// these context and breadcrumb data keys — top-level (`"job"`) or
+    /// dotted paths (`"request.method"`) — are sent. Defaults to `None`.
+    pub context_allowlist: Option<Vec<String>>,
+
+    /// Collect personal data automatically: usernames in source paths
+    /// (otherwise shown as `<home>`), and IP addresses, user emails, and
+    /// full request headers and cookies in integrations that see them.
+    /// Defaults to `false`.
+    pub send_default_pii: bool,
+
+    /// Print events to stderr in a readable, colored form (title, type,
+    /// top frames, context changes since the previous event), `AndSend`
+    /// or `Instead` of sending them — to see locally what production
+    /// would receive. Defaults to `Off`.
+    pub print_events: PrintEvents,
+
+    /// Block in the panic hook until the panic event is delivered, instead
+    /// of relying on the `Guard` flush. Always enabled when the binary is
+    /// built with `panic = "abort"`, where the `Guard` never gets dropped.
+    /// Defaults to `false`.
+    pub flush_on_panic: bool,
+
+    /// List all threads of the process (OS ID, name, state) in the context
+    /// of panic events, to spot deadlocked or starving siblings.
+    /// Linux only. Defaults to `false`.
+    pub thread_inventory: bool,
+
+    /// Crates or modules (`"vendored_parser"`, `"my_app::legacy"`) whose
+    /// panics are not reported, matched against the code that panicked.
+    /// Empty by default.
+    pub ignore_panic_modules: Vec<String>,
+
+    /// Whether to install signal handlers recording native crashes
+    /// (SIGSEGV, SIGBUS, SIGABRT, ...) — on Windows, an unhandled exception
+    /// filter (access violations, stack overflows, ...). Crashes are
+    /// reported on the next start. Defaults to `false`.
+    pub catch_crashes: bool,
+
+    /// Directory for crash records. Must persist across restarts.
+    /// Defaults to `<temp dir>/hawk-crashes/<integration>-<exe>`.
+    pub crash_dir: Option<PathBuf>,
+
+    /// Whether crashes also leave a minidump (registers, stack memory,
+    /// loaded modules), sent as an attachment of the crash event. Stack
+    /// memory may hold user data and is not scrubbed. Linux, Android, and
+    /// Windows. Needs `catch_crashes`, and no `spool_key`: minidumps
+    /// cannot be encrypted. Defaults to `true`.
+    pub minidumps: bool,
+
+    /// Flush pending events when the process calls `std::process::exit()`,
+    /// which skips the `Guard`'s destructor. Unix only (elsewhere, use
+    /// `hawk::exit()`). Defaults to `true`.
+    pub flush_on_exit: bool,
+
+    /// Flush pending events on SIGINT / SIGTERM before the process
+    /// terminates. Leave off if the app handles these signals itself
+    /// (graceful shutdown) — call `hawk::flush()` there instead.
+    /// Unix only. Defaults to `false`.
+    #[cfg(feature = "shutdown-signals")]
+    pub flush_on_shutdown_signal: bool,
 }
 
 impl Default for Options {
@@ -84,6 +600,53 @@ impl Default for Options {
             token: String::new(),
             catch_panics: true,
             before_send: None,
+            group_by: None,
+            dedupe_window: None,
+            sample_rate: 1.0,
+            collector_endpoint: None,
+            destinations: Vec::new(),
+            shadow: None,
+            validate_connection: false,
+            wire_encoding: WireEncoding::Json,
+            on_rate_limit: None,
+            on_dormant: None,
+            max_queue_bytes: hawk_core::DEFAULT_MAX_QUEUE_BYTES,
+            clock: None,
+            event_id_generator: None,
+            in_app_include: Vec::new(),
+            max_backtrace_frames: hawk_core::DEFAULT_MAX_BACKTRACE_FRAMES,
+            include_addresses: false,
+            workspace_root: None,
+            max_breadcrumbs: hawk_core::DEFAULT_MAX_BREADCRUMBS,
+            log_tail_bytes: 0,
+            state_file: None,
+            startup_crash_window: hawk_core::DEFAULT_STARTUP_CRASH_WINDOW,
+            startup_crash_threshold: hawk_core::DEFAULT_STARTUP_CRASH_THRESHOLD,
+            fatal_event_dir: None,
+            spool_key: None,
+            max_string_length: hawk_core::DEFAULT_MAX_STRING_LENGTH,
+            max_context_depth: hawk_core::DEFAULT_MAX_CONTEXT_DEPTH,
+            max_context_nodes: hawk_core::DEFAULT_MAX_CONTEXT_NODES,
+            scrub_data: true,
+            scrub_keys: hawk_core::DEFAULT_SCRUB_KEYS
+                .iter()
+                .map(|key| key.to_string())
+                .collect(),
+            scrub_env_allowlist: Vec::new(),
+            scrub_patterns: Vec::new(),
+            scrub_headers: Vec::new(),
+            context_allowlist: None,
+            send_default_pii: false,
+            print_events: PrintEvents::Off,
+            flush_on_panic: false,
+            thread_inventory: false,
+            ignore_panic_modules: Vec::new(),
+            catch_crashes: false,
+            crash_dir: None,
+            minidumps: true,
+            flush_on_exit: true,
+            #[cfg(feature = "shutdown-signals")]
+            flush_on_shutdown_signal: false,
         }
     }
 }
@@ -122,33 +685,189 @@ impl From<&str> for Options {
  * });
  * ```
  *
- * # Panics
- * Panics if the token is malformed or `init` is called more than once.
+ * Never panics: if the SDK cannot be initialized (see `try_init()`), the
+ * reason is printed to stderr, and the app runs without error tracking.
  *
  * # Returns
  * A `Guard` — keep it alive for the duration of your app.
  * When it drops, all pending events are flushed.
  */
 pub fn init(options: impl Into<Options>) -> Guard {
+    try_init(options).unwrap_or_else(|err| {
+        eprintln!("[Hawk] Failed to initialize SDK: {err}");
+        Guard::inert()
+    })
+}
+
+/**
+ * Why `try_init()` failed.
+ */
+#[derive(Debug)]
+pub struct InitError {
+    message: String,
+}
+
+impl fmt::Display for InitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for InitError {}
+
+/**
+ * Initializes the Hawk SDK like `init()`, but returns an error instead of
+ * running without error tracking when:
+ *
+ * - the token or a `scrub_patterns` regex is malformed;
+ * - a destination or the `shadow` rate is invalid, or `wire_encoding` is
+ *   not compiled in;
+ * - `validate_connection` is set and the collector cannot be reached;
+ * - the SDK is already initialized.
+ *
+ * No addon is installed when it fails.
+ */
+pub fn try_init(options: impl Into<Options>) -> Result<Guard, InitError> {
     let opts = options.into();
 
     /*
-     * Split Options into the core part (before_send) and addon flags.
+     * Split Options into the core part and addon flags.
      */
     let core_options = hawk_core::Options {
         before_send: opts.before_send,
+        group_by: opts.group_by,
+        dedupe_window: opts.dedupe_window,
+        sample_rate: opts.sample_rate,
+        collector_endpoint: opts.collector_endpoint,
+        destinations: opts.destinations,
+        shadow: opts.shadow,
+        validate_connection: opts.validate_connection,
+        wire_encoding: opts.wire_encoding,
+        on_rate_limit: opts.on_rate_limit,
+        on_dormant: opts.on_dormant,
+        max_queue_bytes: opts.max_queue_bytes,
+        clock: opts.clock,
+        event_id_generator: opts.event_id_generator,
+        in_app_include: opts.in_app_include,
+        max_backtrace_frames: opts.max_backtrace_frames,
+        include_addresses: opts.include_addresses,
+        workspace_root: opts.workspace_root,
+        max_breadcrumbs: opts.max_breadcrumbs,
+        log_tail_bytes: opts.log_tail_bytes,
+        state_file: opts.state_file,
+        startup_crash_window: opts.startup_crash_window,
+        startup_crash_threshold: opts.startup_crash_threshold,
+        fatal_event_dir: opts.fatal_event_dir,
+        spool_key: opts.spool_key,
+        max_string_length: opts.max_string_length,
+        max_context_depth: opts.max_context_depth,
+        max_context_nodes: opts.max_context_nodes,
+        scrub_data: opts.scrub_data,
+        scrub_keys: opts.scrub_keys,
+        scrub_env_allowlist: opts.scrub_env_allowlist,
+        scrub_patterns: opts.scrub_patterns,
+        scrub_headers: opts.scrub_headers,
+        context_allowlist: opts.context_allowlist,
+        send_default_pii: opts.send_default_pii,
+        print_events: opts.print_events,
     };
 
-    let guard = hawk_core::init(&opts.token, core_options)
-        .expect("[Hawk] Failed to initialize SDK");
+    let guard =
+        hawk_core::init(&opts.token, core_options).map_err(|message| InitError { message })?;
 
     /*
      * Install addons based on the options.
      * Panic hook is opt-out (enabled by default) — most users want it.
      */
     if opts.catch_panics {
+        hawk_panic::set_flush_on_panic(opts.flush_on_panic);
+        hawk_panic::set_thread_inventory(opts.thread_inventory);
+        hawk_panic::set_ignore_modules(opts.ignore_panic_modules);
         hawk_panic::install();
     }
 
-    guard
+    /*
+     * Crash handler is opt-in: it replaces signal handlers (or the
+     * unhandled exception filter) process-wide.
+     */
+    if opts.catch_crashes {
+        let crash_dir = opts.crash_dir.unwrap_or_else(hawk_crash::default_crash_dir);
+        hawk_crash::set_minidumps(opts.minidumps);
+        hawk_crash::install(&crash_dir);
+    }
+
+    #[cfg(unix)]
+    if opts.flush_on_exit {
+        hawk_crash::install_exit_flush();
+    }
+
+    #[cfg(all(unix, feature = "shutdown-signals"))]
+    if opts.flush_on_shutdown_signal {
+        hawk_crash::install_shutdown_flush();
+    }
+
+    Ok(guard)
+}
+
+// ---------------------------------------------------------------------------
+// exit
+// ---------------------------------------------------------------------------
+
+/**
+ * Flushes pending events, then terminates the process with `code`.
+ *
+ * Drop-in replacement for `std::process::exit()`, which skips destructors
+ * — including the `Guard`'s flush. On Unix `flush_on_exit` covers plain
+ * `process::exit()` calls too; this function works on every platform.
+ */
+pub fn exit(code: i32) -> ! {
+    flush();
+    hawk_core::mark_clean_exit();
+    std::process::exit(code)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * Verifies that a malformed token makes `try_init()` fail and `init()`
+     * return an inert guard instead of panicking.
+     */
+    #[test]
+    fn test_init_failure() {
+        let err = try_init("not a token").err().expect("should fail");
+        assert!(!err.to_string().is_empty());
+
+        let guard = init("not a token");
+        drop(guard);
+    }
+
+    /**
+     * Verifies that a report is sent as type `"report"` with its payload as
+     * context, under `data` if it is not an object, and that structured
+     * messages keep the type `"message"`.
+     */
+    #[test]
+    fn test_capture_report() {
+        let events = hawk_test::with_captured_events(|| {
+            capture_report("startup config", &serde_json::json!({ "workers": 4 }));
+            capture_report("enabled flags", &["search", "export"]);
+            capture_json("reconciliation failed", serde_json::json!({ "delta": 3 }));
+        });
+
+        let types: Vec<_> = events
+            .iter()
+            .map(|event| event.event_type.as_deref())
+            .collect();
+        assert_eq!(types, [Some("report"), Some("report"), Some("message")]);
+        assert!(events.iter().all(|event| event.backtrace.is_none()));
+
+        let context =
+            |index: usize, key: &str| events[index].context.as_ref().unwrap()[key].clone();
+        assert_eq!(events[0].title, "startup config");
+        assert_eq!(context(0, "workers"), serde_json::json!(4));
+        assert_eq!(context(1, "data"), serde_json::json!(["search", "export"]));
+        assert_eq!(context(2, "delta"), serde_json::json!(3));
+    }
 }
@@ -0,0 +1,221 @@
+/*!
+ * Timed blocks — `hawk::time_block!()`.
+ *
+ * `time_block!("name", { ... })` runs a block as a span of the work
+ * already being measured on this thread (a transaction or span bound with
+ * `bind()`, or an enclosing timed block), or as a transaction of its own
+ * if nothing is. Blocks nested inside it become its child spans.
+ *
+ * A block evaluating to `Err` is reported as an event with its name and
+ * duration, and its span gets the `"internal_error"` status. A panic is
+ * reported the same way (through the panic hook, or here if the hook is
+ * not installed), then resumed.
+ */
+
+use std::fmt::Display;
+use std::panic::{self, AssertUnwindSafe};
+use std::time::{Duration, Instant};
+
+use hawk_core::{EventData, Span, Transaction, CATCHER_VERSION};
+use serde_json::json;
+
+/// `op` of the spans and transactions of timed blocks.
+const TIMED_OP: &str = "function";
+
+/**
+ * Times a block and reports it as a span, capturing an event if it
+ * evaluates to `Err` or panics. Evaluates to the block's value.
+ *
+ * The block runs in a closure: `?` inside it applies to the block, and
+ * `return` is not allowed.
+ *
+ * ```ignore
+ * let orders = hawk::time_block!("load_orders", {
+ *     let rows = db.query("SELECT * FROM orders")?;
+ *     Ok::<_, DbError>(parse(rows))
+ * })?;
+ * ```
+ */
+#[macro_export]
+macro_rules! time_block {
+    ($name:expr, $body:block) => {{
+        let mut __hawk_timer = $crate::__private::BlockTimer::start($name);
+        let __hawk_value = __hawk_timer.run(|| $body);
+        {
+            #[allow(unused_imports)]
+            use $crate::__private::{AnyOutcome as _, ResultOutcome as _};
+            __hawk_timer.finish((&__hawk_value).hawk_failure());
+        }
+        __hawk_value
+    }};
+}
+
+// ---------------------------------------------------------------------------
+// Outcome detection
+// ---------------------------------------------------------------------------
+
+/*
+ * `time_block!` accepts blocks of any type but reports only `Err`s. It
+ * calls `(&value).hawk_failure()`: method lookup finds `ResultOutcome`
+ * without auto-referencing when the value is a `Result` with a
+ * displayable error, and falls back to `AnyOutcome` (one more reference
+ * away) for everything else.
+ */
+
+/// Failure of a block that evaluated to a `Result`.
+pub trait ResultOutcome {
+    fn hawk_failure(&self) -> Option<String>;
+}
+
+impl<T, E: Display> ResultOutcome for Result<T, E> {
+    fn hawk_failure(&self) -> Option<String> {
+        self.as_ref().err().map(ToString::to_string)
+    }
+}
+
+/// Blocks of any other type never fail.
+pub trait AnyOutcome {
+    fn hawk_failure(&self) -> Option<String> {
+        None
+    }
+}
+
+impl<T> AnyOutcome for &T {}
+
+// ---------------------------------------------------------------------------
+// BlockTimer
+// ---------------------------------------------------------------------------
+
+/**
+ * The span or transaction of a running timed block.
+ */
+pub struct BlockTimer {
+    name: String,
+    handle: Handle,
+    started: Instant,
+}
+
+enum Handle {
+    Transaction(Transaction),
+    Span(Span),
+}
+
+impl BlockTimer {
+    /**
+     * Starts a span under the work bound to this thread, or a transaction
+     * if nothing is bound.
+     */
+    pub fn start(name: &str) -> Self {
+        let handle = match hawk_core::start_span(TIMED_OP, name) {
+            Some(span) => Handle::Span(span),
+            None => Handle::Transaction(hawk_core::start_transaction(name, TIMED_OP)),
+        };
+
+        Self {
+            name: name.to_string(),
+            handle,
+            started: Instant::now(),
+        }
+    }
+
+    /**
+     * Runs the block with its span bound to this thread. A panic is
+     * reported if the panic hook will not report it, then resumed; the
+     * span is recorded as the timer is dropped during unwinding.
+     */
+    pub fn run<R>(&mut self, f: impl FnOnce() -> R) -> R {
+        let result = match &self.handle {
+            Handle::Transaction(transaction) => {
+                transaction.bind(|| panic::catch_unwind(AssertUnwindSafe(f)))
+            }
+            Handle::Span(span) => span.bind(|| panic::catch_unwind(AssertUnwindSafe(f))),
+        };
+
+        match result {
+            Ok(value) => value,
+            Err(payload) => {
+                self.set_status("panicked");
+                if !hawk_panic::is_installed() {
+                    let message = hawk_panic::payload_message(payload.as_ref());
+                    hawk_core::capture_event(self.event(
+                        format!("panic: {message} [{}]", self.name),
+                        hawk_core::panic_type(&message),
+                    ));
+                }
+                panic::resume_unwind(payload)
+            }
+        }
+    }
+
+    /**
+     * Reports the block's failure, if any, and finishes its span.
+     */
+    pub fn finish(mut self, failure: Option<String>) {
+        if let Some(error) = failure {
+            self.set_status("internal_error");
+            hawk_core::capture_event(self.event(format!("{error} [{}]", self.name), "error"));
+        }
+    }
+
+    fn set_status(&mut self, status: &str) {
+        match &mut self.handle {
+            Handle::Transaction(transaction) => transaction.set_status(status),
+            Handle::Span(span) => span.set_status(status),
+        }
+    }
+
+    /**
+     * Builds the event for a failed or panicked block. As with jobs, the
+     * stack here is not the failure site's, so no backtrace is attached.
+     */
+    fn event(&self, title: String, event_type: &str) -> EventData {
+        EventData {
+            title,
+            event_type: Some(event_type.to_string()),
+            backtrace: None,
+            context: Some(json!({
+                "timed": {
+                    "name": self.name,
+                    "duration_ms": duration_ms(self.started.elapsed()),
+                }
+            })),
+            breadcrumbs: None,
+            addons: None,
+            fingerprint: None,
+            user: None,
+            catcher_version: CATCHER_VERSION.into(),
+        }
+    }
+}
+
+fn duration_ms(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * Verifies that blocks of any type are accepted, that only `Err`
+     * values count as failures, and that nested blocks become spans.
+     */
+    #[test]
+    fn test_time_block() {
+        let parsed = crate::time_block!("parse", { "42".parse::<i32>() });
+        assert_eq!(parsed, Ok(42));
+
+        let failed = crate::time_block!("parse", { "x".parse::<i32>() });
+        assert_eq!(
+            failed.hawk_failure(),
+            Some("invalid digit found in string".to_string())
+        );
+        assert_eq!((&42).hawk_failure(), None);
+
+        let nested = crate::time_block!("outer", {
+            crate::time_block!("inner", { hawk_core::start_span("op", "").is_some() })
+        });
+        assert!(nested);
+        assert!(hawk_core::start_span("op", "").is_none());
+    }
+}
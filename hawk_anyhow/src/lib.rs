@@ -0,0 +1,127 @@
+/*!
+ * Hawk anyhow integration — reporting `anyhow::Error` values.
+ *
+ * `capture_anyhow()` sends an error that was handled (logged, turned into
+ * an HTTP 500, ...) rather than panicked on:
+ *
+ * - The title is the whole chain, outermost context first
+ *   (`"loading config: reading file: No such file or directory"`).
+ * - The event `type` is derived from the root cause by
+ *   `hawk_core::error_type()` (`NotFound` for an `io::Error`,
+ *   `ParseIntError`, ...) — `dyn Error` carries no type name.
+ * - The context lists every layer of the chain under `anyhow.chain`.
+ * - If anyhow captured a backtrace when the error was created
+ *   (`RUST_BACKTRACE=1` / `RUST_LIB_BACKTRACE=1`), it is used instead of the
+ *   stack at the report site, which is usually far from the failure.
+ *
+ * # Example
+ *
+ * ```ignore
+ * if let Err(err) = load_config() {
+ *     hawk_anyhow::capture_anyhow(&err);
+ * }
+ * ```
+ */
+
+use hawk_core::{EventData, CATCHER_VERSION};
+use serde_json::json;
+
+/// Event type used when the root cause's type cannot be determined,
+/// e.g. for `anyhow!("message")` errors.
+const DEFAULT_ERROR_TYPE: &str = "Error";
+
+// ---------------------------------------------------------------------------
+// Public API
+// ---------------------------------------------------------------------------
+
+/**
+ * Sends an `anyhow::Error` to Hawk, with its context chain and backtrace.
+ *
+ * Silent no-op if the SDK is not initialized.
+ */
+pub fn capture_anyhow(error: &anyhow::Error) {
+    if !hawk_core::accepts_events() {
+        return;
+    }
+
+    let mut event = build_event(error);
+
+    let frames = hawk_core::convert_std_backtrace(error.backtrace());
+    if frames.is_empty() {
+        hawk_core::capture_event_with_backtrace(event, hawk_core::Backtrace::new_unresolved());
+    } else {
+        event.backtrace = Some(frames);
+        hawk_core::capture_event(event);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Internal helpers
+// ---------------------------------------------------------------------------
+
+/**
+ * Builds the event for `error`, without a backtrace.
+ */
+fn build_event(error: &anyhow::Error) -> EventData {
+    let chain: Vec<String> = error.chain().map(ToString::to_string).collect();
+    let root_type = hawk_core::error_type(error.root_cause());
+
+    EventData {
+        title: format!("{error:#}"),
+        event_type: Some(root_type.unwrap_or_else(|| DEFAULT_ERROR_TYPE.into())),
+        backtrace: None,
+        context: Some(json!({ "anyhow": { "chain": chain } })),
+        breadcrumbs: None,
+        addons: None,
+        fingerprint: None,
+        user: None,
+        catcher_version: CATCHER_VERSION.into(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anyhow::Context;
+
+    /**
+     * Verifies the title, root cause type, and chain of a wrapped error,
+     * and the types of an `io::Error` root cause and of a plain message.
+     */
+    #[test]
+    fn test_build_event() {
+        let error = "x1"
+            .parse::<u32>()
+            .context("parsing port")
+            .context("loading config")
+            .unwrap_err();
+
+        let event = build_event(&error);
+
+        assert_eq!(
+            event.title,
+            "loading config: parsing port: invalid digit found in string"
+        );
+        assert_eq!(event.event_type.as_deref(), Some("ParseIntError"));
+        assert_eq!(
+            event.context.unwrap()["anyhow"]["chain"],
+            json!([
+                "loading config",
+                "parsing port",
+                "invalid digit found in string"
+            ])
+        );
+
+        let not_found = anyhow::Error::new(std::io::Error::from(std::io::ErrorKind::NotFound))
+            .context("reading file");
+        assert_eq!(
+            build_event(&not_found).event_type.as_deref(),
+            Some("NotFound")
+        );
+        let message = anyhow::anyhow!("no route for {}", "/admin");
+        assert_eq!(
+            build_event(&message).event_type.as_deref(),
+            Some(DEFAULT_ERROR_TYPE)
+        );
+    }
+}
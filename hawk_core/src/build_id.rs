@@ -0,0 +1,112 @@
+/*!
+ * Build ID / debug ID detection for the running executable.
+ *
+ * Release binaries are usually stripped, so frames captured in production
+ * carry addresses but no file/line info. The build ID uniquely identifies
+ * the exact binary that produced an event, letting the backend symbolicate
+ * its frames from debug files uploaded separately.
+ *
+ * Supported formats:
+ * - ELF — GNU build ID note (`.note.gnu.build-id`), as lowercase hex
+ * - Mach-O — `LC_UUID` load command, as a hyphenated UUID
+ * - PE — CodeView GUID + age, in the symbol-server key format
+ *   (`GUID` fields + age as uppercase hex, as used in PDB lookups)
+ */
+
+use std::fs::File;
+
+use object::read::ReadCache;
+use object::Object;
+
+/**
+ * Reads the build ID of the current executable.
+ *
+ * The file is read lazily through `ReadCache`, so only the headers and
+ * the note / load command carrying the ID are pulled from disk — not the
+ * whole binary.
+ *
+ * # Returns
+ * `None` if the executable cannot be located or parsed, or if it was
+ * linked without a build ID (e.g. `-Wl,--build-id=none`).
+ */
+pub(crate) fn read_build_id() -> Option<String> {
+    let path = std::env::current_exe().ok()?;
+    let file = File::open(path).ok()?;
+    let cache = ReadCache::new(file);
+    let object = object::File::parse(&cache).ok()?;
+
+    if let Ok(Some(id)) = object.build_id() {
+        return Some(to_hex(id));
+    }
+
+    if let Ok(Some(uuid)) = object.mach_uuid() {
+        return Some(format_uuid(&uuid));
+    }
+
+    if let Ok(Some(info)) = object.pdb_info() {
+        return Some(format_pdb_id(&info.guid(), info.age()));
+    }
+
+    None
+}
+
+/**
+ * Encodes bytes as a lowercase hex string.
+ */
+fn to_hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/**
+ * Formats 16 raw bytes as a hyphenated UUID (`8-4-4-4-12`).
+ */
+fn format_uuid(bytes: &[u8; 16]) -> String {
+    let hex = to_hex(bytes);
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[0..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..32]
+    )
+}
+
+/**
+ * Formats a CodeView GUID + age as a symbol-server key.
+ *
+ * The GUID is stored as a little-endian struct (`u32`, `u16`, `u16`,
+ * `[u8; 8]`), so the first three fields are byte-swapped before printing.
+ */
+fn format_pdb_id(guid: &[u8; 16], age: u32) -> String {
+    let data1 = u32::from_le_bytes([guid[0], guid[1], guid[2], guid[3]]);
+    let data2 = u16::from_le_bytes([guid[4], guid[5]]);
+    let data3 = u16::from_le_bytes([guid[6], guid[7]]);
+
+    format!(
+        "{data1:08X}{data2:04X}{data3:04X}{}{age:X}",
+        to_hex(&guid[8..]).to_uppercase()
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * Verifies the Mach-O UUID and PDB identifier layouts.
+     */
+    #[test]
+    fn test_format_ids() {
+        let bytes: [u8; 16] = [
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, 0x09, 0x0a, 0x0b, 0x0c, 0x0d, 0x0e,
+            0x0f, 0x10,
+        ];
+
+        assert_eq!(format_uuid(&bytes), "01020304-0506-0708-090a-0b0c0d0e0f10");
+        assert_eq!(
+            format_pdb_id(&bytes, 1),
+            "0403020106050807090A0B0C0D0E0F101"
+        );
+    }
+}
@@ -0,0 +1,218 @@
+/*!
+ * Event builder — `event("title")...capture()`.
+ *
+ * Building an `EventData` with a struct literal means spelling out every
+ * field, and remembering that a raw backtrace is passed separately. The
+ * builder sets only what is given and sends the event through the same
+ * pipeline as every other capture:
+ *
+ * ```ignore
+ * hawk::event("payment declined")
+ *     .level(hawk::Level::Warn)
+ *     .user(hawk::User::new("u-42"))
+ *     .context("order", json!({ "id": "A-17", "total": 99.5 }))
+ *     .fingerprint(["payment-declined"])
+ *     .capture_backtrace()
+ *     .capture();
+ * ```
+ */
+
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use crate::protocol::types::{BacktraceFrame, EventData, User};
+use crate::stacktrace::Backtrace;
+
+/**
+ * Severity of an event, sent as the event type.
+ */
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Level {
+    Fatal,
+    Error,
+    Warn,
+    Info,
+    Debug,
+}
+
+impl Level {
+    /**
+     * The event type: `"fatal"`, `"error"`, `"warning"`, `"info"`, or
+     * `"debug"` — the same names as log integrations use.
+     */
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Level::Fatal => "fatal",
+            Level::Error => "error",
+            Level::Warn => "warning",
+            Level::Info => "info",
+            Level::Debug => "debug",
+        }
+    }
+}
+
+/**
+ * Starts building an event with the given title.
+ */
+pub fn event(title: impl Into<String>) -> EventBuilder {
+    EventBuilder {
+        event: EventData::new(title),
+        backtrace: None,
+        dropped: false,
+    }
+}
+
+/**
+ * Fluent builder of an event, created by `event()`.
+ */
+#[must_use = "an event is only sent by `capture()`"]
+pub struct EventBuilder {
+    event: EventData,
+
+    /// Raw backtrace, resolved on the worker thread.
+    backtrace: Option<Backtrace>,
+
+    /// Whether `capture_backtrace()` found the event would be dropped:
+    /// `capture()` then drops it, instead of sending it without frames.
+    dropped: bool,
+}
+
+impl EventBuilder {
+    /**
+     * Sets the severity, sent as the event type.
+     */
+    pub fn level(self, level: Level) -> Self {
+        self.event_type(level.as_str())
+    }
+
+    /**
+     * Sets the event type, e.g. an error type name such as `"TimeoutError"`.
+     */
+    pub fn event_type(mut self, event_type: impl Into<String>) -> Self {
+        self.event.event_type = Some(event_type.into());
+        self
+    }
+
+    /**
+     * Attaches a backtrace captured with `Backtrace::new_unresolved()`;
+     * symbols are resolved on the worker thread.
+     */
+    pub fn backtrace(mut self, backtrace: Backtrace) -> Self {
+        self.backtrace = Some(backtrace);
+        self
+    }
+
+    /**
+     * Attaches the backtrace of the current call stack. Skipped if the
+     * event would be dropped anyway (see `accepts_events()`), in which
+     * case `capture()` drops it.
+     */
+    pub fn capture_backtrace(mut self) -> Self {
+        if !crate::accepts_events() {
+            self.dropped = true;
+            return self;
+        }
+        self.backtrace(Backtrace::new_unresolved())
+    }
+
+    /**
+     * Sets frames built by hand, e.g. from another language's stack
+     * trace. They are kept on top of a raw `backtrace`, if any.
+     */
+    pub fn frames(mut self, frames: Vec<BacktraceFrame>) -> Self {
+        self.event.backtrace = Some(frames);
+        self
+    }
+
+    /**
+     * Sets the user affected by this event.
+     */
+    pub fn user(mut self, user: User) -> Self {
+        self.event.user = Some(user);
+        self
+    }
+
+    /**
+     * Adds a context entry. Values that fail to serialize are sent as
+     * `null`.
+     */
+    pub fn context(mut self, key: impl Into<String>, value: impl Serialize) -> Self {
+        let value = serde_json::to_value(value).unwrap_or(Value::Null);
+        if let Value::Object(context) = self
+            .event
+            .context
+            .get_or_insert_with(|| Value::Object(Map::new()))
+        {
+            context.insert(key.into(), value);
+        }
+        self
+    }
+
+    /**
+     * Sets the grouping key: events with equal fingerprints are grouped
+     * together.
+     */
+    pub fn fingerprint<I>(mut self, fingerprint: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<String>,
+    {
+        self.event.fingerprint = Some(fingerprint.into_iter().map(Into::into).collect());
+        self
+    }
+
+    /**
+     * Returns the event without sending it, e.g. to send it later with
+     * `capture_event()`. A raw backtrace is dropped.
+     */
+    pub fn build(self) -> EventData {
+        self.event
+    }
+
+    /**
+     * Sends the event. Silent no-op if the SDK has not been initialized.
+     */
+    pub fn capture(self) {
+        if self.dropped {
+            return;
+        }
+        match self.backtrace {
+            Some(backtrace) => crate::capture_event_with_backtrace(self.event, backtrace),
+            None => crate::capture_event(self.event),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    /**
+     * Verifies that each setter fills its field and that context entries
+     * accumulate.
+     */
+    #[test]
+    fn test_event_builder() {
+        let event = event("payment declined")
+            .level(Level::Warn)
+            .user(User::new("u-42"))
+            .context("order", json!({ "id": "A-17" }))
+            .context("attempt", 2)
+            .fingerprint(["payment-declined"])
+            .build();
+
+        assert_eq!(event.title, "payment declined");
+        assert_eq!(event.event_type.as_deref(), Some("warning"));
+        assert_eq!(event.user, Some(User::new("u-42")));
+        assert_eq!(
+            event.context,
+            Some(json!({ "order": { "id": "A-17" }, "attempt": 2 }))
+        );
+        assert_eq!(
+            event.fingerprint,
+            Some(vec!["payment-declined".to_string()])
+        );
+        assert!(event.backtrace.is_none());
+    }
+}
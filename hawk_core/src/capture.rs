@@ -0,0 +1,117 @@
+/*!
+ * Event capture — keeping events in memory instead of sending them.
+ *
+ * Meant for tests of error-reporting paths. Inside `capture_events()`,
+ * events sent from the calling thread go through the whole pipeline
+ * (scopes, breadcrumbs, `before_send`, scrubbing, truncation) and are then
+ * collected, with their backtrace resolved, rather than enqueued. Events
+ * from other threads are unaffected.
+ *
+ * Capture is per thread so that tests running in parallel each see only
+ * their own events. It still needs an initialized client; the `hawk_test`
+ * crate sets one up, with the test transport: events, transactions, and
+ * metrics not captured are dropped instead of being queued, so nothing
+ * reaches the network.
+ */
+
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use crate::protocol::types::EventData;
+
+thread_local! {
+    /// One buffer per active `capture_events()` call, innermost last.
+    static CAPTURED: RefCell<Vec<Vec<EventData>>> = const { RefCell::new(Vec::new()) };
+}
+
+/// Whether the test transport replaces delivery (`use_test_transport()`).
+static TEST_TRANSPORT: AtomicBool = AtomicBool::new(false);
+
+/**
+ * Runs `f`, returning its result along with the events sent from this
+ * thread meanwhile. Nested calls capture into the innermost one only.
+ */
+pub fn capture_events<R>(f: impl FnOnce() -> R) -> (R, Vec<EventData>) {
+    struct PopOnDrop;
+
+    impl Drop for PopOnDrop {
+        fn drop(&mut self) {
+            CAPTURED.with(|captured| {
+                captured.borrow_mut().pop();
+            });
+        }
+    }
+
+    CAPTURED.with(|captured| captured.borrow_mut().push(Vec::new()));
+    let pop = PopOnDrop;
+
+    let result = f();
+
+    let events = CAPTURED.with(|captured| captured.borrow_mut().last_mut().map(std::mem::take));
+    drop(pop);
+    (result, events.unwrap_or_default())
+}
+
+/**
+ * Whether events sent from this thread are currently captured.
+ */
+pub fn is_capturing() -> bool {
+    CAPTURED.with(|captured| !captured.borrow().is_empty())
+}
+
+/**
+ * Switches the process to the test transport: from now on, events sent
+ * inside `capture_events()` are captured and everything else the client
+ * would queue — other events, transactions, metrics — is dropped.
+ */
+#[doc(hidden)]
+pub fn use_test_transport() {
+    TEST_TRANSPORT.store(true, Ordering::Relaxed);
+}
+
+/**
+ * Whether `use_test_transport()` was called.
+ */
+pub(crate) fn uses_test_transport() -> bool {
+    TEST_TRANSPORT.load(Ordering::Relaxed)
+}
+
+/**
+ * Keeps the event if this thread is capturing. Gives it back otherwise.
+ */
+pub(crate) fn intercept(event: EventData) -> Option<EventData> {
+    CAPTURED.with(|captured| match captured.borrow_mut().last_mut() {
+        Some(events) => {
+            events.push(event);
+            None
+        }
+        None => Some(event),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * Verifies that nested captures keep their own events and that nothing
+     * is kept outside of a capture.
+     */
+    #[test]
+    fn test_capture_events() {
+        assert!(intercept(EventData::new("outside")).is_some());
+
+        let ((), outer) = capture_events(|| {
+            assert!(intercept(EventData::new("first")).is_none());
+            let ((), inner) = capture_events(|| {
+                intercept(EventData::new("inner"));
+            });
+            assert_eq!(inner[0].title, "inner");
+            intercept(EventData::new("second"));
+        });
+
+        let titles: Vec<_> = outer.iter().map(|event| event.title.as_str()).collect();
+        assert_eq!(titles, ["first", "second"]);
+        assert!(!is_capturing());
+    }
+}
@@ -13,15 +13,44 @@
  * The client is intentionally **not** `Clone` — there is exactly one
  * instance per process, held in the `OnceLock`.
  */
-use std::sync::{Arc, OnceLock};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 use std::time::Duration;
 
 use crossbeam_channel::{Sender, TrySendError};
 
+use crate::build_id;
+use crate::capture;
+use crate::clock::{self, Clock};
+use crate::context::{self, ContextManager};
+use crate::dedupe::Deduper;
+use crate::event_id::{self, EventIdGenerator};
+use crate::log_tail::LogTail;
+use crate::metrics::{Aggregator, MetricKind};
+use crate::overhead;
+use crate::persisted::PersistedEvents;
+use crate::pretty::{PrintEvents, Printer};
+use crate::process::{self, ProcessInfo};
 use crate::protocol::constants::CATCHER_TYPE;
+use crate::protocol::metrics::HawkMetrics;
+use crate::protocol::performance::{HawkTransaction, TransactionData};
 use crate::protocol::token;
-use crate::protocol::types::{EventData, HawkEvent};
-use crate::transport::{FlushSignal, Transport, Worker, WorkerMsg};
+use crate::protocol::types::{Addons, Breadcrumb, EventData, HawkEvent};
+use crate::sampling::Sampler;
+use crate::sanitize;
+use crate::scrubbing::{Scrubber, DEFAULT_SCRUB_KEYS};
+use crate::spool::SpoolCipher;
+use crate::stacktrace::{self, Backtrace};
+use crate::status;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::transport::FlushSignal;
+use crate::transport::{
+    json_size, QueueSlot, QueuedEvent, Route, Transport, WireEncoding, Worker, WorkerMsg,
+};
+use crate::truncation::{
+    self, DEFAULT_MAX_CONTEXT_DEPTH, DEFAULT_MAX_CONTEXT_NODES, DEFAULT_MAX_STRING_LENGTH,
+};
 
 // ---------------------------------------------------------------------------
 // Global singleton
@@ -44,10 +73,25 @@ pub fn get_client() -> Option<&'static Client> {
     GLOBAL_CLIENT.get()
 }
 
+/**
+ * Returns the global client if an event captured now would be kept, so
+ * that capture paths can skip the backtrace and the event when it would
+ * be dropped anyway.
+ */
+pub(crate) fn accepting_client() -> Option<&'static Client> {
+    get_client().filter(|client| client.accepts_events())
+}
+
 // ---------------------------------------------------------------------------
 // Options
 // ---------------------------------------------------------------------------
 
+/// Grouping callback of `Options::group_by`.
+type GroupBy = Arc<dyn Fn(&EventData) -> Option<Vec<String>> + Send + Sync>;
+
+/// Dormancy callback of `Options::on_dormant`.
+type OnDormant = Arc<dyn Fn(Duration, &str) + Send + Sync>;
+
 /**
  * Configuration options for the Hawk SDK.
  *
@@ -67,7 +111,6 @@ pub fn get_client() -> Option<&'static Client> {
  * });
  * ```
  */
-#[derive(Default)]
 pub struct Options {
     /// Optional callback invoked before each event is sent.
     ///
@@ -80,6 +123,335 @@ pub struct Options {
     ///
     /// If not set, events are sent as-is.
     pub before_send: Option<Arc<dyn Fn(EventData) -> Option<EventData> + Send + Sync>>,
+
+    /// Optional callback computing the grouping key of each event that
+    /// has no `fingerprint` yet, after `before_send`, scrubbing, and
+    /// truncation — e.g. the title with UUIDs stripped, so that
+    /// `"order 3f2a... failed"` events group together without rewriting
+    /// their titles. `None` keeps the default grouping.
+    ///
+    /// If the callback panics, the event is sent without a fingerprint
+    /// and a warning is printed to stderr.
+    pub group_by: Option<GroupBy>,
+
+    /// Drop events identical to one sent less than this long ago: same
+    /// fingerprint, or same type and title if they have none. The number
+    /// of dropped repeats is attached to the next occurrence sent, under
+    /// `dedupe.suppressed` in its context. Defaults to `None` (every
+    /// event is sent).
+    pub dedupe_window: Option<Duration>,
+
+    /// Share of events sent, from `0.0` (none) to `1.0` (all), drawn
+    /// independently for each event, or each batch. A sampled-out event
+    /// is dropped before its backtrace is captured or the event is built
+    /// (see `accepts_events()`). Events captured with `capture_events()`
+    /// are all kept. Defaults to `1.0`.
+    pub sample_rate: f64,
+
+    /// URL events are POSTed to instead of the collector derived from the
+    /// token (`https://{integrationId}.k1.hawk.so/`) — a self-hosted
+    /// collector, a proxy, or a mock collector in tests. The token is
+    /// still validated and sent in every envelope. Defaults to `None`.
+    pub collector_endpoint: Option<String>,
+
+    /// Additional projects every event, transaction, and metrics payload
+    /// is delivered to as well, e.g. a company-wide aggregate next to the
+    /// team's project. Delivery errors and rate limits are tracked per
+    /// destination (`status().destinations`). Defaults to empty.
+    pub destinations: Vec<Destination>,
+
+    /// Mirror a fraction of events to a secondary collector, e.g. a
+    /// staging Hawk instance under evaluation during a backend migration.
+    /// Its failures never affect the other destinations. Defaults to
+    /// `None`.
+    pub shadow: Option<Shadow>,
+
+    /// Make `init()` send a `HEAD` request to the collector endpoint and
+    /// fail if it cannot be reached (DNS, connection, TLS errors, HTTP
+    /// 5xx), instead of finding out when the first event silently fails
+    /// to be delivered. Blocks `init()` for up to 5 s. Not supported on
+    /// wasm targets, where the check is skipped. Defaults to `false`.
+    pub validate_connection: bool,
+
+    /// Serialization of payloads on the wire. A binary encoding
+    /// (`MessagePack` with the `msgpack` feature, `Cbor` with the `cbor`
+    /// feature) is smaller and cheaper for high event volumes; it is used
+    /// with collectors that advertise it, JSON otherwise. Not supported in
+    /// the browser. Defaults to `WireEncoding::Json`.
+    pub wire_encoding: WireEncoding,
+
+    /// Called once, on the worker thread, when the collector starts rate
+    /// limiting the client (`429 Too Many Requests`), with the time until
+    /// payloads are sent again. Meanwhile payloads are dropped; apps can
+    /// capture less until then. `status()` reports the limit too.
+    /// Defaults to `None`.
+    pub on_rate_limit: Option<Arc<dyn Fn(Duration) + Send + Sync>>,
+
+    /// Called once, on the worker thread, when the collector puts the
+    /// client to sleep because the project is disabled (`403`) or over
+    /// its quota (`402`), with the time until payloads are sent again and
+    /// the reason, `"disabled"` or `"over quota"`. Meanwhile events are
+    /// dropped before they are built. `status()` reports it too.
+    /// Defaults to `None`.
+    pub on_dormant: Option<OnDormant>,
+
+    /// Maximum estimated size, in bytes of JSON, of the events waiting in
+    /// the queue; events that would exceed it are dropped, like those
+    /// arriving when the queue is full. Bounds the queue's memory when
+    /// events are large. `0` disables the limit. Defaults to 16 MiB.
+    pub max_queue_bytes: usize,
+
+    /// Source of the timestamps of breadcrumbs, transactions, metrics
+    /// intervals, and `addons.queuedAt`, and of the time rate limit and
+    /// dormancy windows are measured against, e.g. a fixed clock for
+    /// deterministic payloads in tests. Durations are still measured with
+    /// the monotonic clock. Defaults to `None` (the system clock).
+    pub clock: Option<Arc<dyn Clock>>,
+
+    /// Generates the ID of each event (`addons.eventId`) instead of a
+    /// random UUID, e.g. ULIDs or IDs with a tenant prefix. Called on the
+    /// thread reporting the event; a panic falls back to a UUID.
+    /// Defaults to `None`.
+    pub event_id_generator: Option<Arc<dyn Fn() -> String + Send + Sync>>,
+
+    /// Module path prefixes whose frames are always marked `in_app`,
+    /// e.g. `["my_company_lib"]` for an internal crate that is pulled in
+    /// through the cargo registry or a git dependency.
+    ///
+    /// Frames not matching any prefix are classified by their file path:
+    /// workspace sources are in app, registry / toolchain sources are not.
+    pub in_app_include: Vec<String>,
+
+    /// Maximum number of frames kept per backtrace. Deeper stacks are cut,
+    /// keeping the topmost (most recent) frames. Defaults to 100.
+    pub max_backtrace_frames: usize,
+
+    /// Attach each frame's raw instruction address and its offset within
+    /// the containing binary. Combined with the build ID this lets offline
+    /// tools re-symbolize events captured from stripped binaries.
+    /// Defaults to `false`.
+    pub include_addresses: bool,
+
+    /// Absolute path of the project on the build machine, replaced with
+    /// `<workspace>` in frame file paths — e.g. `env!("CARGO_MANIFEST_DIR")`.
+    /// Cargo registry paths are always scrubbed, home directories unless
+    /// `send_default_pii` is set.
+    pub workspace_root: Option<String>,
+
+    /// Number of most recent breadcrumbs attached to each event.
+    /// `0` disables breadcrumbs. Defaults to 20.
+    pub max_breadcrumbs: usize,
+
+    /// Size, in bytes, of a ring buffer of the most recent log lines seen
+    /// by the `log` and `tracing` integrations, attached to fatal events
+    /// only (panics, crashes) under `log_tail` in their context. `0`
+    /// disables it. Defaults to `0`.
+    pub log_tail_bytes: usize,
+
+    /// File keeping the restart history of the process across runs: each
+    /// event then carries under `process` the number of earlier runs, how
+    /// the previous one ended, and how many in a row ended uncleanly
+    /// (without dropping the `Guard`). Must persist across restarts and
+    /// not be shared between processes. Process uptime is always sent.
+    /// Defaults to `None`.
+    pub state_file: Option<PathBuf>,
+
+    /// Time after `init()` within which a run that ends uncleanly — a
+    /// panic, a crash, a kill — counts as a startup crash. Needs
+    /// `state_file`. Defaults to 5 seconds.
+    pub startup_crash_window: Duration,
+
+    /// Consecutive runs ending in a startup crash that make a crash loop:
+    /// their fatal events get `startup_crash: true`, those left by earlier
+    /// runs are delivered before `init()` returns, and
+    /// `is_crash_looping()` turns true. `0` turns detection off. Needs
+    /// `state_file`. Defaults to `2`.
+    pub startup_crash_threshold: u32,
+
+    /// Directory fatal events (panics, aborts) are written to until the
+    /// worker has sent them. Events left there by a run that ended first
+    /// are sent at the next `init()`, flagged with `previous_run: true` in
+    /// their context. Must persist across restarts and not be shared
+    /// between processes. Defaults to `None`.
+    pub fatal_event_dir: Option<PathBuf>,
+
+    /// 256-bit key that events in `fatal_event_dir` and native crash
+    /// records are encrypted with (ChaCha20-Poly1305), so that user data
+    /// is not left on disk in plaintext. Keep it outside the app's data,
+    /// e.g. in the OS keyring: files written with another key are
+    /// discarded. Minidumps are not written while it is set. Not supported
+    /// on wasm targets. Defaults to `None`.
+    pub spool_key: Option<[u8; 32]>,
+
+    /// Maximum length, in characters, of every string in an event (title,
+    /// context values, breadcrumbs). Longer strings are cut and end with
+    /// `"...[truncated]"`. `0` disables the limit. Defaults to 8192.
+    pub max_string_length: usize,
+
+    /// Maximum nesting depth of event context and breadcrumb data; deeper
+    /// arrays and objects are replaced with `"...[truncated]"`. `0`
+    /// disables the limit. Defaults to 16.
+    pub max_context_depth: usize,
+
+    /// Maximum number of values in event context and breadcrumb data,
+    /// counted breadth first; the rest is replaced with a
+    /// `"...[truncated]"` marker. `0` disables the limit. Defaults to 5000.
+    pub max_context_nodes: usize,
+
+    /// Redact values stored under `scrub_keys` in event context and
+    /// breadcrumb data, and card numbers, bearer tokens, and email
+    /// addresses in any event string, before sending. Defaults to `true`.
+    pub scrub_data: bool,
+
+    /// Keys whose values are redacted, matched as whole words of a key
+    /// (`X-Api-Key`, `csrf_token`, but not `max_tokens` or `token_count`)
+    /// or at its end, ignoring case, `-`, and `_`. Defaults to
+    /// `DEFAULT_SCRUB_KEYS` (password, secret, token, authorization,
+    /// cookie, api_key).
+    pub scrub_keys: Vec<String>,
+
+    /// Environment variables kept in environment snapshots (context
+    /// objects under `env` / `environment` keys) although their names look
+    /// like credentials (`*_KEY`, `*_SECRET`, `*_TOKEN`, `AWS_*`), matched
+    /// ignoring case. Empty by default.
+    pub scrub_env_allowlist: Vec<String>,
+
+    /// Request headers whose values are replaced with `[Filtered]` in
+    /// `RequestContext`, matched ignoring case — on top of
+    /// `Authorization`, `Cookie`, and `Set-Cookie`, which are scrubbed
+    /// unless `send_default_pii` is set. Empty by default.
+    pub scrub_headers: Vec<String>,
+
+    /// Strict mode: when set, only these keys are sent and everything
+    /// else in the context and in breadcrumb data is dropped. Entries are
+    /// top-level keys (`"job"`) or dotted paths (`"request.method"`).
+    /// Defaults to `None` (everything is sent).
+    pub context_allowlist: Option<Vec<String>>,
+
+    /// Collect personal data automatically: usernames in source paths,
+    /// and IP addresses, user emails, and full request headers and
+    /// cookies in integrations that see them. Defaults to `false`.
+    pub send_default_pii: bool,
+
+    /// Regexes whose matches are replaced with `[Filtered]` in the title,
+    /// context strings, and breadcrumbs of every event, even with
+    /// `scrub_data` off. `init()` fails on an invalid pattern. Empty by
+    /// default.
+    pub scrub_patterns: Vec<String>,
+
+    /// Print every event to stderr in a readable, colored form — title,
+    /// type, top frames, context changes since the previous event — in
+    /// addition to (`AndSend`) or instead of (`Instead`) sending it. For
+    /// local development. Defaults to `Off`.
+    pub print_events: PrintEvents,
+}
+
+/// Default for `Options::max_backtrace_frames`. Deep async / recursive
+/// stacks otherwise inflate payloads with thousands of frames.
+pub const DEFAULT_MAX_BACKTRACE_FRAMES: usize = 100;
+
+/**
+ * An additional project events are delivered to (`Options::destinations`).
+ */
+#[derive(Clone, Debug)]
+pub struct Destination {
+    /// Integration token of the project.
+    pub token: String,
+
+    /// Collector URL, derived from the token when `None`.
+    pub endpoint: Option<String>,
+}
+
+impl Destination {
+    /**
+     * Resolves the destination into a route, validating its token.
+     */
+    fn route(&self) -> Result<Route, String> {
+        let decoded = token::decode_token(&self.token)
+            .map_err(|err| format!("Invalid token in Options::destinations: {err}"))?;
+        Ok(Route {
+            endpoint: self
+                .endpoint
+                .clone()
+                .unwrap_or_else(|| token::default_endpoint(&decoded.integration_id)),
+            token: self.token.as_str().into(),
+        })
+    }
+}
+
+/**
+ * Shadow traffic to a secondary collector (`Options::shadow`).
+ *
+ * Mirrored events carry the primary token. Which events are mirrored
+ * depends only on their ID, so that two processes with the same rate
+ * mirror the same share. Transactions and metrics are not mirrored, and
+ * `validate_connection` does not check the shadow collector.
+ */
+#[derive(Clone, Debug)]
+pub struct Shadow {
+    /// Collector URL of the secondary instance.
+    pub endpoint: String,
+
+    /// Fraction of events mirrored, from `0.0` (none) to `1.0` (all).
+    pub rate: f64,
+}
+
+/// Default for `Options::max_breadcrumbs`.
+pub const DEFAULT_MAX_BREADCRUMBS: usize = 20;
+
+/// Default for `Options::max_queue_bytes`: 16 MiB.
+pub const DEFAULT_MAX_QUEUE_BYTES: usize = 16 * 1024 * 1024;
+
+/// Default for `Options::startup_crash_window`.
+pub const DEFAULT_STARTUP_CRASH_WINDOW: Duration = Duration::from_secs(5);
+
+/// Default for `Options::startup_crash_threshold`.
+pub const DEFAULT_STARTUP_CRASH_THRESHOLD: u32 = 2;
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            before_send: None,
+            group_by: None,
+            dedupe_window: None,
+            sample_rate: 1.0,
+            collector_endpoint: None,
+            destinations: Vec::new(),
+            shadow: None,
+            validate_connection: false,
+            wire_encoding: WireEncoding::Json,
+            on_rate_limit: None,
+            on_dormant: None,
+            max_queue_bytes: DEFAULT_MAX_QUEUE_BYTES,
+            clock: None,
+            event_id_generator: None,
+            in_app_include: Vec::new(),
+            max_backtrace_frames: DEFAULT_MAX_BACKTRACE_FRAMES,
+            include_addresses: false,
+            workspace_root: None,
+            max_breadcrumbs: DEFAULT_MAX_BREADCRUMBS,
+            log_tail_bytes: 0,
+            state_file: None,
+            startup_crash_window: DEFAULT_STARTUP_CRASH_WINDOW,
+            startup_crash_threshold: DEFAULT_STARTUP_CRASH_THRESHOLD,
+            fatal_event_dir: None,
+            spool_key: None,
+            max_string_length: DEFAULT_MAX_STRING_LENGTH,
+            max_context_depth: DEFAULT_MAX_CONTEXT_DEPTH,
+            max_context_nodes: DEFAULT_MAX_CONTEXT_NODES,
+            scrub_data: true,
+            scrub_keys: DEFAULT_SCRUB_KEYS
+                .iter()
+                .map(|key| key.to_string())
+                .collect(),
+            scrub_env_allowlist: Vec::new(),
+            scrub_headers: Vec::new(),
+            context_allowlist: None,
+            send_default_pii: false,
+            scrub_patterns: Vec::new(),
+            print_events: PrintEvents::Off,
+        }
+    }
 }
 
 // ---------------------------------------------------------------------------
@@ -88,10 +460,11 @@ pub struct Options {
 
 /// Bounded channel capacity — internal implementation detail.
 /// When full, new events are silently dropped (back-pressure).
-const QUEUE_CAPACITY: usize = 100;
+pub(crate) const QUEUE_CAPACITY: usize = 100;
 
 /// Maximum time that `flush()` will block waiting for the worker
 /// to drain pending events before giving up.
+#[cfg(not(target_arch = "wasm32"))]
 const FLUSH_TIMEOUT: Duration = Duration::from_secs(2);
 
 // ---------------------------------------------------------------------------
@@ -107,13 +480,105 @@ const FLUSH_TIMEOUT: Duration = Duration::from_secs(2);
  */
 pub struct Client {
     /// Raw base64-encoded integration token — included in every `HawkEvent`.
-    token: String,
+    /// Shared with the envelopes instead of copied into each.
+    token: Arc<str>,
 
     /// Sender side of the bounded event channel.
     sender: Sender<WorkerMsg>,
 
+    /// Destinations payloads are POSTed to, the primary one first.
+    routes: Vec<Route>,
+
+    /// Destination events are mirrored to (`shadow`), with the primary
+    /// token.
+    shadow_route: Option<Route>,
+
+    /// Byte budget of the queued events; `0` for no limit.
+    max_queue_bytes: usize,
+
+    /// Optional rate limit callback (`on_rate_limit`).
+    on_rate_limit: Option<Arc<dyn Fn(Duration) + Send + Sync>>,
+
+    /// Optional dormancy callback (`on_dormant`).
+    on_dormant: Option<OnDormant>,
+
+    /// Source of event timestamps (`clock`).
+    clock: Arc<dyn Clock>,
+
+    /// Optional event ID generator (`event_id_generator`).
+    event_id_generator: Option<EventIdGenerator>,
+
     /// Optional before_send callback.
     before_send: Option<Arc<dyn Fn(EventData) -> Option<EventData> + Send + Sync>>,
+
+    /// Optional grouping callback (`group_by`).
+    group_by: Option<GroupBy>,
+
+    /// Drops repeated events; `None` unless `dedupe_window` is set.
+    deduper: Option<Deduper>,
+
+    /// Keeps a share of events (`sample_rate`).
+    sampler: Sampler,
+
+    /// Module prefixes always treated as application code.
+    in_app_include: Vec<String>,
+
+    /// Maximum number of frames kept per backtrace.
+    max_backtrace_frames: usize,
+
+    /// Whether frames carry raw instruction addresses.
+    include_addresses: bool,
+
+    /// Build-machine project path scrubbed from frame file paths.
+    workspace_root: Option<String>,
+
+    /// Whether integrations may collect personal data automatically.
+    send_default_pii: bool,
+
+    /// Extra request headers scrubbed in `RequestContext`.
+    scrub_headers: Vec<String>,
+
+    /// Integration ID decoded from the token.
+    integration_id: String,
+
+    /// Build ID of the running executable, read once at init.
+    build_id: Option<String>,
+
+    /// Process-wide context (breadcrumbs) attached to every event.
+    context: ContextManager,
+
+    /// Recent log lines for fatal events; `None` unless `log_tail_bytes`
+    /// is set.
+    log_tail: Option<LogTail>,
+
+    /// Uptime and restart history (`state_file`).
+    process: ProcessInfo,
+
+    /// Fatal events kept on disk until delivered (`fatal_event_dir`).
+    persisted: Option<PersistedEvents>,
+
+    /// Seals files on disk; `None` unless `spool_key` is set.
+    spool: Option<Arc<SpoolCipher>>,
+
+    /// Redacts sensitive values; `None` if scrubbing is disabled.
+    scrubber: Option<Scrubber>,
+
+    /// Maximum string length in events; `0` for no limit.
+    max_string_length: usize,
+
+    /// Maximum depth and number of values of context and breadcrumb
+    /// data; `0` for no limit.
+    max_context_depth: usize,
+    max_context_nodes: usize,
+
+    /// Custom metrics recorded since the last metrics payload.
+    metrics: Mutex<Aggregator>,
+
+    /// Renders events to the terminal (`print_events`).
+    printer: Printer,
+
+    /// Sequence number of the next event enqueued (`Addons::sequence`).
+    next_sequence: AtomicU64,
 }
 
 impl Client {
@@ -124,19 +589,25 @@ impl Client {
      * Subsequent calls return `Err` because the `OnceLock` is already set.
      *
      * # Steps
-     * 1. Decode the integration token to extract `integrationId`.
-     * 2. Derive the collector endpoint from the integration ID.
+     * 1. Decode the integration token to extract `integrationId`, and
+     *    compile the scrubbing patterns.
+     * 2. Derive the collector endpoint from the integration ID, unless
+     *    `collector_endpoint` is set; without a TLS feature, reject
+     *    `https://` endpoints.
      * 3. Create the bounded channel.
-     * 4. Build and spawn the transport + worker.
-     * 5. Store the client in `GLOBAL_CLIENT`.
+     * 4. Build the transport, check that the endpoint is reachable if
+     *    `validate_connection` is set, and spawn the worker.
+     * 5. Read the executable's build ID.
+     * 6. Store the client in `GLOBAL_CLIENT`.
      *
      * # Arguments
      * * `token_str` — The raw base64-encoded integration token.
      * * `options` — SDK configuration (use `Default::default()` for defaults).
      *
      * # Returns
-     * `Ok(())` on success, `Err(String)` if the token is invalid or the
-     * client has already been initialized.
+     * `Ok(())` on success, `Err(String)` if the token is invalid, the
+     * collector is unreachable (`validate_connection`) or needs TLS this
+     * build lacks, or the client has already been initialized.
      */
     pub fn init(token_str: &str, options: Options) -> Result<(), String> {
         /*
@@ -146,17 +617,106 @@ impl Client {
             return Err("Hawk SDK is already initialized".into());
         }
 
+        let (client, previous_events) = Self::new(token_str, options)?;
+
+        /*
+         * Step 6: Store in the global singleton.
+         * `set()` returns `Err(value)` if already initialized.
+         */
+        GLOBAL_CLIENT
+            .set(client)
+            .map_err(|_| "Hawk SDK is already initialized".to_string())?;
+
+        /*
+         * Step 7: Send the fatal events of runs that ended before
+         * delivering them, as they were — without this run's context.
+         */
+        if let Some(client) = get_client() {
+            let looping = client.is_crash_looping();
+            let waiting = looping && !previous_events.is_empty();
+            for mut event in previous_events {
+                if looping {
+                    process::mark_startup_crash(&mut event);
+                }
+                let hawk_event = HawkEvent::new(Arc::clone(&client.token), event);
+                let persisted = client.is_persisted(&hawk_event.payload);
+                if let Some(queued) = QueuedEvent::new(&hawk_event, persisted) {
+                    let msg = WorkerMsg::Event(Box::new(queued), None, QueueSlot::new(0));
+                    client.enqueue(msg, "event");
+                }
+            }
+            /*
+             * A crash loop may end this run before the worker gets to
+             * them: deliver them now.
+             */
+            if waiting {
+                client.flush();
+            }
+        }
+
+        Ok(())
+    }
+
+    /**
+     * Builds a client and spawns its worker (steps 1 to 5 of `init()`).
+     * Returns it along with the fatal events persisted by earlier runs.
+     */
+    pub(crate) fn new(token_str: &str, options: Options) -> Result<(Self, Vec<EventData>), String> {
         /*
          * Step 1: Decode the integration token.
          * This validates the token format and extracts the integrationId.
          */
         let decoded = token::decode_token(token_str)?;
 
+        /*
+         * Compile the scrubbing patterns too, so that an invalid one fails
+         * `init` before anything is spawned.
+         */
+        let scrubber = Scrubber::new(&options)?;
+
         /*
          * Step 2: Derive the collector endpoint from the integration ID.
          * Format: https://{integrationId}.k1.hawk.so/
          */
-        let endpoint = token::default_endpoint(&decoded.integration_id);
+        let endpoint = match options.collector_endpoint {
+            Some(ref endpoint) => endpoint.clone(),
+            None => token::default_endpoint(&decoded.integration_id),
+        };
+        let token: Arc<str> = token_str.into();
+        let mut routes = vec![Route {
+            endpoint,
+            token: Arc::clone(&token),
+        }];
+        for destination in &options.destinations {
+            routes.push(destination.route()?);
+        }
+        #[cfg(not(target_arch = "wasm32"))]
+        for endpoint in routes
+            .iter()
+            .map(|route| &route.endpoint)
+            .chain(options.shadow.as_ref().map(|shadow| &shadow.endpoint))
+        {
+            crate::transport::http::check_scheme(endpoint)?;
+        }
+        if !(0.0..=1.0).contains(&options.sample_rate) {
+            return Err(format!(
+                "Options::sample_rate must be between 0.0 and 1.0, got {}",
+                options.sample_rate
+            ));
+        }
+        if let Some(Shadow { rate, .. }) = options.shadow {
+            if !(0.0..=1.0).contains(&rate) {
+                return Err(format!(
+                    "Options::shadow rate must be between 0.0 and 1.0, got {rate}"
+                ));
+            }
+        }
+        let spool = options
+            .spool_key
+            .as_ref()
+            .map(SpoolCipher::new)
+            .transpose()?
+            .map(Arc::new);
 
         /*
          * Step 3: Create the bounded channel.
@@ -169,42 +729,560 @@ impl Client {
         /*
          * Step 4: Create the transport (HTTP client) and spawn the worker.
          */
-        let transport = Transport::new()?;
-        Worker::spawn(receiver, endpoint, transport)?;
+        options.wire_encoding.check_available()?;
+        let transport = Transport::new(options.wire_encoding)?;
+        if options.validate_connection {
+            for Route { endpoint, .. } in &routes {
+                transport
+                    .check(endpoint)
+                    .map_err(|err| format!("Hawk collector {endpoint} is unreachable: {err}"))?;
+            }
+        }
+        let shadow_route = options.shadow.as_ref().map(|shadow| Route {
+            endpoint: shadow.endpoint.clone(),
+            token: Arc::clone(&token),
+        });
+        Worker::spawn(receiver, routes.clone(), options.shadow, transport)?;
 
         /*
-         * Step 5: Store in the global singleton.
-         * `set()` returns `Err(value)` if already initialized.
+         * Step 5: Read the build ID once — it never changes for the
+         * lifetime of the process.
          */
+        let build_id = build_id::read_build_id();
+
+        let clock = clock::from_options(options.clock);
+        let (persisted, previous_events) = match options.fatal_event_dir {
+            Some(dir) => {
+                let (persisted, previous_events) = PersistedEvents::open(dir, spool.clone());
+                (Some(persisted), previous_events)
+            }
+            None => (None, Vec::new()),
+        };
+        let metrics = Mutex::new(Aggregator::new(clock.now_millis()));
+
         let client = Client {
-            token: token_str.to_string(),
+            token,
             sender,
+            routes,
+            shadow_route,
+            max_queue_bytes: options.max_queue_bytes,
+            on_rate_limit: options.on_rate_limit,
+            on_dormant: options.on_dormant,
+            clock,
+            event_id_generator: options.event_id_generator,
             before_send: options.before_send,
+            group_by: options.group_by,
+            deduper: options.dedupe_window.map(Deduper::new),
+            sampler: Sampler::new(options.sample_rate),
+            in_app_include: options.in_app_include,
+            max_backtrace_frames: options.max_backtrace_frames,
+            include_addresses: options.include_addresses,
+            workspace_root: options.workspace_root,
+            send_default_pii: options.send_default_pii,
+            scrub_headers: options.scrub_headers.clone(),
+            integration_id: decoded.integration_id.clone(),
+            build_id,
+            context: ContextManager::new(options.max_breadcrumbs),
+            log_tail: (options.log_tail_bytes > 0).then(|| LogTail::new(options.log_tail_bytes)),
+            process: ProcessInfo::start(
+                options.state_file,
+                options.startup_crash_window,
+                options.startup_crash_threshold,
+            ),
+            persisted,
+            spool,
+            scrubber,
+            max_string_length: options.max_string_length,
+            max_context_depth: options.max_context_depth,
+            max_context_nodes: options.max_context_nodes,
+            metrics,
+            printer: Printer::new(options.print_events),
+            next_sequence: AtomicU64::new(0),
         };
+        Ok((client, previous_events))
+    }
 
-        GLOBAL_CLIENT
-            .set(client)
-            .map_err(|_| "Hawk SDK is already initialized".to_string())?;
+    /**
+     * Module prefixes configured via `Options::in_app_include`.
+     */
+    pub fn in_app_include(&self) -> &[String] {
+        &self.in_app_include
+    }
 
-        Ok(())
+    /**
+     * Frame limit configured via `Options::max_backtrace_frames`.
+     */
+    pub fn max_backtrace_frames(&self) -> usize {
+        self.max_backtrace_frames
+    }
+
+    /**
+     * Whether `Options::include_addresses` is enabled.
+     */
+    pub fn include_addresses(&self) -> bool {
+        self.include_addresses
+    }
+
+    /**
+     * Project path configured via `Options::workspace_root`.
+     */
+    pub fn workspace_root(&self) -> Option<&str> {
+        self.workspace_root.as_deref()
+    }
+
+    /**
+     * Whether `Options::send_default_pii` is enabled.
+     */
+    pub fn send_default_pii(&self) -> bool {
+        self.send_default_pii
+    }
+
+    /**
+     * Request headers configured via `Options::scrub_headers`.
+     */
+    pub fn scrub_headers(&self) -> &[String] {
+        &self.scrub_headers
+    }
+
+    /**
+     * Integration ID decoded from the token.
+     */
+    pub fn integration_id(&self) -> &str {
+        &self.integration_id
+    }
+
+    /**
+     * Build ID of the running executable, if one was found at init.
+     */
+    pub fn build_id(&self) -> Option<&str> {
+        self.build_id.as_deref()
+    }
+
+    /**
+     * The key files on disk are sealed with, if `spool_key` is set.
+     */
+    pub fn spool_cipher(&self) -> Option<&SpoolCipher> {
+        self.spool.as_deref()
+    }
+
+    /**
+     * Records a breadcrumb for subsequent events, its data capped in
+     * shape (`max_context_depth`, `max_context_nodes`).
+     */
+    pub fn add_breadcrumb(&self, mut breadcrumb: Breadcrumb) {
+        if let Some(data) = breadcrumb.data.as_mut() {
+            truncation::limit_value(data, self.max_context_depth, self.max_context_nodes);
+        }
+        self.context.add_breadcrumb(breadcrumb);
+    }
+
+    /**
+     * Drops all recorded breadcrumbs.
+     */
+    pub fn clear_breadcrumbs(&self) {
+        self.context.clear_breadcrumbs();
+    }
+
+    /**
+     * Forgets what the client remembers from the events sent so far:
+     * breadcrumbs, dedupe history, log tail, and metrics not yet sent.
+     */
+    pub(crate) fn reset(&self) {
+        self.context.clear_breadcrumbs();
+        if let Some(ref deduper) = self.deduper {
+            deduper.clear();
+        }
+        if let Some(ref log_tail) = self.log_tail {
+            log_tail.clear();
+        }
+        self.lock_metrics().take(self.clock.now_millis());
+    }
+
+    /**
+     * Collector URL events are POSTed to (the primary destination).
+     */
+    pub fn endpoint(&self) -> &str {
+        &self.routes[0].endpoint
+    }
+
+    /**
+     * Every destination, the primary one first.
+     */
+    pub(crate) fn routes(&self) -> &[Route] {
+        &self.routes
+    }
+
+    /**
+     * Destination events are mirrored to, if any.
+     */
+    pub(crate) fn shadow_route(&self) -> Option<&Route> {
+        self.shadow_route.as_ref()
+    }
+
+    /**
+     * Calls the `on_rate_limit` callback, if configured. A panic in it is
+     * caught, so that it cannot kill the worker.
+     */
+    pub(crate) fn notify_rate_limit(&self, retry_after: Duration) {
+        if let Some(ref callback) = self.on_rate_limit {
+            let result =
+                std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| callback(retry_after)));
+            if result.is_err() {
+                eprintln!("[Hawk] on_rate_limit panicked");
+            }
+        }
+    }
+
+    /**
+     * Calls the `on_dormant` callback, if configured. A panic in it is
+     * caught, so that it cannot kill the worker.
+     */
+    pub(crate) fn notify_dormant(&self, retry_after: Duration, reason: &str) {
+        if let Some(ref callback) = self.on_dormant {
+            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                callback(retry_after, reason)
+            }));
+            if result.is_err() {
+                eprintln!("[Hawk] on_dormant panicked");
+            }
+        }
+    }
+
+    /**
+     * Records a clean exit in the state file, if any.
+     */
+    pub fn mark_clean_exit(&self) {
+        self.process.mark_clean_exit();
+    }
+
+    /**
+     * Records a clean exit, unless a panic was reported in this run.
+     */
+    pub fn mark_clean_exit_unless_panicked(&self) {
+        self.process.mark_clean_exit_unless_panicked();
+    }
+
+    /**
+     * Whether the runs before this one were a startup crash loop.
+     */
+    pub fn is_crash_looping(&self) -> bool {
+        self.process.is_crash_looping()
+    }
+
+    /**
+     * Records that a panic was reported in this run.
+     */
+    pub fn mark_panicked(&self) {
+        self.process.mark_panicked();
+    }
+
+    /**
+     * Whether an event captured now would be kept: `false` while the queue
+     * is full or over `max_queue_bytes`, or every collector rate limits
+     * the client or puts it to sleep, and for events sampled out
+     * (`sample_rate`). Events captured in memory are always kept, and
+     * printed ones unless sampled out.
+     */
+    pub(crate) fn accepts_events(&self) -> bool {
+        if capture::is_capturing() {
+            return true;
+        }
+        if capture::uses_test_transport() {
+            return false;
+        }
+        let now = self.clock.now_millis();
+        let room = self.printer.is_enabled()
+            || (!self.sender.is_full()
+                && (self.max_queue_bytes == 0 || status::queue_bytes() < self.max_queue_bytes)
+                && !self
+                    .routes
+                    .iter()
+                    .all(|route| status::is_paused(route, now)));
+        room && self.sampler.peek()
+    }
+
+    /**
+     * Builds an event with `build` and sends it with the backtrace of the
+     * call stack, unless it would be dropped anyway (`accepts_events()`):
+     * then the event is not built and no backtrace is captured.
+     */
+    pub(crate) fn capture_with(&self, build: impl FnOnce() -> EventData) {
+        if self.accepts_events() {
+            self.send_event_with_backtrace(build(), stacktrace::capture_unresolved());
+        }
+    }
+
+    /**
+     * Whether the event, or batch, being sent is kept by `sample_rate`.
+     * Uses up the decision drawn by `accepts_events()`, if any.
+     */
+    fn sampled(&self) -> bool {
+        capture::is_capturing() || self.sampler.take()
+    }
+
+    /**
+     * Clock configured via `Options::clock`.
+     */
+    pub(crate) fn clock(&self) -> &dyn Clock {
+        self.clock.as_ref()
+    }
+
+    /**
+     * Mirrors a log line into the log tail, if enabled.
+     */
+    pub fn record_log_line(&self, line: &str) {
+        if let Some(ref log_tail) = self.log_tail {
+            log_tail.push(line);
+        }
+    }
+
+    /**
+     * Whether `record_log_line()` keeps lines (`log_tail_bytes` > 0).
+     */
+    pub fn log_tail_enabled(&self) -> bool {
+        self.log_tail.is_some()
+    }
+
+    /**
+     * Process-wide event context: breadcrumbs and tags.
+     */
+    pub(crate) fn context(&self) -> &ContextManager {
+        &self.context
     }
 
     /**
      * Enqueues a fully built `EventData` for delivery.
      *
      * This is the internal "send" path used by all public functions.
-     * It:
-     * 1. Fills in `catcher_version` if empty.
+     * Equivalent to `send_event_with_backtrace(event, None)`.
+     *
+     * # Arguments
+     * * `event` — The event data to send.
+     */
+    pub fn send_event(&self, event: EventData) {
+        self.send_event_with_backtrace(event, None);
+    }
+
+    /**
+     * Enqueues an `EventData` together with an optional *unresolved*
+     * backtrace.
+     *
+     * Unless the event is sampled out (`sample_rate`), it:
+     * 1. Merges the thread's scoped context (`with_context()`) and the
+     *    tags (`tag_scope()`), and fills in `breadcrumbs` and `addons` (build ID) if not set.
+     *    Fatal events also get the log tail (`log_tail_bytes`). Caps the
+     *    depth and size of context and breadcrumb data
+     *    (`max_context_depth`, `max_context_nodes`).
      * 2. Runs the `before_send` callback if configured.
-     * 3. Wraps the payload in a `HawkEvent` envelope.
-     * 4. Enqueues the envelope on the bounded channel (non-blocking).
+     * 3. Strips ANSI escapes and control characters, redacts sensitive
+     *    values (`scrub_data`), then truncates long strings
+     *    (`max_string_length`).
+     * 4. Computes the fingerprint with `group_by` if configured. Inside
+     *    `capture_events()`, keeps the event and stops there; otherwise
+     *    drops it if it repeats one sent within `dedupe_window`.
+     * 5. Prints the event if `print_events` is set, and stops there if
+     *    it replaces sending.
+     * 6. Numbers the event (`addons.sequence`, `addons.queuedAt`),
+     *    writes it to `fatal_event_dir` if fatal, and wraps it in a
+     *    `HawkEvent` envelope.
+     * 7. Enqueues the envelope and the raw backtrace on the bounded channel
+     *    (non-blocking). Symbols are resolved later on the worker thread,
+     *    keeping DWARF lookups off the caller's hot path.
+     *
+     * If `before_send` or `group_by` is configured, the backtrace is
+     * resolved eagerly instead so that the callbacks see the final frames.
      *
      * If the queue is full, the event is silently dropped.
      *
      * # Arguments
      * * `event` — The event data to send.
+     * * `backtrace` — Raw frame addresses captured via
+     *   `Backtrace::new_unresolved()`; resolved frames are appended after
+     *   any frames already set on `event.backtrace`.
+     */
+    pub fn send_event_with_backtrace(&self, event: EventData, backtrace: Option<Backtrace>) {
+        if !self.sampled() {
+            return;
+        }
+        let Some((hawk_event, backtrace)) = self.prepare_event(event, backtrace) else {
+            return;
+        };
+
+        let persisted = self.is_persisted(&hawk_event.payload);
+        let Some(queued) = QueuedEvent::new(&hawk_event, persisted) else {
+            return;
+        };
+
+        /*
+         * Hold the event's size against the byte budget until it is sent.
+         * Unresolved backtraces are a few words per frame and are not
+         * counted.
+         */
+        let bytes = if self.max_queue_bytes > 0 {
+            let bytes = queued.size();
+            if !status::reserve_queue_bytes(bytes, self.max_queue_bytes) {
+                eprintln!("[Hawk] Event queue is over max_queue_bytes — dropping event");
+                return;
+            }
+            bytes
+        } else {
+            0
+        };
+
+        self.enqueue(
+            WorkerMsg::Event(Box::new(queued), backtrace, QueueSlot::new(bytes)),
+            "event",
+        );
+    }
+
+    /**
+     * Enqueues related events as one batch, all tagged with the same
+     * `addons.groupCorrelationId`: they go through the pipeline one by
+     * one, then take a single slot of the queue, so that either all the
+     * events kept by the pipeline are sent or none is. Returns the
+     * correlation ID, `None` if the batch was dropped (sampled out, queue
+     * full or over `max_queue_bytes`).
      */
-    pub fn send_event(&self, mut event: EventData) {
+    pub fn send_batch(&self, events: Vec<EventData>) -> Option<String> {
+        if !self.sampled() {
+            return None;
+        }
+        let correlation_id = event_id::generate(None);
+        let built: Vec<EventData> = events
+            .into_iter()
+            .filter_map(|mut event| {
+                let _timer = overhead::Timer::event();
+                let addons = event.addons.get_or_insert_with(|| Addons {
+                    build_id: self.build_id.clone(),
+                    ..Addons::default()
+                });
+                addons.group_correlation_id = Some(correlation_id.clone());
+                self.build_event(event, None).map(|(event, _)| event)
+            })
+            .collect();
+        if built.is_empty() {
+            return Some(correlation_id);
+        }
+
+        /*
+         * Take room in the queue before anything is recorded — counted by
+         * dedupe, printed, persisted — so that a dropped batch leaves no
+         * trace. Its size is estimated from the events before numbering.
+         */
+        if self.sender.is_full() {
+            eprintln!("[Hawk] Event queue is full — dropping batch");
+            return None;
+        }
+        let bytes = if self.max_queue_bytes > 0 {
+            let bytes = built.iter().map(json_size).sum();
+            if !status::reserve_queue_bytes(bytes, self.max_queue_bytes) {
+                eprintln!("[Hawk] Event queue is over max_queue_bytes — dropping batch");
+                return None;
+            }
+            bytes
+        } else {
+            0
+        };
+        let slot = QueueSlot::new(bytes);
+
+        let batch: Vec<QueuedEvent> = built
+            .into_iter()
+            .filter_map(|event| {
+                let (hawk_event, _) = self.commit_event(event, None)?;
+                QueuedEvent::new(&hawk_event, self.is_persisted(&hawk_event.payload))
+            })
+            .collect();
+        if batch.is_empty() {
+            return Some(correlation_id);
+        }
+
+        /*
+         * Another thread may have filled the queue meanwhile: then none of
+         * the events is kept for the next run either.
+         */
+        let persisted: Vec<String> = batch
+            .iter()
+            .filter_map(|event| event.persisted().map(str::to_string))
+            .collect();
+        if self.enqueue(WorkerMsg::Batch(batch, slot), "batch") {
+            return Some(correlation_id);
+        }
+        for id in &persisted {
+            self.remove_persisted(id);
+        }
+        None
+    }
+
+    /**
+     * Runs an event through the pipeline (steps 1 to 6 above). Returns
+     * the envelope and the backtrace still to resolve, `None` if the event
+     * is dropped, captured, or only printed.
+     */
+    fn prepare_event(
+        &self,
+        event: EventData,
+        backtrace: Option<Backtrace>,
+    ) -> Option<(HawkEvent, Option<Backtrace>)> {
+        let _timer = overhead::Timer::event();
+        let (event, backtrace) = self.build_event(event, backtrace)?;
+        self.commit_event(event, backtrace)
+    }
+
+    /**
+     * Builds the event to send (steps 1 to 4 above, up to `group_by`),
+     * recording nothing. Returns `None` if `before_send` drops it.
+     */
+    fn build_event(
+        &self,
+        mut event: EventData,
+        mut backtrace: Option<Backtrace>,
+    ) -> Option<(EventData, Option<Backtrace>)> {
+        /*
+         * If the `backtrace` crate cannot resolve anything here, the raw
+         * capture is useless to the worker — capture via `std::backtrace`
+         * now, while still on the caller's stack.
+         */
+        if backtrace.is_some() && !stacktrace::backtrace_crate_usable() {
+            backtrace = None;
+            let fallback = stacktrace::capture_std_fallback();
+            if !fallback.is_empty() {
+                event
+                    .backtrace
+                    .get_or_insert_with(Vec::new)
+                    .extend(fallback);
+            }
+        }
+
+        context::apply_scopes(
+            &mut event.context,
+            self.max_context_depth,
+            self.max_context_nodes,
+        );
+        self.context.apply_tags(&mut event.context);
+
+        if event.breadcrumbs.is_none() {
+            event.breadcrumbs = Some(self.context.breadcrumbs());
+        }
+
+        if let Some(ref log_tail) = self.log_tail {
+            log_tail.attach(&mut event);
+        }
+        self.process.attach(&mut event);
+
+        if event.addons.is_none() {
+            event.addons = Some(Addons {
+                build_id: self.build_id.clone(),
+                ..Addons::default()
+            });
+        }
+
+        /*
+         * Cap the shape of the data as soon as it is assembled, so that
+         * neither `before_send`'s copy nor the passes below walk more than
+         * what will be sent.
+         */
+        truncation::limit_event(&mut event, self.max_context_depth, self.max_context_nodes);
+
         /*
          * Run the before_send callback if configured.
          *
@@ -215,43 +1293,248 @@ impl Client {
          * - Panics → send the original event unchanged, print a warning.
          */
         if let Some(ref callback) = self.before_send {
+            if let Some(bt) = backtrace.take() {
+                stacktrace::resolve_into(&mut event, &bt);
+            }
+
             let original = event.clone();
 
-            let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                callback(original)
-            }));
+            let result =
+                std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| callback(original)));
 
             match result {
-                Ok(None) => return,
+                Ok(None) => return None,
                 Ok(Some(modified)) => event = modified,
                 Err(_) => {
-                    eprintln!(
-                        "[Hawk] before_send panicked — sending original event unchanged"
-                    );
+                    eprintln!("[Hawk] before_send panicked — sending original event unchanged");
                 }
             }
+
+            /*
+             * The callback may have added data of any shape.
+             */
+            truncation::limit_event(&mut event, self.max_context_depth, self.max_context_nodes);
+        }
+
+        /*
+         * Strip terminal escapes first, so that scrubbing patterns see
+         * clean text.
+         */
+        sanitize::sanitize_event(&mut event);
+
+        /*
+         * Scrub last, so that data added by before_send is covered too.
+         */
+        if let Some(ref scrubber) = self.scrubber {
+            scrubber.scrub_event(&mut event);
+        }
+
+        /*
+         * Truncate after scrubbing, so that patterns see whole values.
+         */
+        if self.max_string_length > 0 {
+            truncation::truncate_event(&mut event, self.max_string_length);
+        }
+
+        /*
+         * Compute the grouping key on the final event, unless one was set
+         * explicitly.
+         */
+        if let (Some(callback), None) = (&self.group_by, &event.fingerprint) {
+            if let Some(bt) = backtrace.take() {
+                stacktrace::resolve_into(&mut event, &bt);
+            }
+
+            let result =
+                std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| callback(&event)));
+            match result {
+                Ok(fingerprint) => event.fingerprint = fingerprint,
+                Err(_) => {
+                    eprintln!("[Hawk] group_by panicked — sending event without a fingerprint");
+                }
+            }
+        }
+        Some((event, backtrace))
+    }
+
+    /**
+     * Records a built event (steps 4 to 6 above, from `dedupe_window`):
+     * captures it, or counts it for dedupe and prints, numbers and
+     * persists it. Returns the envelope and the backtrace still to
+     * resolve, `None` if the event is dropped, captured, or only printed.
+     */
+    fn commit_event(
+        &self,
+        mut event: EventData,
+        mut backtrace: Option<Backtrace>,
+    ) -> Option<(HawkEvent, Option<Backtrace>)> {
+        /*
+         * Inside `capture_events()`, keep the event instead of sending it —
+         * before dedupe, so that a test sees every event its code sent.
+         */
+        let mut event = if capture::is_capturing() {
+            if let Some(bt) = backtrace.take() {
+                stacktrace::resolve_into(&mut event, &bt);
+            }
+            capture::intercept(event)?
+        } else {
+            event
+        };
+
+        if let Some(ref deduper) = self.deduper {
+            if !deduper.check(&mut event) {
+                return None;
+            }
+        }
+
+        if self.printer.is_enabled() {
+            if let Some(bt) = backtrace.take() {
+                stacktrace::resolve_into(&mut event, &bt);
+            }
+            self.printer.print(&event);
+            if !self.printer.sends() {
+                return None;
+            }
+        }
+
+        /*
+         * Number the event last, so that only events lost after this
+         * point (queue full, delivery failure) leave gaps.
+         */
+        let addons = event.addons.get_or_insert_with(Addons::default);
+        if addons.event_id.is_none() {
+            addons.event_id = Some(event_id::generate(self.event_id_generator.as_ref()));
+        }
+        addons.sequence = Some(self.next_sequence.fetch_add(1, Ordering::Relaxed));
+        addons.queued_at = Some(self.clock.now_millis());
+
+        /*
+         * Keep fatal events on disk until delivered, resolved: the process
+         * may not outlive the queue.
+         */
+        if let Some(persisted) = self
+            .persisted
+            .as_ref()
+            .filter(|persisted| persisted.keeps(&event))
+        {
+            if let Some(bt) = backtrace.take() {
+                stacktrace::resolve_into(&mut event, &bt);
+            }
+            persisted.write(&event);
         }
 
         /*
          * Wrap in the HawkEvent envelope — the exact format the backend expects.
          */
         let hawk_event = HawkEvent {
-            token: self.token.clone(),
-            catcher_type: CATCHER_TYPE.to_string(),
+            token: Arc::clone(&self.token),
+            catcher_type: CATCHER_TYPE.into(),
             payload: event,
         };
+        Some((hawk_event, backtrace))
+    }
 
-        /*
-         * Non-blocking enqueue. If the channel is full, the event is dropped
-         * silently — this is the intended back-pressure behaviour.
-         */
-        match self.sender.try_send(WorkerMsg::Event(hawk_event)) {
-            Ok(()) => {}
+    /**
+     * Whether the event has a persisted copy to delete once delivered.
+     */
+    pub(crate) fn is_persisted(&self, event: &EventData) -> bool {
+        self.persisted
+            .as_ref()
+            .is_some_and(|persisted| persisted.keeps(event))
+    }
+
+    /**
+     * Deletes the persisted copy of an event, by its ID: sent by the
+     * worker, or dropped.
+     */
+    pub(crate) fn remove_persisted(&self, id: &str) {
+        if let Some(ref persisted) = self.persisted {
+            persisted.remove(id);
+        }
+    }
+
+    /**
+     * Enqueues a finished transaction for delivery, in its own
+     * `HawkTransaction` envelope. `before_send` does not apply.
+     */
+    pub fn send_transaction(&self, transaction: TransactionData) {
+        let envelope = HawkTransaction::new(Arc::clone(&self.token), transaction);
+        self.enqueue(
+            WorkerMsg::Transaction(Box::new(envelope), QueueSlot::new(0)),
+            "transaction",
+        );
+    }
+
+    /**
+     * Aggregates one custom metric value, sending the interval's metrics
+     * if it has lasted long enough.
+     */
+    pub(crate) fn record_metric(
+        &self,
+        kind: MetricKind,
+        name: &str,
+        value: f64,
+        tags: &[(&str, &str)],
+    ) {
+        self.lock_metrics().add(kind, name, value, tags);
+        self.flush_metrics(false);
+    }
+
+    /**
+     * Enqueues the metrics aggregated so far, in a `HawkMetrics`
+     * envelope — unconditionally if `force`, otherwise only once the flush
+     * interval has elapsed. Also called periodically by the worker, so
+     * metrics recorded once still go out.
+     */
+    pub(crate) fn flush_metrics(&self, force: bool) {
+        let now = self.clock.now_millis();
+        let data = {
+            let mut metrics = self.lock_metrics();
+            if !force && !metrics.is_due(now) {
+                return;
+            }
+            metrics.take(now)
+        };
+
+        if let Some(data) = data {
+            let envelope = HawkMetrics::new(Arc::clone(&self.token), data);
+            self.enqueue(
+                WorkerMsg::Metrics(Box::new(envelope), QueueSlot::new(0)),
+                "metrics",
+            );
+        }
+    }
+
+    fn lock_metrics(&self) -> std::sync::MutexGuard<'_, Aggregator> {
+        self.metrics.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /**
+     * Non-blocking enqueue. If the channel is full, the message is dropped
+     * — this is the intended back-pressure behaviour — and its
+     * `QueueSlot` with it. `kind` names the payload in the warning.
+     * Returns whether the message was enqueued.
+     */
+    fn enqueue(&self, msg: WorkerMsg, kind: &str) -> bool {
+        if capture::uses_test_transport() {
+            return true;
+        }
+        match self.sender.try_send(msg) {
+            Ok(()) => {
+                #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+                Worker::schedule_drain();
+
+                #[cfg(all(target_arch = "wasm32", target_os = "wasi"))]
+                Worker::drain();
+                true
+            }
             Err(TrySendError::Full(_)) => {
-                eprintln!("[Hawk] Event queue is full — dropping event");
+                eprintln!("[Hawk] Event queue is full — dropping {kind}");
+                false
             }
             Err(TrySendError::Disconnected(_)) => {
-                eprintln!("[Hawk] Worker thread has shut down — dropping event");
+                eprintln!("[Hawk] Worker thread has shut down — dropping {kind}");
+                false
             }
         }
     }
@@ -266,7 +1549,10 @@ impl Client {
      * # Returns
      * `true` if the flush completed within the timeout, `false` otherwise.
      */
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn flush(&self) -> bool {
+        self.flush_metrics(true);
+
         let signal = Arc::new(FlushSignal::new());
 
         /*
@@ -274,9 +1560,289 @@ impl Client {
          * by the time the worker processes this message, all preceding
          * Event messages will have been sent.
          */
-        match self.sender.send_timeout(WorkerMsg::Flush(signal.clone()), FLUSH_TIMEOUT) {
+        match self
+            .sender
+            .send_timeout(WorkerMsg::Flush(signal.clone()), FLUSH_TIMEOUT)
+        {
             Ok(()) => signal.wait_timeout(FLUSH_TIMEOUT),
             Err(_) => false,
         }
     }
+
+    /**
+     * No-thread variant of `flush()` (wasm32): there is no worker to wait
+     * for, so the queue is drained on the calling thread. Always `true`.
+     */
+    #[cfg(target_arch = "wasm32")]
+    pub fn flush(&self) -> bool {
+        self.flush_metrics(true);
+        Worker::drain();
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Token of the test clients: integration ID `test`, never contacted.
+    const TEST_TOKEN: &str = "eyJpbnRlZ3JhdGlvbklkIjoidGVzdCIsInNlY3JldCI6InRlc3QifQ==";
+
+    /**
+     * Verifies that the events of a batch share a correlation ID, and that
+     * a batch without room in the queue is dropped whole, before any of
+     * its events is persisted or counted by dedupe, and that dedupe does
+     * not swallow captured repeats.
+     */
+    #[test]
+    fn test_send_batch() {
+        let dir = std::env::temp_dir().join(format!("hawk-batch-{}", std::process::id()));
+        let client = |max_queue_bytes| {
+            let options = Options {
+                collector_endpoint: Some("http://127.0.0.1:9/".into()),
+                fatal_event_dir: Some(dir.clone()),
+                dedupe_window: Some(Duration::from_secs(60)),
+                max_queue_bytes,
+                ..Options::default()
+            };
+            Client::new(TEST_TOKEN, options).unwrap().0
+        };
+        let fatal = |title: &str| {
+            let mut event = EventData::new(title);
+            event.event_type = Some("panic".into());
+            event
+        };
+
+        let full = client(1);
+        assert_eq!(full.send_batch(vec![fatal("first"), fatal("second")]), None);
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 0);
+        assert!(full.deduper.as_ref().unwrap().check(&mut fatal("first")));
+
+        let (id, events) = capture::capture_events(|| {
+            client(0).send_batch(vec![EventData::new("first"), EventData::new("second")])
+        });
+        let id = id.unwrap();
+        assert_eq!(events.len(), 2);
+        assert!(events.iter().all(|event| {
+            event
+                .addons
+                .as_ref()
+                .unwrap()
+                .group_correlation_id
+                .as_deref()
+                == Some(id.as_str())
+        }));
+
+        let deduped = client(0);
+        let ((), events) = capture::capture_events(|| {
+            deduped.send_event(EventData::new("repeat"));
+            deduped.send_event(EventData::new("repeat"));
+        });
+        assert_eq!(events.len(), 2);
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    /**
+     * Verifies that `group_by` sets the fingerprint of events without one,
+     * that an explicit fingerprint wins, and that an event whose callback
+     * panics is sent without a fingerprint.
+     */
+    #[test]
+    fn test_group_by() {
+        let options = Options {
+            collector_endpoint: Some("http://127.0.0.1:9/".into()),
+            group_by: Some(Arc::new(|event| {
+                if event.title == "panics" {
+                    panic!("group_by failed");
+                }
+                let title: String = event
+                    .title
+                    .chars()
+                    .filter(|c| !c.is_ascii_digit())
+                    .collect();
+                Some(vec![title])
+            })),
+            ..Options::default()
+        };
+        let (client, _) = Client::new(TEST_TOKEN, options).unwrap();
+
+        let ((), events) = capture::capture_events(|| {
+            client.send_event(EventData::new("order 17 failed"));
+            let mut explicit = EventData::new("order 18 failed");
+            explicit.fingerprint = Some(vec!["orders".into()]);
+            client.send_event(explicit);
+            client.send_event(EventData::new("panics"));
+        });
+
+        let fingerprints: Vec<_> = events
+            .iter()
+            .map(|event| event.fingerprint.clone())
+            .collect();
+        assert_eq!(
+            fingerprints,
+            [
+                Some(vec!["order  failed".to_string()]),
+                Some(vec!["orders".to_string()]),
+                None,
+            ]
+        );
+    }
+
+    /**
+     * Verifies that events are numbered in the order they are enqueued,
+     * without gaps for events dropped by `before_send`, and stamped with
+     * the time they were queued.
+     */
+    #[test]
+    fn test_sequence() {
+        let options = Options {
+            collector_endpoint: Some("http://127.0.0.1:9/".into()),
+            before_send: Some(Arc::new(|event: EventData| {
+                (event.title != "dropped").then_some(event)
+            })),
+            ..Options::default()
+        };
+        let (client, _) = Client::new(TEST_TOKEN, options).unwrap();
+
+        let before = crate::protocol::types::now_millis();
+        let addons: Vec<Addons> = ["first", "dropped", "second", "third"]
+            .into_iter()
+            .filter_map(|title| client.prepare_event(EventData::new(title), None))
+            .map(|(event, _)| event.payload.addons.unwrap())
+            .collect();
+        let after = crate::protocol::types::now_millis();
+
+        let sequences: Vec<_> = addons.iter().map(|addons| addons.sequence).collect();
+        assert_eq!(sequences, [Some(0), Some(1), Some(2)]);
+        assert!(addons.iter().all(|addons| addons
+            .queued_at
+            .is_some_and(|at| (before..=after).contains(&at))));
+    }
+
+    /**
+     * Verifies that an injected clock stamps `queuedAt`, and that rate
+     * limit windows are opened and checked with it.
+     */
+    #[test]
+    fn test_clock() {
+        struct ManualClock(AtomicU64);
+
+        impl Clock for ManualClock {
+            fn now_millis(&self) -> u64 {
+                self.0.load(Ordering::SeqCst)
+            }
+        }
+
+        let clock = Arc::new(ManualClock(AtomicU64::new(1_000_000)));
+        let options = Options {
+            collector_endpoint: Some("http://127.0.0.1:9/clock".into()),
+            clock: Some(clock.clone()),
+            ..Options::default()
+        };
+        let (client, _) = Client::new(TEST_TOKEN, options).unwrap();
+
+        let (event, _) = client.prepare_event(EventData::new("boom"), None).unwrap();
+        assert_eq!(event.payload.addons.unwrap().queued_at, Some(1_000_000));
+
+        status::open_rate_limit(
+            Some(&client),
+            &client.routes[0],
+            Some(Duration::from_secs(30)),
+        );
+        clock.0.store(1_029_999, Ordering::SeqCst);
+        assert!(!client.accepts_events());
+        clock.0.store(1_030_000, Ordering::SeqCst);
+        assert!(client.accepts_events());
+    }
+
+    /**
+     * Verifies that no event is built, and no backtrace captured, while
+     * the queue is over its byte budget, while every route is paused, or
+     * for events sampled out.
+     */
+    #[test]
+    fn test_dropped_events_not_built() {
+        let client = |endpoint: &str, max_queue_bytes, sample_rate| {
+            let options = Options {
+                collector_endpoint: Some(endpoint.into()),
+                max_queue_bytes,
+                sample_rate,
+                ..Options::default()
+            };
+            Client::new(TEST_TOKEN, options).unwrap().0
+        };
+        let built = |client: &Client| {
+            let mut built = false;
+            client.capture_with(|| {
+                built = true;
+                EventData::new("boom")
+            });
+            built
+        };
+
+        assert!(built(&client("http://127.0.0.1:9/kept", 0, 1.0)));
+        assert!(!built(&client("http://127.0.0.1:9/sampled", 0, 0.0)));
+
+        let paused = client("http://127.0.0.1:9/paused", 0, 1.0);
+        status::record_rate_limit(&paused.routes[0], Some(Duration::from_secs(60)));
+        assert!(!built(&paused));
+
+        assert!(status::reserve_queue_bytes(1, usize::MAX));
+        let full = built(&client("http://127.0.0.1:9/full", 1, 1.0));
+        status::release_queue_bytes(1);
+        assert!(!full);
+
+        let ((), events) = capture::capture_events(|| {
+            assert!(built(&paused));
+        });
+        assert_eq!(events.len(), 1);
+    }
+
+    /**
+     * Verifies that context too deep to copy, the event's own or scoped,
+     * is capped before `before_send` gets a copy of the event.
+     */
+    #[test]
+    #[cfg(feature = "context")]
+    fn test_context_capped_before_send() {
+        let deep = || {
+            let mut deep = serde_json::Value::Null;
+            for _ in 0..1_000_000 {
+                deep = serde_json::Value::Array(vec![deep]);
+            }
+            deep
+        };
+        let options = Options {
+            collector_endpoint: Some("http://127.0.0.1:9/".into()),
+            before_send: Some(Arc::new(Some)),
+            ..Options::default()
+        };
+        let (client, _) = Client::new(TEST_TOKEN, options).unwrap();
+
+        let mut scope = serde_json::json!({});
+        scope["scoped"] = deep();
+        let scope = Arc::new(scope);
+        let (_, events) = capture::capture_events(|| {
+            let mut event = EventData::new("deep");
+            let mut context = serde_json::json!({});
+            context["own"] = deep();
+            event.context = Some(context);
+            context::with_scope(&scope, || client.send_event(event));
+        });
+
+        let context = events[0].context.as_ref().unwrap();
+        for key in ["own", "scoped"] {
+            let mut value = &context[key];
+            let mut depth = 1;
+            while let Some(inner) = value.get(0) {
+                value = inner;
+                depth += 1;
+            }
+            assert_eq!(depth, DEFAULT_MAX_CONTEXT_DEPTH);
+            assert_eq!(value, truncation::TRUNCATION_MARKER);
+        }
+        let mut scope = Arc::try_unwrap(scope).unwrap();
+        truncation::limit_value(&mut scope, 1, 0);
+    }
 }
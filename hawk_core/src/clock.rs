@@ -0,0 +1,74 @@
+/*!
+ * Clock — where the SDK reads the time from.
+ *
+ * Every timestamp the SDK records — breadcrumbs, transactions, metrics
+ * intervals, `addons.queuedAt`, delivery errors and the rate limit and
+ * dormancy windows of `status()` — comes from the `Clock` of
+ * `Options::clock`, the system clock by default. Tests inject a fixed or
+ * manually advanced clock to get deterministic payloads; simulations and
+ * embedded targets without a wall clock inject their own time source.
+ *
+ * Durations (span timings, job durations, the dedupe window) are measured
+ * with the monotonic clock and are not affected.
+ */
+
+use std::sync::Arc;
+
+use crate::client;
+use crate::protocol::types;
+
+/**
+ * A source of wall-clock timestamps.
+ *
+ * ```ignore
+ * struct FixedClock(u64);
+ *
+ * impl hawk::Clock for FixedClock {
+ *     fn now_millis(&self) -> u64 {
+ *         self.0
+ *     }
+ * }
+ *
+ * hawk::init(hawk::Options {
+ *     clock: Some(Arc::new(FixedClock(1_700_000_000_000))),
+ *     ..Default::default()
+ * });
+ * ```
+ */
+pub trait Clock: Send + Sync {
+    /// Current time in milliseconds since the Unix epoch.
+    fn now_millis(&self) -> u64;
+}
+
+/**
+ * The system clock (`Date.now()` in the browser) — the default.
+ */
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now_millis(&self) -> u64 {
+        types::now_millis()
+    }
+}
+
+/**
+ * Resolves `Options::clock`.
+ */
+pub(crate) fn from_options(clock: Option<Arc<dyn Clock>>) -> Arc<dyn Clock> {
+    clock.unwrap_or_else(|| Arc::new(SystemClock))
+}
+
+/**
+ * Current time in milliseconds since the Unix epoch, read from
+ * `Options::clock` — the system clock before `init()`.
+ *
+ * Integrations stamp breadcrumbs with it (`Breadcrumb::at(now_millis(),
+ * ...)`), so that they follow an injected clock too.
+ */
+pub fn now_millis() -> u64 {
+    match client::get_client() {
+        Some(client) => client.clock().now_millis(),
+        None => types::now_millis(),
+    }
+}
@@ -0,0 +1,411 @@
+/*!
+ * Context attached to events.
+ *
+ * - Process-wide: the breadcrumb trail, a bounded ring buffer of the most
+ *   recent breadcrumbs, oldest first. When full, the oldest entry is
+ *   evicted. Owned by the `Client`; written from any thread through the
+ *   public `hawk_core::add_breadcrumb()` and read when an event is sent.
+ * - Process-wide: tags set by `hawk_core::tag_scope()`, sent under `tags`
+ *   in the context of every event until their guard is dropped.
+ * - Per thread: the scoped context objects pushed by
+ *   `hawk_core::with_context()`, merged into every event sent from the
+ *   thread while they are in place.
+ *
+ * The `breadcrumbs` feature compiles in the breadcrumb trail, the
+ * `context` feature scoped context and tags; without them, the APIs that
+ * record them are no-ops.
+ */
+
+use std::cell::RefCell;
+use std::collections::{BTreeMap, VecDeque};
+use std::sync::{Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard, RwLockWriteGuard};
+
+use serde_json::{Map, Value};
+
+use crate::client;
+use crate::protocol::types::Breadcrumb;
+use crate::truncation;
+
+thread_local! {
+    /**
+     * Context objects pushed by `with_context()`, outermost first.
+     */
+    static SCOPE_STACK: RefCell<Vec<Arc<Value>>> = const { RefCell::new(Vec::new()) };
+}
+
+/**
+ * Shared, lock-protected store for process-wide event context.
+ *
+ * Breadcrumbs and tags have locks of their own, so that threads recording
+ * breadcrumbs do not hold up events reading the tags, and the other way
+ * around.
+ */
+pub(crate) struct ContextManager {
+    /// Most recent breadcrumbs, oldest first.
+    breadcrumbs: Mutex<VecDeque<Breadcrumb>>,
+
+    /// Capacity of `breadcrumbs`; `0` disables breadcrumbs.
+    #[cfg_attr(not(feature = "breadcrumbs"), allow(dead_code))]
+    max_breadcrumbs: usize,
+
+    tags: RwLock<Tags>,
+}
+
+struct Tags {
+    /// Values of each tag, one per live guard, oldest first; the last one
+    /// is sent.
+    values: BTreeMap<String, Vec<(u64, String)>>,
+
+    /// ID of the next tag guard.
+    #[cfg_attr(not(feature = "context"), allow(dead_code))]
+    next_id: u64,
+
+    /// Current value of each tag, as sent; built on the first event after
+    /// a tag changes, and shared by the events that follow.
+    json: Option<Arc<Map<String, Value>>>,
+}
+
+impl ContextManager {
+    pub fn new(max_breadcrumbs: usize) -> Self {
+        Self {
+            breadcrumbs: Mutex::new(VecDeque::with_capacity(max_breadcrumbs)),
+            max_breadcrumbs,
+            tags: RwLock::new(Tags {
+                values: BTreeMap::new(),
+                next_id: 0,
+                json: None,
+            }),
+        }
+    }
+
+    /**
+     * Appends a breadcrumb, evicting the oldest one if the buffer is full.
+     */
+    #[cfg(feature = "breadcrumbs")]
+    pub fn add_breadcrumb(&self, breadcrumb: Breadcrumb) {
+        if self.max_breadcrumbs == 0 {
+            return;
+        }
+
+        let mut breadcrumbs = self.lock_breadcrumbs();
+        if breadcrumbs.len() == self.max_breadcrumbs {
+            breadcrumbs.pop_front();
+        }
+        breadcrumbs.push_back(breadcrumb);
+    }
+
+    /**
+     * Without the `breadcrumbs` feature: drops the breadcrumb.
+     */
+    #[cfg(not(feature = "breadcrumbs"))]
+    pub fn add_breadcrumb(&self, _breadcrumb: Breadcrumb) {}
+
+    /**
+     * Snapshot of the current breadcrumbs, oldest first.
+     */
+    pub fn breadcrumbs(&self) -> Vec<Breadcrumb> {
+        self.lock_breadcrumbs().iter().cloned().collect()
+    }
+
+    /**
+     * Drops all breadcrumbs.
+     */
+    pub fn clear_breadcrumbs(&self) {
+        self.lock_breadcrumbs().clear();
+    }
+
+    /**
+     * Sets a tag until `remove_tag()` is called with the returned ID.
+     */
+    #[cfg(feature = "context")]
+    pub fn push_tag(&self, key: &str, value: &str) -> u64 {
+        let mut tags = self.write_tags();
+        let id = tags.next_id;
+        tags.next_id += 1;
+        tags.values
+            .entry(key.to_string())
+            .or_default()
+            .push((id, value.to_string()));
+        tags.json = None;
+        id
+    }
+
+    /**
+     * Without the `context` feature: sets nothing.
+     */
+    #[cfg(not(feature = "context"))]
+    pub fn push_tag(&self, _key: &str, _value: &str) -> u64 {
+        0
+    }
+
+    /**
+     * Removes the value set by `push_tag()`. The tag falls back to the
+     * most recent value still set, which is right even when guards are
+     * dropped out of order.
+     */
+    pub fn remove_tag(&self, key: &str, id: u64) {
+        let mut tags = self.write_tags();
+        if let Some(values) = tags.values.get_mut(key) {
+            values.retain(|(value_id, _)| *value_id != id);
+            if values.is_empty() {
+                tags.values.remove(key);
+            }
+            tags.json = None;
+        }
+    }
+
+    /**
+     * Adds the current tags under `tags` in `context`. Tags already in the
+     * event's context, including from scopes, win. A non-object `context`
+     * is left alone.
+     */
+    pub fn apply_tags(&self, context: &mut Option<Value>) {
+        let Some(current) = self.tags_json() else {
+            return;
+        };
+
+        let Value::Object(fields) = context.get_or_insert_with(|| Value::Object(Map::new())) else {
+            return;
+        };
+        match fields.get_mut("tags") {
+            Some(Value::Object(tags)) => {
+                for (key, value) in current.iter() {
+                    tags.entry(key.clone()).or_insert_with(|| value.clone());
+                }
+            }
+            Some(_) => {}
+            None => {
+                fields.insert("tags".into(), Value::Object(current.as_ref().clone()));
+            }
+        }
+    }
+
+    /**
+     * Current tags as sent, `None` if there are none. Built under the
+     * write lock only when they changed since the last event; merging them
+     * into events happens outside the lock.
+     */
+    fn tags_json(&self) -> Option<Arc<Map<String, Value>>> {
+        {
+            let tags = self.read_tags();
+            if tags.values.is_empty() {
+                return None;
+            }
+            if let Some(ref json) = tags.json {
+                return Some(json.clone());
+            }
+        }
+
+        let mut tags = self.write_tags();
+        if tags.values.is_empty() {
+            return None;
+        }
+        if tags.json.is_none() {
+            let json = tags
+                .values
+                .iter()
+                .filter_map(|(key, values)| {
+                    values
+                        .last()
+                        .map(|(_, value)| (key.clone(), Value::String(value.clone())))
+                })
+                .collect();
+            tags.json = Some(Arc::new(json));
+        }
+        tags.json.clone()
+    }
+
+    /*
+     * Lock poisoning is ignored: every write leaves the data consistent,
+     * and losing all context because some thread panicked mid-call would
+     * be worse than reading it.
+     */
+
+    fn lock_breadcrumbs(&self) -> MutexGuard<'_, VecDeque<Breadcrumb>> {
+        self.breadcrumbs.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn read_tags(&self) -> RwLockReadGuard<'_, Tags> {
+        self.tags.read().unwrap_or_else(|e| e.into_inner())
+    }
+
+    fn write_tags(&self) -> RwLockWriteGuard<'_, Tags> {
+        self.tags.write().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Tags
+// ---------------------------------------------------------------------------
+
+/**
+ * Keeps a tag set by `hawk_core::tag_scope()`. Dropping it removes the
+ * tag, or restores the value it had before.
+ */
+#[must_use = "the tag is removed as soon as the guard is dropped"]
+pub struct TagGuard {
+    key: String,
+    id: Option<u64>,
+}
+
+impl TagGuard {
+    pub(crate) fn new(key: &str, value: &str) -> Self {
+        let id = client::get_client().map(|client| client.context().push_tag(key, value));
+        Self {
+            key: key.to_string(),
+            id,
+        }
+    }
+}
+
+impl Drop for TagGuard {
+    fn drop(&mut self) {
+        if let (Some(id), Some(client)) = (self.id, client::get_client()) {
+            client.context().remove_tag(&self.key, id);
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Scoped context
+// ---------------------------------------------------------------------------
+
+/**
+ * Runs `f` with `context` pushed on this thread's scope stack.
+ */
+#[cfg(feature = "context")]
+pub(crate) fn with_scope<R>(context: &Arc<Value>, f: impl FnOnce() -> R) -> R {
+    struct PopOnDrop;
+
+    impl Drop for PopOnDrop {
+        fn drop(&mut self) {
+            SCOPE_STACK.with(|stack| {
+                stack.borrow_mut().pop();
+            });
+        }
+    }
+
+    SCOPE_STACK.with(|stack| stack.borrow_mut().push(context.clone()));
+    let _pop = PopOnDrop;
+
+    f()
+}
+
+/**
+ * Without the `context` feature: just runs `f`.
+ */
+#[cfg(not(feature = "context"))]
+pub(crate) fn with_scope<R>(_context: &Arc<Value>, f: impl FnOnce() -> R) -> R {
+    f()
+}
+
+/**
+ * Merges this thread's scoped context objects under `context`: inner
+ * scopes override outer ones, and the event's own keys override them
+ * all. A non-object `context` is left alone. Scopes are copied capped in
+ * shape (see `truncation`); `0` disables either limit.
+ */
+pub(crate) fn apply_scopes(context: &mut Option<Value>, max_depth: usize, max_nodes: usize) {
+    SCOPE_STACK.with(|stack| {
+        let stack = stack.borrow();
+        if stack.is_empty() || context.as_ref().is_some_and(|context| !context.is_object()) {
+            return;
+        }
+
+        let mut merged = Map::new();
+        for scope in stack.iter().filter(|scope| scope.is_object()) {
+            if let Value::Object(fields) = truncation::clone_limited(scope, max_depth, max_nodes) {
+                merged.extend(fields);
+            }
+        }
+        if let Some(Value::Object(fields)) = context.take() {
+            merged.extend(fields);
+        }
+
+        *context = Some(Value::Object(merged));
+    });
+}
+
+#[cfg(all(test, any(feature = "breadcrumbs", feature = "context")))]
+mod tests {
+    use super::*;
+
+    /**
+     * Verifies that the buffer keeps only the most recent breadcrumbs.
+     */
+    #[test]
+    #[cfg(feature = "breadcrumbs")]
+    fn test_breadcrumb_ring_buffer() {
+        let context = ContextManager::new(2);
+        for message in ["a", "b", "c"] {
+            context.add_breadcrumb(Breadcrumb::new(message));
+        }
+
+        let messages: Vec<_> = context
+            .breadcrumbs()
+            .into_iter()
+            .filter_map(|b| b.message)
+            .collect();
+        assert_eq!(messages, ["b", "c"]);
+    }
+
+    /**
+     * Verifies that scopes nest and that event keys override scope keys.
+     */
+    #[test]
+    #[cfg(feature = "context")]
+    fn test_apply_scopes() {
+        let outer = Arc::new(serde_json::json!({ "job": "import", "attempt": 1 }));
+        let inner = Arc::new(serde_json::json!({ "attempt": 2 }));
+
+        let mut context = Some(serde_json::json!({ "job": "override" }));
+        with_scope(&outer, || {
+            with_scope(&inner, || apply_scopes(&mut context, 0, 0))
+        });
+        assert_eq!(
+            context,
+            Some(serde_json::json!({ "job": "override", "attempt": 2 }))
+        );
+
+        let mut context = None;
+        apply_scopes(&mut context, 0, 0);
+        assert_eq!(context, None);
+    }
+
+    /**
+     * Verifies that tags fall back to the previous value, even when
+     * removed out of order, that event tags win, and that the sent tags
+     * are rebuilt only after a change.
+     */
+    #[test]
+    #[cfg(feature = "context")]
+    fn test_tags() {
+        let context = ContextManager::new(0);
+        let outer = context.push_tag("phase", "migration");
+        let inner = context.push_tag("phase", "backfill");
+
+        let mut event_context = Some(serde_json::json!({ "tags": { "region": "eu" } }));
+        context.apply_tags(&mut event_context);
+        assert_eq!(
+            event_context,
+            Some(serde_json::json!({ "tags": { "region": "eu", "phase": "backfill" } }))
+        );
+
+        context.remove_tag("phase", outer);
+        let mut event_context = None;
+        context.apply_tags(&mut event_context);
+        assert_eq!(
+            event_context,
+            Some(serde_json::json!({ "tags": { "phase": "backfill" } }))
+        );
+        assert!(Arc::ptr_eq(
+            &context.tags_json().unwrap(),
+            &context.tags_json().unwrap()
+        ));
+
+        context.remove_tag("phase", inner);
+        let mut event_context = None;
+        context.apply_tags(&mut event_context);
+        assert_eq!(event_context, None);
+    }
+}
@@ -0,0 +1,175 @@
+/*!
+ * Deduplication — dropping repeats of an event within a time window.
+ *
+ * A retry loop failing the same way thousands of times would otherwise
+ * send thousands of identical occurrences. With `Options::dedupe_window`
+ * set, an event identical to one sent less than the window ago is dropped
+ * and counted; the next occurrence sent carries the count under
+ * `dedupe.suppressed` in its context.
+ *
+ * Events are identical when their fingerprints are equal (set explicitly
+ * or computed by `Options::group_by`), or, without fingerprints, when
+ * their type and title are equal.
+ *
+ * At most `CAPACITY` distinct events are remembered: past that, the least
+ * recently seen one is forgotten, with its dropped repeats.
+ */
+
+use std::num::NonZeroUsize;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use lru::LruCache;
+use serde_json::{json, Value};
+
+use crate::protocol::types::EventData;
+
+/// Number of distinct events remembered.
+const CAPACITY: usize = 1024;
+
+/**
+ * When an event was last sent, and how many repeats were dropped since.
+ */
+struct Seen {
+    sent_at: Instant,
+    suppressed: u64,
+}
+
+/**
+ * Drops repeats of recently sent events.
+ */
+pub(crate) struct Deduper {
+    window: Duration,
+    seen: Mutex<LruCache<Vec<String>, Seen>>,
+}
+
+impl Deduper {
+    pub(crate) fn new(window: Duration) -> Self {
+        Self {
+            window,
+            seen: Mutex::new(LruCache::new(
+                NonZeroUsize::new(CAPACITY).unwrap_or(NonZeroUsize::MIN),
+            )),
+        }
+    }
+
+    /**
+     * Returns `false` if the event repeats one sent within the window.
+     * Otherwise attaches the number of repeats dropped since the last
+     * occurrence, if any, and returns `true`.
+     */
+    pub(crate) fn check(&self, event: &mut EventData) -> bool {
+        self.check_at(event, Instant::now())
+    }
+
+    /**
+     * Forgets every event seen so far.
+     */
+    pub(crate) fn clear(&self) {
+        self.seen
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .clear();
+    }
+
+    fn check_at(&self, event: &mut EventData, now: Instant) -> bool {
+        let key = key(event);
+        let mut seen = self
+            .seen
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+
+        if let Some(entry) = seen.get_mut(&key) {
+            if now.duration_since(entry.sent_at) < self.window {
+                entry.suppressed += 1;
+                return false;
+            }
+            if entry.suppressed > 0 {
+                attach_count(event, entry.suppressed);
+            }
+            *entry = Seen {
+                sent_at: now,
+                suppressed: 0,
+            };
+            return true;
+        }
+
+        seen.put(
+            key,
+            Seen {
+                sent_at: now,
+                suppressed: 0,
+            },
+        );
+        true
+    }
+}
+
+/**
+ * Identity of an event: its fingerprint, or its type and title.
+ */
+fn key(event: &EventData) -> Vec<String> {
+    match &event.fingerprint {
+        Some(fingerprint) => fingerprint.clone(),
+        None => vec![
+            event.event_type.clone().unwrap_or_default(),
+            event.title.clone(),
+        ],
+    }
+}
+
+fn attach_count(event: &mut EventData, suppressed: u64) {
+    let dedupe = json!({ "suppressed": suppressed });
+    match event.context.as_mut() {
+        Some(Value::Object(context)) => {
+            context.insert("dedupe".into(), dedupe);
+        }
+        _ => event.context = Some(json!({ "dedupe": dedupe })),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * Verifies that repeats within the window are dropped and counted on
+     * the next occurrence, and that fingerprints take precedence over
+     * titles.
+     */
+    #[test]
+    fn test_dedupe_window() {
+        let deduper = Deduper::new(Duration::from_secs(10));
+        let start = Instant::now();
+        let event = EventData::new("connection refused");
+
+        assert!(deduper.check_at(&mut event.clone(), start));
+        assert!(!deduper.check_at(&mut event.clone(), start + Duration::from_secs(1)));
+        assert!(!deduper.check_at(&mut event.clone(), start + Duration::from_secs(9)));
+        assert!(deduper.check_at(
+            &mut EventData::new("timeout"),
+            start + Duration::from_secs(9)
+        ));
+
+        let mut next = event.clone();
+        assert!(deduper.check_at(&mut next, start + Duration::from_secs(10)));
+        assert_eq!(next.context, Some(json!({ "dedupe": { "suppressed": 2 } })));
+
+        let mut grouped = EventData::new("order 17 failed");
+        grouped.fingerprint = Some(vec!["order-failed".into()]);
+        assert!(deduper.check_at(&mut grouped.clone(), start));
+        grouped.title = "order 18 failed".into();
+        assert!(!deduper.check_at(&mut grouped, start + Duration::from_secs(1)));
+
+        let deduper = Deduper::new(Duration::from_secs(10));
+        for n in 0..=CAPACITY {
+            assert!(deduper.check_at(&mut EventData::new(format!("order {n} failed")), start));
+        }
+        assert_eq!(deduper.seen.lock().unwrap().len(), CAPACITY);
+        assert!(deduper.check_at(&mut EventData::new("order 0 failed"), start));
+        assert!(!deduper.check_at(
+            &mut EventData::new(format!("order {CAPACITY} failed")),
+            start
+        ));
+    }
+}
@@ -0,0 +1,287 @@
+/*!
+ * Error capture — reporting handled `std::error::Error` values.
+ *
+ * `capture_error(&err)` sends the error's message as the title, its type
+ * path as the event type (`core::num::error::ParseIntError`), and the
+ * messages of its
+ * `source()` chain under `error.sources` in the context.
+ * `capture_error_for_user(&err, user)` also attaches the affected user, to
+ * that event only.
+ *
+ * `std::io::Error` gets its own treatment, so that filesystem and network
+ * failures group by what went wrong:
+ *
+ * - the event type is the `ErrorKind` (`NotFound`, `ConnectionRefused`, ...);
+ * - the context holds `io.kind`, the OS error code (`io.os_error`), and on
+ *   Unix its errno name (`io.errno`, e.g. `"ENOENT"`);
+ * - `capture_io_error_at(&err, path)` adds the path involved as `io.path`,
+ *   with the home directory hidden unless `send_default_pii` is set.
+ */
+
+use std::error::Error;
+use std::io;
+use std::path::Path;
+
+use serde_json::{json, Map, Value};
+
+use crate::client;
+use crate::protocol::types::{EventData, User};
+use crate::stacktrace;
+
+/**
+ * Sends a handled error, with a backtrace of the call site.
+ *
+ * The event type is the path of `E` without its generic arguments
+ * (`core::num::error::ParseIntError`), so that events group by error type,
+ * like `error.name` in the Node.js catcher. The path keeps the many types
+ * named `Error` (`serde_json::Error`, `std::fmt::Error`, ...) apart.
+ *
+ * Silent no-op if the SDK has not been initialized.
+ */
+pub fn capture_error<E: Error + 'static>(error: &E) {
+    send(|| any_error_event(error));
+}
+
+/**
+ * Sends a handled error on behalf of `user`, the user affected by this one
+ * event. Nothing shared is changed, so concurrent requests of different
+ * users cannot see each other's.
+ *
+ * Silent no-op if the SDK has not been initialized.
+ */
+pub fn capture_error_for_user<E: Error + 'static>(error: &E, user: User) {
+    send(|| EventData {
+        user: Some(user),
+        ..any_error_event(error)
+    });
+}
+
+/**
+ * Sends an `io::Error` along with the path of the file or directory it
+ * concerns, which `io::Error` itself does not carry. The path goes under
+ * `io.path` only: the title stays the error's message, so that the same
+ * failure on different files groups together.
+ *
+ * Silent no-op if the SDK has not been initialized.
+ */
+pub fn capture_io_error_at(error: &io::Error, path: &Path) {
+    send(|| io_event(error, Some(path)));
+}
+
+/**
+ * Event type of an error known only as `dyn Error`, which carries no type
+ * name, e.g. the root cause of an `anyhow::Error`.
+ *
+ * An `io::Error` is typed by its `ErrorKind`, as by `capture_error()`.
+ * Any other error by the name its `Debug` output starts with: for a
+ * derived `Debug`, the struct name (`ParseIntError`) or, for an enum, the
+ * variant name rather than the type's. `None` when the output starts with
+ * no name, such as the quoted message of `anyhow!("message")`.
+ */
+pub fn error_type(error: &(dyn Error + 'static)) -> Option<String> {
+    if let Some(error) = error.downcast_ref::<io::Error>() {
+        return Some(format!("{:?}", error.kind()));
+    }
+
+    let debug = format!("{error:?}");
+    let end = debug
+        .find(|c: char| !(c.is_alphanumeric() || c == '_' || c == ':'))
+        .unwrap_or(debug.len());
+    let name = &debug[..end];
+
+    name.starts_with(|c: char| c.is_alphabetic())
+        .then(|| name.to_string())
+}
+
+/**
+ * Builds and sends an event, unless it would be dropped anyway.
+ */
+fn send(build: impl FnOnce() -> EventData) {
+    if let Some(client) = client::get_client() {
+        client.capture_with(build);
+    }
+}
+
+/**
+ * Builds the event of an error of static type `E`, dispatching
+ * `io::Error`s to `io_event`.
+ */
+fn any_error_event<E: Error + 'static>(error: &E) -> EventData {
+    match (error as &(dyn Error + 'static)).downcast_ref::<io::Error>() {
+        Some(error) => io_event(error, None),
+        None => error_event(error, type_path::<E>()),
+    }
+}
+
+/**
+ * Builds the event of any error: message, type name, source chain.
+ */
+fn error_event(error: &(dyn Error + 'static), type_name: &str) -> EventData {
+    let mut event = EventData::new(error.to_string());
+    event.event_type = Some(type_name.to_string());
+
+    let sources: Vec<String> = std::iter::successors(error.source(), |&source| source.source())
+        .map(ToString::to_string)
+        .collect();
+    if !sources.is_empty() {
+        event.context = Some(json!({ "error": { "sources": sources } }));
+    }
+    event
+}
+
+/**
+ * Builds the event of an `io::Error`, typed by its `ErrorKind`.
+ */
+fn io_event(error: &io::Error, path: Option<&Path>) -> EventData {
+    let kind = format!("{:?}", error.kind());
+    let mut event = error_event(error, &kind);
+
+    let mut io = Map::new();
+    io.insert("kind".into(), json!(kind));
+    if let Some(code) = error.raw_os_error() {
+        io.insert("os_error".into(), json!(code));
+        if let Some(name) = errno_name(code) {
+            io.insert("errno".into(), json!(name));
+        }
+    }
+    if let Some(path) = path {
+        let keep_home = client::get_client().is_some_and(|client| client.send_default_pii());
+        let path = stacktrace::scrub_path(&path.to_string_lossy(), None, keep_home);
+        io.insert("path".into(), json!(path));
+    }
+
+    match event.context.as_mut() {
+        Some(Value::Object(context)) => {
+            context.insert("io".into(), Value::Object(io));
+        }
+        _ => event.context = Some(json!({ "io": io })),
+    }
+    event
+}
+
+/**
+ * Path of a type, without generic arguments:
+ * `alloc::boxed::Box<dyn Error>` → `alloc::boxed::Box`.
+ */
+fn type_path<E>() -> &'static str {
+    let name = std::any::type_name::<E>();
+    name.split('<').next().unwrap_or(name)
+}
+
+/**
+ * Symbolic name of a Unix errno value, for the common ones.
+ */
+#[cfg(unix)]
+fn errno_name(code: i32) -> Option<&'static str> {
+    let name = match code {
+        libc::EPERM => "EPERM",
+        libc::ENOENT => "ENOENT",
+        libc::ESRCH => "ESRCH",
+        libc::EINTR => "EINTR",
+        libc::EIO => "EIO",
+        libc::ENXIO => "ENXIO",
+        libc::E2BIG => "E2BIG",
+        libc::ENOEXEC => "ENOEXEC",
+        libc::EBADF => "EBADF",
+        libc::ECHILD => "ECHILD",
+        libc::EAGAIN => "EAGAIN",
+        libc::ENOMEM => "ENOMEM",
+        libc::EACCES => "EACCES",
+        libc::EFAULT => "EFAULT",
+        libc::EBUSY => "EBUSY",
+        libc::EEXIST => "EEXIST",
+        libc::EXDEV => "EXDEV",
+        libc::ENODEV => "ENODEV",
+        libc::ENOTDIR => "ENOTDIR",
+        libc::EISDIR => "EISDIR",
+        libc::EINVAL => "EINVAL",
+        libc::ENFILE => "ENFILE",
+        libc::EMFILE => "EMFILE",
+        libc::ENOTTY => "ENOTTY",
+        libc::ETXTBSY => "ETXTBSY",
+        libc::EFBIG => "EFBIG",
+        libc::ENOSPC => "ENOSPC",
+        libc::ESPIPE => "ESPIPE",
+        libc::EROFS => "EROFS",
+        libc::EMLINK => "EMLINK",
+        libc::EPIPE => "EPIPE",
+        libc::EDEADLK => "EDEADLK",
+        libc::ENAMETOOLONG => "ENAMETOOLONG",
+        libc::ENOTEMPTY => "ENOTEMPTY",
+        libc::ELOOP => "ELOOP",
+        libc::EADDRINUSE => "EADDRINUSE",
+        libc::EADDRNOTAVAIL => "EADDRNOTAVAIL",
+        libc::ENETDOWN => "ENETDOWN",
+        libc::ENETUNREACH => "ENETUNREACH",
+        libc::ECONNABORTED => "ECONNABORTED",
+        libc::ECONNRESET => "ECONNRESET",
+        libc::ENOTCONN => "ENOTCONN",
+        libc::ETIMEDOUT => "ETIMEDOUT",
+        libc::ECONNREFUSED => "ECONNREFUSED",
+        libc::EHOSTUNREACH => "EHOSTUNREACH",
+        _ => return None,
+    };
+    Some(name)
+}
+
+/**
+ * OS error codes outside Unix (Win32 error codes, WASI) have no errno
+ * name.
+ */
+#[cfg(not(unix))]
+fn errno_name(_code: i32) -> Option<&'static str> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * Verifies that `io::Error`s are typed by kind and carry their OS
+     * code, errno name, and path, and that other errors are typed by name
+     * and list their sources.
+     */
+    #[test]
+    fn test_error_events() {
+        let error = io::Error::from_raw_os_error(2);
+        let event = io_event(&error, Some(Path::new("/etc/app.toml")));
+        assert_eq!(event.event_type.as_deref(), Some("NotFound"));
+        assert_eq!(event.title, error.to_string());
+        let io = &event.context.as_ref().unwrap()["io"];
+        assert_eq!(io["kind"], "NotFound");
+        assert_eq!(io["os_error"], 2);
+        assert_eq!(io["path"], "/etc/app.toml");
+        #[cfg(unix)]
+        assert_eq!(io["errno"], "ENOENT");
+
+        let wrapped = io::Error::new(
+            io::ErrorKind::InvalidData,
+            "abc".parse::<i32>().unwrap_err(),
+        );
+        let event = io_event(&wrapped, None);
+        assert_eq!(event.event_type.as_deref(), Some("InvalidData"));
+        assert_eq!(event.context.as_ref().unwrap()["io"].get("os_error"), None);
+
+        let parse_error = "abc".parse::<i32>().unwrap_err();
+        let event = error_event(&parse_error, type_path::<std::num::ParseIntError>());
+        assert_eq!(
+            event.event_type.as_deref(),
+            Some("core::num::error::ParseIntError")
+        );
+        assert_eq!(event.context, None);
+        assert_ne!(
+            type_path::<serde_json::Error>(),
+            type_path::<std::fmt::Error>()
+        );
+        assert_eq!(type_path::<Box<dyn Error>>(), "alloc::boxed::Box");
+
+        let event = any_error_event(&io::Error::from(io::ErrorKind::TimedOut));
+        assert_eq!(event.event_type.as_deref(), Some("TimedOut"));
+
+        assert_eq!(error_type(&parse_error).as_deref(), Some("ParseIntError"));
+        assert_eq!(error_type(&error).as_deref(), Some("NotFound"));
+        let message: Box<dyn Error> = "plain message".into();
+        assert_eq!(error_type(message.as_ref()), None);
+    }
+}
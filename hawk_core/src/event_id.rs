@@ -0,0 +1,132 @@
+/*!
+ * Event IDs — the identifier of each event sent, as `addons.eventId`.
+ *
+ * By default every event gets a random UUID (version 4). With
+ * `Options::event_id_generator` set, IDs come from the application
+ * instead, e.g. ULIDs that sort by time in downstream systems, or IDs
+ * carrying a shard or tenant prefix. An event whose ID was already set
+ * (by `before_send`, for instance) keeps it.
+ */
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, OnceLock};
+
+/// User-provided generator of event IDs (`Options::event_id_generator`).
+pub(crate) type EventIdGenerator = Arc<dyn Fn() -> String + Send + Sync>;
+
+/**
+ * Returns a new event ID from `generator`, or a random UUID without one.
+ * A panicking generator falls back to a UUID, so that the event is still
+ * sent.
+ */
+pub(crate) fn generate(generator: Option<&EventIdGenerator>) -> String {
+    if let Some(generator) = generator {
+        match std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| generator())) {
+            Ok(id) => return id,
+            Err(_) => eprintln!("[Hawk] event_id_generator panicked — using a random ID"),
+        }
+    }
+    uuid_v4()
+}
+
+/**
+ * Random UUID (version 4, RFC 4122 variant), hyphenated: 128 bits from
+ * the system's random source (`crypto.getRandomValues` in browsers).
+ */
+fn uuid_v4() -> String {
+    let mut bytes = [0u8; 16];
+    if !fill_random(&mut bytes) {
+        bytes = fallback_bytes();
+    }
+    bytes[6] = (bytes[6] & 0x0f) | 0x40;
+    bytes[8] = (bytes[8] & 0x3f) | 0x80;
+
+    let hex: String = bytes.iter().map(|byte| format!("{byte:02x}")).collect();
+    format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    )
+}
+
+/**
+ * Fills `bytes` from the system's random source. `false` if it failed.
+ */
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+fn fill_random(bytes: &mut [u8]) -> bool {
+    getrandom::fill(bytes).is_ok()
+}
+
+/**
+ * Browser variant: fills `bytes` through `crypto.getRandomValues`,
+ * available in windows and workers alike.
+ */
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+fn fill_random(bytes: &mut [u8]) -> bool {
+    get_random_values(bytes).is_ok()
+}
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+fn get_random_values(bytes: &mut [u8]) -> Result<(), wasm_bindgen::JsValue> {
+    use js_sys::{Function, Reflect, Uint8Array};
+    use wasm_bindgen::{JsCast, JsValue};
+
+    let crypto = Reflect::get(&js_sys::global(), &JsValue::from_str("crypto"))?;
+    let get_random_values: Function =
+        Reflect::get(&crypto, &JsValue::from_str("getRandomValues"))?.dyn_into()?;
+    let array = Uint8Array::new_with_length(bytes.len() as u32);
+    get_random_values.call1(&crypto, &array)?;
+    array.copy_to(bytes);
+    Ok(())
+}
+
+/**
+ * 128 bits hashed from a counter with a key chosen once per process, for
+ * when the random source fails. The key is fixed on some targets, so
+ * these IDs are only unique within the process.
+ */
+fn fallback_bytes() -> [u8; 16] {
+    static KEYS: OnceLock<RandomState> = OnceLock::new();
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let keys = KEYS.get_or_init(RandomState::new);
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let mut bytes = [0u8; 16];
+    for (half, chunk) in bytes.chunks_exact_mut(8).enumerate() {
+        let mut hasher = keys.build_hasher();
+        hasher.write_u64(count);
+        hasher.write_usize(half);
+        chunk.copy_from_slice(&hasher.finish().to_be_bytes());
+    }
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * Verifies the UUID layout and uniqueness, and that a custom
+     * generator is used unless it panics.
+     */
+    #[test]
+    fn test_generate() {
+        let first = generate(None);
+        assert_eq!(first.len(), 36);
+        assert_eq!(first.matches('-').count(), 4);
+        assert_eq!(&first[14..15], "4");
+        assert!(matches!(&first[19..20], "8" | "9" | "a" | "b"));
+        assert_ne!(first, generate(None));
+
+        let prefixed: EventIdGenerator = Arc::new(|| "eu1-0001".to_string());
+        assert_eq!(generate(Some(&prefixed)), "eu1-0001");
+
+        let panicking: EventIdGenerator = Arc::new(|| panic!("no entropy"));
+        assert_eq!(generate(Some(&panicking)).len(), 36);
+    }
+}
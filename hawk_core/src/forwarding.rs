@@ -0,0 +1,69 @@
+/*!
+ * Helpers shared by the logging integrations (`hawk_log`, `hawk_tracing`,
+ * `hawk_slog`): module filters and a guard against forwarding loops.
+ */
+
+use std::cell::Cell;
+
+thread_local! {
+    /**
+     * Set while a record is being forwarded to Hawk, by any integration.
+     */
+    static FORWARDING: Cell<bool> = const { Cell::new(false) };
+}
+
+/**
+ * Runs `forward` unless this thread is already forwarding a record: what
+ * the SDK logs while forwarding one — through any of the integrations —
+ * is not forwarded again.
+ */
+pub fn forward_once(forward: impl FnOnce()) {
+    struct Reset;
+
+    impl Drop for Reset {
+        fn drop(&mut self) {
+            FORWARDING.with(|flag| flag.set(false));
+        }
+    }
+
+    if FORWARDING.with(|flag| flag.replace(true)) {
+        return;
+    }
+    let _reset = Reset;
+
+    forward();
+}
+
+/**
+ * Returns `true` if the module path `target` is `prefix` or a module
+ * below it: `my_app::db` matches `my_app`, `my_application` does not.
+ */
+pub fn matches_target(target: &str, prefix: &str) -> bool {
+    target
+        .strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * Verifies that a record forwarded while forwarding another is
+     * dropped, and that module prefixes match whole path segments.
+     */
+    #[test]
+    fn test_forwarding() {
+        let mut forwarded = Vec::new();
+        forward_once(|| {
+            forwarded.push("outer");
+            forward_once(|| forwarded.push("inner"));
+        });
+        forward_once(|| forwarded.push("next"));
+        assert_eq!(forwarded, ["outer", "next"]);
+
+        assert!(matches_target("my_app", "my_app"));
+        assert!(matches_target("my_app::db", "my_app"));
+        assert!(!matches_target("my_application", "my_app"));
+    }
+}
@@ -19,6 +19,10 @@
  *
  * If the flush times out (default 2 seconds), the guard drops silently
  * without blocking further. Best-effort delivery is the contract.
+ *
+ * Dropping the guard also marks the run as cleanly exited in
+ * `Options::state_file`, if set — unless it is dropped while unwinding
+ * from a panic.
  */
 use crate::client;
 
@@ -38,9 +42,9 @@ use crate::client;
  * the flush on scope exit.
  */
 pub struct Guard {
-    /// Intentionally private and zero-sized — the guard is just a token
-    /// whose only purpose is to trigger `Drop`.
-    _private: (),
+    /// Whether dropping the guard flushes. Private, so that guards are
+    /// only made by `init()` or `Guard::inert()`.
+    active: bool,
 }
 
 impl Guard {
@@ -50,7 +54,16 @@ impl Guard {
      * This is `pub(crate)` because only `hawk::init()` should create guards.
      */
     pub(crate) fn new() -> Self {
-        Self { _private: () }
+        Self { active: true }
+    }
+
+    /**
+     * Creates a guard that does nothing when dropped, for when the SDK
+     * could not be initialized: `hawk::init()` returns one instead of
+     * panicking.
+     */
+    pub fn inert() -> Self {
+        Self { active: false }
     }
 }
 
@@ -66,11 +79,17 @@ impl Drop for Guard {
      * this is a no-op.
      */
     fn drop(&mut self) {
+        if !self.active {
+            return;
+        }
         if let Some(client) = client::get_client() {
             let flushed: bool = client.flush();
             if !flushed {
                 eprintln!("[Hawk] Flush timed out — some events may not have been sent");
             }
+            if !std::thread::panicking() {
+                client.mark_clean_exit();
+            }
         }
     }
 }
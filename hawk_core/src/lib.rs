@@ -9,23 +9,128 @@
  *
  * - `protocol/` — what we send: types, constants, token handling
  * - `transport/` — how we deliver: HTTP client, background worker
+ * - `build_id` — executable build ID detection
+ * - `builder` — fluent construction of events
+ * - `capture` — in-memory event capture for tests
  * - `client` — SDK lifecycle: init, global state, event routing
+ * - `clock` — injectable time source for timestamps
+ * - `context` — event context: breadcrumbs, tags, scoped context
+ * - `dedupe` — dropping repeated events within a time window
+ * - `error` — capture of `std::error::Error` values, `io::Error` in detail
+ * - `event_id` — event ID generation
+ * - `forwarding` — helpers shared by the logging integrations
  * - `guard` — RAII flush-on-drop
+ * - `log_tail` — recent log lines attached to fatal events
+ * - `metrics` — custom counters, gauges, and distributions
+ * - `overhead` — counters of the SDK's cost to the application
+ * - `panic_type` — normalized event types for std's own panics
+ * - `performance` — transactions and spans
+ * - `persisted` — fatal events kept on disk until delivered
+ * - `pretty` — terminal rendering of events for local development
+ * - `process` — process uptime and restart history
+ * - `request` — HTTP request context with header scrubbing
+ * - `sanitize` — removal of terminal escapes and control characters
+ * - `scrubbing` — redaction of sensitive values
+ * - `stacktrace` — backtrace capture, SDK frame filtering, in-app detection
+ * - `status` — SDK health introspection
+ * - `trace` — distributed trace propagation header
+ * - `truncation` — size cap on event strings, shape cap on context
  */
 
+#![forbid(unsafe_code)]
+
+mod build_id;
+mod builder;
+mod capture;
 mod client;
+mod clock;
+mod context;
+mod dedupe;
+mod error;
+mod event_id;
+// Shared with the logging integrations; not public API.
+#[doc(hidden)]
+pub mod forwarding;
 mod guard;
+mod log_tail;
+pub mod metrics;
+mod overhead;
+mod panic_type;
+mod performance;
+mod persisted;
+mod pretty;
+mod process;
 mod protocol;
+mod request;
+mod sampling;
+mod sanitize;
+mod scrubbing;
+// Shared with `hawk_crash`; not public API.
+#[doc(hidden)]
+pub mod spool;
+mod stacktrace;
+mod status;
+mod trace;
 mod transport;
+mod truncation;
 
 // ---------------------------------------------------------------------------
 // Re-exports
 // ---------------------------------------------------------------------------
 
-pub use client::Options;
+pub use builder::{event, EventBuilder, Level};
+pub use capture::{capture_events, is_capturing, use_test_transport};
+pub use client::{
+    Destination, Options, Shadow, DEFAULT_MAX_BACKTRACE_FRAMES, DEFAULT_MAX_BREADCRUMBS,
+    DEFAULT_MAX_QUEUE_BYTES, DEFAULT_STARTUP_CRASH_THRESHOLD, DEFAULT_STARTUP_CRASH_WINDOW,
+};
+pub use clock::{now_millis, Clock, SystemClock};
+pub use context::TagGuard;
+pub use error::{capture_error, capture_error_for_user, capture_io_error_at, error_type};
 pub use guard::Guard;
+pub use overhead::{estimated_overhead, Overhead};
+pub use panic_type::panic_type;
+pub use performance::{start_span, start_transaction, Span, Transaction};
+pub use pretty::PrintEvents;
 pub use protocol::constants::{CATCHER_TYPE, CATCHER_VERSION};
-pub use protocol::types::{BacktraceFrame, EventData, HawkEvent};
+pub use protocol::metrics::{
+    HawkMetrics, MetricData, MetricValue, MetricsData, MAX_DISTRIBUTION_VALUES,
+};
+pub use protocol::performance::{HawkTransaction, SpanData, TransactionData};
+pub use protocol::types::{
+    Addons, Attachment, BacktraceFrame, Breadcrumb, EventData, HawkEvent, User,
+};
+pub use request::{RequestContext, DEFAULT_SCRUB_HEADERS};
+pub use scrubbing::DEFAULT_SCRUB_KEYS;
+pub use stacktrace::{
+    convert_backtrace, convert_std_backtrace, frame_from_location, get_backtrace, Backtrace,
+};
+pub use status::{pending_events, status, DestinationStatus, SdkStatus};
+pub use trace::{current_trace_header, TraceContext, TRACE_HEADER};
+pub use transport::WireEncoding;
+pub use truncation::{
+    DEFAULT_MAX_CONTEXT_DEPTH, DEFAULT_MAX_CONTEXT_NODES, DEFAULT_MAX_STRING_LENGTH,
+    TRUNCATION_MARKER,
+};
+
+/*
+ * The client is shared by every thread, and handles move between threads:
+ * fail the build if a field change ever makes them lose `Send` / `Sync`,
+ * rather than reaching for an `unsafe impl`.
+ */
+const _: fn() = || {
+    fn assert_send_sync<T: Send + Sync>() {}
+    fn assert_send<T: Send>() {}
+
+    assert_send_sync::<client::Client>();
+    assert_send_sync::<Options>();
+    assert_send_sync::<TraceContext>();
+    assert_send_sync::<RequestContext>();
+    assert_send::<Guard>();
+    assert_send::<Transaction>();
+    assert_send::<Span>();
+    assert_send::<TagGuard>();
+};
 
 // ---------------------------------------------------------------------------
 // Public functions
@@ -37,7 +142,8 @@ pub use protocol::types::{BacktraceFrame, EventData, HawkEvent};
  * Returns `Ok(Guard)` on success. The `Guard` flushes pending events
  * when dropped — keep it alive for the duration of your app.
  *
- * Returns `Err` if the token is malformed or `init` was already called.
+ * Returns `Err` if the token or a scrub pattern is malformed, or `init`
+ * was already called.
  */
 pub fn init(token: &str, options: Options) -> Result<Guard, String> {
     client::Client::init(token, options)?;
@@ -49,18 +155,100 @@ pub fn init(token: &str, options: Options) -> Result<Guard, String> {
  *
  * Accepts anything that implements `Display` — strings, errors, formatted
  * messages. A backtrace is captured at the call site so the Hawk dashboard
- * shows exactly where `hawk::send(...)` was called from.
+ * shows exactly where `hawk::send(...)` was called from. Only raw frame
+ * addresses are captured here; symbols are resolved on the worker thread.
+ *
+ * The event type is always `"error"`; use `capture_error()` to have
+ * errors typed, and grouped, by their type name.
  *
  * Silent no-op if the SDK has not been initialized.
  */
 pub fn send(message: &(impl std::fmt::Display + ?Sized)) {
+    send_message(message, None);
+}
+
+/**
+ * Sends an event to Hawk, like `send()`, with `context` attached to this
+ * event only. `context` should be a JSON object, e.g. built with
+ * `hawk::context!{}`.
+ *
+ * Silent no-op if the SDK has not been initialized.
+ */
+pub fn send_with_context(message: &(impl std::fmt::Display + ?Sized), context: serde_json::Value) {
+    send_message(message, Some(context));
+}
+
+fn send_message(message: &(impl std::fmt::Display + ?Sized), context: Option<serde_json::Value>) {
     if let Some(client) = client::get_client() {
-        let event = EventData {
+        client.capture_with(|| EventData {
             title: message.to_string(),
             event_type: Some("error".to_string()),
-            backtrace: get_backtrace(),
-            catcher_version: CATCHER_VERSION.to_string(),
+            backtrace: None,
+            context,
+            breadcrumbs: None,
+            addons: None,
+            fingerprint: None,
+            user: None,
+            catcher_version: CATCHER_VERSION.into(),
+        });
+    }
+}
+
+/**
+ * Sends a structured "business event" — a failed reconciliation, a data
+ * anomaly — rather than an error: an event of type `"message"`, without a
+ * backtrace, with `data` as its context. A `data` that is not a JSON
+ * object is sent under `data`.
+ *
+ * ```ignore
+ * hawk::capture_json("reconciliation failed", serde_json::json!({
+ *     "account": account.id,
+ *     "expected": expected,
+ *     "actual": actual,
+ * }));
+ * ```
+ *
+ * Silent no-op if the SDK has not been initialized.
+ */
+pub fn capture_json(title: &str, data: serde_json::Value) {
+    send_data(title, "message", data);
+}
+
+/**
+ * Sends a report — a configuration snapshot at startup, the state of
+ * feature flags, a migration summary — as an event of type `"report"`,
+ * so that it is not counted as an error. Like `capture_json()`, it has no
+ * backtrace and `payload` is its context.
+ *
+ * ```ignore
+ * hawk::capture_report("startup config", &config);
+ * ```
+ *
+ * A payload that cannot be represented as JSON is sent as `null`.
+ * Silent no-op if the SDK has not been initialized.
+ */
+pub fn capture_report(name: &str, payload: &(impl serde::Serialize + ?Sized)) {
+    send_data(
+        name,
+        "report",
+        serde_json::to_value(payload).unwrap_or(serde_json::Value::Null),
+    );
+}
+
+/**
+ * Sends an event of the given type without a backtrace, with `data` as
+ * its context, under `data` if it is not a JSON object.
+ */
+fn send_data(title: &str, event_type: &str, data: serde_json::Value) {
+    if let Some(client) = client::accepting_client() {
+        let context = match data {
+            serde_json::Value::Object(_) => data,
+            data => serde_json::json!({ "data": data }),
         };
+
+        let mut event = EventData::new(title);
+        event.event_type = Some(event_type.to_string());
+        event.context = Some(context);
         client.send_event(event);
     }
 }
@@ -78,57 +266,243 @@ pub fn capture_event(event: EventData) {
 }
 
 /**
- * Manually flushes all pending events, blocking until drained or timeout.
+ * Sends related events together, e.g. all validation failures of one
+ * request: they share a fresh `addons.groupCorrelationId`, so that the
+ * backend can present them together, and are enqueued atomically — all
+ * of them or, if the queue has no room, none.
  *
- * Normally you don't need this — the `Guard` handles it on drop.
+ * Each event goes through the pipeline like one sent by `capture_event()`
+ * (`before_send` may still drop some). Returns the correlation ID, `None`
+ * if the batch was dropped or the SDK is not initialized.
  */
-pub fn flush() -> bool {
+pub fn capture_batch(events: impl IntoIterator<Item = EventData>) -> Option<String> {
+    let client = client::accepting_client()?;
+    client.send_batch(events.into_iter().collect())
+}
+
+/**
+ * Sends a pre-built `EventData` along with a backtrace captured via
+ * `Backtrace::new_unresolved()`.
+ *
+ * Symbol resolution is deferred to the worker thread, so the caller only
+ * pays for the stack walk. Frames already set on `event.backtrace` are kept
+ * on top of the resolved ones (e.g. a synthetic panic-location frame).
+ * Silent no-op if not initialized.
+ */
+pub fn capture_event_with_backtrace(event: EventData, backtrace: Backtrace) {
     if let Some(client) = client::get_client() {
-        client.flush()
-    } else {
-        true
+        client.send_event_with_backtrace(event, Some(backtrace));
     }
 }
 
-// ---------------------------------------------------------------------------
-// Internal helpers
-// ---------------------------------------------------------------------------
+/**
+ * Records a breadcrumb, attached to every subsequent event until it is
+ * evicted by newer ones (see `Options::max_breadcrumbs`).
+ *
+ * Silent no-op if not initialized.
+ */
+pub fn add_breadcrumb(breadcrumb: Breadcrumb) {
+    if let Some(client) = client::get_client() {
+        client.add_breadcrumb(breadcrumb);
+    }
+}
+
+/**
+ * Mirrors a log line into the buffer of recent lines attached to fatal
+ * events (see `Options::log_tail_bytes`). Called by the `log` and
+ * `tracing` integrations for every record they see.
+ *
+ * Silent no-op if not initialized or the log tail is disabled.
+ */
+pub fn record_log_line(line: &str) {
+    if let Some(client) = client::get_client() {
+        client.record_log_line(line);
+    }
+}
+
+/**
+ * Whether `record_log_line()` keeps lines: lets integrations skip
+ * formatting them when it would not. `false` before `init()`.
+ */
+pub fn log_tail_enabled() -> bool {
+    client::get_client().is_some_and(|client| client.log_tail_enabled())
+}
+
+/**
+ * Drops all recorded breadcrumbs, e.g. at the start of a new job in a
+ * long-running worker. Silent no-op if not initialized.
+ */
+pub fn clear_breadcrumbs() {
+    if let Some(client) = client::get_client() {
+        client.clear_breadcrumbs();
+    }
+}
+
+/**
+ * Runs `f` with `context` merged into every event sent from this thread
+ * while it runs — panics, `send()`, integrations alike.
+ *
+ * Calls nest: inner contexts are merged over outer ones, key by key, and
+ * an event's own context keys win over both. `context` should be a JSON
+ * object; other values are ignored. It can be passed as an `Arc`, so that
+ * code entering the same scope repeatedly does not rebuild it.
+ */
+pub fn with_context<R>(
+    context: impl Into<std::sync::Arc<serde_json::Value>>,
+    f: impl FnOnce() -> R,
+) -> R {
+    context::with_scope(&context.into(), f)
+}
+
+/**
+ * Sets a tag, sent under `tags` in the context of every event, from any
+ * thread, until the returned guard is dropped. Dropping it restores the
+ * value the tag had before, or removes it.
+ *
+ * ```ignore
+ * let _phase = hawk::tag_scope("phase", "migration");
+ * migrate()?; // an early return drops the guard too
+ * ```
+ *
+ * The guard does nothing if the SDK has not been initialized.
+ */
+pub fn tag_scope(key: &str, value: &str) -> TagGuard {
+    TagGuard::new(key, value)
+}
+
+/**
+ * Returns `true` once `init()` has succeeded.
+ *
+ * Lets addons defer work that needs a running client (e.g. delivering
+ * crash records from a previous run) instead of dropping it.
+ */
+pub fn is_initialized() -> bool {
+    client::get_client().is_some()
+}
+
+/**
+ * Whether an event captured now would be kept. `false` before `init()`,
+ * while the queue is full, while every collector rate limits the client
+ * or puts it to sleep, or if the event is sampled out
+ * (`Options::sample_rate`) — in which case `send()`, `capture_error()`,
+ * and the integrations return without capturing a backtrace or building
+ * the event.
+ *
+ * Lets hot error paths skip expensive work of their own, too.
+ */
+pub fn accepts_events() -> bool {
+    client::accepting_client().is_some()
+}
+
+/**
+ * Whether `Options::send_default_pii` is enabled. Integrations check it
+ * before attaching personal data (IP addresses, user emails, full request
+ * headers and cookies) on their own. `false` before `init()`.
+ */
+pub fn send_default_pii() -> bool {
+    client::get_client().is_some_and(|client| client.send_default_pii())
+}
+
+/**
+ * Integration ID of the client's token. `None` before `init()`.
+ */
+pub fn integration_id() -> Option<String> {
+    client::get_client().map(|client| client.integration_id().to_string())
+}
+
+/**
+ * Build ID of the running executable (as sent in `addons.buildId`).
+ *
+ * `None` if the SDK is not initialized or the binary has no build ID.
+ */
+pub fn build_id() -> Option<String> {
+    client::get_client().and_then(|client| client.build_id().map(str::to_string))
+}
+
+/**
+ * The key files on disk are sealed with (`Options::spool_key`), for
+ * `hawk_crash`'s records. `None` if the SDK is not initialized or no key
+ * is set.
+ */
+#[doc(hidden)]
+pub fn spool_cipher() -> Option<&'static spool::SpoolCipher> {
+    client::get_client().and_then(|client| client.spool_cipher())
+}
 
 /**
- * Captures a backtrace at the current call site.
- * Returns `None` if no useful frames were resolved.
+ * Clears the breadcrumbs, dedupe history, log tail, and pending metrics of
+ * the client, so that one test does not see what an earlier one left.
+ * For `hawk_test`.
  */
-pub fn get_backtrace() -> Option<Vec<BacktraceFrame>> {
-    let bt = backtrace::Backtrace::new();
-    let frames = convert_backtrace(&bt);
-    if frames.is_empty() { None } else { Some(frames) }
+#[doc(hidden)]
+pub fn reset_client() {
+    if let Some(client) = client::get_client() {
+        client.reset();
+    }
 }
 
 /**
- * Converts a `backtrace::Backtrace` into `Vec<BacktraceFrame>`.
- * Filters out frames with no useful info (no file AND no function).
+ * Records in `Options::state_file` that the process is exiting cleanly,
+ * for exits that skip the `Guard`'s destructor. No-op without a state
+ * file.
  */
-pub fn convert_backtrace(bt: &backtrace::Backtrace) -> Vec<BacktraceFrame> {
-    let mut frames = Vec::new();
+pub fn mark_clean_exit() {
+    if let Some(client) = client::get_client() {
+        client.mark_clean_exit();
+    }
+}
 
-    for frame in bt.frames() {
-        for symbol in frame.symbols() {
-            let function = symbol.name().map(|n| n.to_string());
-            let file = symbol.filename().map(|p| p.display().to_string());
-            let line = symbol.lineno();
+/**
+ * Like `mark_clean_exit()`, but a no-op if a panic was reported in this run
+ * (`mark_panicked()`). For `atexit` handlers: after a panic in `main`, the
+ * runtime exits through `exit(101)`.
+ */
+pub fn mark_clean_exit_unless_panicked() {
+    if let Some(client) = client::get_client() {
+        client.mark_clean_exit_unless_panicked();
+    }
+}
 
-            if function.is_none() && file.is_none() {
-                continue;
-            }
+/**
+ * Whether the runs before this one ended in a startup crash loop
+ * (`Options::startup_crash_threshold`), for the app to fall back to a safe
+ * mode (default settings, no restored session, ...). `false` before
+ * `init()` and without `Options::state_file`.
+ */
+pub fn is_crash_looping() -> bool {
+    client::get_client().is_some_and(|client| client.is_crash_looping())
+}
 
-            frames.push(BacktraceFrame {
-                file,
-                line,
-                column: symbol.colno(),
-                function,
-            });
-        }
+/**
+ * Marks a fatal event left by an earlier run as part of a startup crash
+ * loop, if this run found one. For `hawk_crash`'s records.
+ */
+#[doc(hidden)]
+pub fn mark_startup_crash(event: &mut EventData) {
+    if is_crash_looping() {
+        process::mark_startup_crash(event);
     }
+}
 
-    frames
+/**
+ * Records that a panic was reported in this run, so that the exit is not
+ * recorded as clean. Called by the panic hook.
+ */
+pub fn mark_panicked() {
+    if let Some(client) = client::get_client() {
+        client.mark_panicked();
+    }
+}
+
+/**
+ * Manually flushes all pending events, blocking until drained or timeout.
+ *
+ * Normally you don't need this — the `Guard` handles it on drop.
+ */
+pub fn flush() -> bool {
+    if let Some(client) = client::get_client() {
+        client.flush()
+    } else {
+        true
+    }
 }
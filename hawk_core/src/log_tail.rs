@@ -0,0 +1,149 @@
+/*!
+ * Log tail — the last log lines before a crash.
+ *
+ * With `Options::log_tail_bytes` set, the `log` and `tracing` integrations
+ * mirror every record they see into a ring buffer holding the most recent
+ * lines, up to that many bytes. Only fatal events (panics, crashes) carry
+ * it, under `log_tail` in their context, one string per line, oldest
+ * first — the full pre-crash log without the cost on every event.
+ *
+ * Lines go through `before_send`, scrubbing, and truncation like the rest
+ * of the context. Events of a previous run delivered now (crash records)
+ * do not get this run's lines.
+ */
+
+use std::collections::VecDeque;
+use std::sync::Mutex;
+
+use serde_json::{json, Value};
+
+use crate::panic_type::is_fatal;
+use crate::process::is_previous_run;
+use crate::protocol::types::EventData;
+
+/**
+ * Most recent log lines, at most `capacity` bytes in total.
+ */
+pub(crate) struct LogTail {
+    capacity: usize,
+    lines: Mutex<Lines>,
+}
+
+#[derive(Default)]
+struct Lines {
+    lines: VecDeque<String>,
+    bytes: usize,
+}
+
+impl LogTail {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            lines: Mutex::new(Lines::default()),
+        }
+    }
+
+    /**
+     * Appends a line, evicting the oldest ones beyond the capacity. A line
+     * longer than the whole capacity is cut to fit.
+     */
+    pub(crate) fn push(&self, line: &str) {
+        let mut end = line.len().min(self.capacity);
+        while !line.is_char_boundary(end) {
+            end -= 1;
+        }
+        let line = &line[..end];
+
+        let mut lines = self
+            .lines
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        while lines.bytes + line.len() > self.capacity {
+            match lines.lines.pop_front() {
+                Some(evicted) => lines.bytes -= evicted.len(),
+                None => break,
+            }
+        }
+        lines.bytes += line.len();
+        lines.lines.push_back(line.to_string());
+    }
+
+    /**
+     * Drops every buffered line.
+     */
+    pub(crate) fn clear(&self) {
+        *self
+            .lines
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner()) = Lines::default();
+    }
+
+    /**
+     * Attaches the buffered lines to a fatal event that has none yet,
+     * unless it comes from a previous run: these lines are not its own.
+     */
+    pub(crate) fn attach(&self, event: &mut EventData) {
+        if !event.event_type.as_deref().is_some_and(is_fatal) || is_previous_run(event) {
+            return;
+        }
+
+        let lines: Vec<String> = {
+            let lines = self
+                .lines
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            lines.lines.iter().cloned().collect()
+        };
+        if lines.is_empty() {
+            return;
+        }
+
+        match event.context.as_mut() {
+            Some(Value::Object(context)) => {
+                context.entry("log_tail").or_insert_with(|| json!(lines));
+            }
+            _ => event.context = Some(json!({ "log_tail": lines })),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * Verifies that the oldest lines are evicted beyond the capacity and
+     * that only fatal events of this run carry the tail.
+     */
+    #[test]
+    fn test_log_tail() {
+        let tail = LogTail::new(16);
+        for line in ["INFO a: one", "INFO a: two", "WARN b: three"] {
+            tail.push(line);
+        }
+
+        let mut error = EventData::new("handled");
+        error.event_type = Some("error".into());
+        tail.attach(&mut error);
+        assert_eq!(error.context, None);
+
+        let mut fatal = EventData::new("panicked");
+        fatal.event_type = Some("UnwrapNone".into());
+        tail.attach(&mut fatal);
+        assert_eq!(
+            fatal.context,
+            Some(json!({ "log_tail": ["WARN b: three"] }))
+        );
+
+        let mut crashed = EventData::new("SIGSEGV");
+        crashed.event_type = Some("SIGSEGV".into());
+        crashed.context = Some(json!({ "previous_run": true }));
+        tail.attach(&mut crashed);
+        assert_eq!(crashed.context, Some(json!({ "previous_run": true })));
+
+        tail.push("ERROR c: ünïcödé overflow");
+        let lines = tail.lines.lock().unwrap();
+        assert_eq!(lines.lines, ["ERROR c: ünïc"]);
+        assert!(lines.bytes <= 16);
+    }
+}
@@ -0,0 +1,209 @@
+/*!
+ * Custom metrics — counters, gauges, and distributions.
+ *
+ * Recorded values are aggregated in memory, per name, type, and tag set,
+ * and sent every `FLUSH_INTERVAL_MS` through the worker as one
+ * `HawkMetrics` payload — a counter bumped on every request costs a map
+ * lookup, not a network call. Pending metrics are also sent on `flush()`,
+ * so the `Guard` delivers them at exit.
+ *
+ * On wasm32 there is no worker thread to wake up: metrics are sent with
+ * the first value recorded after the interval, or on `flush()`.
+ *
+ * ```ignore
+ * hawk::metrics::increment("orders.failed", 1, &[("region", "eu")]);
+ * hawk::metrics::gauge("queue.depth", queue.len() as f64, &[]);
+ * hawk::metrics::distribution("checkout.total", order.total, &[("currency", "EUR")]);
+ * ```
+ *
+ * All functions are silent no-ops before `init()`.
+ */
+
+use std::collections::BTreeMap;
+
+use crate::client;
+use crate::protocol::metrics::{MetricData, MetricValue, MetricsData};
+
+/// How long values are aggregated before being sent, in milliseconds.
+pub(crate) const FLUSH_INTERVAL_MS: u64 = 10_000;
+
+/**
+ * Adds `value` to the counter `name`. Counters are summed over the flush
+ * interval.
+ */
+pub fn increment(name: &str, value: impl Into<f64>, tags: &[(&str, &str)]) {
+    record(MetricKind::Counter, name, value.into(), tags);
+}
+
+/**
+ * Sets the gauge `name` to `value`. The last value, the range, the sum,
+ * and the count of the values set in the flush interval are sent.
+ */
+pub fn gauge(name: &str, value: impl Into<f64>, tags: &[(&str, &str)]) {
+    record(MetricKind::Gauge, name, value.into(), tags);
+}
+
+/**
+ * Records `value` in the distribution `name` (latencies, sizes, amounts).
+ * Up to `MAX_DISTRIBUTION_VALUES` of the values recorded in the flush
+ * interval are sent, sampled uniformly, with the range, sum, and count of
+ * all of them.
+ */
+pub fn distribution(name: &str, value: impl Into<f64>, tags: &[(&str, &str)]) {
+    record(MetricKind::Distribution, name, value.into(), tags);
+}
+
+fn record(kind: MetricKind, name: &str, value: f64, tags: &[(&str, &str)]) {
+    if let Some(client) = client::get_client() {
+        client.record_metric(kind, name, value, tags);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Aggregator
+// ---------------------------------------------------------------------------
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub(crate) enum MetricKind {
+    Counter,
+    Gauge,
+    Distribution,
+}
+
+/// Identity of an aggregated metric: tags are sorted, so their order at
+/// the call site does not matter.
+type MetricKey = (String, MetricKind, BTreeMap<String, String>);
+
+/**
+ * Values recorded since the last flush, held by the `Client`.
+ */
+pub(crate) struct Aggregator {
+    metrics: BTreeMap<MetricKey, MetricValue>,
+
+    /// When the current interval started.
+    started: u64,
+}
+
+impl Aggregator {
+    /**
+     * Starts the first interval at `now` (Unix milliseconds).
+     */
+    pub(crate) fn new(now: u64) -> Self {
+        Self {
+            metrics: BTreeMap::new(),
+            started: now,
+        }
+    }
+
+    /**
+     * Folds one recorded value into its metric.
+     */
+    pub(crate) fn add(&mut self, kind: MetricKind, name: &str, value: f64, tags: &[(&str, &str)]) {
+        let tags = tags
+            .iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect();
+
+        self.metrics
+            .entry((name.to_string(), kind, tags))
+            .and_modify(|metric| metric.add(value))
+            .or_insert_with(|| match kind {
+                MetricKind::Counter => MetricValue::Counter { value },
+                MetricKind::Gauge => MetricValue::Gauge {
+                    last: value,
+                    min: value,
+                    max: value,
+                    sum: value,
+                    count: 1,
+                },
+                MetricKind::Distribution => MetricValue::distribution(value),
+            });
+    }
+
+    /**
+     * Whether the current interval has lasted `FLUSH_INTERVAL_MS` at `now`.
+     */
+    pub(crate) fn is_due(&self, now: u64) -> bool {
+        now.saturating_sub(self.started) >= FLUSH_INTERVAL_MS
+    }
+
+    /**
+     * Ends the current interval at `now`, returning its metrics, or `None`
+     * if nothing was recorded.
+     */
+    pub(crate) fn take(&mut self, now: u64) -> Option<MetricsData> {
+        let started = std::mem::replace(&mut self.started, now);
+        if self.metrics.is_empty() {
+            return None;
+        }
+
+        let metrics = std::mem::take(&mut self.metrics)
+            .into_iter()
+            .map(|((name, _, tags), value)| MetricData { name, tags, value })
+            .collect();
+
+        Some(MetricsData::new(started, now, metrics))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::metrics::MAX_DISTRIBUTION_VALUES;
+
+    /**
+     * Verifies that values are aggregated per name, type, and tag set
+     * regardless of tag order, and that taking them starts a new interval.
+     */
+    #[test]
+    fn test_aggregation() {
+        let mut aggregator = Aggregator::new(1_700_000_000_000);
+        aggregator.add(
+            MetricKind::Counter,
+            "orders.failed",
+            1.0,
+            &[("region", "eu"), ("tier", "pro")],
+        );
+        aggregator.add(
+            MetricKind::Counter,
+            "orders.failed",
+            2.0,
+            &[("tier", "pro"), ("region", "eu")],
+        );
+        aggregator.add(MetricKind::Counter, "orders.failed", 1.0, &[]);
+        aggregator.add(MetricKind::Gauge, "queue.depth", 5.0, &[]);
+        aggregator.add(MetricKind::Gauge, "queue.depth", 3.0, &[]);
+        aggregator.add(MetricKind::Distribution, "latency", 12.5, &[]);
+        aggregator.add(MetricKind::Distribution, "latency", 7.5, &[]);
+
+        let started = aggregator.started;
+        assert!(!aggregator.is_due(started));
+        assert!(aggregator.is_due(started + FLUSH_INTERVAL_MS));
+
+        let data = aggregator.take(started + 1).unwrap();
+        assert_eq!(data.start_timestamp, started);
+        assert_eq!(
+            serde_json::to_value(&data.metrics).unwrap(),
+            serde_json::json!([
+                { "name": "latency", "type": "distribution", "values": [12.5, 7.5], "min": 7.5, "max": 12.5, "sum": 20.0, "count": 2 },
+                { "name": "orders.failed", "type": "counter", "value": 1.0 },
+                { "name": "orders.failed", "tags": { "region": "eu", "tier": "pro" }, "type": "counter", "value": 3.0 },
+                { "name": "queue.depth", "type": "gauge", "last": 3.0, "min": 3.0, "max": 5.0, "sum": 8.0, "count": 2 },
+            ])
+        );
+
+        assert!(aggregator.take(started + 2).is_none());
+        assert_eq!(aggregator.started, started + 2);
+
+        for n in 0..3 * MAX_DISTRIBUTION_VALUES {
+            aggregator.add(MetricKind::Distribution, "latency", n as f64, &[]);
+        }
+        let data = aggregator.take(started + 3).unwrap();
+        let MetricValue::Distribution { values, count, .. } = &data.metrics[0].value else {
+            panic!("not a distribution");
+        };
+        assert_eq!(values.len(), MAX_DISTRIBUTION_VALUES);
+        assert_eq!(*count, 3 * MAX_DISTRIBUTION_VALUES as u64);
+        assert!(values.iter().any(|&v| v >= MAX_DISTRIBUTION_VALUES as f64));
+    }
+}
@@ -0,0 +1,188 @@
+/*!
+ * Panic types — a normalized event type for the panics std raises itself.
+ *
+ * Panic titles are free text: `index out of bounds: the len is 3 but the
+ * index is 7` differs with every length and index. The messages of the
+ * panics raised by std and its macros follow fixed patterns, so they map to
+ * a stable type instead of `"fatal"`:
+ *
+ * | Message                                          | Type                 |
+ * |--------------------------------------------------|----------------------|
+ * | ``called `Option::unwrap()` on a `None` value``  | `UnwrapNone`         |
+ * | ``called `Result::unwrap()` on an `Err` value``  | `UnwrapErr`          |
+ * | `index out of bounds`, slice and `str` ranges    | `IndexOutOfBounds`   |
+ * | `assert!`, `assert_eq!`, `assert_ne!`            | `AssertionFailed`    |
+ * | `attempt to divide by zero`, remainder by zero   | `DivideByZero`       |
+ * | `attempt to add with overflow`, ...              | `ArithmeticOverflow` |
+ * | `unreachable!()`                                 | `Unreachable`        |
+ * | `unimplemented!()`                               | `Unimplemented`      |
+ * | `todo!()`                                        | `Todo`               |
+ *
+ * Other panics, including `expect()` and `assert!` with a custom message,
+ * keep `"fatal"`. Events of all these types are fatal: they carry the log
+ * tail like any other.
+ */
+
+/// Type of panic events whose message matches no known pattern.
+const FATAL_EVENT_TYPE: &str = "fatal";
+
+/// Message prefixes and the type of the panics they start.
+const PREFIXES: &[(&str, &str)] = &[
+    ("called `Option::unwrap()` on a `None` value", "UnwrapNone"),
+    ("called `Result::unwrap()` on an `Err` value", "UnwrapErr"),
+    ("index out of bounds:", "IndexOutOfBounds"),
+    ("range start index ", "IndexOutOfBounds"),
+    ("range end index ", "IndexOutOfBounds"),
+    ("slice index starts at ", "IndexOutOfBounds"),
+    ("byte index ", "IndexOutOfBounds"),
+    ("start byte index ", "IndexOutOfBounds"),
+    ("end byte index ", "IndexOutOfBounds"),
+    ("assertion failed: ", "AssertionFailed"),
+    ("assertion `left ", "AssertionFailed"),
+    ("attempt to divide by zero", "DivideByZero"),
+    (
+        "attempt to calculate the remainder with a divisor of zero",
+        "DivideByZero",
+    ),
+];
+
+/// Messages of the `unreachable!`, `todo!`, and `unimplemented!` panics and
+/// their types. Matched whole, or followed by `": "` and the message passed
+/// to the macro, so that free text such as `"not implemented yet for X"`
+/// stays `"fatal"`.
+const MACRO_MESSAGES: &[(&str, &str)] = &[
+    ("internal error: entered unreachable code", "Unreachable"),
+    ("not yet implemented", "Todo"),
+    ("not implemented", "Unimplemented"),
+];
+
+/// Types of fatal events: every type `panic_type()` returns, and aborts.
+const FATAL_TYPES: &[&str] = &[
+    FATAL_EVENT_TYPE,
+    "abort",
+    "UnwrapNone",
+    "UnwrapErr",
+    "IndexOutOfBounds",
+    "AssertionFailed",
+    "DivideByZero",
+    "ArithmeticOverflow",
+    "Unreachable",
+    "Unimplemented",
+    "Todo",
+];
+
+/**
+ * Event type for a panic with the given message: one of the normalized
+ * types above, or `"fatal"`.
+ */
+pub fn panic_type(message: &str) -> &'static str {
+    if let Some(&(_, event_type)) = PREFIXES
+        .iter()
+        .find(|(prefix, _)| message.starts_with(prefix))
+    {
+        return event_type;
+    }
+    if let Some(&(_, event_type)) = MACRO_MESSAGES.iter().find(|(macro_message, _)| {
+        message
+            .strip_prefix(macro_message)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(": "))
+    }) {
+        return event_type;
+    }
+    if message.starts_with("attempt to ") && message.ends_with(" with overflow") {
+        return "ArithmeticOverflow";
+    }
+    FATAL_EVENT_TYPE
+}
+
+/**
+ * Whether events of this type are fatal — panics, aborts, and crashes.
+ */
+pub(crate) fn is_fatal(event_type: &str) -> bool {
+    FATAL_TYPES.contains(&event_type)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::hint::black_box;
+
+    use super::*;
+
+    /**
+     * Verifies the types of the panics std raises, and that other messages
+     * stay `"fatal"`.
+     */
+    #[test]
+    fn test_panic_type() {
+        let message = |f: fn()| {
+            let payload = std::panic::catch_unwind(f).unwrap_err();
+            match payload.downcast::<String>() {
+                Ok(message) => *message,
+                Err(payload) => payload.downcast_ref::<&str>().unwrap().to_string(),
+            }
+        };
+
+        let cases = [
+            (
+                message(|| {
+                    black_box(black_box(None::<u8>).unwrap());
+                }),
+                "UnwrapNone",
+            ),
+            (
+                message(|| {
+                    black_box("x".parse::<u8>().unwrap());
+                }),
+                "UnwrapErr",
+            ),
+            (
+                message(|| {
+                    black_box(black_box([1, 2, 3])[black_box(7)]);
+                }),
+                "IndexOutOfBounds",
+            ),
+            (
+                message(|| {
+                    black_box(&black_box(&[1, 2, 3][..])[..black_box(7)]);
+                }),
+                "IndexOutOfBounds",
+            ),
+            (
+                message(|| {
+                    black_box(&black_box("héllo")[..black_box(2)]);
+                }),
+                "IndexOutOfBounds",
+            ),
+            (message(|| assert!(black_box(1) > 2)), "AssertionFailed"),
+            (message(|| assert_eq!(black_box(1), 2)), "AssertionFailed"),
+            (
+                message(|| {
+                    black_box(1 / black_box(0));
+                }),
+                "DivideByZero",
+            ),
+            (
+                message(|| {
+                    black_box(black_box(u8::MAX).checked_add(1).expect("counter overflow"));
+                }),
+                "fatal",
+            ),
+            (message(|| unreachable!()), "Unreachable"),
+            (message(|| unimplemented!("export")), "Unimplemented"),
+            (message(|| todo!()), "Todo"),
+            (message(|| todo!("parser")), "Todo"),
+        ];
+
+        for (message, expected) in cases {
+            assert_eq!(panic_type(&message), expected, "{message}");
+        }
+        assert_eq!(
+            panic_type("attempt to multiply with overflow"),
+            "ArithmeticOverflow"
+        );
+        assert_eq!(panic_type("payment declined"), "fatal");
+        assert_eq!(panic_type("not implemented yet for X"), "fatal");
+        assert_eq!(panic_type("not yet implemented, see #12"), "fatal");
+        assert!(is_fatal("UnwrapNone") && is_fatal("fatal") && !is_fatal("error"));
+    }
+}
@@ -0,0 +1,385 @@
+/*!
+ * Performance monitoring — transactions and spans.
+ *
+ * `start_transaction()` returns a `Transaction` handle timing one unit of
+ * work. Child spans are started from it (or from other spans), and are
+ * collected into the transaction when they finish. Finishing the
+ * transaction sends it, with its spans, through the same worker and
+ * transport as error events, in a `HawkTransaction` envelope.
+ *
+ * Handles finish themselves when dropped, so an early return or `?`
+ * still records the work. Spans finished after their transaction are
+ * lost.
+ *
+ * A transaction or span can be bound to the current thread while a
+ * closure runs (`bind()`); `start_span()` then starts children of it
+ * without the handle being passed down.
+ *
+ * ```ignore
+ * let transaction = hawk::start_transaction("GET /orders", "http.server");
+ * {
+ *     let mut span = transaction.start_child("db.query", "SELECT * FROM orders");
+ *     span.set_data("rows", 42);
+ * } // span finished
+ * transaction.finish();
+ * ```
+ */
+
+use std::cell::RefCell;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, OnceLock};
+
+use serde_json::{Map, Value};
+
+use crate::client;
+use crate::clock::now_millis;
+use crate::protocol::performance::{SpanData, TransactionData};
+
+/// Status of work that completed normally.
+const STATUS_OK: &str = "ok";
+
+thread_local! {
+    /**
+     * Transactions and spans bound with `bind()`, innermost last: the
+     * transaction new children belong to and the ID they get as parent.
+     */
+    static BOUND: RefCell<Vec<(Arc<Mutex<TransactionState>>, String)>> = const { RefCell::new(Vec::new()) };
+}
+
+// ---------------------------------------------------------------------------
+// Transaction
+// ---------------------------------------------------------------------------
+
+/**
+ * Starts timing a transaction — a request, a job run, any unit of work
+ * worth measuring. `op` names the kind of work (`"http.server"`, `"job"`).
+ *
+ * Works before `init()` too; the transaction is then discarded when it
+ * finishes.
+ */
+pub fn start_transaction(name: &str, op: &str) -> Transaction {
+    let mut data = TransactionData::new(new_id(), name, op);
+    data.start_timestamp = now_millis();
+
+    Transaction {
+        state: Arc::new(Mutex::new(TransactionState {
+            data,
+            stopwatch: Stopwatch::start(),
+            finished: false,
+        })),
+    }
+}
+
+/**
+ * Starts a span under the innermost transaction or span bound to this
+ * thread with `bind()`. `None` if nothing is bound.
+ */
+pub fn start_span(op: &str, description: &str) -> Option<Span> {
+    BOUND.with(|bound| {
+        bound.borrow().last().map(|(transaction, parent_id)| {
+            Span::start(transaction.clone(), parent_id.clone(), op, description)
+        })
+    })
+}
+
+/**
+ * Handle to a running transaction. Finishes (and is sent) on `finish()` or
+ * when dropped. Shareable across threads by reference, so spans can be
+ * started from worker threads.
+ */
+pub struct Transaction {
+    state: Arc<Mutex<TransactionState>>,
+}
+
+struct TransactionState {
+    data: TransactionData,
+    stopwatch: Stopwatch,
+
+    /// Set once the transaction has been sent; later spans are dropped.
+    finished: bool,
+}
+
+impl Transaction {
+    /**
+     * ID of the transaction, referenced by its top-level spans.
+     */
+    pub fn id(&self) -> String {
+        self.lock().data.id.clone()
+    }
+
+    /**
+     * Starts a top-level span of this transaction.
+     */
+    pub fn start_child(&self, op: &str, description: &str) -> Span {
+        let parent_id = self.id();
+        Span::start(self.state.clone(), parent_id, op, description)
+    }
+
+    /**
+     * Runs `f` with this transaction bound to the current thread, as the
+     * parent of spans started with `start_span()`.
+     */
+    pub fn bind<R>(&self, f: impl FnOnce() -> R) -> R {
+        bind_parent(&self.state, self.id(), f)
+    }
+
+    /**
+     * Sets the outcome, e.g. `"internal_error"`. Defaults to `"ok"`.
+     */
+    pub fn set_status(&self, status: &str) {
+        self.lock().data.status = status.to_string();
+    }
+
+    /**
+     * Attaches a key-value pair to the transaction's data.
+     */
+    pub fn set_data(&self, key: &str, value: impl Into<Value>) {
+        insert_data(&mut self.lock().data.data, key, value.into());
+    }
+
+    /**
+     * Stops the clock and sends the transaction. Same as dropping it.
+     */
+    pub fn finish(self) {}
+
+    fn lock(&self) -> MutexGuard<'_, TransactionState> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for Transaction {
+    fn drop(&mut self) {
+        let data = {
+            let mut state = self.lock();
+            state.finished = true;
+            state.data.duration = state.stopwatch.elapsed_ms();
+            state.data.clone()
+        };
+
+        if let Some(client) = client::get_client() {
+            client.send_transaction(data);
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Span
+// ---------------------------------------------------------------------------
+
+/**
+ * Handle to a running span. Recorded in its transaction on `finish()` or
+ * when dropped.
+ */
+pub struct Span {
+    transaction: Arc<Mutex<TransactionState>>,
+    data: SpanData,
+    stopwatch: Stopwatch,
+}
+
+impl Span {
+    fn start(
+        transaction: Arc<Mutex<TransactionState>>,
+        parent_id: String,
+        op: &str,
+        description: &str,
+    ) -> Self {
+        Self {
+            transaction,
+            data: SpanData {
+                id: new_id(),
+                parent_id,
+                op: op.to_string(),
+                description: (!description.is_empty()).then(|| description.to_string()),
+                status: STATUS_OK.to_string(),
+                start_timestamp: now_millis(),
+                duration: 0.0,
+                data: None,
+            },
+            stopwatch: Stopwatch::start(),
+        }
+    }
+
+    /**
+     * ID of the span, referenced by its children.
+     */
+    pub fn id(&self) -> &str {
+        &self.data.id
+    }
+
+    /**
+     * Starts a span nested in this one.
+     */
+    pub fn start_child(&self, op: &str, description: &str) -> Span {
+        Span::start(
+            self.transaction.clone(),
+            self.data.id.clone(),
+            op,
+            description,
+        )
+    }
+
+    /**
+     * Runs `f` with this span bound to the current thread, as the parent
+     * of spans started with `start_span()`.
+     */
+    pub fn bind<R>(&self, f: impl FnOnce() -> R) -> R {
+        bind_parent(&self.transaction, self.data.id.clone(), f)
+    }
+
+    /**
+     * Sets the outcome, e.g. `"internal_error"`. Defaults to `"ok"`.
+     */
+    pub fn set_status(&mut self, status: &str) {
+        self.data.status = status.to_string();
+    }
+
+    /**
+     * Attaches a key-value pair to the span's data.
+     */
+    pub fn set_data(&mut self, key: &str, value: impl Into<Value>) {
+        insert_data(&mut self.data.data, key, value.into());
+    }
+
+    /**
+     * Stops the clock and records the span. Same as dropping it.
+     */
+    pub fn finish(self) {}
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        self.data.duration = self.stopwatch.elapsed_ms();
+
+        let mut transaction = self.transaction.lock().unwrap_or_else(|e| e.into_inner());
+        if !transaction.finished {
+            transaction.data.spans.push(self.data.clone());
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Internal helpers
+// ---------------------------------------------------------------------------
+
+/**
+ * Monotonic clock for durations. `Instant` panics on
+ * `wasm32-unknown-unknown`; `Date.now()` is used there instead.
+ */
+pub(crate) struct Stopwatch {
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    started: std::time::Instant,
+
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    started: f64,
+}
+
+impl Stopwatch {
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub(crate) fn start() -> Self {
+        Self {
+            started: std::time::Instant::now(),
+        }
+    }
+
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    pub(crate) fn start() -> Self {
+        Self {
+            started: js_sys::Date::now(),
+        }
+    }
+
+    /// Milliseconds since `start()`.
+    #[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+    pub(crate) fn elapsed_ms(&self) -> f64 {
+        self.started.elapsed().as_secs_f64() * 1000.0
+    }
+
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    pub(crate) fn elapsed_ms(&self) -> f64 {
+        js_sys::Date::now() - self.started
+    }
+}
+
+/**
+ * Runs `f` with `parent_id` of `transaction` pushed on this thread's
+ * `BOUND` stack.
+ */
+fn bind_parent<R>(
+    transaction: &Arc<Mutex<TransactionState>>,
+    parent_id: String,
+    f: impl FnOnce() -> R,
+) -> R {
+    struct PopOnDrop;
+
+    impl Drop for PopOnDrop {
+        fn drop(&mut self) {
+            BOUND.with(|bound| {
+                bound.borrow_mut().pop();
+            });
+        }
+    }
+
+    BOUND.with(|bound| bound.borrow_mut().push((transaction.clone(), parent_id)));
+    let _pop = PopOnDrop;
+
+    f()
+}
+
+/**
+ * Random 64-bit ID as 16 hex digits: a counter hashed with a key chosen
+ * randomly once per process.
+ */
+pub(crate) fn new_id() -> String {
+    static KEYS: OnceLock<RandomState> = OnceLock::new();
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let mut hasher = KEYS.get_or_init(RandomState::new).build_hasher();
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    format!("{:016x}", hasher.finish())
+}
+
+/**
+ * Inserts `key` into a `data` object, creating it if needed.
+ */
+fn insert_data(data: &mut Option<Value>, key: &str, value: Value) {
+    if let Value::Object(map) = data.get_or_insert_with(|| Value::Object(Map::new())) {
+        map.insert(key.to_string(), value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * Verifies that finished spans are collected into their transaction
+     * with the right parents, and that IDs are unique.
+     */
+    #[test]
+    fn test_spans_collected_into_transaction() {
+        let transaction = start_transaction("import", "job");
+        let transaction_id = transaction.id();
+        {
+            let mut outer = transaction.start_child("db.query", "SELECT 1");
+            outer.set_data("rows", 1);
+            outer.start_child("db.fetch", "").finish();
+        }
+
+        let state = transaction.lock();
+        let spans = &state.data.spans;
+        assert_eq!(spans.len(), 2);
+        assert_eq!(spans[0].op, "db.fetch");
+        assert_eq!(spans[0].parent_id, spans[1].id);
+        assert_eq!(spans[1].parent_id, transaction_id);
+        assert_eq!(spans[1].data, Some(serde_json::json!({ "rows": 1 })));
+        assert_ne!(spans[0].id, spans[1].id);
+        drop(state);
+
+        assert!(start_span("cache.get", "").is_none());
+        let bound = transaction
+            .bind(|| start_span("cache.get", "").map(|span| span.data.parent_id.clone()));
+        assert_eq!(bound, Some(transaction_id));
+    }
+}
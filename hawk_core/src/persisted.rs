@@ -0,0 +1,328 @@
+/*!
+ * Persisted fatal events — delivery of fatal events across the end of the
+ * process.
+ *
+ * A panic under `panic = "abort"`, or one followed by a kill or a native
+ * crash, can end the process before the worker delivers its event. With
+ * `Options::fatal_event_dir` set, fatal events (panics, aborts) are
+ * written to that directory before they are enqueued, breadcrumbs and
+ * all, and deleted once the worker has sent them. Files still there at
+ * the next `init()` belong to a run that ended first: they are sent then,
+ * with `previous_run: true` in their context.
+ *
+ * Events are written as they are sent — after `before_send`, scrubbing,
+ * and truncation — with their backtrace resolved on the calling thread,
+ * as frame addresses mean nothing to the next run. That costs a few
+ * milliseconds per fatal event.
+ *
+ * Files hold `{"v": FORMAT_VERSION, "event": ...}`: events left by an
+ * older SDK before an upgrade are migrated to the current layout when
+ * read, rather than discarded, and those left by a newer one (after a
+ * downgrade) are read with the fields this one knows — or kept for it if
+ * they cannot be. With `Options::spool_key` set, files are encrypted (see
+ * `spool`); otherwise they are plaintext JSON.
+ *
+ * Only delivery cut short by the end of the process is covered: an event
+ * the collector rejects, or that fails on the network, is not kept.
+ */
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use serde_json::{json, Value};
+
+use crate::panic_type::is_fatal;
+use crate::protocol::types::EventData;
+use crate::spool::{self, SpoolCipher};
+
+/// File extension of persisted events.
+const EXTENSION: &str = "json";
+
+/// File extension of events being written.
+const PARTIAL_EXTENSION: &str = "partial";
+
+/**
+ * Version of the file format written by this SDK:
+ *
+ * - `0` — the bare event, without the `{"v": ...}` wrapper.
+ * - `1` — the event under `event`, with its version under `v`.
+ */
+const FORMAT_VERSION: u64 = 1;
+
+/**
+ * The directory fatal events are kept in until delivered.
+ */
+pub(crate) struct PersistedEvents {
+    dir: PathBuf,
+    cipher: Option<Arc<SpoolCipher>>,
+}
+
+impl PersistedEvents {
+    /**
+     * Opens `dir`, creating it if needed, and reads the events left in it
+     * by earlier runs, flagged as such. Their files stay until they are
+     * delivered. With a `cipher`, files are written encrypted.
+     */
+    pub(crate) fn open(dir: PathBuf, cipher: Option<Arc<SpoolCipher>>) -> (Self, Vec<EventData>) {
+        if let Err(e) = std::fs::create_dir_all(&dir) {
+            eprintln!(
+                "[Hawk] Failed to create fatal event directory {}: {e}",
+                dir.display()
+            );
+        }
+        let previous = read_previous(&dir, cipher.as_deref());
+        (Self { dir, cipher }, previous)
+    }
+
+    /**
+     * Whether the event is kept until delivered: a fatal one, numbered.
+     */
+    pub(crate) fn keeps(&self, event: &EventData) -> bool {
+        event.event_type.as_deref().is_some_and(is_fatal) && event_id(event).is_some()
+    }
+
+    /**
+     * Writes the event, replacing a previous copy. The file only appears
+     * once complete, so that a crash mid-write leaves nothing to misread.
+     */
+    pub(crate) fn write(&self, event: &EventData) {
+        let Some(path) = self.path(event) else {
+            return;
+        };
+        let partial = path.with_extension(PARTIAL_EXTENSION);
+        let result = serde_json::to_value(event)
+            .and_then(|event| serde_json::to_vec(&json!({ "v": FORMAT_VERSION, "event": event })))
+            .map_err(io::Error::from)
+            .and_then(|json| match &self.cipher {
+                Some(cipher) => cipher.seal(&json).map_err(io::Error::other),
+                None => Ok(json),
+            })
+            .and_then(|contents| std::fs::write(&partial, contents))
+            .and_then(|()| std::fs::rename(&partial, &path));
+        if let Err(e) = result {
+            eprintln!(
+                "[Hawk] Failed to persist fatal event to {}: {e}",
+                path.display()
+            );
+        }
+    }
+
+    /**
+     * Deletes the file of a delivered event, by its ID, if it was kept.
+     */
+    pub(crate) fn remove(&self, id: &str) {
+        let path = self.file(id);
+        if let Err(e) = std::fs::remove_file(&path) {
+            if e.kind() != io::ErrorKind::NotFound {
+                eprintln!(
+                    "[Hawk] Failed to delete persisted event {}: {e}",
+                    path.display()
+                );
+            }
+        }
+    }
+
+    /**
+     * File of the event, if it has an ID.
+     */
+    fn path(&self, event: &EventData) -> Option<PathBuf> {
+        event_id(event).map(|id| self.file(id))
+    }
+
+    /**
+     * File of the event with the given ID, named after it with anything
+     * but ASCII letters, digits, `-`, and `_` replaced.
+     */
+    fn file(&self, id: &str) -> PathBuf {
+        let name: String = id
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.dir.join(name).with_extension(EXTENSION)
+    }
+}
+
+fn event_id(event: &EventData) -> Option<&str> {
+    event.addons.as_ref()?.event_id.as_deref()
+}
+
+/**
+ * Reads the events in `dir`. Unreadable files — including ones encrypted
+ * with another key — are reported and deleted, as are files left
+ * half-written.
+ */
+fn read_previous(dir: &Path, cipher: Option<&SpoolCipher>) -> Vec<EventData> {
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut events = Vec::new();
+    for path in entries.flatten().map(|entry| entry.path()) {
+        let extension = path.extension().and_then(|extension| extension.to_str());
+        if extension == Some(PARTIAL_EXTENSION) {
+            let _ = std::fs::remove_file(&path);
+            continue;
+        }
+        if extension != Some(EXTENSION) {
+            continue;
+        }
+
+        let file = std::fs::read(&path)
+            .and_then(|contents| spool::read(contents, cipher).map_err(io::Error::other))
+            .and_then(|json| serde_json::from_slice::<Value>(&json).map_err(io::Error::from));
+        let version = file.as_ref().map_or(0, version);
+        let parsed = file.and_then(|file| {
+            serde_json::from_value::<EventData>(migrate(file)).map_err(io::Error::from)
+        });
+        match parsed {
+            Ok(mut event) => {
+                match event.context.as_mut() {
+                    Some(Value::Object(context)) => {
+                        context.insert("previous_run".into(), Value::Bool(true));
+                    }
+                    _ => event.context = Some(json!({ "previous_run": true })),
+                }
+                events.push(event);
+            }
+            Err(e) if version > FORMAT_VERSION => {
+                eprintln!(
+                    "[Hawk] Keeping persisted event {} of a newer SDK version: {e}",
+                    path.display()
+                );
+            }
+            Err(e) => {
+                eprintln!(
+                    "[Hawk] Discarding unreadable persisted event {}: {e}",
+                    path.display()
+                );
+                let _ = std::fs::remove_file(&path);
+            }
+        }
+    }
+    events
+}
+
+/**
+ * Format version of a persisted file.
+ */
+fn version(file: &Value) -> u64 {
+    file.get("v").and_then(Value::as_u64).unwrap_or(0)
+}
+
+/**
+ * The event of a persisted file of any version, converted to the current
+ * layout one version at a time.
+ */
+fn migrate(file: Value) -> Value {
+    match file {
+        // 0 → 1: the event moved under `event`; its layout is unchanged.
+        Value::Object(mut file) if file.contains_key("v") => {
+            file.remove("event").unwrap_or_default()
+        }
+        event => event,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::types::{Addons, Breadcrumb};
+
+    /**
+     * Verifies that a fatal event left by a run is read back, flagged,
+     * at the next, and that delivery deletes its file.
+     */
+    #[test]
+    fn test_persisted_events() {
+        let dir = std::env::temp_dir().join(format!("hawk-persisted-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+
+        let (persisted, previous) = PersistedEvents::open(dir.clone(), None);
+        assert!(previous.is_empty());
+
+        let mut event = EventData::new("panic: boom");
+        event.event_type = Some("UnwrapNone".into());
+        event.breadcrumbs = Some(vec![Breadcrumb::at(1, "loaded orders")]);
+        event.addons = Some(Addons {
+            event_id: Some("../7f3a".into()),
+            ..Addons::default()
+        });
+        let mut handled = EventData::new("handled");
+        handled.event_type = Some("error".into());
+        handled.addons = event.addons.clone();
+        assert!(persisted.keeps(&event) && !persisted.keeps(&handled));
+
+        persisted.write(&event);
+        std::fs::write(dir.join("torn.json"), "{\"title\": \"pan").unwrap();
+        std::fs::write(dir.join("torn.partial"), "{").unwrap();
+
+        let (persisted, previous) = PersistedEvents::open(dir.clone(), None);
+        assert_eq!(previous.len(), 1);
+        assert_eq!(previous[0].title, "panic: boom");
+        assert_eq!(
+            previous[0].breadcrumbs.as_ref().unwrap()[0]
+                .message
+                .as_deref(),
+            Some("loaded orders")
+        );
+        assert_eq!(previous[0].context, Some(json!({ "previous_run": true })));
+        assert!(dir.join("___7f3a.json").exists());
+        assert!(!dir.join("torn.json").exists() && !dir.join("torn.partial").exists());
+
+        persisted.remove(event_id(&previous[0]).unwrap());
+        assert!(PersistedEvents::open(dir.clone(), None).1.is_empty());
+
+        // Encrypted with a spool key: unreadable on disk and with another
+        // key, which discards the file.
+        let cipher = Arc::new(SpoolCipher::new(&[1; 32]).unwrap());
+        let (persisted, _) = PersistedEvents::open(dir.clone(), Some(cipher.clone()));
+        persisted.write(&event);
+        let contents = std::fs::read(dir.join("___7f3a.json")).unwrap();
+        assert!(!contents.windows(4).any(|window| window == b"boom"));
+        let (_, previous) = PersistedEvents::open(dir.clone(), Some(cipher));
+        assert_eq!(previous[0].title, "panic: boom");
+        let other = Arc::new(SpoolCipher::new(&[2; 32]).unwrap());
+        assert!(PersistedEvents::open(dir.clone(), Some(other)).1.is_empty());
+        assert!(!dir.join("___7f3a.json").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+
+    /**
+     * Verifies that files of an older format version are migrated, and
+     * that those of a newer one are read with the known fields, or kept
+     * if they cannot be.
+     */
+    #[test]
+    fn test_persisted_versions() {
+        let dir = std::env::temp_dir().join(format!("hawk-versions-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&dir);
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let bare = serde_json::to_vec(&EventData::new("panic: before the wrapper")).unwrap();
+        std::fs::write(dir.join("v0.json"), bare).unwrap();
+        let newer = json!({ "v": 2, "event": {
+            "title": "panic: newer",
+            "catcherVersion": "9.0.0",
+            "severity": 3,
+        } });
+        std::fs::write(dir.join("v2.json"), newer.to_string()).unwrap();
+        let unknown = json!({ "v": 2, "event": { "headline": "panic: newer" } });
+        std::fs::write(dir.join("v2-unknown.json"), unknown.to_string()).unwrap();
+        std::fs::write(dir.join("v1-broken.json"), r#"{"v": 1, "event": {}}"#).unwrap();
+
+        let (_, previous) = PersistedEvents::open(dir.clone(), None);
+        let mut titles: Vec<&str> = previous.iter().map(|event| event.title.as_str()).collect();
+        titles.sort();
+        assert_eq!(titles, ["panic: before the wrapper", "panic: newer"]);
+        assert!(dir.join("v0.json").exists() && dir.join("v2-unknown.json").exists());
+        assert!(!dir.join("v1-broken.json").exists());
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,272 @@
+/*!
+ * Pretty printing — events rendered to the terminal for local development.
+ *
+ * With `Options::print_events`, every event is printed to stderr exactly
+ * as it would be sent (after `before_send`, scrubbing, and truncation):
+ * title, type, the topmost frames, and the context as a diff against the
+ * previously printed event, so that what changed between two runs of the
+ * same failure stands out.
+ *
+ * ```text
+ * [Hawk] Connection refused
+ *   type: Error
+ *   at my_app::db::connect (src/db.rs:42:9)
+ *   at my_app::main (src/main.rs:12:5)
+ *   context:
+ *     + request.method = "GET"
+ *     ~ attempt = 3 (was 2)
+ *     (4 unchanged)
+ * ```
+ *
+ * Colored when stderr is a terminal, unless `NO_COLOR` is set.
+ */
+
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::io::IsTerminal;
+use std::sync::Mutex;
+
+use serde_json::Value;
+
+use crate::protocol::types::EventData;
+
+/// Number of frames printed; the rest are summarized.
+const MAX_FRAMES: usize = 5;
+
+const RESET: &str = "\x1b[0m";
+const BOLD: &str = "\x1b[1m";
+const DIM: &str = "\x1b[2m";
+const RED: &str = "\x1b[31m";
+const GREEN: &str = "\x1b[32m";
+const YELLOW: &str = "\x1b[33m";
+
+/**
+ * Whether events are printed to the terminal, and whether they are still
+ * sent.
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PrintEvents {
+    /// Events are only sent.
+    #[default]
+    Off,
+
+    /// Events are printed and sent.
+    AndSend,
+
+    /// Events are printed instead of being sent.
+    Instead,
+}
+
+/**
+ * Prints events, remembering the last context printed to diff against.
+ */
+pub(crate) struct Printer {
+    mode: PrintEvents,
+    previous_context: Mutex<BTreeMap<String, String>>,
+}
+
+impl Printer {
+    pub(crate) fn new(mode: PrintEvents) -> Self {
+        Self {
+            mode,
+            previous_context: Mutex::new(BTreeMap::new()),
+        }
+    }
+
+    pub(crate) fn is_enabled(&self) -> bool {
+        self.mode != PrintEvents::Off
+    }
+
+    /**
+     * Whether printed events are sent as well.
+     */
+    pub(crate) fn sends(&self) -> bool {
+        self.mode != PrintEvents::Instead
+    }
+
+    /**
+     * Prints the event to stderr in one piece, so that events printed
+     * from several threads do not interleave.
+     */
+    pub(crate) fn print(&self, event: &EventData) {
+        let color = std::io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+        let context = flatten_context(event.context.as_ref());
+
+        let text = {
+            let mut previous = self
+                .previous_context
+                .lock()
+                .unwrap_or_else(|poisoned| poisoned.into_inner());
+            let text = render(event, &previous, &context, color);
+            *previous = context;
+            text
+        };
+        eprint!("{text}");
+    }
+}
+
+/**
+ * Renders the event, diffing its flattened `context` against `previous`.
+ */
+fn render(
+    event: &EventData,
+    previous: &BTreeMap<String, String>,
+    context: &BTreeMap<String, String>,
+    color: bool,
+) -> String {
+    let paint = |style: &str, text: &str| {
+        if color {
+            format!("{style}{text}{RESET}")
+        } else {
+            text.to_string()
+        }
+    };
+
+    let mut out = String::new();
+    let _ = writeln!(
+        out,
+        "[Hawk] {}",
+        paint(&format!("{BOLD}{RED}"), &event.title)
+    );
+    if let Some(event_type) = &event.event_type {
+        let _ = writeln!(out, "  type: {event_type}");
+    }
+
+    let frames = event.backtrace.as_deref().unwrap_or_default();
+    for frame in frames.iter().take(MAX_FRAMES) {
+        let mut line = format!("at {}", frame.function.as_deref().unwrap_or("<unknown>"));
+        if let Some(file) = &frame.file {
+            let _ = write!(line, " ({file}");
+            for number in [frame.line, frame.column].into_iter().flatten() {
+                let _ = write!(line, ":{number}");
+            }
+            line.push(')');
+        }
+        let _ = writeln!(
+            out,
+            "  {}",
+            paint(if frame.in_app { BOLD } else { DIM }, &line)
+        );
+    }
+    if frames.len() > MAX_FRAMES {
+        let _ = writeln!(
+            out,
+            "  {}",
+            paint(
+                DIM,
+                &format!("... {} more frames", frames.len() - MAX_FRAMES)
+            )
+        );
+    }
+
+    if !context.is_empty() || !previous.is_empty() {
+        let _ = writeln!(out, "  context:");
+        let mut unchanged = 0;
+        for (path, value) in context {
+            match previous.get(path) {
+                None => {
+                    let _ = writeln!(out, "    {}", paint(GREEN, &format!("+ {path} = {value}")));
+                }
+                Some(old) if old != value => {
+                    let _ = writeln!(
+                        out,
+                        "    {}",
+                        paint(YELLOW, &format!("~ {path} = {value} (was {old})"))
+                    );
+                }
+                Some(_) => unchanged += 1,
+            }
+        }
+        for path in previous.keys().filter(|path| !context.contains_key(*path)) {
+            let _ = writeln!(out, "    {}", paint(RED, &format!("- {path}")));
+        }
+        if unchanged > 0 {
+            let _ = writeln!(
+                out,
+                "    {}",
+                paint(DIM, &format!("({unchanged} unchanged)"))
+            );
+        }
+    }
+
+    out
+}
+
+/**
+ * Flattens the context into dotted paths of its leaf values, as JSON.
+ */
+fn flatten_context(context: Option<&Value>) -> BTreeMap<String, String> {
+    fn walk(prefix: &str, value: &Value, out: &mut BTreeMap<String, String>) {
+        match value {
+            Value::Object(fields) if !fields.is_empty() => {
+                for (key, field) in fields {
+                    let path = if prefix.is_empty() {
+                        key.clone()
+                    } else {
+                        format!("{prefix}.{key}")
+                    };
+                    walk(&path, field, out);
+                }
+            }
+            _ => {
+                out.insert(prefix.to_string(), value.to_string());
+            }
+        }
+    }
+
+    let mut out = BTreeMap::new();
+    if let Some(context) = context {
+        walk("", context, &mut out);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::types::BacktraceFrame;
+    use serde_json::json;
+
+    /**
+     * Verifies the rendered frames and the context diff against the
+     * previous event.
+     */
+    #[test]
+    fn test_render_event() {
+        let frame = BacktraceFrame {
+            file: Some("src/db.rs".into()),
+            line: Some(42),
+            column: Some(9),
+            function: Some("my_app::db::connect".into()),
+            module: None,
+            instruction_addr: None,
+            module_offset: None,
+            inlined: false,
+            in_app: true,
+        };
+        let mut event = EventData::new("Connection refused");
+        event.event_type = Some("Error".into());
+        event.backtrace = Some(vec![frame; MAX_FRAMES + 2]);
+        event.context = Some(json!({ "request": { "method": "GET" }, "attempt": 3, "host": "db" }));
+
+        let previous = flatten_context(Some(
+            &json!({ "attempt": 2, "host": "db", "user": { "id": 7 } }),
+        ));
+        let text = render(
+            &event,
+            &previous,
+            &flatten_context(event.context.as_ref()),
+            false,
+        );
+
+        let expected = "[Hawk] Connection refused\n  type: Error\n".to_string()
+            + &"  at my_app::db::connect (src/db.rs:42:9)\n".repeat(MAX_FRAMES)
+            + "  ... 2 more frames\n"
+            + "  context:\n"
+            + "    ~ attempt = 3 (was 2)\n"
+            + "    + request.method = \"GET\"\n"
+            + "    - user.id\n"
+            + "    (1 unchanged)\n";
+        assert_eq!(text, expected);
+    }
+}
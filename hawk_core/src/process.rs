@@ -0,0 +1,483 @@
+/*!
+ * Process context — uptime and restart history, under `process` in the
+ * context of every event.
+ *
+ * - `uptime_ms`: milliseconds since `init()`.
+ *
+ * With `Options::state_file` set, the SDK also keeps a small file across
+ * runs, marking the process as running at `init()` and as cleanly exited
+ * when the `Guard` is dropped. A run that ends without dropping its guard
+ * (a crash, a kill, `std::process::exit`) leaves the mark in place, so the
+ * next run knows. Events then also carry:
+ *
+ * - `restarts`: number of earlier runs recorded in the file;
+ * - `previous_exit`: `"clean"` or `"crash"`, absent on the first run;
+ * - `consecutive_crashes`: earlier runs in a row that ended uncleanly —
+ *   a crash loop shows up as a growing count on every event;
+ * - `startup_crashes`: how many of those ended within
+ *   `Options::startup_crash_window` of their start.
+ *
+ * Startup crashes are told apart with a second mark: the run is marked as
+ * starting up at `init()` (along with its `started_at` time), and a timer
+ * clears that mark once the window has passed. Once
+ * `Options::startup_crash_threshold` runs in a row ended while marked,
+ * the process is crash looping (`is_crash_looping()`): fatal events
+ * reported during startup, and those of earlier runs, carry
+ * `startup_crash: true` in their context. Panics count like native
+ * crashes, as neither clears the marks.
+ *
+ * The file holds `key=value` lines; one file per process, as concurrent
+ * processes would overwrite each other's marks.
+ */
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use serde_json::{json, Map, Value};
+
+use crate::panic_type::is_fatal;
+use crate::performance::Stopwatch;
+use crate::protocol::types::{now_millis, EventData};
+
+/**
+ * Uptime and restart history of the process, attached to every event.
+ */
+pub(crate) struct ProcessInfo {
+    /// Started at `init()`.
+    started: Stopwatch,
+
+    /// Restart history; `None` without `state_file`.
+    history: Option<Arc<History>>,
+
+    /// Whether a panic was reported in this run.
+    panicked: AtomicBool,
+}
+
+/**
+ * What the state file said about earlier runs, and where it is.
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct RunState {
+    /// Runs recorded, including the current one once written.
+    runs: u64,
+
+    /// Earlier runs in a row that ended without a clean exit.
+    consecutive_crashes: u64,
+
+    /// Earlier runs in a row that ended without a clean exit while
+    /// starting up.
+    startup_crashes: u64,
+
+    /// When the last recorded run started, in milliseconds since the Unix
+    /// epoch.
+    started_at: u64,
+
+    /// Whether the last recorded run has not exited cleanly (yet).
+    running: bool,
+
+    /// Whether the last recorded run is still within its startup window.
+    starting: bool,
+}
+
+struct History {
+    path: PathBuf,
+
+    /// State of the current run, as last written.
+    state: Mutex<RunState>,
+
+    /// State of the current run as written at `init()`, for events.
+    initial: RunState,
+
+    /// Whether the previous run exited cleanly; `None` on the first run.
+    previous_clean: Option<bool>,
+
+    /// Startup crashes in a row making a crash loop; `0` if detection is
+    /// off.
+    threshold: u32,
+}
+
+impl ProcessInfo {
+    /**
+     * Starts the uptime clock and, with a state file, records the start of
+     * this run in it, and starts the startup window.
+     */
+    pub(crate) fn start(state_file: Option<PathBuf>, window: Duration, threshold: u32) -> Self {
+        let history = state_file.map(|path| {
+            // Without threads (wasm) there is no timer to end the window.
+            let off = window.is_zero() || cfg!(target_arch = "wasm32");
+            let threshold = if off { 0 } else { threshold };
+            Arc::new(History::start(path, threshold))
+        });
+
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(history) = history.as_ref().filter(|history| history.threshold != 0) {
+            let history = Arc::clone(history);
+            let spawned = std::thread::Builder::new()
+                .name("hawk-startup".into())
+                .spawn(move || {
+                    std::thread::sleep(window);
+                    history.end_startup();
+                });
+            if let Err(e) = spawned {
+                eprintln!("[Hawk] Failed to spawn startup timer: {e}");
+            }
+        }
+
+        Self {
+            started: Stopwatch::start(),
+            history,
+            panicked: AtomicBool::new(false),
+        }
+    }
+
+    /**
+     * Adds the `process` entry to the context, unless the event already
+     * has one. Fatal events of this run reported during the startup of a
+     * crash loop get `startup_crash: true`.
+     */
+    pub(crate) fn attach(&self, event: &mut EventData) {
+        let mut process = Map::new();
+        process.insert("uptime_ms".into(), json!(self.started.elapsed_ms() as u64));
+        if let Some(ref history) = self.history {
+            let state = history.initial;
+            process.insert("restarts".into(), json!(state.runs - 1));
+            if let Some(clean) = history.previous_clean {
+                process.insert(
+                    "previous_exit".into(),
+                    json!(if clean { "clean" } else { "crash" }),
+                );
+            }
+            process.insert(
+                "consecutive_crashes".into(),
+                json!(state.consecutive_crashes),
+            );
+            process.insert("startup_crashes".into(), json!(state.startup_crashes));
+        }
+
+        match event.context.as_mut() {
+            Some(Value::Object(context)) => {
+                context.entry("process").or_insert(Value::Object(process));
+            }
+            _ => event.context = Some(json!({ "process": process })),
+        }
+
+        let fatal = event.event_type.as_deref().is_some_and(is_fatal);
+        if fatal && !is_previous_run(event) && self.history.as_ref().is_some_and(|h| h.ends_loop())
+        {
+            mark_startup_crash(event);
+        }
+    }
+
+    /**
+     * Whether the runs before this one were a startup crash loop.
+     */
+    pub(crate) fn is_crash_looping(&self) -> bool {
+        self.history
+            .as_ref()
+            .is_some_and(|history| history.is_looping(history.initial.startup_crashes))
+    }
+
+    /**
+     * Records that a panic was reported in this run.
+     */
+    pub(crate) fn mark_panicked(&self) {
+        self.panicked.store(true, Ordering::SeqCst);
+    }
+
+    /**
+     * Marks this run as cleanly exited, unless a panic was reported in it:
+     * after a panic in `main` the runtime exits through `exit(101)`, which
+     * must still count as a crash.
+     */
+    pub(crate) fn mark_clean_exit_unless_panicked(&self) {
+        if !self.panicked.load(Ordering::SeqCst) {
+            self.mark_clean_exit();
+        }
+    }
+
+    /**
+     * Marks this run as cleanly exited in the state file, if any.
+     */
+    pub(crate) fn mark_clean_exit(&self) {
+        if let Some(ref history) = self.history {
+            history.update(|state| {
+                state.running = false;
+                state.starting = false;
+            });
+        }
+    }
+}
+
+impl History {
+    fn start(path: PathBuf, threshold: u32) -> Self {
+        let previous = match std::fs::read_to_string(&path) {
+            Ok(contents) => parse_state(&contents),
+            Err(e) => {
+                if e.kind() != io::ErrorKind::NotFound {
+                    eprintln!("[Hawk] Failed to read state file {}: {e}", path.display());
+                }
+                RunState::default()
+            }
+        };
+
+        let state = RunState {
+            started_at: now_millis(),
+            starting: threshold != 0,
+            ..next_run(previous)
+        };
+        if let Err(e) = write_state(&path, state) {
+            eprintln!("[Hawk] Failed to write state file {}: {e}", path.display());
+        }
+        Self {
+            path,
+            state: Mutex::new(state),
+            initial: state,
+            previous_clean: (previous.runs > 0).then_some(!previous.running),
+            threshold,
+        }
+    }
+
+    /**
+     * Whether `startup_crashes` in a row make a crash loop.
+     */
+    fn is_looping(&self, startup_crashes: u64) -> bool {
+        self.threshold != 0 && startup_crashes >= u64::from(self.threshold)
+    }
+
+    /**
+     * Whether this run ending now would be a startup crash continuing (or
+     * starting) a crash loop.
+     */
+    fn ends_loop(&self) -> bool {
+        let state = *self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.starting && self.is_looping(state.startup_crashes + 1)
+    }
+
+    /**
+     * Clears the startup mark, once the window has passed.
+     */
+    #[cfg(not(target_arch = "wasm32"))]
+    fn end_startup(&self) {
+        self.update(|state| state.starting = false);
+    }
+
+    /**
+     * Applies `change` to the state of a run still running, and writes it.
+     */
+    fn update(&self, change: impl FnOnce(&mut RunState)) {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        if !state.running {
+            return;
+        }
+        change(&mut state);
+        if let Err(e) = write_state(&self.path, *state) {
+            eprintln!(
+                "[Hawk] Failed to write state file {}: {e}",
+                self.path.display()
+            );
+        }
+    }
+}
+
+/**
+ * Whether the event was recorded by an earlier run and is only delivered
+ * now (flagged `previous_run`): this run's state does not describe it.
+ */
+pub(crate) fn is_previous_run(event: &EventData) -> bool {
+    event
+        .context
+        .as_ref()
+        .is_some_and(|context| context.get("previous_run").is_some())
+}
+
+/**
+ * Flags a fatal event as part of a startup crash loop.
+ */
+pub(crate) fn mark_startup_crash(event: &mut EventData) {
+    match event.context.as_mut() {
+        Some(Value::Object(context)) => {
+            context.insert("startup_crash".into(), Value::Bool(true));
+        }
+        _ => event.context = Some(json!({ "startup_crash": true })),
+    }
+}
+
+/**
+ * State of a new run following `previous`.
+ */
+fn next_run(previous: RunState) -> RunState {
+    RunState {
+        runs: previous.runs + 1,
+        consecutive_crashes: if previous.running {
+            previous.consecutive_crashes + 1
+        } else {
+            0
+        },
+        startup_crashes: if previous.running && previous.starting {
+            previous.startup_crashes + 1
+        } else {
+            0
+        },
+        started_at: 0,
+        running: true,
+        starting: false,
+    }
+}
+
+/**
+ * Parses a state file. Unknown or malformed lines are ignored, so that a
+ * damaged file only resets the history.
+ */
+fn parse_state(contents: &str) -> RunState {
+    let mut state = RunState::default();
+    for line in contents.lines() {
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        match key {
+            "runs" => state.runs = value.parse().unwrap_or(0),
+            "consecutive_crashes" => state.consecutive_crashes = value.parse().unwrap_or(0),
+            "startup_crashes" => state.startup_crashes = value.parse().unwrap_or(0),
+            "started_at" => state.started_at = value.parse().unwrap_or(0),
+            "running" => state.running = value == "1",
+            "starting" => state.starting = value == "1",
+            _ => {}
+        }
+    }
+    state
+}
+
+fn write_state(path: &Path, state: RunState) -> io::Result<()> {
+    std::fs::write(
+        path,
+        format!(
+            "runs={}\nconsecutive_crashes={}\nstartup_crashes={}\nstarted_at={}\nrunning={}\nstarting={}\n",
+            state.runs,
+            state.consecutive_crashes,
+            state.startup_crashes,
+            state.started_at,
+            u8::from(state.running),
+            u8::from(state.starting)
+        ),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * Verifies that unclean exits accumulate across runs — including exits
+     * after a panic — and that a clean exit resets the count.
+     */
+    #[test]
+    fn test_restart_history() {
+        let path = std::env::temp_dir().join(format!("hawk-state-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let first = ProcessInfo::start(Some(path.clone()), Duration::ZERO, 0);
+        let mut event = EventData::new("boom");
+        first.attach(&mut event);
+        let process = &event.context.as_ref().unwrap()["process"];
+        assert_eq!(process["restarts"], 0);
+        assert_eq!(process.get("previous_exit"), None);
+
+        // The first run crashes: its guard is never dropped.
+        let second = ProcessInfo::start(Some(path.clone()), Duration::ZERO, 0);
+        let mut event = EventData::new("boom");
+        second.attach(&mut event);
+        let process = &event.context.as_ref().unwrap()["process"];
+        assert_eq!(process["restarts"], 1);
+        assert_eq!(process["previous_exit"], "crash");
+        assert_eq!(process["consecutive_crashes"], 1);
+
+        // The second run panics in `main`, then exits through `atexit`.
+        second.mark_panicked();
+        second.mark_clean_exit_unless_panicked();
+        let third = ProcessInfo::start(Some(path.clone()), Duration::ZERO, 0);
+        let history = third.history.as_ref().unwrap();
+        assert_eq!(history.previous_clean, Some(false));
+        assert_eq!(history.initial.consecutive_crashes, 2);
+
+        third.mark_clean_exit_unless_panicked();
+        let fourth = ProcessInfo::start(Some(path.clone()), Duration::ZERO, 0);
+        let history = fourth.history.as_ref().unwrap();
+        assert_eq!(history.previous_clean, Some(true));
+        assert_eq!(
+            history.initial,
+            RunState {
+                runs: 4,
+                started_at: history.initial.started_at,
+                running: true,
+                ..RunState::default()
+            }
+        );
+
+        assert_eq!(
+            parse_state("runs=x\ngarbage\nrunning=1"),
+            RunState {
+                running: true,
+                ..RunState::default()
+            }
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+
+    /**
+     * Verifies that runs ending uncleanly within the startup window build
+     * up to a crash loop, flagging fatal events, and that a run outliving
+     * the window resets the count.
+     */
+    #[test]
+    fn test_startup_crash_loop() {
+        let path = std::env::temp_dir().join(format!("hawk-startup-{}", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let window = Duration::from_secs(60);
+        let fatal = || EventData {
+            event_type: Some("fatal".into()),
+            ..EventData::new("boom")
+        };
+
+        // A panic at startup, then a crash at startup: the second makes a loop.
+        let first = ProcessInfo::start(Some(path.clone()), window, 2);
+        let mut event = fatal();
+        first.attach(&mut event);
+        assert_eq!(event.context.as_ref().unwrap().get("startup_crash"), None);
+        first.mark_panicked();
+        first.mark_clean_exit_unless_panicked();
+
+        let second = ProcessInfo::start(Some(path.clone()), window, 2);
+        assert!(!second.is_crash_looping());
+        let mut event = fatal();
+        second.attach(&mut event);
+        assert_eq!(event.context.as_ref().unwrap()["startup_crash"], true);
+        let mut event = EventData::new("warning");
+        second.attach(&mut event);
+        assert_eq!(event.context.as_ref().unwrap().get("startup_crash"), None);
+
+        let third = ProcessInfo::start(Some(path.clone()), Duration::from_millis(10), 2);
+        assert!(third.is_crash_looping());
+        assert_eq!(third.history.as_ref().unwrap().initial.startup_crashes, 2);
+        let mut event = fatal();
+        event.context = Some(json!({ "previous_run": true }));
+        third.attach(&mut event);
+        assert_eq!(event.context.as_ref().unwrap().get("startup_crash"), None);
+
+        // The third run crashes after its window: not a startup crash.
+        std::thread::sleep(Duration::from_millis(500));
+        let mut event = fatal();
+        third.attach(&mut event);
+        assert_eq!(event.context.as_ref().unwrap().get("startup_crash"), None);
+
+        let fourth = ProcessInfo::start(Some(path.clone()), window, 2);
+        assert!(!fourth.is_crash_looping());
+        let history = fourth.history.as_ref().unwrap();
+        assert_eq!(history.initial.consecutive_crashes, 3);
+        assert_eq!(history.initial.startup_crashes, 0);
+        fourth.mark_clean_exit();
+        let _ = std::fs::remove_file(&path);
+    }
+}
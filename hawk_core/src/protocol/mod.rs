@@ -1,12 +1,13 @@
 /*!
  * Protocol layer — data structures, constants, and token handling.
  *
- * Everything related to *what* we send to the Hawk backend:
+ * Lives in the `no_std` compatible `hawk_protocol` crate; re-exported
+ * here under the paths the rest of `hawk_core` uses:
  * - `types` — HawkEvent envelope, EventData payload, BacktraceFrame
+ * - `performance` — HawkTransaction envelope, TransactionData, SpanData
+ * - `metrics` — HawkMetrics envelope, MetricsData, MetricData
  * - `constants` — CATCHER_TYPE, CATCHER_VERSION
  * - `token` — base64 token decoding and endpoint derivation
  */
 
-pub mod constants;
-pub mod token;
-pub mod types;
+pub use hawk_protocol::{constants, metrics, performance, token, types};
@@ -0,0 +1,177 @@
+/*!
+ * HTTP request context — the request being handled when an event is sent.
+ *
+ * Web integrations (and handlers written by hand) describe the incoming
+ * request with a `RequestContext` and run the handler inside
+ * `RequestContext::run()`: every event sent meanwhile carries it under the
+ * `request` context key.
+ *
+ * Headers are safe by default: values of `Authorization`, `Cookie`,
+ * `Set-Cookie`, and the names in `Options::scrub_headers` are replaced
+ * with `"[Filtered]"`, keeping the header names so that their presence is
+ * still visible. With `Options::send_default_pii`, the client IP address
+ * is sent and the three default headers are left to the `scrub_keys`
+ * denylist, which covers them unless it was changed.
+ *
+ * ```ignore
+ * let request = hawk::RequestContext::new(req.method().as_str(), &req.uri().to_string())
+ *     .headers(req.headers().iter().filter_map(|(name, value)| Some((name.as_str(), value.to_str().ok()?))))
+ *     .client_ip(&peer_addr.ip().to_string());
+ * request.run(|| handle(req))
+ * ```
+ */
+
+use std::sync::Arc;
+
+use serde_json::{json, Map, Value};
+
+use crate::client;
+use crate::context;
+use crate::scrubbing::FILTERED;
+
+/// Headers whose values are replaced unless `send_default_pii` is set.
+pub const DEFAULT_SCRUB_HEADERS: &[&str] = &["authorization", "cookie", "set-cookie"];
+
+/**
+ * An incoming HTTP request.
+ */
+#[derive(Clone, Debug, Default)]
+pub struct RequestContext {
+    /// HTTP method, e.g. `"GET"`.
+    pub method: String,
+
+    /// Request URL or path.
+    pub url: String,
+
+    /// Headers in the order received; repeated names are joined with `", "`.
+    pub headers: Vec<(String, String)>,
+
+    /// Address of the client. Sent only with `send_default_pii`.
+    pub client_ip: Option<String>,
+}
+
+impl RequestContext {
+    pub fn new(method: &str, url: &str) -> Self {
+        Self {
+            method: method.to_string(),
+            url: url.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /**
+     * Adds one header.
+     */
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.headers.push((name.to_string(), value.to_string()));
+        self
+    }
+
+    /**
+     * Adds several headers.
+     */
+    pub fn headers<'a>(mut self, headers: impl IntoIterator<Item = (&'a str, &'a str)>) -> Self {
+        self.headers.extend(
+            headers
+                .into_iter()
+                .map(|(name, value)| (name.to_string(), value.to_string())),
+        );
+        self
+    }
+
+    /**
+     * Sets the client's IP address.
+     */
+    pub fn client_ip(mut self, ip: &str) -> Self {
+        self.client_ip = Some(ip.to_string());
+        self
+    }
+
+    /**
+     * The `request` context object, with headers scrubbed according to the
+     * client options.
+     */
+    pub fn to_value(&self) -> Value {
+        let (send_default_pii, extra_headers) = match client::get_client() {
+            Some(client) => (client.send_default_pii(), client.scrub_headers()),
+            None => (false, &[][..]),
+        };
+
+        let is_scrubbed = |name: &str| {
+            (!send_default_pii
+                && DEFAULT_SCRUB_HEADERS
+                    .iter()
+                    .any(|header| header.eq_ignore_ascii_case(name)))
+                || extra_headers
+                    .iter()
+                    .any(|header| header.eq_ignore_ascii_case(name))
+        };
+
+        let mut headers = Map::new();
+        for (name, value) in &self.headers {
+            let value = if is_scrubbed(name) {
+                FILTERED
+            } else {
+                value.as_str()
+            };
+            match headers.get_mut(name) {
+                Some(Value::String(joined)) => {
+                    joined.push_str(", ");
+                    joined.push_str(value);
+                }
+                _ => {
+                    headers.insert(name.clone(), Value::String(value.to_string()));
+                }
+            }
+        }
+
+        let mut request = json!({
+            "method": self.method,
+            "url": self.url,
+            "headers": headers,
+        });
+        if let Some(ip) = self.client_ip.as_ref().filter(|_| send_default_pii) {
+            request["client_ip"] = json!(ip);
+        }
+        request
+    }
+
+    /**
+     * Runs `f` with this request attached, under `request`, to every event
+     * sent from this thread meanwhile.
+     */
+    pub fn run<R>(&self, f: impl FnOnce() -> R) -> R {
+        let scope = Arc::new(json!({ "request": self.to_value() }));
+        context::with_scope(&scope, f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * Verifies that sensitive header values are replaced, names kept,
+     * repeated headers joined, and the client IP left out by default.
+     */
+    #[test]
+    fn test_request_headers_scrubbed() {
+        let request = RequestContext::new("POST", "/login")
+            .header("Authorization", "Bearer abc")
+            .headers([
+                ("cookie", "sid=1"),
+                ("Accept", "text/html"),
+                ("Accept", "*/*"),
+            ])
+            .client_ip("203.0.113.7");
+
+        assert_eq!(
+            request.to_value(),
+            json!({
+                "method": "POST",
+                "url": "/login",
+                "headers": { "Authorization": FILTERED, "cookie": FILTERED, "Accept": "text/html, */*" },
+            })
+        );
+    }
+}
@@ -0,0 +1,117 @@
+/*!
+ * Event sampling — sending a share of events (`Options::sample_rate`).
+ *
+ * Whether an event is kept is drawn ahead, by the first check of
+ * `accepts_events()` on its thread, so that a sampled-out event costs
+ * neither a backtrace nor the event itself: the capture paths return
+ * before building it. The send then uses the decision already drawn, so
+ * that an event checked first is not drawn twice.
+ *
+ * A decision to drop is used up by the check that returns it, since its
+ * caller drops the event without sending it. A decision to keep lasts
+ * until an event is sent from the thread. Either way every event sent
+ * uses up one draw, so the share kept is the rate.
+ *
+ * Events captured with `capture_events()` are never sampled out.
+ */
+
+use std::cell::Cell;
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::OnceLock;
+
+thread_local! {
+    /// Whether the next event sent from this thread is kept, if drawn.
+    static NEXT: Cell<Option<bool>> = const { Cell::new(None) };
+}
+
+/**
+ * Keeps each event with the probability `rate`.
+ */
+pub(crate) struct Sampler {
+    rate: f64,
+}
+
+impl Sampler {
+    /**
+     * A sampler keeping the share `rate` of events, from `0.0` to `1.0`.
+     */
+    pub(crate) fn new(rate: f64) -> Self {
+        Self { rate }
+    }
+
+    /**
+     * Whether the next event of this thread will be kept, for the checks
+     * made before building it. A `false` is used up.
+     */
+    pub(crate) fn peek(&self) -> bool {
+        if self.rate >= 1.0 {
+            return true;
+        }
+        NEXT.with(|next| {
+            let kept = next.get().unwrap_or_else(|| self.draw());
+            next.set(kept.then_some(true));
+            kept
+        })
+    }
+
+    /**
+     * Whether the event being sent is kept: the decision drawn by
+     * `peek()`, or a new one. Either way it is used up.
+     */
+    pub(crate) fn take(&self) -> bool {
+        if self.rate >= 1.0 {
+            return true;
+        }
+        NEXT.with(|next| next.take().unwrap_or_else(|| self.draw()))
+    }
+
+    fn draw(&self) -> bool {
+        self.rate > 0.0 && unit_random() < self.rate
+    }
+}
+
+/**
+ * Random number in `[0, 1)`: a counter hashed with a key chosen randomly
+ * once per process.
+ */
+fn unit_random() -> f64 {
+    static KEYS: OnceLock<RandomState> = OnceLock::new();
+    static COUNTER: AtomicU64 = AtomicU64::new(0);
+
+    let mut hasher = KEYS.get_or_init(RandomState::new).build_hasher();
+    hasher.write_u64(COUNTER.fetch_add(1, Ordering::Relaxed));
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * Verifies that the share of events kept follows the rate, that a
+     * send uses the decision its check drew, and that a check drawing a
+     * drop uses it up.
+     */
+    #[test]
+    fn test_sampler() {
+        let half = Sampler::new(0.5);
+        let kept = (0..1000).filter(|_| half.take()).count();
+        assert!((400..600).contains(&kept), "{kept} of 1000 kept");
+
+        for _ in 0..100 {
+            match half.peek() {
+                true => {
+                    assert!(half.peek());
+                    assert!(half.take());
+                }
+                false => assert_eq!(NEXT.with(Cell::get), None),
+            }
+        }
+
+        assert!(!Sampler::new(0.0).peek());
+        assert!(!Sampler::new(0.0).take());
+        assert!(Sampler::new(1.0).peek() && Sampler::new(1.0).take());
+    }
+}
@@ -0,0 +1,151 @@
+/*!
+ * Sanitation — terminal escapes and control characters out of events.
+ *
+ * Errors built by terminal-oriented formatters (colored logs, `color-eyre`
+ * reports, CLI tools' output) carry ANSI escape sequences and control
+ * characters that show up as `\u001b[31m` noise in the UI and split
+ * otherwise identical titles into separate issues.
+ *
+ * Applied to every event after `before_send` and before scrubbing (so
+ * that patterns see clean text): ANSI escape sequences (CSI such as colors
+ * and cursor movement, OSC such as hyperlinks and window titles, and
+ * two-character escapes) are removed, then every other control character
+ * except newline and tab.
+ *
+ * Covers the title, every string in the context (keys excluded), and
+ * breadcrumb messages, categories, and data.
+ */
+
+use serde_json::Value;
+
+use crate::protocol::types::EventData;
+
+const ESC: char = '\x1b';
+const BEL: char = '\x07';
+
+/// Single-character CSI (`ESC [`), as used by some 8-bit terminals.
+const CSI: char = '\u{9b}';
+
+/**
+ * Strips escape sequences and control characters from every string of the
+ * event.
+ */
+pub(crate) fn sanitize_event(event: &mut EventData) {
+    sanitize_string(&mut event.title);
+
+    if let Some(context) = event.context.as_mut() {
+        sanitize_value(context);
+    }
+
+    for breadcrumb in event.breadcrumbs.iter_mut().flatten() {
+        for text in [&mut breadcrumb.message, &mut breadcrumb.category]
+            .into_iter()
+            .flatten()
+        {
+            sanitize_string(text);
+        }
+        if let Some(data) = breadcrumb.data.as_mut() {
+            sanitize_value(data);
+        }
+    }
+}
+
+fn sanitize_value(value: &mut Value) {
+    match value {
+        Value::String(text) => sanitize_string(text),
+        Value::Array(items) => items.iter_mut().for_each(sanitize_value),
+        Value::Object(fields) => fields.values_mut().for_each(sanitize_value),
+        _ => {}
+    }
+}
+
+fn is_stripped(c: char) -> bool {
+    c.is_control() && c != '\n' && c != '\t'
+}
+
+/**
+ * Strips in place; strings without control characters — nearly all of
+ * them — are not copied.
+ */
+fn sanitize_string(text: &mut String) {
+    if !text.chars().any(is_stripped) {
+        return;
+    }
+
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            ESC => match chars.next() {
+                /*
+                 * CSI: parameter and intermediate bytes, up to a final
+                 * byte in `@`..=`~`.
+                 */
+                Some('[') => skip_csi(&mut chars),
+
+                /*
+                 * OSC, DCS, and the other string sequences: up to BEL or
+                 * the string terminator `ESC \`.
+                 */
+                Some(']' | 'P' | 'X' | '^' | '_') => {
+                    while let Some(c) = chars.next() {
+                        if c == BEL || (c == ESC && chars.next_if_eq(&'\\').is_some()) {
+                            break;
+                        }
+                    }
+                }
+
+                /* Two-character escapes (`ESC 7`, `ESC c`, ...). */
+                _ => {}
+            },
+            CSI => skip_csi(&mut chars),
+            c if is_stripped(c) => {}
+            c => out.push(c),
+        }
+    }
+    *text = out;
+}
+
+fn skip_csi(chars: &mut impl Iterator<Item = char>) {
+    for c in chars.by_ref() {
+        if ('@'..='~').contains(&c) {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::types::Breadcrumb;
+    use serde_json::json;
+
+    /**
+     * Verifies that colors, hyperlinks, and control characters are
+     * stripped everywhere in the event, keeping newlines, tabs, and
+     * non-ASCII text.
+     */
+    #[test]
+    fn test_sanitize_event() {
+        let mut event = EventData::new("\x1b[1;31merror\x1b[0m: café\r\x00");
+        event.context = Some(json!({
+            "report": "line 1\n\tline 2\x1b[K",
+            "link": ["\x1b]8;;https://example.com\x1b\\docs\x1b]8;;\x07", 42],
+        }));
+        event.breadcrumbs = Some(vec![Breadcrumb {
+            category: Some("\x1b7cli\x1b8".into()),
+            ..Breadcrumb::at(0, "\u{9b}32mok\u{9b}0m\x7f")
+        }]);
+
+        sanitize_event(&mut event);
+
+        assert_eq!(event.title, "error: café");
+        assert_eq!(
+            event.context,
+            Some(json!({ "report": "line 1\n\tline 2", "link": ["docs", 42] }))
+        );
+        let breadcrumb = &event.breadcrumbs.unwrap()[0];
+        assert_eq!(breadcrumb.message.as_deref(), Some("ok"));
+        assert_eq!(breadcrumb.category.as_deref(), Some("cli"));
+    }
+}
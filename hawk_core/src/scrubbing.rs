@@ -0,0 +1,524 @@
+/*!
+ * PII scrubbing — redacting sensitive values before events leave the
+ * process.
+ *
+ * Applied to every event right before it is enqueued, after
+ * `before_send`, in up to four passes:
+ *
+ * 1. Allowlist (strict mode, `Options::context_allowlist`) — everything
+ *    in the context and in breadcrumb data except the allowlisted keys
+ *    is dropped. Entries are top-level keys (`"job"`) or dotted paths
+ *    (`"request.method"`).
+ * 2. Keys — values stored under a sensitive key anywhere in the event
+ *    context (request data, headers, scoped context, ...) or in
+ *    breadcrumb data are replaced with `"[Filtered]"`, whole objects
+ *    included. Keys are split into words on `-`, `_`, and case changes;
+ *    they match when a denylisted key is one of their words or a run of
+ *    them — `api_key` covers `apiKey`, `X-Api-Key`, and `stripe_api_key`
+ *    — unless the next word names a quantity (`token_count`), and when
+ *    they end with it, ignoring case, `-`, and `_` (`authtoken`).
+ *    `max_tokens` matches neither way.
+ * 3. Environment — inside objects stored under `env` / `environment`
+ *    keys (environment snapshots), values of variables named like
+ *    credentials (`*_KEY`, `*_SECRET`, `*_TOKEN`, `AWS_*`) are replaced,
+ *    except for variables on the allowlist.
+ * 4. Patterns — matches of the built-in patterns (card numbers, bearer
+ *    tokens, email addresses) and of user-supplied regexes are replaced
+ *    with `[Filtered]` inside the title, every context string, and
+ *    breadcrumb messages and data.
+ *
+ * Backtrace frames are code, not data, and are left alone.
+ */
+
+use regex::Regex;
+use serde_json::{Map, Value};
+
+use crate::client::Options;
+use crate::protocol::types::EventData;
+
+/// Default for `Options::scrub_keys`.
+pub const DEFAULT_SCRUB_KEYS: &[&str] = &[
+    "password",
+    "secret",
+    "token",
+    "authorization",
+    "cookie",
+    "api_key",
+];
+
+/// Words after a denylisted key that make its key name a quantity, not a
+/// value: `token_count`, `password_length`.
+const QUANTITY_WORDS: &[&str] = &["count", "len", "length", "size", "limit", "total"];
+
+/// Replacement for scrubbed values.
+pub(crate) const FILTERED: &str = "[Filtered]";
+
+/// Glob patterns (`*` at either end) of environment variables holding
+/// credentials, matched ignoring case.
+const ENV_PATTERNS: &[&str] = &["*_KEY", "*_SECRET", "*_TOKEN", "AWS_*"];
+
+/// Context keys holding environment snapshots, normalized with
+/// `normalize_key()`.
+const ENV_CONTEXT_KEYS: &[&str] = &[
+    "env",
+    "environ",
+    "environment",
+    "envvars",
+    "environmentvariables",
+];
+
+/// Card numbers: 13 to 19 digits, optionally grouped with spaces or
+/// dashes. Matches are redacted only if they pass the Luhn check.
+const CARD_PATTERN: &str = r"\b\d(?:[ -]?\d){12,18}\b";
+
+/// Built-in patterns redacted without further checks.
+const BUILTIN_PATTERNS: &[&str] = &[
+    // `Authorization: Bearer ...` values.
+    r"(?i)\bbearer\s+[a-z0-9\-._~+/]+=*",
+    // Email addresses.
+    r"[a-zA-Z0-9._%+\-]+@[a-zA-Z0-9.\-]+\.[a-zA-Z]{2,}",
+];
+
+/**
+ * A compiled pattern, with an extra check on each match.
+ */
+struct Pattern {
+    regex: Regex,
+    accepts: fn(&str) -> bool,
+}
+
+/**
+ * Redacts values under the configured keys and matches of the configured
+ * patterns.
+ */
+pub(crate) struct Scrubber {
+    /// Denylisted keys, normalized with `normalize_key()` and split with
+    /// `key_words()`.
+    keys: Vec<(String, Vec<String>)>,
+
+    /// Whether environment snapshots are scrubbed.
+    scrub_env: bool,
+
+    /// Environment variables never redacted, uppercased.
+    env_allowlist: Vec<String>,
+
+    /// In strict mode, the context and breadcrumb data paths kept, split
+    /// on `.`.
+    context_allowlist: Option<Vec<Vec<String>>>,
+
+    patterns: Vec<Pattern>,
+}
+
+impl Scrubber {
+    /**
+     * Builds the scrubber configured by `options`: keys, environment
+     * snapshots, and built-in patterns with `scrub_data`, plus the
+     * user-supplied `scrub_patterns` and `context_allowlist`. Fails on an
+     * invalid pattern; `None` if there is nothing to scrub.
+     */
+    pub(crate) fn new(options: &Options) -> Result<Option<Self>, String> {
+        let keys: Vec<(String, Vec<String>)> = match options.scrub_data {
+            true => options
+                .scrub_keys
+                .iter()
+                .map(|key| (normalize_key(key), key_words(key)))
+                .filter(|(key, words)| !key.is_empty() && !words.is_empty())
+                .collect(),
+            false => Vec::new(),
+        };
+
+        let mut compiled = Vec::new();
+        if options.scrub_data {
+            compiled.push(Pattern {
+                regex: compile(CARD_PATTERN)?,
+                accepts: luhn_valid,
+            });
+            for pattern in BUILTIN_PATTERNS {
+                compiled.push(Pattern {
+                    regex: compile(pattern)?,
+                    accepts: |_| true,
+                });
+            }
+        }
+        for pattern in &options.scrub_patterns {
+            compiled.push(Pattern {
+                regex: compile(pattern)?,
+                accepts: |_| true,
+            });
+        }
+
+        if !options.scrub_data && compiled.is_empty() && options.context_allowlist.is_none() {
+            return Ok(None);
+        }
+
+        Ok(Some(Self {
+            keys,
+            scrub_env: options.scrub_data,
+            env_allowlist: options
+                .scrub_env_allowlist
+                .iter()
+                .map(|name| name.to_ascii_uppercase())
+                .collect(),
+            context_allowlist: options.context_allowlist.as_ref().map(|paths| {
+                paths
+                    .iter()
+                    .map(|path| path.split('.').map(str::to_string).collect())
+                    .collect()
+            }),
+            patterns: compiled,
+        }))
+    }
+
+    /**
+     * Scrubs the event's title, context, and breadcrumbs in place.
+     */
+    pub(crate) fn scrub_event(&self, event: &mut EventData) {
+        if let Some(allowlist) = &self.context_allowlist {
+            let paths: Vec<&[String]> = allowlist.iter().map(Vec::as_slice).collect();
+            event.context = retain_allowed(event.context.take(), &paths);
+            for breadcrumb in event.breadcrumbs.iter_mut().flatten() {
+                breadcrumb.data = retain_allowed(breadcrumb.data.take(), &paths);
+            }
+        }
+
+        self.scrub_string(&mut event.title);
+
+        if let Some(context) = event.context.as_mut() {
+            self.scrub_value(context);
+        }
+
+        for breadcrumb in event.breadcrumbs.iter_mut().flatten() {
+            if let Some(message) = breadcrumb.message.as_mut() {
+                self.scrub_string(message);
+            }
+            if let Some(data) = breadcrumb.data.as_mut() {
+                self.scrub_value(data);
+            }
+        }
+    }
+
+    fn scrub_value(&self, value: &mut Value) {
+        match value {
+            Value::Object(fields) => {
+                for (key, value) in fields.iter_mut() {
+                    if self.is_sensitive(key) {
+                        *value = Value::String(FILTERED.to_string());
+                        continue;
+                    }
+                    if self.scrub_env && ENV_CONTEXT_KEYS.contains(&normalize_key(key).as_str()) {
+                        self.scrub_env_snapshot(value);
+                    }
+                    self.scrub_value(value);
+                }
+            }
+            Value::Array(items) => items.iter_mut().for_each(|item| self.scrub_value(item)),
+            Value::String(text) => self.scrub_string(text),
+            _ => {}
+        }
+    }
+
+    /**
+     * Replaces the pattern matches in `text`.
+     */
+    fn scrub_string(&self, text: &mut String) {
+        for pattern in &self.patterns {
+            if !pattern.regex.is_match(text) {
+                continue;
+            }
+
+            let scrubbed = pattern
+                .regex
+                .replace_all(text, |captures: &regex::Captures<'_>| {
+                    let matched = &captures[0];
+                    if (pattern.accepts)(matched) {
+                        FILTERED.to_string()
+                    } else {
+                        matched.to_string()
+                    }
+                });
+            *text = scrubbed.into_owned();
+        }
+    }
+
+    /**
+     * Replaces the values of credential-like variables in an environment
+     * snapshot (`{"NAME": "value", ...}`).
+     */
+    fn scrub_env_snapshot(&self, snapshot: &mut Value) {
+        let Value::Object(variables) = snapshot else {
+            return;
+        };
+
+        for (name, value) in variables.iter_mut() {
+            let name = name.to_ascii_uppercase();
+            if ENV_PATTERNS
+                .iter()
+                .any(|pattern| matches_glob(&name, pattern))
+                && !self.env_allowlist.contains(&name)
+            {
+                *value = Value::String(FILTERED.to_string());
+            }
+        }
+    }
+
+    /**
+     * Whether values under `key` are redacted, as described in step 2
+     * above.
+     */
+    fn is_sensitive(&self, key: &str) -> bool {
+        let normalized = normalize_key(key);
+        let words = key_words(key);
+        self.keys.iter().any(|(denied, denied_words)| {
+            normalized.ends_with(denied.as_str())
+                || words
+                    .windows(denied_words.len())
+                    .enumerate()
+                    .any(|(start, run)| {
+                        run == denied_words.as_slice()
+                            && !words
+                                .get(start + run.len())
+                                .is_some_and(|next| QUANTITY_WORDS.contains(&next.as_str()))
+                    })
+        })
+    }
+}
+
+/**
+ * Keeps the fields of an object on one of `paths`, the whole value being
+ * dropped if it is not an object.
+ */
+fn retain_allowed(value: Option<Value>, paths: &[&[String]]) -> Option<Value> {
+    match value {
+        Some(Value::Object(fields)) => Some(Value::Object(retain_paths(fields, paths))),
+        _ => None,
+    }
+}
+
+/**
+ * Keeps the fields of `fields` on one of `paths`: a whole field if a path
+ * ends at it, the allowed part of an object field if paths continue
+ * below it.
+ */
+fn retain_paths(fields: Map<String, Value>, paths: &[&[String]]) -> Map<String, Value> {
+    let mut kept = Map::new();
+
+    for (key, value) in fields {
+        let below: Vec<&[String]> = paths
+            .iter()
+            .filter_map(|path| path.split_first())
+            .filter(|(first, _)| **first == key)
+            .map(|(_, rest)| rest)
+            .collect();
+
+        if below.iter().any(|rest| rest.is_empty()) {
+            kept.insert(key, value);
+        } else if let Value::Object(inner) = value {
+            if !below.is_empty() {
+                let inner = retain_paths(inner, &below);
+                if !inner.is_empty() {
+                    kept.insert(key, Value::Object(inner));
+                }
+            }
+        }
+    }
+
+    kept
+}
+
+/**
+ * Lowercases `key` and drops `-` and `_`, so that naming conventions do
+ * not matter.
+ */
+fn normalize_key(key: &str) -> String {
+    key.chars()
+        .filter(|c| *c != '-' && *c != '_')
+        .map(|c| c.to_ascii_lowercase())
+        .collect()
+}
+
+/**
+ * Splits `key` into lowercase words, on characters other than letters and
+ * digits and on case changes: `apiKey` and `APIKey` give `["api", "key"]`,
+ * `X-Api-Key` gives `["x", "api", "key"]`.
+ */
+fn key_words(key: &str) -> Vec<String> {
+    let chars: Vec<char> = key.chars().collect();
+    let mut words = Vec::new();
+    let mut word = String::new();
+
+    for (index, &c) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if !word.is_empty() {
+                words.push(std::mem::take(&mut word));
+            }
+            continue;
+        }
+
+        /*
+         * A word starts at an uppercase letter after a lowercase one or a
+         * digit (`apiKey`), or at the last uppercase letter of an acronym
+         * followed by a lowercase one (`APIKey`).
+         */
+        let previous = index.checked_sub(1).map(|index| chars[index]);
+        let next = chars.get(index + 1);
+        let starts_word = c.is_uppercase()
+            && previous.is_some_and(|previous| {
+                previous.is_lowercase()
+                    || previous.is_ascii_digit()
+                    || (previous.is_uppercase() && next.is_some_and(|next| next.is_lowercase()))
+            });
+        if starts_word && !word.is_empty() {
+            words.push(std::mem::take(&mut word));
+        }
+        word.extend(c.to_lowercase());
+    }
+    if !word.is_empty() {
+        words.push(word);
+    }
+    words
+}
+
+/**
+ * Matches `name` against a pattern with an optional `*` at either end.
+ */
+fn matches_glob(name: &str, pattern: &str) -> bool {
+    match (pattern.strip_prefix('*'), pattern.strip_suffix('*')) {
+        (Some(suffix), _) => name.ends_with(suffix),
+        (None, Some(prefix)) => name.starts_with(prefix),
+        (None, None) => name == pattern,
+    }
+}
+
+fn compile(pattern: &str) -> Result<Regex, String> {
+    Regex::new(pattern).map_err(|e| format!("Invalid scrub pattern {pattern:?}: {e}"))
+}
+
+/**
+ * Whether the digits of `number` pass the Luhn checksum of card numbers.
+ */
+fn luhn_valid(number: &str) -> bool {
+    let mut sum = 0;
+    for (i, digit) in number
+        .bytes()
+        .rev()
+        .filter(u8::is_ascii_digit)
+        .map(|b| u32::from(b - b'0'))
+        .enumerate()
+    {
+        sum += match (i % 2 == 1, digit * 2) {
+            (false, _) => digit,
+            (true, doubled) if doubled > 9 => doubled - 9,
+            (true, doubled) => doubled,
+        };
+    }
+    sum % 10 == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::types::Breadcrumb;
+    use serde_json::json;
+
+    /**
+     * Verifies that sensitive keys are redacted at any depth of the
+     * context and breadcrumb data, whatever their naming convention, but
+     * not keys naming a quantity or another word (`max_tokens`), that
+     * credential-like variables of environment snapshots are redacted
+     * unless allowlisted, and that built-in and custom patterns are
+     * redacted inside strings.
+     */
+    #[test]
+    fn test_scrub_event() {
+        let options = Options {
+            scrub_patterns: vec![r"ORD-\d+".to_string()],
+            scrub_env_allowlist: vec!["public_key".to_string()],
+            ..Options::default()
+        };
+        let scrubber = Scrubber::new(&options).unwrap().unwrap();
+        assert!(Scrubber::new(&Options {
+            scrub_patterns: vec!["(".to_string()],
+            ..Options::default()
+        })
+        .is_err());
+        assert!(Scrubber::new(&Options {
+            scrub_data: false,
+            ..Options::default()
+        })
+        .unwrap()
+        .is_none());
+
+        let mut event = EventData::new("login failed for ann@example.com");
+        event.context = Some(json!({
+            "request": {
+                "headers": { "Authorization": "Bearer abc", "X-Api-Key": "k", "Accept": "*/*" },
+                "body": [{ "user": "ann", "newPassword": "hunter2" }],
+            },
+            "credentials": { "secret": { "nested": 1 }, "secret_key": "s", "APIKey": "k" },
+            "llm": { "max_tokens": 4096, "token_count": 12, "authtoken": "t" },
+            "note": "paid with 4111 1111 1111 1111 (order ORD-42), ref 4111 1111 1111 1112",
+            "upstream": "sent Bearer eyJhbGciOi.x-y_z",
+            "env": { "STRIPE_KEY": "sk", "aws_region": "eu-1", "PUBLIC_KEY": "pk", "PATH": "/bin" },
+        }));
+        event.breadcrumbs = Some(vec![Breadcrumb {
+            data: Some(json!({ "csrf_token": "t", "path": "/login" })),
+            ..Breadcrumb::at(0, "POST /login")
+        }]);
+
+        scrubber.scrub_event(&mut event);
+
+        assert_eq!(
+            event.context.unwrap(),
+            json!({
+                "request": {
+                    "headers": { "Authorization": FILTERED, "X-Api-Key": FILTERED, "Accept": "*/*" },
+                    "body": [{ "user": "ann", "newPassword": FILTERED }],
+                },
+                "credentials": { "secret": FILTERED, "secret_key": FILTERED, "APIKey": FILTERED },
+                "llm": { "max_tokens": 4096, "token_count": 12, "authtoken": FILTERED },
+                "note": "paid with [Filtered] (order [Filtered]), ref 4111 1111 1111 1112",
+                "upstream": "sent [Filtered]",
+                "env": { "STRIPE_KEY": FILTERED, "aws_region": FILTERED, "PUBLIC_KEY": "pk", "PATH": "/bin" },
+            })
+        );
+        assert_eq!(event.title, "login failed for [Filtered]");
+        assert_eq!(
+            event.breadcrumbs.unwrap()[0].data,
+            Some(json!({ "csrf_token": FILTERED, "path": "/login" }))
+        );
+    }
+
+    /**
+     * Verifies that strict mode keeps only allowlisted keys and paths, in
+     * the context and in breadcrumb data.
+     */
+    #[test]
+    fn test_context_allowlist() {
+        let options = Options {
+            scrub_data: false,
+            context_allowlist: Some(vec!["job".to_string(), "request.method".to_string()]),
+            ..Options::default()
+        };
+        let scrubber = Scrubber::new(&options).unwrap().unwrap();
+
+        let mut event = EventData::new("failed");
+        event.context = Some(json!({
+            "job": { "name": "import" },
+            "request": { "method": "GET", "body": "..." },
+            "user": { "email": "a@b.c" },
+        }));
+        let mut breadcrumb = Breadcrumb::new("request");
+        breadcrumb.data = Some(json!({ "request": { "method": "POST", "body": "..." } }));
+        event.breadcrumbs = Some(vec![breadcrumb]);
+        scrubber.scrub_event(&mut event);
+
+        assert_eq!(
+            event.context,
+            Some(json!({ "job": { "name": "import" }, "request": { "method": "GET" } }))
+        );
+        assert_eq!(
+            event.breadcrumbs.unwrap()[0].data,
+            Some(json!({ "request": { "method": "POST" } }))
+        );
+    }
+}
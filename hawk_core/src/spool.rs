@@ -0,0 +1,420 @@
+/*!
+ * Encryption of what the SDK keeps on disk (`Options::spool_key`).
+ *
+ * Persisted fatal events (`Options::fatal_event_dir`) carry everything an
+ * event does — messages, context, breadcrumbs — and crash records wait in
+ * the crash directory until the next run. With a spool key, both are
+ * sealed with ChaCha20-Poly1305 (RFC 8439) before they reach the disk:
+ * user data is not left in plaintext, and files altered on disk are
+ * rejected.
+ *
+ * An encrypted file is `MAGIC` followed by chunks, each
+ * `[length: u32 LE][nonce: 12 bytes][ciphertext and tag: length bytes]`
+ * and sealed on its own with a random nonce. The crash handler seals the
+ * record's header before walking the stack, so a walk that never returns
+ * only loses the frames; a chunk cut short by the end of the process is
+ * ignored. Files without `MAGIC` — written before a key was set — are read
+ * as plaintext.
+ *
+ * The crash handler cannot run the cipher itself: it may run on the small
+ * alternate signal stack, and the vectorized ChaCha20 and Poly1305
+ * backends need far more than that in unoptimized builds. `PreparedChunk`
+ * moves the ChaCha20 work to `install()` — nonce, Poly1305 key, and
+ * keystream — and leaves the handler an XOR and a compact Poly1305,
+ * producing the same chunks as the AEAD.
+ *
+ * Keeping the key is up to the app, e.g. in the OS keyring: files sealed
+ * with another key cannot be read, and are discarded. Not supported on
+ * wasm targets.
+ */
+
+/// First bytes of an encrypted file.
+pub const MAGIC: &[u8; 8] = b"HAWKENC1";
+
+/// Bytes before a chunk's ciphertext: its length and nonce.
+pub const CHUNK_HEADER: usize = 4 + NONCE_LEN;
+
+/// Bytes a chunk adds to its plaintext: header and tag.
+pub const CHUNK_OVERHEAD: usize = CHUNK_HEADER + TAG_LEN;
+
+/// Plaintext bytes a `PreparedChunk` can seal, at most.
+pub const PREPARED_CHUNK_LEN: usize = 2048;
+
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+/**
+ * Whether `file` was written encrypted.
+ */
+pub fn is_sealed(file: &[u8]) -> bool {
+    file.starts_with(MAGIC)
+}
+
+/**
+ * Contents of a file that may be encrypted, which then needs `cipher`.
+ */
+pub fn read(file: Vec<u8>, cipher: Option<&SpoolCipher>) -> Result<Vec<u8>, String> {
+    if !is_sealed(&file) {
+        return Ok(file);
+    }
+    match cipher {
+        Some(cipher) => cipher.open(&file),
+        None => Err("encrypted, and no spool key is set".into()),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+mod imp {
+    use chacha20::cipher::{KeyIvInit, StreamCipher};
+    use chacha20::ChaCha20;
+    use chacha20poly1305::aead::{AeadInPlace, KeyInit};
+    use chacha20poly1305::{ChaCha20Poly1305, Nonce, Tag};
+
+    use super::{CHUNK_HEADER, CHUNK_OVERHEAD, MAGIC, NONCE_LEN, PREPARED_CHUNK_LEN, TAG_LEN};
+
+    /**
+     * The key files on disk are sealed with.
+     */
+    pub struct SpoolCipher {
+        key: [u8; 32],
+        cipher: ChaCha20Poly1305,
+    }
+
+    impl SpoolCipher {
+        pub fn new(key: &[u8; 32]) -> Result<Self, String> {
+            Ok(Self {
+                key: *key,
+                cipher: ChaCha20Poly1305::new(key.into()),
+            })
+        }
+
+        /**
+         * Seals `plaintext` into the contents of a file: `MAGIC` and one
+         * chunk.
+         */
+        pub fn seal(&self, plaintext: &[u8]) -> Result<Vec<u8>, String> {
+            let nonce = random_nonce()?;
+            let mut data = plaintext.to_vec();
+            let tag = self
+                .cipher
+                .encrypt_in_place_detached(Nonce::from_slice(&nonce), MAGIC, &mut data)
+                .map_err(|_| "Failed to encrypt")?;
+
+            let mut file = Vec::with_capacity(MAGIC.len() + CHUNK_OVERHEAD + data.len());
+            file.extend_from_slice(MAGIC);
+            file.extend_from_slice(&((data.len() + TAG_LEN) as u32).to_le_bytes());
+            file.extend_from_slice(&nonce);
+            file.extend_from_slice(&data);
+            file.extend_from_slice(&tag);
+            Ok(file)
+        }
+
+        /**
+         * Opens the contents of an encrypted file: the plaintext of its
+         * complete chunks, in order.
+         */
+        pub fn open(&self, file: &[u8]) -> Result<Vec<u8>, String> {
+            let mut rest = file
+                .strip_prefix(MAGIC.as_slice())
+                .ok_or("not an encrypted file")?;
+            let mut plaintext = Vec::new();
+            while rest.len() >= CHUNK_HEADER {
+                let len = u32::from_le_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+                let Some(sealed) = rest
+                    .get(CHUNK_HEADER..CHUNK_HEADER + len)
+                    .filter(|sealed| sealed.len() >= TAG_LEN)
+                else {
+                    // Cut short by the end of the process.
+                    break;
+                };
+                let (data, tag) = sealed.split_at(sealed.len() - TAG_LEN);
+                let mut data = data.to_vec();
+                self.cipher
+                    .decrypt_in_place_detached(
+                        Nonce::from_slice(&rest[4..CHUNK_HEADER]),
+                        MAGIC,
+                        &mut data,
+                        Tag::from_slice(tag),
+                    )
+                    .map_err(|_| "wrong spool key, or altered on disk")?;
+                plaintext.extend_from_slice(&data);
+                rest = &rest[CHUNK_HEADER + len..];
+            }
+            Ok(plaintext)
+        }
+
+        /**
+         * Prepares a chunk for the crash handler to seal (see
+         * `PreparedChunk`).
+         */
+        pub fn prepare_chunk(&self) -> Result<PreparedChunk, String> {
+            let nonce = random_nonce()?;
+            let mut chacha = ChaCha20::new(&self.key.into(), &nonce.into());
+
+            // Block 0 keys Poly1305; the plaintext starts at block 1.
+            let mut block = [0u8; 64];
+            chacha.apply_keystream(&mut block);
+            let mut keystream = [0u8; PREPARED_CHUNK_LEN];
+            chacha.apply_keystream(&mut keystream);
+
+            let mut mac_key = [0u8; 32];
+            mac_key.copy_from_slice(&block[..32]);
+            Ok(PreparedChunk {
+                nonce,
+                mac_key,
+                keystream,
+            })
+        }
+    }
+
+    fn random_nonce() -> Result<[u8; NONCE_LEN], String> {
+        let mut nonce = [0u8; NONCE_LEN];
+        getrandom::fill(&mut nonce).map_err(|e| format!("Failed to generate a nonce: {e}"))?;
+        Ok(nonce)
+    }
+
+    /**
+     * One chunk's worth of ChaCha20 output, computed ahead of a crash.
+     * Sealing with it does not allocate, and takes a few hundred bytes of
+     * stack. Each one must seal a single chunk: its nonce is used up.
+     */
+    pub struct PreparedChunk {
+        nonce: [u8; NONCE_LEN],
+        mac_key: [u8; 32],
+        keystream: [u8; PREPARED_CHUNK_LEN],
+    }
+
+    impl PreparedChunk {
+        /**
+         * Seals a chunk in place. `buf` holds the plaintext at
+         * `CHUNK_HEADER..CHUNK_HEADER + len` and room for the tag after
+         * it. Returns the length of the chunk, or 0 if it does not fit.
+         */
+        pub fn seal(&self, buf: &mut [u8], len: usize) -> usize {
+            if len > PREPARED_CHUNK_LEN {
+                return 0;
+            }
+            let Some(chunk) = buf.get_mut(..CHUNK_OVERHEAD + len) else {
+                return 0;
+            };
+            let (header, rest) = chunk.split_at_mut(CHUNK_HEADER);
+            let (data, tag) = rest.split_at_mut(len);
+            for (byte, key) in data.iter_mut().zip(&self.keystream) {
+                *byte ^= key;
+            }
+            tag.copy_from_slice(&aead_tag(&self.mac_key, MAGIC, data));
+            header[..4].copy_from_slice(&((len + TAG_LEN) as u32).to_le_bytes());
+            header[4..].copy_from_slice(&self.nonce);
+            chunk.len()
+        }
+    }
+
+    /**
+     * Tag of RFC 8439's AEAD construction: Poly1305 of the AAD and the
+     * ciphertext, each zero-padded to 16 bytes, then both lengths.
+     */
+    fn aead_tag(key: &[u8; 32], aad: &[u8], ciphertext: &[u8]) -> [u8; TAG_LEN] {
+        let mut mac = Poly1305::new(key);
+        mac.update_padded(aad);
+        mac.update_padded(ciphertext);
+        let mut lengths = [0u8; 16];
+        lengths[..8].copy_from_slice(&(aad.len() as u64).to_le_bytes());
+        lengths[8..].copy_from_slice(&(ciphertext.len() as u64).to_le_bytes());
+        mac.block(&lengths);
+        mac.finish()
+    }
+
+    /// Mask of a 26-bit limb.
+    const LIMB: u32 = 0x3ff_ffff;
+
+    /**
+     * Poly1305 with 26-bit limbs (after poly1305-donna), for full 16-byte
+     * blocks only — all the AEAD construction feeds it.
+     */
+    struct Poly1305 {
+        r: [u32; 5],
+        h: [u32; 5],
+        pad: [u32; 4],
+    }
+
+    impl Poly1305 {
+        fn new(key: &[u8; 32]) -> Self {
+            Self {
+                r: [
+                    le32(&key[0..]) & 0x3ff_ffff,
+                    (le32(&key[3..]) >> 2) & 0x3ff_ff03,
+                    (le32(&key[6..]) >> 4) & 0x3ff_c0ff,
+                    (le32(&key[9..]) >> 6) & 0x3f0_3fff,
+                    (le32(&key[12..]) >> 8) & 0x00f_ffff,
+                ],
+                h: [0; 5],
+                pad: [
+                    le32(&key[16..]),
+                    le32(&key[20..]),
+                    le32(&key[24..]),
+                    le32(&key[28..]),
+                ],
+            }
+        }
+
+        fn update_padded(&mut self, data: &[u8]) {
+            for part in data.chunks(16) {
+                let mut block = [0u8; 16];
+                block[..part.len()].copy_from_slice(part);
+                self.block(&block);
+            }
+        }
+
+        fn block(&mut self, block: &[u8; 16]) {
+            let [r0, r1, r2, r3, r4] = self.r.map(u64::from);
+            let (s1, s2, s3, s4) = (r1 * 5, r2 * 5, r3 * 5, r4 * 5);
+
+            let h = &mut self.h;
+            let h0 = u64::from(h[0] + (le32(&block[0..]) & LIMB));
+            let h1 = u64::from(h[1] + ((le32(&block[3..]) >> 2) & LIMB));
+            let h2 = u64::from(h[2] + ((le32(&block[6..]) >> 4) & LIMB));
+            let h3 = u64::from(h[3] + ((le32(&block[9..]) >> 6) & LIMB));
+            let h4 = u64::from(h[4] + ((le32(&block[12..]) >> 8) | (1 << 24)));
+
+            let d0 = h0 * r0 + h1 * s4 + h2 * s3 + h3 * s2 + h4 * s1;
+            let mut d1 = h0 * r1 + h1 * r0 + h2 * s4 + h3 * s3 + h4 * s2;
+            let mut d2 = h0 * r2 + h1 * r1 + h2 * r0 + h3 * s4 + h4 * s3;
+            let mut d3 = h0 * r3 + h1 * r2 + h2 * r1 + h3 * r0 + h4 * s4;
+            let mut d4 = h0 * r4 + h1 * r3 + h2 * r2 + h3 * r1 + h4 * r0;
+
+            h[0] = d0 as u32 & LIMB;
+            d1 += d0 >> 26;
+            h[1] = d1 as u32 & LIMB;
+            d2 += d1 >> 26;
+            h[2] = d2 as u32 & LIMB;
+            d3 += d2 >> 26;
+            h[3] = d3 as u32 & LIMB;
+            d4 += d3 >> 26;
+            h[4] = d4 as u32 & LIMB;
+            h[0] += (d4 >> 26) as u32 * 5;
+            h[1] += h[0] >> 26;
+            h[0] &= LIMB;
+        }
+
+        fn finish(self) -> [u8; TAG_LEN] {
+            let mut h = self.h;
+            for i in 1..5 {
+                h[i] += h[i - 1] >> 26;
+                h[i - 1] &= LIMB;
+            }
+            h[0] += (h[4] >> 26) * 5;
+            h[4] &= LIMB;
+            h[1] += h[0] >> 26;
+            h[0] &= LIMB;
+
+            // h + 5 - 2^130 replaces h if it does not go negative, i.e.
+            // if h >= p = 2^130 - 5.
+            let mut g = [0u32; 5];
+            let mut carry = 5;
+            for i in 0..5 {
+                g[i] = h[i] + carry;
+                carry = g[i] >> 26;
+                g[i] &= LIMB;
+            }
+            let use_g = carry.wrapping_neg();
+            for i in 0..5 {
+                h[i] = (h[i] & !use_g) | (g[i] & use_g);
+            }
+
+            let words = [
+                h[0] | (h[1] << 26),
+                (h[1] >> 6) | (h[2] << 20),
+                (h[2] >> 12) | (h[3] << 14),
+                (h[3] >> 18) | (h[4] << 8),
+            ];
+            let mut tag = [0u8; TAG_LEN];
+            let mut sum = 0u64;
+            for i in 0..4 {
+                sum += u64::from(words[i]) + u64::from(self.pad[i]);
+                tag[i * 4..i * 4 + 4].copy_from_slice(&(sum as u32).to_le_bytes());
+                sum >>= 32;
+            }
+            tag
+        }
+    }
+
+    fn le32(bytes: &[u8]) -> u32 {
+        u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+mod imp {
+    /**
+     * The key files on disk are sealed with. Never constructed on wasm
+     * targets.
+     */
+    pub enum SpoolCipher {}
+
+    impl SpoolCipher {
+        pub fn new(_key: &[u8; 32]) -> Result<Self, String> {
+            Err("Options::spool_key is not supported on wasm targets".into())
+        }
+
+        pub fn seal(&self, _plaintext: &[u8]) -> Result<Vec<u8>, String> {
+            match *self {}
+        }
+
+        pub fn open(&self, _file: &[u8]) -> Result<Vec<u8>, String> {
+            match *self {}
+        }
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+pub use imp::PreparedChunk;
+pub use imp::SpoolCipher;
+
+#[cfg(all(test, not(target_arch = "wasm32")))]
+mod tests {
+    use super::*;
+
+    /**
+     * Verifies that sealed files read back — whole, chunk by chunk from
+     * prepared chunks, and with a torn last chunk — and only with their
+     * key.
+     */
+    #[test]
+    fn test_spool_round_trip() {
+        let cipher = SpoolCipher::new(&[7; 32]).unwrap();
+        let file = cipher.seal(b"{\"title\":\"boom\"}").unwrap();
+        assert!(is_sealed(&file));
+        assert!(!file.windows(4).any(|window| window == b"boom"));
+        assert_eq!(
+            read(file.clone(), Some(&cipher)).unwrap(),
+            b"{\"title\":\"boom\"}"
+        );
+        assert!(read(file.clone(), None).is_err());
+        let other = SpoolCipher::new(&[8; 32]).unwrap();
+        assert!(read(file.clone(), Some(&other)).is_err());
+        let mut altered = file;
+        *altered.last_mut().unwrap() ^= 1;
+        assert!(read(altered, Some(&cipher)).is_err());
+
+        // Prepared chunks of every padding, opened by the AEAD.
+        let mut file = MAGIC.to_vec();
+        let mut expected = Vec::new();
+        for len in [0, 1, 15, 16, 17, 100, PREPARED_CHUNK_LEN] {
+            let part: Vec<u8> = (0..len).map(|i| (i * 7) as u8).collect();
+            let mut buf = vec![0xff; CHUNK_OVERHEAD + len];
+            buf[CHUNK_HEADER..CHUNK_HEADER + len].copy_from_slice(&part);
+            let chunk = cipher.prepare_chunk().unwrap();
+            assert_eq!(chunk.seal(&mut buf, len), CHUNK_OVERHEAD + len);
+            file.extend_from_slice(&buf);
+            expected.extend_from_slice(&part);
+        }
+        assert_eq!(cipher.open(&file).unwrap(), expected);
+        file.truncate(file.len() - 3);
+        assert_eq!(
+            cipher.open(&file).unwrap(),
+            expected[..expected.len() - PREPARED_CHUNK_LEN]
+        );
+
+        assert_eq!(read(b"plain".to_vec(), Some(&cipher)).unwrap(), b"plain");
+    }
+}
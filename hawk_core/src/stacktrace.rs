@@ -0,0 +1,1317 @@
+/*!
+ * Backtrace capture and conversion into protocol `BacktraceFrame`s.
+ *
+ * Responsibilities:
+ * - capture a backtrace at the call site, resolving symbols lazily;
+ * - cache resolved symbols per instruction address, so hot error paths
+ *   don't repeat DWARF lookups;
+ * - drop frames that belong to the SDK itself (`hawk_core`, panic hook, …);
+ * - demangle and normalize function names so they are readable and stable
+ *   across builds (no hash suffixes, no generic arguments);
+ * - classify every remaining frame as application code or library code
+ *   (`in_app`), so the dashboard can highlight the frames that matter;
+ * - scrub file paths of usernames and machine-specific prefixes.
+ *
+ * Without the `backtrace` feature, capture and symbol resolution are
+ * compiled out: every entry point yields no frames, and only
+ * `frame_from_location` (a panic's location) still produces one.
+ */
+
+#[cfg(feature = "backtrace")]
+use std::num::NonZeroUsize;
+#[cfg(feature = "backtrace")]
+use std::sync::{Arc, Mutex, OnceLock};
+
+#[cfg(feature = "backtrace")]
+pub use backtrace::Backtrace;
+#[cfg(feature = "backtrace")]
+use lru::LruCache;
+
+use crate::client::{self, DEFAULT_MAX_BACKTRACE_FRAMES};
+use crate::protocol::types::{BacktraceFrame, EventData};
+
+/**
+ * Stand-in for `backtrace::Backtrace` without the `backtrace` feature, so
+ * capture call sites compile either way. It records nothing: events sent
+ * with it carry no resolved frames.
+ */
+#[cfg(not(feature = "backtrace"))]
+#[derive(Clone, Debug, Default)]
+pub struct Backtrace;
+
+#[cfg(not(feature = "backtrace"))]
+impl Backtrace {
+    /** Captures nothing. */
+    pub fn new() -> Self {
+        Self
+    }
+
+    /** Captures nothing. */
+    pub fn new_unresolved() -> Self {
+        Self
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Constants
+// ---------------------------------------------------------------------------
+
+/**
+ * Module path prefixes of frames that belong to the SDK itself (or to the
+ * machinery it runs on) rather than to user code.
+ *
+ * These frames sit at the top of every captured trace — `hawk_core::send`,
+ * `backtrace::Backtrace::new`, the panic hook — and would otherwise push
+ * the actual call site out of view on the dashboard. The frame of
+ * `hawk::capture!()` would also sit between its call-site frame and the
+ * caller's resolved frame, which then could not replace it.
+ */
+#[cfg(feature = "backtrace")]
+const SDK_FRAME_PREFIXES: &[&str] = &[
+    "hawk_core",
+    "hawk_panic",
+    "hawk_tokio",
+    "hawk_rayon",
+    "hawk_crash",
+    "hawk_log",
+    "hawk_tracing",
+    "hawk_slog",
+    "hawk_anyhow",
+    "hawk_sql",
+    "hawk::capture",
+    "anyhow",
+    "backtrace",
+    "log",
+    "slog",
+    "tracing",
+    "tracing_core",
+    "tracing_subscriber",
+    "std::panicking",
+    "core::panicking",
+];
+
+/**
+ * Module path prefixes that are never application code: the standard
+ * library crates shipped with the toolchain.
+ */
+const STD_MODULE_PREFIXES: &[&str] = &["std", "core", "alloc"];
+
+/**
+ * File path fragments identifying code that was not written by the user:
+ * crates downloaded into the cargo registry / git checkouts, and std sources
+ * (rustc embeds them as `/rustc/<commit-hash>/library/...`).
+ */
+const LIBRARY_PATH_MARKERS: &[&str] = &[
+    "/.cargo/registry/",
+    "/.cargo/git/",
+    "\\.cargo\\registry\\",
+    "\\.cargo\\git\\",
+    "/rustc/",
+];
+
+/**
+ * Number of instruction addresses whose resolved symbols are kept in the
+ * LRU cache. Each entry is small (a few strings), and real services hit a
+ * few hundred distinct addresses on their error paths at most.
+ */
+#[cfg(feature = "backtrace")]
+const SYMBOL_CACHE_CAPACITY: usize = 2048;
+
+// ---------------------------------------------------------------------------
+// Symbol cache
+// ---------------------------------------------------------------------------
+
+/**
+ * One symbol resolved for an instruction address. A single address can
+ * map to several symbols when functions were inlined into each other.
+ *
+ * `function` is already cleaned via `clean_function_name`, so demangling
+ * is cached along with the DWARF lookup.
+ */
+#[cfg(feature = "backtrace")]
+struct ResolvedSymbol {
+    function: Option<String>,
+    file: Option<String>,
+    line: Option<u32>,
+    column: Option<u32>,
+}
+
+/**
+ * Process-wide LRU cache: instruction address → resolved symbols.
+ *
+ * Shared by the worker thread and by callers resolving inline
+ * (`get_backtrace`, `before_send`); the lock is only held for the map
+ * lookup / insert, never during resolution itself.
+ */
+#[cfg(feature = "backtrace")]
+fn symbol_cache() -> &'static Mutex<LruCache<usize, Arc<[ResolvedSymbol]>>> {
+    static CACHE: OnceLock<Mutex<LruCache<usize, Arc<[ResolvedSymbol]>>>> = OnceLock::new();
+
+    CACHE.get_or_init(|| {
+        let capacity = NonZeroUsize::new(SYMBOL_CACHE_CAPACITY).unwrap_or(NonZeroUsize::MIN);
+        Mutex::new(LruCache::new(capacity))
+    })
+}
+
+/**
+ * Returns the symbols for a captured frame.
+ *
+ * Frames from an already resolved `Backtrace` are converted directly.
+ * Unresolved frames are looked up in the cache by instruction address
+ * and resolved through `backtrace::resolve` on a miss.
+ */
+#[cfg(feature = "backtrace")]
+fn symbols_for(frame: &backtrace::BacktraceFrame) -> Arc<[ResolvedSymbol]> {
+    if !frame.symbols().is_empty() {
+        return frame.symbols().iter().map(resolved_from_capture).collect();
+    }
+
+    let ip = frame.ip() as usize;
+
+    if let Some(hit) = symbol_cache()
+        .lock()
+        .ok()
+        .and_then(|mut c| c.get(&ip).cloned())
+    {
+        return hit;
+    }
+
+    let mut symbols = Vec::new();
+    backtrace::resolve(frame.ip(), |symbol| {
+        symbols.push(ResolvedSymbol {
+            function: symbol.name().map(|n| clean_symbol_name(&n)),
+            file: symbol.filename().map(|p| p.display().to_string()),
+            line: symbol.lineno(),
+            column: symbol.colno(),
+        });
+    });
+
+    let symbols: Arc<[ResolvedSymbol]> = symbols.into();
+    if let Ok(mut cache) = symbol_cache().lock() {
+        cache.put(ip, symbols.clone());
+    }
+
+    symbols
+}
+
+/**
+ * Converts a symbol of an already resolved `Backtrace`.
+ */
+#[cfg(feature = "backtrace")]
+fn resolved_from_capture(symbol: &backtrace::BacktraceSymbol) -> ResolvedSymbol {
+    ResolvedSymbol {
+        function: symbol.name().map(|n| clean_symbol_name(&n)),
+        file: symbol.filename().map(|p| p.display().to_string()),
+        line: symbol.lineno(),
+        column: symbol.colno(),
+    }
+}
+
+/**
+ * Cleans a `SymbolName`, preferring its raw (mangled) form so that
+ * `rustc-demangle` sees the original symbol.
+ */
+#[cfg(feature = "backtrace")]
+fn clean_symbol_name(name: &backtrace::SymbolName<'_>) -> String {
+    match name.as_str() {
+        Some(raw) => clean_function_name(raw),
+        None => clean_function_name(&name.to_string()),
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Frame settings
+// ---------------------------------------------------------------------------
+
+/**
+ * Client options that shape frame conversion, read once per conversion.
+ * Falls back to defaults when the SDK is not initialized.
+ */
+#[derive(Clone, Copy)]
+struct FrameSettings<'a> {
+    in_app_include: &'a [String],
+    workspace_root: Option<&'a str>,
+    max_frames: usize,
+    #[cfg_attr(not(feature = "backtrace"), allow(dead_code))]
+    include_addresses: bool,
+    send_default_pii: bool,
+}
+
+impl Default for FrameSettings<'_> {
+    fn default() -> Self {
+        Self {
+            in_app_include: &[],
+            workspace_root: None,
+            max_frames: DEFAULT_MAX_BACKTRACE_FRAMES,
+            include_addresses: false,
+            send_default_pii: false,
+        }
+    }
+}
+
+impl FrameSettings<'static> {
+    fn current() -> Self {
+        match client::get_client() {
+            Some(c) => Self {
+                in_app_include: c.in_app_include(),
+                workspace_root: c.workspace_root(),
+                max_frames: c.max_backtrace_frames(),
+                include_addresses: c.include_addresses(),
+                send_default_pii: c.send_default_pii(),
+            },
+            None => Self::default(),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Public API
+// ---------------------------------------------------------------------------
+
+/**
+ * Captures the backtrace of the calling thread and resolves its symbols
+ * right away, on that thread (through the symbol cache). SDK frames are
+ * left out. Returns `None` if no frame could be resolved.
+ *
+ * Where the `backtrace` crate cannot resolve symbols, `std::backtrace` is
+ * used instead. The capture paths (`hawk::send()`, the panic hook) do not
+ * call this: they capture addresses only and leave resolution to the
+ * worker (see `resolve_into()`).
+ */
+#[cfg(feature = "backtrace")]
+pub fn get_backtrace() -> Option<Vec<BacktraceFrame>> {
+    let frames = if backtrace_crate_usable() {
+        convert_backtrace(&Backtrace::new_unresolved())
+    } else {
+        capture_std_fallback()
+    };
+
+    if frames.is_empty() {
+        None
+    } else {
+        Some(frames)
+    }
+}
+
+/**
+ * Without the `backtrace` feature: nothing to capture.
+ */
+#[cfg(not(feature = "backtrace"))]
+pub fn get_backtrace() -> Option<Vec<BacktraceFrame>> {
+    None
+}
+
+/**
+ * Captures the raw frame addresses of the current stack, to be resolved
+ * later with `resolve_into`.
+ */
+#[cfg(feature = "backtrace")]
+pub(crate) fn capture_unresolved() -> Option<Backtrace> {
+    Some(Backtrace::new_unresolved())
+}
+
+/**
+ * Without the `backtrace` feature: nothing to capture.
+ */
+#[cfg(not(feature = "backtrace"))]
+pub(crate) fn capture_unresolved() -> Option<Backtrace> {
+    None
+}
+
+/**
+ * Resolves symbols of a backtrace captured with `new_unresolved()` and
+ * stores the converted frames on the event.
+ *
+ * Frames already present on the event are treated as synthetic top frames
+ * (e.g. the exact panic location) and kept in front of the resolved ones.
+ * If the first resolved frame points at the same source line as the last
+ * synthetic one, the resolved frame replaces it — it additionally carries
+ * the function name.
+ */
+pub(crate) fn resolve_into(event: &mut EventData, bt: &Backtrace) {
+    let frames = merge_resolved(event.backtrace.take().unwrap_or_default(), bt);
+    if !frames.is_empty() {
+        event.backtrace = Some(frames);
+    }
+}
+
+/**
+ * `resolve_into()` for an event serialized already: `payload` is the
+ * JSON of its `EventData`.
+ */
+pub(crate) fn resolve_into_json(payload: &mut serde_json::Value, bt: &Backtrace) {
+    let Some(payload) = payload.as_object_mut() else {
+        return;
+    };
+
+    let frames = payload
+        .remove("backtrace")
+        .and_then(|frames| serde_json::from_value(frames).ok())
+        .unwrap_or_default();
+    let frames = merge_resolved(frames, bt);
+    if let Some(frames) = (!frames.is_empty())
+        .then(|| serde_json::to_value(frames).ok())
+        .flatten()
+    {
+        payload.insert("backtrace".into(), frames);
+    }
+}
+
+/**
+ * Appends the resolved frames of `bt` to the synthetic `frames`, as
+ * described on `resolve_into()`.
+ */
+fn merge_resolved(mut frames: Vec<BacktraceFrame>, bt: &Backtrace) -> Vec<BacktraceFrame> {
+    let resolved = convert_backtrace(bt);
+    if let (Some(last), Some(first)) = (frames.last(), resolved.first()) {
+        if is_same_location(last, first) {
+            frames.pop();
+        }
+    }
+
+    frames.extend(resolved);
+    frames.truncate(FrameSettings::current().max_frames);
+    frames
+}
+
+/**
+ * Builds a frame from a source location, e.g. `PanicHookInfo::location()`.
+ *
+ * The location is exact even in release builds where the first resolvable
+ * backtrace frame may be an inlined caller or missing entirely. The frame
+ * carries no function name; `in_app` is derived from the file path.
+ */
+pub fn frame_from_location(location: &std::panic::Location<'_>) -> BacktraceFrame {
+    let settings = FrameSettings::current();
+    let file = location.file();
+    let in_app = is_in_app(None, Some(file), settings.in_app_include);
+
+    BacktraceFrame {
+        file: Some(scrub_path(
+            file,
+            settings.workspace_root,
+            settings.send_default_pii,
+        )),
+        line: Some(location.line()),
+        column: Some(location.column()),
+        function: None,
+        module: None,
+        instruction_addr: None,
+        module_offset: None,
+        inlined: false,
+        in_app,
+    }
+}
+
+/**
+ * Formats a frame's instruction pointer and its offset from the base of
+ * the containing module as hex strings.
+ *
+ * The module base is unknown on some platforms (and for JIT / anonymous
+ * memory), in which case only the absolute address is returned.
+ */
+#[cfg(feature = "backtrace")]
+fn frame_addresses(frame: &backtrace::BacktraceFrame) -> (Option<String>, Option<String>) {
+    let ip = frame.ip() as usize;
+    let offset = frame
+        .module_base_address()
+        .map(|base| ip.wrapping_sub(base as usize));
+
+    (Some(format!("{ip:#x}")), offset.map(|o| format!("{o:#x}")))
+}
+
+/**
+ * Returns `true` if both frames point at the same source line.
+ *
+ * `Location::file()` is relative for workspace crates (`src/main.rs`)
+ * while debug info usually holds absolute paths, so files match when
+ * either one is a path suffix of the other.
+ */
+fn is_same_location(a: &BacktraceFrame, b: &BacktraceFrame) -> bool {
+    let (Some(file_a), Some(file_b)) = (&a.file, &b.file) else {
+        return false;
+    };
+
+    let is_suffix = |long: &str, short: &str| {
+        long == short
+            || long
+                .strip_suffix(short)
+                .is_some_and(|head| head.ends_with('/') || head.ends_with('\\'))
+    };
+
+    a.line == b.line && (is_suffix(file_a, file_b) || is_suffix(file_b, file_a))
+}
+
+/**
+ * Converts a `backtrace::Backtrace` into `Vec<BacktraceFrame>`.
+ *
+ * Accepts both resolved and unresolved backtraces; unresolved frames are
+ * symbolized through the per-address symbol cache. Filters out frames with
+ * no useful info (no file AND no function) and frames that belong to the
+ * SDK itself (see `SDK_FRAME_PREFIXES`).
+ *
+ * At most `Options::max_backtrace_frames` frames are returned — the
+ * topmost ones, closest to the capture site.
+ */
+#[cfg(feature = "backtrace")]
+pub fn convert_backtrace(bt: &Backtrace) -> Vec<BacktraceFrame> {
+    let settings = FrameSettings::current();
+    let mut frames = Vec::new();
+
+    for frame in bt.frames() {
+        let (instruction_addr, module_offset) = if settings.include_addresses {
+            frame_addresses(frame)
+        } else {
+            (None, None)
+        };
+
+        for mut converted in convert_symbols(&symbols_for(frame), &settings) {
+            if frames.len() >= settings.max_frames {
+                return frames;
+            }
+
+            converted.instruction_addr = instruction_addr.clone();
+            converted.module_offset = module_offset.clone();
+            frames.push(converted);
+        }
+    }
+
+    frames
+}
+
+/**
+ * Converts the symbols of one physical frame, skipping those
+ * `build_frame()` rejects.
+ *
+ * A physical frame resolves to several symbols when calls were inlined:
+ * innermost callee first, the real (outer) function last. All but the
+ * last frame kept are marked `inlined` — counted after filtering, so that
+ * a frame whose caller was skipped is not marked inlined into nothing.
+ */
+#[cfg(feature = "backtrace")]
+fn convert_symbols(
+    symbols: &[ResolvedSymbol],
+    settings: &FrameSettings<'_>,
+) -> Vec<BacktraceFrame> {
+    let mut frames: Vec<BacktraceFrame> = symbols
+        .iter()
+        .filter_map(|symbol| {
+            build_frame(
+                symbol.function.clone(),
+                symbol.file.clone(),
+                symbol.line,
+                symbol.column,
+                settings,
+            )
+        })
+        .collect();
+
+    let kept = frames.len();
+    for (index, frame) in frames.iter_mut().enumerate() {
+        frame.inlined = index + 1 < kept;
+    }
+    frames
+}
+
+/**
+ * Without the `backtrace` feature: there are no frames to convert.
+ */
+#[cfg(not(feature = "backtrace"))]
+pub fn convert_backtrace(_bt: &Backtrace) -> Vec<BacktraceFrame> {
+    Vec::new()
+}
+
+/**
+ * Builds a protocol frame from resolved symbol info.
+ *
+ * Returns `None` for frames that must be skipped: SDK-internal frames and
+ * frames carrying neither a function name nor a file. Address fields and
+ * `inlined` are left unset for the caller to fill in.
+ *
+ * The file path is scrubbed *after* `in_app` classification, which relies
+ * on the raw registry / toolchain path markers.
+ */
+#[cfg(feature = "backtrace")]
+fn build_frame(
+    function: Option<String>,
+    file: Option<String>,
+    line: Option<u32>,
+    column: Option<u32>,
+    settings: &FrameSettings<'_>,
+) -> Option<BacktraceFrame> {
+    if function.as_deref().is_some_and(is_sdk_frame) {
+        return None;
+    }
+
+    if function.is_none() && file.is_none() {
+        return None;
+    }
+
+    let in_app = is_in_app(
+        function.as_deref(),
+        file.as_deref(),
+        settings.in_app_include,
+    );
+    let module = function.as_deref().and_then(module_path);
+    let file = file.map(|f| scrub_path(&f, settings.workspace_root, settings.send_default_pii));
+
+    Some(BacktraceFrame {
+        file,
+        line,
+        column,
+        function,
+        module,
+        instruction_addr: None,
+        module_offset: None,
+        inlined: false,
+        in_app,
+    })
+}
+
+// ---------------------------------------------------------------------------
+// Fallback: std::backtrace
+// ---------------------------------------------------------------------------
+
+/**
+ * Returns `true` if the `backtrace` crate resolves symbols on this
+ * platform / build configuration.
+ *
+ * Probed once per process: a backtrace captured here must contain at
+ * least one frame with a function name or a file.
+ */
+#[cfg(feature = "backtrace")]
+pub(crate) fn backtrace_crate_usable() -> bool {
+    static USABLE: OnceLock<bool> = OnceLock::new();
+
+    *USABLE.get_or_init(|| {
+        Backtrace::new().frames().iter().any(|frame| {
+            frame
+                .symbols()
+                .iter()
+                .any(|s| s.name().is_some() || s.filename().is_some())
+        })
+    })
+}
+
+/**
+ * Captures the current stack via `std::backtrace::Backtrace` and parses
+ * its textual output into frames.
+ *
+ * Used when the `backtrace` crate yields nothing. Must run on the thread
+ * whose stack is wanted — std captures and resolves synchronously, and the
+ * output carries no addresses, so frames are approximate: no
+ * `instruction_addr`, no `inlined` information.
+ */
+#[cfg(feature = "backtrace")]
+pub(crate) fn capture_std_fallback() -> Vec<BacktraceFrame> {
+    convert_std_backtrace(&std::backtrace::Backtrace::force_capture())
+}
+
+/**
+ * Without the `backtrace` feature: never usable, so no event keeps a raw
+ * capture for the worker.
+ */
+#[cfg(not(feature = "backtrace"))]
+pub(crate) fn backtrace_crate_usable() -> bool {
+    false
+}
+
+/**
+ * Without the `backtrace` feature: nothing to capture.
+ */
+#[cfg(not(feature = "backtrace"))]
+pub(crate) fn capture_std_fallback() -> Vec<BacktraceFrame> {
+    Vec::new()
+}
+
+/**
+ * Converts a `std::backtrace::Backtrace` captured elsewhere — e.g. the one
+ * stored in an `anyhow::Error` — into frames.
+ *
+ * Empty unless the backtrace was actually captured (`RUST_BACKTRACE` /
+ * `RUST_LIB_BACKTRACE`, or `force_capture()`). Parsed from the textual
+ * output, so frames carry no addresses (see `capture_std_fallback`).
+ */
+#[cfg(feature = "backtrace")]
+pub fn convert_std_backtrace(bt: &std::backtrace::Backtrace) -> Vec<BacktraceFrame> {
+    if bt.status() != std::backtrace::BacktraceStatus::Captured {
+        return Vec::new();
+    }
+
+    let settings = FrameSettings::current();
+    let mut frames = parse_std_backtrace(&bt.to_string(), &settings);
+    frames.truncate(settings.max_frames);
+    frames
+}
+
+/**
+ * Without the `backtrace` feature: there are no frames to convert.
+ */
+#[cfg(not(feature = "backtrace"))]
+pub fn convert_std_backtrace(_bt: &std::backtrace::Backtrace) -> Vec<BacktraceFrame> {
+    Vec::new()
+}
+
+/**
+ * Parses the `Display` output of `std::backtrace::Backtrace`:
+ *
+ * ```text
+ *    0: my_app::main
+ *              at ./src/main.rs:12:5
+ *    1: std::rt::lang_start::{{closure}}
+ *              at /rustc/<hash>/library/std/src/rt.rs:206:18
+ * ```
+ *
+ * Every non-`at` line starts a new frame; an `at file:line:col` line
+ * attaches a location to the frame above it. `<unknown>` names are
+ * treated as missing.
+ */
+#[cfg(feature = "backtrace")]
+fn parse_std_backtrace(text: &str, settings: &FrameSettings<'_>) -> Vec<BacktraceFrame> {
+    struct Parsed {
+        function: Option<String>,
+        file: Option<String>,
+        line: Option<u32>,
+        column: Option<u32>,
+    }
+
+    let mut parsed: Vec<Parsed> = Vec::new();
+
+    for raw_line in text.lines() {
+        let line = raw_line.trim();
+        if line.is_empty() {
+            continue;
+        }
+
+        if let Some(location) = line.strip_prefix("at ") {
+            if let Some(last) = parsed.last_mut() {
+                let (file, line, column) = parse_location(location);
+                last.file = Some(file);
+                last.line = line;
+                last.column = column;
+            }
+            continue;
+        }
+
+        /* Strip the `N: ` frame index, if present. */
+        let name = match line.split_once(": ") {
+            Some((index, rest)) if index.bytes().all(|b| b.is_ascii_digit()) => rest,
+            _ => line,
+        };
+
+        let function = (name != "<unknown>").then(|| clean_function_name(name));
+        parsed.push(Parsed {
+            function,
+            file: None,
+            line: None,
+            column: None,
+        });
+    }
+
+    parsed
+        .into_iter()
+        .filter_map(|p| build_frame(p.function, p.file, p.line, p.column, settings))
+        .collect()
+}
+
+/**
+ * Splits `path:line:column` (column optional) from the right, so Windows
+ * drive letters (`C:\...`) stay part of the path.
+ */
+#[cfg(feature = "backtrace")]
+fn parse_location(location: &str) -> (String, Option<u32>, Option<u32>) {
+    let mut parts = location.rsplitn(3, ':');
+    let last = parts.next().and_then(|p| p.parse::<u32>().ok());
+    let middle = parts.next();
+    let rest = parts.next();
+
+    match (rest, middle.and_then(|m| m.parse::<u32>().ok()), last) {
+        (Some(path), Some(line), Some(column)) => (path.to_string(), Some(line), Some(column)),
+        _ => match location.rsplit_once(':') {
+            Some((path, line)) if line.parse::<u32>().is_ok() => {
+                (path.to_string(), line.parse().ok(), None)
+            }
+            _ => (location.to_string(), None, None),
+        },
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Symbol names
+// ---------------------------------------------------------------------------
+
+/**
+ * Turns a (possibly mangled) symbol name into a stable, readable path.
+ *
+ * - Demangles legacy (`_ZN…E`) and v0 (`_R…`) symbols via `rustc-demangle`,
+ *   in the alternate form which drops the `::h1a2b3c4d…` hash suffix and
+ *   v0 crate disambiguators (`std[1a2b]::`).
+ * - Strips generic arguments (`catch_unwind::<i32, F>` → `catch_unwind`,
+ *   `Box<F, A>` → `Box`) — they differ between monomorphizations of the
+ *   same source function.
+ * - Normalizes v0 closure names (`{closure#0}`) to the legacy `{{closure}}`
+ *   form so both mangling schemes group together.
+ *
+ * Names that are not Rust symbols (e.g. `__libc_start_main`) pass through.
+ */
+#[cfg(feature = "backtrace")]
+pub(crate) fn clean_function_name(raw: &str) -> String {
+    let demangled = format!("{:#}", rustc_demangle::demangle(raw));
+    let without_hash = strip_hash_suffix(&demangled);
+    let without_generics = strip_generic_args(without_hash);
+
+    normalize_closures(&without_generics)
+}
+
+/**
+ * Removes a trailing legacy hash segment (`::h` + 16 hex digits), in case
+ * the name was demangled elsewhere without the alternate flag.
+ */
+#[cfg(feature = "backtrace")]
+fn strip_hash_suffix(name: &str) -> &str {
+    match name.rsplit_once("::h") {
+        Some((head, hash)) if hash.len() == 16 && hash.bytes().all(|b| b.is_ascii_hexdigit()) => {
+            head
+        }
+        _ => name,
+    }
+}
+
+/**
+ * Drops generic argument lists while keeping qualified-path heads.
+ *
+ * A `<` directly following an identifier or `::` opens generic arguments
+ * and is skipped up to its matching `>` (along with a turbofish `::`).
+ * Any other `<` — at the start, or after `&` / a space — opens a
+ * qualified path like `<T as Trait>` and is kept.
+ */
+#[cfg(feature = "backtrace")]
+fn strip_generic_args(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut chars = name.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let opens_generics = c == '<'
+            && out
+                .chars()
+                .last()
+                .is_some_and(|prev| prev.is_alphanumeric() || prev == '_' || prev == ':');
+
+        if !opens_generics {
+            out.push(c);
+            continue;
+        }
+
+        if out.ends_with("::") {
+            out.truncate(out.len() - 2);
+        }
+
+        /* `->` inside `fn(A) -> B` arguments is not a closing bracket. */
+        let mut depth = 1;
+        let mut prev = '<';
+        for inner in chars.by_ref() {
+            match inner {
+                '<' => depth += 1,
+                '>' if prev != '-' => {
+                    depth -= 1;
+                    if depth == 0 {
+                        break;
+                    }
+                }
+                _ => {}
+            }
+            prev = inner;
+        }
+    }
+
+    out
+}
+
+/**
+ * Rewrites v0-style `{closure#N}` segments as `{{closure}}`.
+ */
+#[cfg(feature = "backtrace")]
+fn normalize_closures(name: &str) -> String {
+    let mut out = String::with_capacity(name.len());
+    let mut rest = name;
+
+    while let Some(start) = rest.find("{closure#") {
+        out.push_str(&rest[..start]);
+        match rest[start..].find('}') {
+            Some(end) => {
+                out.push_str("{{closure}}");
+                rest = &rest[start + end + 1..];
+            }
+            None => {
+                rest = &rest[start..];
+                break;
+            }
+        }
+    }
+
+    out.push_str(rest);
+    out
+}
+
+/**
+ * Extracts the owning module path from a cleaned function name.
+ *
+ * - `my_app::db::query` → `my_app::db`
+ * - `my_app::main::{{closure}}` → `my_app` (closures belong to their function)
+ * - `<my_app::Handler as tower::Service>::call` → `my_app` (the module of the
+ *   implementing type, not of the trait)
+ *
+ * For inherent methods the result includes the type name
+ * (`my_app::Handler::new` → `my_app::Handler`) — a type and a module are
+ * indistinguishable in a demangled path.
+ *
+ * Returns `None` for single-segment names like `main` or C symbols.
+ */
+#[cfg(feature = "backtrace")]
+fn module_path(function: &str) -> Option<String> {
+    let path = match function.strip_prefix('<') {
+        Some(qualified) => {
+            let self_type = qualified.split(" as ").next().unwrap_or(qualified);
+            let self_type = self_type.trim_end_matches('>');
+            self_type
+                .trim_start_matches(['&', '*'])
+                .trim_start_matches("mut ")
+                .trim_start_matches("const ")
+                .trim_start_matches("dyn ")
+                .to_string()
+        }
+        None => function.to_string(),
+    };
+
+    let mut segments: Vec<&str> = path.split("::").collect();
+    while segments.last().is_some_and(|s| s.starts_with('{')) {
+        segments.pop();
+    }
+    segments.pop();
+
+    if segments.is_empty() || segments.iter().any(|s| s.is_empty()) {
+        return None;
+    }
+
+    Some(segments.join("::"))
+}
+
+// ---------------------------------------------------------------------------
+// Path scrubbing
+// ---------------------------------------------------------------------------
+
+/**
+ * Normalizes a source file path so it neither leaks the build machine's
+ * username nor varies between build machines (which would split grouping):
+ *
+ * - `…/.cargo/registry/src/<index>/tokio-1.38.0/src/x.rs` → `<cargo>/tokio-1.38.0/src/x.rs`
+ * - `…/.cargo/git/checkouts/<repo>/<rev>/src/x.rs` → `<cargo-git>/<repo>/<rev>/src/x.rs`
+ * - `/rustc/<commit>/library/std/src/rt.rs` → `<rustc>/library/std/src/rt.rs`
+ * - `<workspace_root>/src/main.rs` → `<workspace>/src/main.rs`
+ * - `/home/<user>/…`, `/Users/<user>/…`, `C:\Users\<user>\…` → `<home>/…`,
+ *   unless `keep_home` (`Options::send_default_pii`)
+ */
+pub(crate) fn scrub_path(path: &str, workspace_root: Option<&str>, keep_home: bool) -> String {
+    for marker in ["/.cargo/registry/src/", "\\.cargo\\registry\\src\\"] {
+        if let Some((_, rest)) = path.split_once(marker) {
+            /* Skip the registry index directory (`index.crates.io-<hash>`). */
+            let rest = rest.split_once(['/', '\\']).map_or(rest, |(_, r)| r);
+            return format!("<cargo>/{rest}");
+        }
+    }
+
+    for marker in ["/.cargo/git/checkouts/", "\\.cargo\\git\\checkouts\\"] {
+        if let Some((_, rest)) = path.split_once(marker) {
+            return format!("<cargo-git>/{rest}");
+        }
+    }
+
+    if let Some(rest) = path.strip_prefix("/rustc/") {
+        let rest = rest.split_once('/').map_or(rest, |(_, r)| r);
+        return format!("<rustc>/{rest}");
+    }
+
+    if let Some(root) = workspace_root.map(|r| r.trim_end_matches(['/', '\\'])) {
+        if !root.is_empty() {
+            if let Some(rest) = path.strip_prefix(root) {
+                if rest.starts_with(['/', '\\']) {
+                    return format!("<workspace>{rest}");
+                }
+            }
+        }
+    }
+
+    if keep_home {
+        return path.to_string();
+    }
+
+    for home in ["/home/", "/Users/", "C:\\Users\\"] {
+        if let Some(rest) = path.strip_prefix(home) {
+            if let Some((_user, tail)) = rest.split_once(['/', '\\']) {
+                return format!("<home>/{tail}");
+            }
+        }
+    }
+
+    if let Some(rest) = path.strip_prefix("/root/") {
+        return format!("<home>/{rest}");
+    }
+
+    path.to_string()
+}
+
+// ---------------------------------------------------------------------------
+// Frame classification
+// ---------------------------------------------------------------------------
+
+/**
+ * Returns `true` if the demangled symbol name belongs to one of the
+ * `SDK_FRAME_PREFIXES` modules.
+ */
+#[cfg(feature = "backtrace")]
+fn is_sdk_frame(name: &str) -> bool {
+    SDK_FRAME_PREFIXES
+        .iter()
+        .any(|prefix| matches_module_prefix(name, prefix))
+}
+
+/**
+ * Decides whether a frame is application code.
+ *
+ * In order of precedence:
+ * 1. The function matches one of the user-configured `in_app_include`
+ *    module prefixes → in app.
+ * 2. The function lives in `std` / `core` / `alloc` → not in app.
+ * 3. The source file is in the cargo registry, a cargo git checkout, or
+ *    the toolchain sources → not in app.
+ * 4. Otherwise the frame is in app if its source file is known — i.e. it
+ *    was compiled from the local workspace with debug info.
+ */
+fn is_in_app(path: Option<&str>, file: Option<&str>, in_app_include: &[String]) -> bool {
+    if let Some(path) = path {
+        if in_app_include
+            .iter()
+            .any(|prefix| matches_module_prefix(path, prefix))
+        {
+            return true;
+        }
+
+        if STD_MODULE_PREFIXES
+            .iter()
+            .any(|prefix| matches_module_prefix(path, prefix))
+        {
+            return false;
+        }
+    }
+
+    match file {
+        Some(file) => !LIBRARY_PATH_MARKERS.iter().any(|m| file.contains(m)),
+        None => false,
+    }
+}
+
+/**
+ * Returns `true` if the demangled path `name` starts with the module
+ * `prefix` on a whole path segment — `backtrace_utils::x` does not match
+ * `backtrace`.
+ *
+ * Trait impl frames look like `<hawk_core::Foo as core::fmt::Display>::fmt`,
+ * so a leading `<` / `&` is skipped before matching.
+ *
+ * Expects a name already passed through `clean_function_name`.
+ */
+fn matches_module_prefix(name: &str, prefix: &str) -> bool {
+    let path = name.trim_start_matches(['<', '&']);
+    let prefix = prefix.trim_end_matches("::");
+
+    path.strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * Verifies that SDK, backtrace, and panic machinery frames are recognised.
+     */
+    #[test]
+    #[cfg(feature = "backtrace")]
+    fn test_is_sdk_frame_matches_internal_modules() {
+        assert!(is_sdk_frame("hawk_core::get_backtrace"));
+        assert!(is_sdk_frame("hawk_panic::install::{{closure}}"));
+        assert!(is_sdk_frame("hawk::capture::capture_message"));
+        assert!(!is_sdk_frame("hawk_app::capture::run"));
+        assert!(is_sdk_frame("backtrace::backtrace::trace"));
+        assert!(is_sdk_frame("std::panicking::begin_panic_handler"));
+        assert!(is_sdk_frame(
+            "<hawk_core::Guard as core::ops::drop::Drop>::drop"
+        ));
+    }
+
+    /**
+     * Verifies that user code and lookalike crate names are kept.
+     */
+    #[test]
+    #[cfg(feature = "backtrace")]
+    fn test_is_sdk_frame_keeps_user_code() {
+        assert!(!is_sdk_frame("my_app::main"));
+        assert!(!is_sdk_frame("backtrace_utils::capture"));
+        assert!(!is_sdk_frame("std::rt::lang_start"));
+        assert!(!is_sdk_frame("<my_app::Handler as hawk_core::Foo>::call"));
+    }
+
+    /**
+     * Verifies the path-based classification: workspace files are in app,
+     * registry and toolchain files are not.
+     */
+    #[test]
+    fn test_is_in_app_by_file_path() {
+        assert!(is_in_app(
+            Some("my_app::main"),
+            Some("/work/my_app/src/main.rs"),
+            &[]
+        ));
+        assert!(!is_in_app(
+            Some("tokio::runtime::park"),
+            Some("/home/u/.cargo/registry/src/index.crates.io-1/tokio-1.38.0/src/park.rs"),
+            &[],
+        ));
+        assert!(!is_in_app(
+            Some("std::rt::lang_start"),
+            Some("/rustc/abc/library/std/src/rt.rs"),
+            &[]
+        ));
+        assert!(!is_in_app(Some("main"), None, &[]));
+    }
+
+    /**
+     * Verifies that configured module prefixes override the path heuristic,
+     * e.g. for an internal crate pulled from a private git registry.
+     */
+    #[test]
+    fn test_is_in_app_by_configured_prefix() {
+        let include = vec!["my_company_lib".to_string()];
+        assert!(is_in_app(
+            Some("my_company_lib::db::query"),
+            Some("/home/u/.cargo/git/checkouts/my_company_lib/src/db.rs"),
+            &include,
+        ));
+    }
+
+    /**
+     * Verifies demangling of a legacy symbol, including hash removal.
+     */
+    #[test]
+    #[cfg(feature = "backtrace")]
+    fn test_clean_function_name_demangles_legacy() {
+        assert_eq!(
+            clean_function_name("_ZN6my_app4main17h5f5b5e3d1f1c0a7bE"),
+            "my_app::main"
+        );
+        assert_eq!(
+            clean_function_name("my_app::main::h5f5b5e3d1f1c0a7b"),
+            "my_app::main"
+        );
+    }
+
+    /**
+     * Verifies that generic arguments are stripped but trait impl heads kept.
+     */
+    #[test]
+    #[cfg(feature = "backtrace")]
+    fn test_clean_function_name_strips_generics() {
+        assert_eq!(
+            clean_function_name(
+                "std::panicking::catch_unwind::<i32, &dyn core::ops::function::Fn<()>>"
+            ),
+            "std::panicking::catch_unwind"
+        );
+        assert_eq!(
+            clean_function_name("my_app::apply::<fn(u8) -> u8>"),
+            "my_app::apply"
+        );
+        assert_eq!(
+            clean_function_name("<alloc::boxed::Box<F, A> as core::ops::function::Fn<Args>>::call"),
+            "<alloc::boxed::Box as core::ops::function::Fn>::call"
+        );
+    }
+
+    /**
+     * Verifies that v0 closure names match the legacy form.
+     */
+    #[test]
+    #[cfg(feature = "backtrace")]
+    fn test_clean_function_name_normalizes_closures() {
+        assert_eq!(
+            clean_function_name("std::rt::lang_start_internal::{closure#0}"),
+            "std::rt::lang_start_internal::{{closure}}"
+        );
+        assert_eq!(
+            clean_function_name("__libc_start_main"),
+            "__libc_start_main"
+        );
+    }
+
+    /**
+     * Verifies that only frames kept from a physical frame are counted
+     * when marking inlined ones, so that a frame whose caller was skipped
+     * is not marked inlined.
+     */
+    #[test]
+    #[cfg(feature = "backtrace")]
+    fn test_inlined_after_filtering() {
+        let symbol = |function: &str| ResolvedSymbol {
+            function: Some(function.to_string()),
+            file: Some("src/lib.rs".to_string()),
+            line: Some(1),
+            column: None,
+        };
+        let inlined = |symbols: &[ResolvedSymbol]| -> Vec<(Option<String>, bool)> {
+            convert_symbols(symbols, &FrameSettings::default())
+                .into_iter()
+                .map(|frame| (frame.function, frame.inlined))
+                .collect()
+        };
+
+        assert_eq!(
+            inlined(&[symbol("app::parse"), symbol("app::run")]),
+            [
+                (Some("app::parse".to_string()), true),
+                (Some("app::run".to_string()), false),
+            ]
+        );
+        assert_eq!(
+            inlined(&[symbol("app::parse"), symbol("hawk_core::send")]),
+            [(Some("app::parse".to_string()), false)]
+        );
+    }
+
+    /**
+     * Verifies that a relative panic location matches an absolute debug-info path.
+     */
+    #[test]
+    fn test_is_same_location() {
+        let frame = |file: &str, line| BacktraceFrame {
+            file: Some(file.to_string()),
+            line: Some(line),
+            column: None,
+            function: None,
+            module: None,
+            instruction_addr: None,
+            module_offset: None,
+            inlined: false,
+            in_app: true,
+        };
+
+        assert!(is_same_location(
+            &frame("src/main.rs", 10),
+            &frame("/work/app/src/main.rs", 10)
+        ));
+        assert!(!is_same_location(
+            &frame("src/main.rs", 10),
+            &frame("/work/app/src/main.rs", 11)
+        ));
+        assert!(!is_same_location(
+            &frame("main.rs", 10),
+            &frame("/work/app/src/domain.rs", 10)
+        ));
+    }
+
+    /**
+     * Verifies module extraction for free functions, closures, and trait impls.
+     */
+    #[test]
+    #[cfg(feature = "backtrace")]
+    fn test_module_path() {
+        assert_eq!(
+            module_path("my_app::db::query").as_deref(),
+            Some("my_app::db")
+        );
+        assert_eq!(
+            module_path("my_app::main::{{closure}}").as_deref(),
+            Some("my_app")
+        );
+        assert_eq!(
+            module_path("<my_app::api::Handler as tower::Service>::call").as_deref(),
+            Some("my_app::api")
+        );
+        assert_eq!(module_path("main"), None);
+    }
+
+    /**
+     * Verifies parsing of `std::backtrace` output, including SDK frame
+     * filtering, `<unknown>` frames, and frames without a location.
+     */
+    #[test]
+    #[cfg(feature = "backtrace")]
+    fn test_parse_std_backtrace() {
+        let text = "   0: hawk_core::get_backtrace
+             at /work/hawk_core/src/stacktrace.rs:10:5
+   1: my_app::main
+             at ./src/main.rs:12:5
+   2: std::rt::lang_start::{{closure}}
+             at /rustc/abc/library/std/src/rt.rs:206:18
+   3: main
+   4: <unknown>
+";
+        let frames = parse_std_backtrace(text, &FrameSettings::default());
+
+        assert_eq!(frames.len(), 3);
+        assert_eq!(frames[0].function.as_deref(), Some("my_app::main"));
+        assert_eq!(frames[0].file.as_deref(), Some("./src/main.rs"));
+        assert_eq!((frames[0].line, frames[0].column), (Some(12), Some(5)));
+        assert!(frames[0].in_app);
+        assert!(!frames[1].in_app);
+        assert_eq!(frames[2].function.as_deref(), Some("main"));
+        assert_eq!(frames[2].file, None);
+    }
+
+    /**
+     * Verifies that Windows drive letters survive location parsing.
+     */
+    #[test]
+    #[cfg(feature = "backtrace")]
+    fn test_parse_location() {
+        assert_eq!(
+            parse_location("C:\\work\\src\\main.rs:12:5"),
+            ("C:\\work\\src\\main.rs".to_string(), Some(12), Some(5))
+        );
+        assert_eq!(
+            parse_location("src/main.rs:7"),
+            ("src/main.rs".to_string(), Some(7), None)
+        );
+    }
+
+    /**
+     * Verifies scrubbing of registry, toolchain, workspace, and home paths.
+     */
+    #[test]
+    fn test_scrub_path() {
+        assert_eq!(
+            scrub_path("/home/alice/.cargo/registry/src/index.crates.io-1949cf8c6b5b557f/tokio-1.38.0/src/lib.rs", None, false),
+            "<cargo>/tokio-1.38.0/src/lib.rs"
+        );
+        assert_eq!(
+            scrub_path("/rustc/59807616e1fa/library/std/src/rt.rs", None, false),
+            "<rustc>/library/std/src/rt.rs"
+        );
+        assert_eq!(
+            scrub_path(
+                "/home/alice/work/app/src/main.rs",
+                Some("/home/alice/work/app/"),
+                false
+            ),
+            "<workspace>/src/main.rs"
+        );
+        assert_eq!(
+            scrub_path("/home/alice/other/src/lib.rs", None, false),
+            "<home>/other/src/lib.rs"
+        );
+        assert_eq!(scrub_path("src/main.rs", None, false), "src/main.rs");
+        assert_eq!(
+            scrub_path("/home/alice/other/src/lib.rs", None, true),
+            "/home/alice/other/src/lib.rs"
+        );
+    }
+}
@@ -0,0 +1,690 @@
+/*!
+ * SDK status — introspection for health endpoints and admin panels.
+ *
+ * `status()` reports whether the SDK is initialized, where events go, how
+ * many are waiting in the queue (also `pending_events()`), the last
+ * delivery error, whether the collector is rate limiting the client, and
+ * whether the worker is still running. Delivery errors are otherwise only
+ * printed to stderr, where a service's operators rarely look.
+ *
+ * # Rate limits
+ *
+ * When a collector answers `429 Too Many Requests`, the worker drops
+ * every payload bound to it for the time given by its `Retry-After`
+ * header (60 s without one) instead of sending it. `status()` reports the end of that
+ * window, and `Options::on_rate_limit` is called once when it begins, so
+ * that the application can capture less in the meantime.
+ *
+ * # Dormancy
+ *
+ * A collector answering `402 Payment Required` (the project is over its
+ * quota) or `403 Forbidden` (the token or project is disabled) puts the
+ * client to sleep for the time given by `Retry-After`. Without one, a
+ * `402` lasts an hour, and a `403` five minutes — a proxy in front of the
+ * collector may answer `403` for a while, and a disabled project is
+ * rechecked soon enough. Payloads bound to it are dropped unsent, and while every
+ * destination sleeps, events are dropped before they are built (see
+ * `accepts_events()`). `status()` reports the end of that window and its
+ * reason, and `Options::on_dormant` is called once when it begins.
+ *
+ * # Destinations
+ *
+ * With `Options::destinations`, delivery errors, rate limits, and
+ * dormancy are tracked per destination — collector endpoint and token, as
+ * projects sharing a self-hosted collector are limited separately: one
+ * failing or rate limiting destination does not stop delivery to the
+ * others. The top-level fields of `SdkStatus` describe the primary
+ * destination; `destinations` lists all of them.
+ *
+ * # Collector features
+ *
+ * Every request carries the SDK's protocol version (`X-Hawk-Protocol`)
+ * and version (`X-Hawk-Sdk`); a collector answers with the optional wire
+ * features it supports in `X-Hawk-Features`. They are kept per endpoint,
+ * so that newer wire features are only used with collectors that support
+ * them — self-hosted collectors may lag behind.
+ */
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Mutex, MutexGuard};
+use std::time::Duration;
+
+use crate::client::{self, Client};
+use crate::protocol::types::now_millis;
+use crate::transport::Route;
+
+/// Delivery state of every destination that failed or rate limited the
+/// client at least once.
+static DESTINATIONS: Mutex<Vec<DestinationState>> = Mutex::new(Vec::new());
+
+/// Optional wire features advertised by each collector endpoint,
+/// lowercase.
+static FEATURES: Mutex<Vec<(String, Vec<String>)>> = Mutex::new(Vec::new());
+
+/// Whether the worker is processing the queue.
+static WORKER_ALIVE: AtomicBool = AtomicBool::new(false);
+
+/// Envelopes enqueued and not yet sent by the worker.
+static PENDING: AtomicUsize = AtomicUsize::new(0);
+
+/// Estimated size in bytes of the events in the queue.
+static PENDING_BYTES: AtomicUsize = AtomicUsize::new(0);
+
+/// Rate limit window when a `429` response has no usable `Retry-After`.
+const DEFAULT_RATE_LIMIT: Duration = Duration::from_secs(60);
+
+/// Dormancy window when a `402` response has no usable `Retry-After`.
+const DEFAULT_DORMANCY: Duration = Duration::from_secs(60 * 60);
+
+/// Dormancy window when a `403` response has no usable `Retry-After`:
+/// shorter, as proxies answer `403` for transient reasons too.
+const DEFAULT_FORBIDDEN_DORMANCY: Duration = Duration::from_secs(5 * 60);
+
+/**
+ * Delivery state of one destination.
+ */
+#[derive(Clone, Debug)]
+struct DestinationState {
+    route: Route,
+
+    /// Last delivery error and when it happened (Unix milliseconds).
+    last_error: Option<(String, u64)>,
+
+    /// Unix milliseconds until which the collector rate limits the
+    /// client; `0` if it never did.
+    rate_limited_until: u64,
+
+    /// Unix milliseconds until which the collector put the client to
+    /// sleep, and why; `0` if it never did.
+    dormant_until: u64,
+    dormant_reason: Option<&'static str>,
+}
+
+/**
+ * A snapshot of the SDK's state, returned by `status()`.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SdkStatus {
+    /// Whether `init()` has succeeded.
+    pub initialized: bool,
+
+    /// Collector URL events are sent to; `None` before `init()`.
+    pub endpoint: Option<String>,
+
+    /// Events, transactions, and metrics waiting to be delivered
+    /// (`pending_events()`).
+    pub queue_depth: usize,
+
+    /// Queue size beyond which new events are dropped.
+    pub queue_capacity: usize,
+
+    /// Estimated size in bytes of the events waiting to be delivered,
+    /// capped by `Options::max_queue_bytes`.
+    pub queue_bytes: usize,
+
+    /// Message of the last failed delivery (network error or non-2xx
+    /// response), if any.
+    pub last_transport_error: Option<String>,
+
+    /// Unix timestamp in milliseconds of `last_transport_error`.
+    pub last_transport_error_at: Option<u64>,
+
+    /// Unix timestamp in milliseconds until which the collector rate
+    /// limits the client and payloads are dropped; `None` if not limited.
+    pub rate_limited_until: Option<u64>,
+
+    /// Unix timestamp in milliseconds until which the collector put the
+    /// client to sleep (project disabled or over quota) and payloads are
+    /// dropped; `None` if it is awake.
+    pub dormant_until: Option<u64>,
+
+    /// Why the collector put the client to sleep: `"disabled"` or
+    /// `"over quota"`; `None` if it is awake.
+    pub dormant_reason: Option<String>,
+
+    /// Whether the worker delivering the queue is running. `false` before
+    /// `init()`, or if the worker died, in which case events pile up and
+    /// are dropped.
+    pub worker_alive: bool,
+
+    /// Every destination events are delivered to, the primary one first,
+    /// with its own delivery state. Empty before `init()`.
+    pub destinations: Vec<DestinationStatus>,
+
+    /// Delivery state of the shadow collector (`Options::shadow`), if any.
+    pub shadow: Option<DestinationStatus>,
+}
+
+/**
+ * Delivery state of one destination (`SdkStatus::destinations`).
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DestinationStatus {
+    /// Collector URL of the destination.
+    pub endpoint: String,
+
+    /// Message of the last failed delivery to it, if any.
+    pub last_transport_error: Option<String>,
+
+    /// Unix timestamp in milliseconds of `last_transport_error`.
+    pub last_transport_error_at: Option<u64>,
+
+    /// Unix timestamp in milliseconds until which it rate limits the
+    /// client; `None` if not limited.
+    pub rate_limited_until: Option<u64>,
+
+    /// Unix timestamp in milliseconds until which it put the client to
+    /// sleep; `None` if it did not.
+    pub dormant_until: Option<u64>,
+
+    /// Why it put the client to sleep: `"disabled"` or `"over quota"`.
+    pub dormant_reason: Option<String>,
+
+    /// Optional wire features the collector advertised in its last
+    /// response (`X-Hawk-Features`), lowercase; empty if none.
+    pub features: Vec<String>,
+}
+
+impl DestinationStatus {
+    fn new(route: &Route, destinations: &[DestinationState], now: u64) -> Self {
+        let state = destinations.iter().find(|state| state.route == *route);
+        let last_error = state.and_then(|state| state.last_error.clone());
+        let dormancy = state.filter(|state| state.dormant_until > now);
+
+        Self {
+            endpoint: route.endpoint.clone(),
+            last_transport_error_at: last_error.as_ref().map(|(_, at)| *at),
+            last_transport_error: last_error.map(|(message, _)| message),
+            rate_limited_until: state
+                .map(|state| state.rate_limited_until)
+                .filter(|&until| until > now),
+            dormant_until: dormancy.map(|state| state.dormant_until),
+            dormant_reason: dormancy
+                .and_then(|state| state.dormant_reason)
+                .map(str::to_string),
+            features: lock(&FEATURES)
+                .iter()
+                .find(|(endpoint, _)| *endpoint == route.endpoint)
+                .map(|(_, features)| features.clone())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/**
+ * Returns the current state of the SDK. Cheap enough to call on every
+ * health check.
+ */
+pub fn status() -> SdkStatus {
+    snapshot(client::get_client())
+}
+
+/**
+ * State of the SDK with `client` as its client.
+ */
+fn snapshot(client: Option<&Client>) -> SdkStatus {
+    let now = now_of(client);
+    let (destinations, shadow) = {
+        let states = lock(&DESTINATIONS).clone();
+        let destinations: Vec<DestinationStatus> = client
+            .map(|client| client.routes())
+            .unwrap_or_default()
+            .iter()
+            .map(|route| DestinationStatus::new(route, &states, now))
+            .collect();
+        let shadow = client
+            .and_then(|client| client.shadow_route())
+            .map(|route| DestinationStatus::new(route, &states, now));
+        (destinations, shadow)
+    };
+    let primary = destinations.first();
+
+    SdkStatus {
+        initialized: client.is_some(),
+        endpoint: client.map(|client| client.endpoint().to_string()),
+        queue_depth: pending_events(),
+        queue_capacity: client::QUEUE_CAPACITY,
+        queue_bytes: queue_bytes(),
+        last_transport_error: primary.and_then(|primary| primary.last_transport_error.clone()),
+        last_transport_error_at: primary.and_then(|primary| primary.last_transport_error_at),
+        rate_limited_until: primary.and_then(|primary| primary.rate_limited_until),
+        dormant_until: primary.and_then(|primary| primary.dormant_until),
+        dormant_reason: primary.and_then(|primary| primary.dormant_reason.clone()),
+        worker_alive: WORKER_ALIVE.load(Ordering::SeqCst),
+        destinations,
+        shadow,
+    }
+}
+
+/**
+ * Number of events, transactions, and metrics payloads enqueued but not
+ * yet sent, including the one being sent. Applications can slow down,
+ * `flush()` early, or alert when it nears the queue capacity (100), past
+ * which new events are dropped.
+ *
+ * Always `0` before `init()`.
+ */
+pub fn pending_events() -> usize {
+    PENDING.load(Ordering::SeqCst)
+}
+
+/**
+ * Records an envelope entering the queue, before it is sent to the worker
+ * so that the count never goes below zero.
+ */
+pub(crate) fn record_enqueued() {
+    PENDING.fetch_add(1, Ordering::SeqCst);
+}
+
+/**
+ * Records an envelope leaving the queue: sent by the worker, or dropped
+ * before it got there.
+ */
+pub(crate) fn record_dequeued() {
+    PENDING.fetch_sub(1, Ordering::SeqCst);
+}
+
+/**
+ * Estimated size in bytes of the events in the queue.
+ */
+pub(crate) fn queue_bytes() -> usize {
+    PENDING_BYTES.load(Ordering::SeqCst)
+}
+
+/**
+ * Reserves `bytes` of the queue's byte budget for an event. Fails, leaving
+ * the budget unchanged, if the events queued would then exceed `max`.
+ */
+pub(crate) fn reserve_queue_bytes(bytes: usize, max: usize) -> bool {
+    PENDING_BYTES
+        .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |pending| {
+            pending.checked_add(bytes).filter(|&total| total <= max)
+        })
+        .is_ok()
+}
+
+/**
+ * Returns bytes reserved by `reserve_queue_bytes()`, once the event was
+ * sent or dropped.
+ */
+pub(crate) fn release_queue_bytes(bytes: usize) {
+    if bytes > 0 {
+        PENDING_BYTES.fetch_sub(bytes, Ordering::SeqCst);
+    }
+}
+
+fn lock<T>(mutex: &'static Mutex<T>) -> MutexGuard<'static, T> {
+    mutex
+        .lock()
+        .unwrap_or_else(|poisoned| poisoned.into_inner())
+}
+
+/**
+ * Runs `f` on the state of `route`, creating it if needed.
+ */
+fn with_destination<R>(route: &Route, f: impl FnOnce(&mut DestinationState) -> R) -> R {
+    let mut destinations = lock(&DESTINATIONS);
+    let index = match destinations.iter().position(|state| state.route == *route) {
+        Some(index) => index,
+        None => {
+            destinations.push(DestinationState {
+                route: route.clone(),
+                last_error: None,
+                rate_limited_until: 0,
+                dormant_until: 0,
+                dormant_reason: None,
+            });
+            destinations.len() - 1
+        }
+    };
+    f(&mut destinations[index])
+}
+
+/**
+ * Records a failed delivery to `route`, reported by the transports.
+ */
+pub(crate) fn record_transport_error(route: &Route, message: String) {
+    let now = now_of(client::get_client());
+    with_destination(route, |state| state.last_error = Some((message, now)));
+}
+
+/**
+ * Records the features a collector advertised in a successful response:
+ * the value of its `X-Hawk-Features` header, or `None` without one.
+ */
+pub(crate) fn record_features(endpoint: &str, header: Option<&str>) {
+    let features: Vec<String> = header
+        .unwrap_or_default()
+        .split(',')
+        .map(|feature| feature.trim().to_ascii_lowercase())
+        .filter(|feature| !feature.is_empty())
+        .collect();
+
+    let mut endpoints = lock(&FEATURES);
+    match endpoints.iter_mut().find(|(known, _)| known == endpoint) {
+        Some((_, known)) => *known = features,
+        /* Endpoints are only tracked once they have something to report. */
+        None if features.is_empty() => {}
+        None => endpoints.push((endpoint.to_string(), features)),
+    }
+}
+
+/**
+ * Whether `endpoint` advertised `feature` in its last response.
+ */
+#[cfg(not(all(target_arch = "wasm32", target_os = "unknown")))]
+pub(crate) fn supports(endpoint: &str, feature: &str) -> bool {
+    lock(&FEATURES).iter().any(|(known, features)| {
+        known == endpoint && features.iter().any(|advertised| advertised == feature)
+    })
+}
+
+/**
+ * Records a `429` response for `route`. Opens a rate limit window of
+ * `retry_after` (or `DEFAULT_RATE_LIMIT`) for it, and, for the primary
+ * destination, calls `Options::on_rate_limit` if no window was open.
+ */
+pub(crate) fn record_rate_limit(route: &Route, retry_after: Option<Duration>) {
+    open_rate_limit(client::get_client(), route, retry_after);
+}
+
+/**
+ * Records a `429` response for `route` of `client`, the SDK's client in
+ * `record_rate_limit()`.
+ */
+pub(crate) fn open_rate_limit(
+    client: Option<&Client>,
+    route: &Route,
+    retry_after: Option<Duration>,
+) {
+    let now = now_of(client);
+    let retry_after = retry_after.unwrap_or(DEFAULT_RATE_LIMIT);
+    let until = now.saturating_add(retry_after.as_millis().try_into().unwrap_or(u64::MAX));
+
+    let previous = with_destination(route, |state| {
+        let previous = state.rate_limited_until;
+        state.rate_limited_until = previous.max(until);
+        previous
+    });
+    if previous > now {
+        return;
+    }
+
+    eprintln!(
+        "[Hawk] Rate limited by {} — dropping events for {} s",
+        route.endpoint,
+        retry_after.as_secs()
+    );
+    if let Some(client) = client.filter(|client| client.routes()[0] == *route) {
+        client.notify_rate_limit(retry_after);
+    }
+}
+
+/**
+ * Reason a response status puts the client to sleep: `402` (over quota)
+ * or `403` (token or project disabled).
+ */
+pub(crate) fn dormancy_reason(status: u16) -> Option<&'static str> {
+    match status {
+        402 => Some("over quota"),
+        403 => Some("disabled"),
+        _ => None,
+    }
+}
+
+/**
+ * Records a response for `route` putting the client to sleep. Opens a
+ * dormancy window of `retry_after` (or the default for `reason`) for it,
+ * and, for the primary destination, calls `Options::on_dormant` if the
+ * client was awake.
+ */
+pub(crate) fn record_dormancy(route: &Route, reason: &'static str, retry_after: Option<Duration>) {
+    let client = client::get_client();
+    let now = now_of(client);
+    let retry_after = retry_after.unwrap_or(match reason {
+        "disabled" => DEFAULT_FORBIDDEN_DORMANCY,
+        _ => DEFAULT_DORMANCY,
+    });
+    let until = now.saturating_add(retry_after.as_millis().try_into().unwrap_or(u64::MAX));
+
+    let previous = with_destination(route, |state| {
+        let previous = state.dormant_until;
+        state.dormant_until = previous.max(until);
+        state.dormant_reason = Some(reason);
+        previous
+    });
+    if previous > now {
+        return;
+    }
+
+    eprintln!(
+        "[Hawk] {} reports the project {reason} — dropping events for {} s",
+        route.endpoint,
+        retry_after.as_secs()
+    );
+    if let Some(client) = client.filter(|client| client.routes()[0] == *route) {
+        client.notify_dormant(retry_after, reason);
+    }
+}
+
+/**
+ * Whether payloads for `route` must be dropped at `now` because it rate
+ * limits the client or puts it to sleep.
+ */
+pub(crate) fn is_paused(route: &Route, now: u64) -> bool {
+    lock(&DESTINATIONS).iter().any(|state| {
+        state.route == *route && (state.rate_limited_until > now || state.dormant_until > now)
+    })
+}
+
+/**
+ * Current time by the clock of `client` (`Options::clock`), the system
+ * clock without one. Windows are opened and checked with it.
+ */
+pub(crate) fn now_of(client: Option<&Client>) -> u64 {
+    client.map_or_else(now_millis, |client| client.clock().now_millis())
+}
+
+/**
+ * Parses a `Retry-After` value in seconds. HTTP dates are not supported
+ * and give `None`.
+ */
+pub(crate) fn parse_retry_after(value: &str) -> Option<Duration> {
+    value.trim().parse().ok().map(Duration::from_secs)
+}
+
+/**
+ * Records that the worker started or stopped.
+ */
+pub(crate) fn set_worker_alive(alive: bool) {
+    WORKER_ALIVE.store(alive, Ordering::SeqCst);
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+    use crate::transport::QueueSlot;
+
+    /// Token of the test clients: integration ID `test`, never contacted.
+    const TEST_TOKEN: &str = "eyJpbnRlZ3JhdGlvbklkIjoidGVzdCIsInNlY3JldCI6InRlc3QifQ==";
+
+    /**
+     * Verifies what the status reports before `init()`, and the endpoint,
+     * destinations, and last delivery error of a client.
+     */
+    #[test]
+    fn test_status() {
+        let idle = snapshot(None);
+        assert!(!idle.initialized);
+        assert_eq!(idle.endpoint, None);
+        assert!(idle.destinations.is_empty());
+        assert_eq!(idle.shadow, None);
+        assert_eq!(idle.queue_capacity, client::QUEUE_CAPACITY);
+
+        let options = client::Options {
+            collector_endpoint: Some("http://127.0.0.1:9/status".into()),
+            destinations: vec![client::Destination {
+                token: TEST_TOKEN.into(),
+                endpoint: Some("http://127.0.0.1:9/status-aggregate".into()),
+            }],
+            shadow: Some(client::Shadow {
+                endpoint: "http://127.0.0.1:9/status-shadow".into(),
+                rate: 0.5,
+            }),
+            ..client::Options::default()
+        };
+        let (client, _) = Client::new(TEST_TOKEN, options).unwrap();
+        let before = now_millis();
+        record_transport_error(&client.routes()[1], "Failed to send event: refused".into());
+
+        let status = snapshot(Some(&client));
+        assert!(status.initialized);
+        assert_eq!(
+            status.endpoint.as_deref(),
+            Some("http://127.0.0.1:9/status")
+        );
+        let endpoints: Vec<_> = status
+            .destinations
+            .iter()
+            .map(|destination| destination.endpoint.as_str())
+            .collect();
+        assert_eq!(
+            endpoints,
+            [
+                "http://127.0.0.1:9/status",
+                "http://127.0.0.1:9/status-aggregate"
+            ]
+        );
+        assert_eq!(
+            status.shadow.map(|shadow| shadow.endpoint).as_deref(),
+            Some("http://127.0.0.1:9/status-shadow")
+        );
+        assert_eq!(status.last_transport_error, None);
+        let aggregate = &status.destinations[1];
+        assert_eq!(
+            aggregate.last_transport_error.as_deref(),
+            Some("Failed to send event: refused")
+        );
+        assert!(aggregate
+            .last_transport_error_at
+            .is_some_and(|at| at >= before));
+    }
+
+    /**
+     * Verifies that payloads holding a place in the queue are counted as
+     * pending, by the status too. Other tests queue payloads as well, so
+     * only lower bounds hold.
+     */
+    #[test]
+    fn test_pending_events() {
+        let slots: Vec<QueueSlot> = (0..3).map(|_| QueueSlot::new(0)).collect();
+        assert!(pending_events() >= 3);
+        assert!(snapshot(None).queue_depth >= 3);
+        drop(slots);
+    }
+
+    /**
+     * Verifies that a rate limit of the primary destination is reported
+     * until its window ends, and that `on_rate_limit` is called once per
+     * window, not for other destinations.
+     */
+    #[test]
+    fn test_rate_limit() {
+        let calls = Arc::new(Mutex::new(Vec::new()));
+        let options = client::Options {
+            collector_endpoint: Some("http://127.0.0.1:9/rate-limited".into()),
+            destinations: vec![client::Destination {
+                token: TEST_TOKEN.into(),
+                endpoint: Some("http://127.0.0.1:9/rate-limited-aggregate".into()),
+            }],
+            on_rate_limit: Some(Arc::new({
+                let calls = Arc::clone(&calls);
+                move |retry_after| calls.lock().unwrap().push(retry_after)
+            })),
+            ..client::Options::default()
+        };
+        let (client, _) = Client::new(TEST_TOKEN, options).unwrap();
+        let (primary, aggregate) = (&client.routes()[0], &client.routes()[1]);
+        assert_eq!(snapshot(Some(&client)).rate_limited_until, None);
+
+        let before = now_millis();
+        open_rate_limit(Some(&client), primary, Some(Duration::from_secs(30)));
+        open_rate_limit(Some(&client), primary, Some(Duration::from_secs(10)));
+        open_rate_limit(Some(&client), aggregate, None);
+        let after = now_millis();
+        assert_eq!(*calls.lock().unwrap(), [Duration::from_secs(30)]);
+
+        let status = snapshot(Some(&client));
+        let until = status.rate_limited_until.unwrap();
+        assert!((before + 30_000..=after + 30_000).contains(&until));
+        assert_eq!(status.destinations[0].rate_limited_until, Some(until));
+        assert!(status.destinations[1]
+            .rate_limited_until
+            .is_some_and(|until| until >= before + DEFAULT_RATE_LIMIT.as_millis() as u64));
+    }
+
+    /**
+     * Verifies that delivery errors, rate limits, and dormancy are tracked
+     * per destination, even on a shared endpoint, and advertised features
+     * per endpoint.
+     */
+    #[test]
+    fn test_destination_state() {
+        let route = |endpoint: &str, token: &str| Route {
+            endpoint: endpoint.into(),
+            token: token.into(),
+        };
+        let start = now_millis();
+        let team = route("http://team.test/", "team");
+        let company = route("http://company.test/", "company");
+        let archive = route("http://archive.test/", "archive");
+        let mirror = route("http://archive.test/", "mirror");
+
+        record_rate_limit(&team, Some(Duration::from_secs(30)));
+        record_transport_error(
+            &company,
+            "Collector responded with HTTP 502: bad gateway".into(),
+        );
+        record_dormancy(&archive, dormancy_reason(402).unwrap(), None);
+        record_dormancy(&mirror, dormancy_reason(403).unwrap(), None);
+
+        assert!(is_paused(&team, start));
+        assert!(!is_paused(&company, start));
+        assert!(is_paused(&archive, start));
+        assert!(!is_paused(&route("http://team.test/", "other"), start));
+        assert_eq!(dormancy_reason(500), None);
+
+        record_features(&team.endpoint, None);
+        record_features(&company.endpoint, Some(" GZip, batch,"));
+        record_features(&archive.endpoint, Some("gzip"));
+
+        let destinations = lock(&DESTINATIONS);
+        let now = now_millis();
+        let team = DestinationStatus::new(&team, &destinations, now);
+        let company = DestinationStatus::new(&company, &destinations, now);
+        let archive = DestinationStatus::new(&archive, &destinations, now);
+        let mirror = DestinationStatus::new(&mirror, &destinations, now);
+        assert!(archive
+            .dormant_until
+            .is_some_and(|until| until >= start + DEFAULT_DORMANCY.as_millis() as u64));
+        assert_eq!(archive.dormant_reason.as_deref(), Some("over quota"));
+        assert!(mirror.dormant_until.is_some_and(
+            |until| until > now && until <= now + DEFAULT_FORBIDDEN_DORMANCY.as_millis() as u64
+        ));
+        assert_eq!(mirror.dormant_reason.as_deref(), Some("disabled"));
+        assert_eq!(mirror.features, ["gzip"]);
+        assert_eq!(archive.rate_limited_until, None);
+        assert_eq!(team.dormant_until, None);
+        assert!(team.rate_limited_until.is_some_and(|until| until > now));
+        assert_eq!(team.last_transport_error, None);
+        assert!(team.features.is_empty());
+        assert_eq!(company.rate_limited_until, None);
+        assert_eq!(
+            company.last_transport_error.as_deref(),
+            Some("Collector responded with HTTP 502: bad gateway")
+        );
+        assert_eq!(company.features, ["gzip", "batch"]);
+    }
+}
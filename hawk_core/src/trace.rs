@@ -0,0 +1,187 @@
+/*!
+ * Distributed tracing — linking events across services.
+ *
+ * A request entering a service runs inside a `TraceContext`: continued
+ * from the incoming `hawk-trace` header, or started fresh at the edge.
+ * Every event sent while it runs carries
+ * `{"trace": {"trace_id", "span_id", "parent_id"}}` in its context, and
+ * outgoing requests forward `current_trace_header()`. An error in a
+ * downstream service then shares the upstream event's `trace_id`, and its
+ * `parent_id` is the upstream service's `span_id`.
+ *
+ * The header value is `{trace_id}-{span_id}`: 32 and 16 lowercase hex
+ * digits.
+ *
+ * ```ignore
+ * let incoming = request.headers().get(hawk::TRACE_HEADER).and_then(|v| v.to_str().ok());
+ * hawk::TraceContext::continue_from(incoming).run(|| {
+ *     let header = hawk::current_trace_header().unwrap();
+ *     client.get(url).header(hawk::TRACE_HEADER, header).send()
+ * })
+ * ```
+ */
+
+use std::cell::RefCell;
+use std::sync::Arc;
+
+use serde_json::json;
+
+use crate::context;
+use crate::performance::new_id;
+
+/// Name of the propagation header.
+pub const TRACE_HEADER: &str = "hawk-trace";
+
+thread_local! {
+    /// Trace contexts running on this thread, innermost last.
+    static CURRENT: RefCell<Vec<TraceContext>> = const { RefCell::new(Vec::new()) };
+}
+
+/**
+ * This service's part of a distributed trace.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TraceContext {
+    /// ID shared by every service handling the same request, 32 hex digits.
+    pub trace_id: String,
+
+    /// ID of this service's part, 16 hex digits. Sent downstream as the
+    /// parent.
+    pub span_id: String,
+
+    /// `span_id` of the upstream service; `None` where the trace started.
+    pub parent_id: Option<String>,
+}
+
+impl TraceContext {
+    /**
+     * Starts a new trace.
+     */
+    pub fn new() -> Self {
+        Self {
+            trace_id: format!("{}{}", new_id(), new_id()),
+            span_id: new_id(),
+            parent_id: None,
+        }
+    }
+
+    /**
+     * Continues the trace of a `hawk-trace` header value. `None` if the
+     * value is malformed.
+     */
+    pub fn from_header(value: &str) -> Option<Self> {
+        let (trace_id, parent_id) = value.trim().split_once('-')?;
+        if !is_hex_id(trace_id, 32) || !is_hex_id(parent_id, 16) {
+            return None;
+        }
+
+        Some(Self {
+            trace_id: trace_id.to_ascii_lowercase(),
+            span_id: new_id(),
+            parent_id: Some(parent_id.to_ascii_lowercase()),
+        })
+    }
+
+    /**
+     * Continues the trace of an incoming header if there is a valid one,
+     * or starts a new trace.
+     */
+    pub fn continue_from(header: Option<&str>) -> Self {
+        header.and_then(Self::from_header).unwrap_or_default()
+    }
+
+    /**
+     * The header value to send downstream: `{trace_id}-{span_id}`.
+     */
+    pub fn header_value(&self) -> String {
+        format!("{}-{}", self.trace_id, self.span_id)
+    }
+
+    /**
+     * Runs `f` inside this trace: events sent from this thread carry its
+     * IDs, and `current_trace_header()` returns its header.
+     */
+    pub fn run<R>(&self, f: impl FnOnce() -> R) -> R {
+        struct PopOnDrop;
+
+        impl Drop for PopOnDrop {
+            fn drop(&mut self) {
+                CURRENT.with(|current| {
+                    current.borrow_mut().pop();
+                });
+            }
+        }
+
+        let scope = Arc::new(json!({
+            "trace": {
+                "trace_id": self.trace_id,
+                "span_id": self.span_id,
+                "parent_id": self.parent_id,
+            }
+        }));
+
+        CURRENT.with(|current| current.borrow_mut().push(self.clone()));
+        let _pop = PopOnDrop;
+
+        context::with_scope(&scope, f)
+    }
+}
+
+impl Default for TraceContext {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+/**
+ * The `hawk-trace` header value for an outgoing request made inside
+ * `TraceContext::run()`. `None` outside of one.
+ */
+pub fn current_trace_header() -> Option<String> {
+    CURRENT.with(|current| current.borrow().last().map(TraceContext::header_value))
+}
+
+/**
+ * Whether `id` is `len` hex digits, not all zero.
+ */
+fn is_hex_id(id: &str, len: usize) -> bool {
+    id.len() == len && id.bytes().all(|b| b.is_ascii_hexdigit()) && id.bytes().any(|b| b != b'0')
+}
+
+#[cfg(all(test, feature = "context"))]
+mod tests {
+    use super::*;
+
+    /**
+     * Verifies header parsing and that a continued trace links to the
+     * upstream span, both in the forwarded header and in event context.
+     */
+    #[test]
+    fn test_continue_trace() {
+        let upstream = TraceContext::new();
+        assert_eq!(upstream.trace_id.len(), 32);
+        assert!(TraceContext::from_header("not-a-header").is_none());
+        assert!(
+            TraceContext::from_header(&format!("{}-{}", "0".repeat(32), upstream.span_id))
+                .is_none()
+        );
+
+        let downstream = TraceContext::continue_from(Some(&upstream.header_value().to_uppercase()));
+        assert_eq!(downstream.trace_id, upstream.trace_id);
+        assert_eq!(
+            downstream.parent_id.as_deref(),
+            Some(upstream.span_id.as_str())
+        );
+        assert_ne!(downstream.span_id, upstream.span_id);
+
+        assert_eq!(current_trace_header(), None);
+        let (header, context) = downstream.run(|| {
+            let mut context = None;
+            context::apply_scopes(&mut context, 0, 0);
+            (current_trace_header(), context.unwrap())
+        });
+        assert_eq!(header, Some(downstream.header_value()));
+        assert_eq!(context["trace"]["trace_id"], upstream.trace_id.as_str());
+        assert_eq!(context["trace"]["parent_id"], upstream.span_id.as_str());
+    }
+}
@@ -0,0 +1,148 @@
+/*!
+ * Wire encodings — how envelopes are serialized for the collector.
+ *
+ * JSON is always available and always understood. For very high event
+ * volumes, `Options::wire_encoding` selects a binary encoding instead,
+ * smaller and cheaper to produce:
+ * - MessagePack (`msgpack` feature), sent as `application/msgpack`;
+ * - CBOR (`cbor` feature), sent as `application/cbor`.
+ *
+ * A binary encoding is only used with a collector that advertised it in
+ * `X-Hawk-Features` (`msgpack` or `cbor`): until its first response, and
+ * for collectors that do not support it, envelopes are sent as JSON.
+ *
+ * The browser transport always sends JSON.
+ */
+
+use serde::Serialize;
+
+/**
+ * Serialization of envelopes on the wire (`Options::wire_encoding`).
+ */
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum WireEncoding {
+    /// JSON, understood by every collector.
+    #[default]
+    Json,
+
+    /// MessagePack; requires the `msgpack` feature.
+    MessagePack,
+
+    /// CBOR; requires the `cbor` feature.
+    Cbor,
+}
+
+#[cfg_attr(all(target_arch = "wasm32", target_os = "unknown"), allow(dead_code))]
+impl WireEncoding {
+    /**
+     * Fails if the encoding was selected without its cargo feature.
+     */
+    pub(crate) fn check_available(self) -> Result<(), String> {
+        match self {
+            Self::MessagePack if !cfg!(feature = "msgpack") => {
+                Err("WireEncoding::MessagePack requires the `msgpack` feature".into())
+            }
+            Self::Cbor if !cfg!(feature = "cbor") => {
+                Err("WireEncoding::Cbor requires the `cbor` feature".into())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /**
+     * Name of the `X-Hawk-Features` entry a collector advertises to
+     * accept this encoding; `None` for JSON.
+     */
+    pub(crate) fn feature(self) -> Option<&'static str> {
+        match self {
+            Self::Json => None,
+            Self::MessagePack => Some("msgpack"),
+            Self::Cbor => Some("cbor"),
+        }
+    }
+
+    /**
+     * `Content-Type` of request bodies in this encoding.
+     */
+    pub(crate) fn content_type(self) -> &'static str {
+        match self {
+            Self::Json => "application/json",
+            Self::MessagePack => "application/msgpack",
+            Self::Cbor => "application/cbor",
+        }
+    }
+
+    /**
+     * Serializes an envelope. Maps keep their field names, as in JSON.
+     */
+    pub(crate) fn encode(self, envelope: &impl Serialize) -> Result<Vec<u8>, String> {
+        match self {
+            Self::Json => serde_json::to_vec(envelope).map_err(|err| err.to_string()),
+
+            #[cfg(feature = "msgpack")]
+            Self::MessagePack => rmp_serde::to_vec_named(envelope).map_err(|err| err.to_string()),
+
+            #[cfg(feature = "cbor")]
+            Self::Cbor => {
+                let mut body = Vec::new();
+                ciborium::into_writer(envelope, &mut body).map_err(|err| err.to_string())?;
+                Ok(body)
+            }
+
+            #[allow(unreachable_patterns)]
+            _ => Err(format!("{self:?} encoding is not enabled")),
+        }
+    }
+}
+
+/**
+ * Length of the JSON encoding of `value`, counted without building it.
+ */
+pub(crate) fn json_size(value: &impl Serialize) -> usize {
+    struct Counter(usize);
+
+    impl std::io::Write for Counter {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.0 += buf.len();
+            Ok(buf.len())
+        }
+
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    let mut counter = Counter(0);
+    let _ = serde_json::to_writer(&mut counter, value);
+    counter.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::types::{EventData, HawkEvent};
+
+    /**
+     * Verifies that JSON is always available, and counted exactly, and
+     * that binary encodings need their feature.
+     */
+    #[test]
+    fn test_encode() {
+        let envelope = HawkEvent::new("token", EventData::new("boom"));
+
+        let json = WireEncoding::Json.encode(&envelope).unwrap();
+        assert_eq!(
+            serde_json::from_slice::<serde_json::Value>(&json).unwrap()["payload"]["title"],
+            "boom"
+        );
+        assert_eq!(json_size(&envelope), json.len());
+
+        for encoding in [WireEncoding::MessagePack, WireEncoding::Cbor] {
+            let enabled = encoding.check_available().is_ok();
+            assert_eq!(encoding.encode(&envelope).is_ok(), enabled);
+            if enabled {
+                assert!(encoding.encode(&envelope).unwrap().len() < json.len());
+            }
+        }
+    }
+}
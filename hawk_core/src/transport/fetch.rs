@@ -0,0 +1,171 @@
+/*!
+ * Browser transport (`wasm32-unknown-unknown`) for sending events to the
+ * Hawk collector.
+ *
+ * Uses the global `fetch()` — available on `window` as well as in web
+ * workers — looked up at send time, so the same binary runs in both.
+ *
+ * Design decisions:
+ * - **Request started synchronously** — `fetch()` is called directly from
+ *   `send()`; only the response is awaited on the microtask queue. With
+ *   `panic = "abort"` (the wasm default) the module traps right after the
+ *   panic hook, and a request that has not been started by then is lost.
+ * - **`keepalive`** — the request outlives the page, so events sent while
+ *   the user navigates away still arrive.
+ * - **No version headers** — custom request headers would make every
+ *   event a CORS preflighted request; the protocol version is only sent
+ *   by the other transports.
+ * - **Best-effort delivery** — failures are logged to the browser console
+ *   and swallowed, as on native targets.
+ */
+
+use js_sys::{Function, Promise, Reflect};
+use wasm_bindgen::{JsCast, JsValue};
+use wasm_bindgen_futures::JsFuture;
+use web_sys::{Headers, RequestInit, Response};
+
+use serde::Serialize;
+
+use super::encoding::WireEncoding;
+use super::Route;
+use crate::protocol::constants::FEATURES_HEADER;
+use crate::status;
+
+/**
+ * Delivers serialized events through the browser's `fetch()`.
+ *
+ * Stateless — the worker creates one during `Client::init()` like the
+ * native transport.
+ */
+pub struct Transport;
+
+impl Transport {
+    /**
+     * Binary encodings are not supported here: the encoding is ignored
+     * and events are always sent as JSON.
+     */
+    pub fn new(_encoding: WireEncoding) -> Result<Self, String> {
+        Ok(Self)
+    }
+
+    /**
+     * Connectivity checks are not supported on this target: `init()`
+     * cannot wait for a response here. Always succeeds.
+     */
+    pub fn check(&self, _endpoint: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    /**
+     * POSTs an envelope (`HawkEvent` or `HawkTransaction`) to the
+     * collector endpoint of `route` as JSON.
+     *
+     * Best-effort: any error is printed to the console and swallowed.
+     */
+    pub fn send(&self, route: &Route, event: &impl Serialize) {
+        let body = match serde_json::to_string(event) {
+            Ok(body) => body,
+            Err(err) => {
+                console_error(&format!("[Hawk] Failed to serialize event: {err}"));
+                return;
+            }
+        };
+
+        let promise = match start_fetch(&route.endpoint, &body) {
+            Ok(promise) => promise,
+            Err(err) => {
+                report_error(route, format!("Failed to send event: {err:?}"));
+                return;
+            }
+        };
+
+        let route = route.clone();
+        wasm_bindgen_futures::spawn_local(async move {
+            match JsFuture::from(promise).await {
+                Ok(response) => {
+                    let response: Response = response.unchecked_into();
+                    if response.status() == 429 {
+                        /*
+                         * `Retry-After` is only readable if the collector
+                         * exposes it through CORS.
+                         */
+                        let retry_after = response
+                            .headers()
+                            .get("Retry-After")
+                            .ok()
+                            .flatten()
+                            .and_then(|value| status::parse_retry_after(&value));
+                        status::record_rate_limit(&route, retry_after);
+                        status::record_transport_error(
+                            &route,
+                            "Collector responded with HTTP 429 (rate limited)".into(),
+                        );
+                    } else if response.ok() {
+                        /*
+                         * Like `Retry-After`, only readable if exposed
+                         * through CORS.
+                         */
+                        let features = response.headers().get(FEATURES_HEADER).ok().flatten();
+                        status::record_features(&route.endpoint, features.as_deref());
+                    } else {
+                        report_error(
+                            &route,
+                            format!("Collector responded with HTTP {}", response.status()),
+                        );
+                        if let Some(reason) = status::dormancy_reason(response.status()) {
+                            let retry_after = response
+                                .headers()
+                                .get("Retry-After")
+                                .ok()
+                                .flatten()
+                                .and_then(|value| status::parse_retry_after(&value));
+                            status::record_dormancy(&route, reason, retry_after);
+                        }
+                    }
+                }
+                Err(err) => {
+                    report_error(&route, format!("Failed to send event: {err:?}"));
+                }
+            }
+        });
+    }
+}
+
+/**
+ * Calls `globalThis.fetch(endpoint, { method: "POST", body, keepalive })`.
+ */
+fn start_fetch(endpoint: &str, body: &str) -> Result<Promise, JsValue> {
+    let global = js_sys::global();
+    let fetch: Function = Reflect::get(&global, &JsValue::from_str("fetch"))?.dyn_into()?;
+
+    let headers = Headers::new()?;
+    headers.set("Content-Type", "application/json")?;
+
+    let init = RequestInit::new();
+    init.set_method("POST");
+    init.set_headers(&headers);
+    init.set_body(&JsValue::from_str(body));
+
+    /* `keepalive` has no typed setter in web-sys. */
+    Reflect::set(&init, &JsValue::from_str("keepalive"), &JsValue::TRUE)?;
+
+    fetch
+        .call2(&global, &JsValue::from_str(endpoint), &init)?
+        .dyn_into()
+}
+
+/**
+ * Logs a failed delivery to the console and keeps it for `status()`.
+ */
+fn report_error(route: &Route, message: String) {
+    console_error(&format!("[Hawk] {message}"));
+    status::record_transport_error(route, message);
+}
+
+/**
+ * Logs to the browser console — `eprintln!` output goes nowhere on
+ * `wasm32-unknown-unknown`.
+ */
+pub(crate) fn console_error(message: &str) {
+    web_sys::console::error_1(&JsValue::from_str(message));
+}
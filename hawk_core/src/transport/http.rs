@@ -8,27 +8,46 @@
  * Design decisions:
  * - **Blocking HTTP** — the worker thread is already a dedicated background
  *   thread, so blocking I/O is perfectly fine.
- * - **Best-effort delivery** — errors are logged to stderr but never
- *   propagated. The SDK must never crash the host application.
+ * - **Best-effort delivery** — errors are logged to stderr and kept for
+ *   `status()`, but never propagated. The SDK must never crash the host application.
  * - **Single attempt** — no retries. The backend is designed to be highly
  *   available; transient failures are acceptable to drop.
+ * - **TLS backend chosen at build time** — rustls by default, the
+ *   platform's TLS library with the `native-tls` feature. Without
+ *   either, `init` rejects `https://` endpoints.
  */
 
 use std::time::Duration;
 
+use ureq::config::ConfigBuilder;
+#[cfg(any(feature = "rustls", feature = "native-tls"))]
+use ureq::tls::{TlsConfig, TlsProvider};
+use ureq::typestate::AgentScope;
 use ureq::Agent;
 
-use crate::protocol::types::HawkEvent;
+use serde::Serialize;
+
+use super::encoding::WireEncoding;
+use super::Route;
+use crate::protocol::constants::{
+    CATCHER_VERSION, FEATURES_HEADER, PROTOCOL_HEADER, PROTOCOL_VERSION, SDK_HEADER,
+};
+use crate::status;
 
 /**
  * Thin wrapper around `ureq::Agent` responsible for delivering
  * serialized events to the Hawk collector.
  *
  * A single `Transport` instance is created during `Client::init()` and
- * moved into the background worker thread.
+ * moved into the background worker thread; the shadow thread gets a clone,
+ * sharing its connection pool.
  */
+#[derive(Clone)]
 pub struct Transport {
     agent: Agent,
+
+    /// Encoding used with collectors that support it.
+    encoding: WireEncoding,
 }
 
 impl Transport {
@@ -41,44 +60,162 @@ impl Transport {
      *
      * Connection pooling and keep-alive are handled by the agent internally.
      */
-    pub fn new() -> Result<Self, String> {
-        let agent: Agent = Agent::config_builder()
+    pub fn new(encoding: WireEncoding) -> Result<Self, String> {
+        let agent: Agent = agent_config()
             .timeout_connect(Some(Duration::from_secs(10)))
             .timeout_global(Some(Duration::from_secs(30)))
             .http_status_as_error(false)
             .build()
             .into();
 
-        Ok(Self { agent })
+        Ok(Self { agent, encoding })
     }
 
     /**
-     * Sends a `HawkEvent` to the given collector endpoint.
+     * Checks that the collector endpoint answers a `HEAD` request, within
+     * 5 s. Any HTTP response below 500 counts as reachable — the collector
+     * only accepts `POST`s.
      *
-     * The event is serialized to JSON and POSTed with
-     * `Content-Type: application/json`.
+     * Uses an agent of its own, so that the probe's connection is never
+     * reused for events.
+     */
+    pub fn check(&self, endpoint: &str) -> Result<(), String> {
+        let agent: Agent = agent_config()
+            .timeout_global(Some(Duration::from_secs(5)))
+            .http_status_as_error(false)
+            .build()
+            .into();
+
+        let response = agent.head(endpoint).call().map_err(|err| err.to_string())?;
+        let status = response.status().as_u16();
+        if status >= 500 {
+            return Err(format!("collector responded with HTTP {status}"));
+        }
+        Ok(())
+    }
+
+    /**
+     * Sends an envelope (`HawkEvent` or `HawkTransaction`) to the
+     * collector endpoint of `route`.
+     *
+     * The event is serialized to JSON — or to the configured binary
+     * encoding if the endpoint advertised it — and POSTed with its
+     * `Content-Type` and the protocol and SDK version headers. The
+     * features advertised in the response are recorded for the endpoint.
      *
      * Best-effort: any error is printed to stderr and swallowed.
      */
-    pub fn send(&self, endpoint: &str, event: &HawkEvent) {
-        let result = self.agent
+    pub fn send(&self, route: &Route, event: &impl Serialize) {
+        let endpoint = route.endpoint.as_str();
+        let encoding = match self.encoding.feature() {
+            Some(feature) if status::supports(endpoint, feature) => self.encoding,
+            _ => WireEncoding::Json,
+        };
+        let body = match encoding.encode(event) {
+            Ok(body) => body,
+            Err(err) => {
+                eprintln!("[Hawk] Failed to serialize event: {err}");
+                status::record_transport_error(route, format!("Failed to serialize event: {err}"));
+                return;
+            }
+        };
+
+        let result = self
+            .agent
             .post(endpoint)
-            .send_json(event);
+            .header("Content-Type", encoding.content_type())
+            .header(PROTOCOL_HEADER, PROTOCOL_VERSION.to_string())
+            .header(SDK_HEADER, CATCHER_VERSION)
+            .send(&body[..]);
 
         match result {
             Ok(response) => {
                 let status = response.status().as_u16();
-                if !(200..300).contains(&status) {
+                let retry_after = response
+                    .headers()
+                    .get("retry-after")
+                    .and_then(|value| value.to_str().ok())
+                    .and_then(status::parse_retry_after);
+                if (200..300).contains(&status) {
+                    let features = response
+                        .headers()
+                        .get(FEATURES_HEADER)
+                        .and_then(|value| value.to_str().ok());
+                    status::record_features(endpoint, features);
+                } else if status == 429 {
+                    status::record_rate_limit(route, retry_after);
+                    status::record_transport_error(
+                        route,
+                        "Collector responded with HTTP 429 (rate limited)".into(),
+                    );
+                } else {
                     let body = response
                         .into_body()
                         .read_to_string()
                         .unwrap_or_else(|_| "<unreadable body>".into());
                     eprintln!("[Hawk] Collector responded with HTTP {status}: {body}");
+                    status::record_transport_error(
+                        route,
+                        format!("Collector responded with HTTP {status}: {body}"),
+                    );
+                    if let Some(reason) = status::dormancy_reason(status) {
+                        status::record_dormancy(route, reason, retry_after);
+                    }
                 }
             }
             Err(err) => {
                 eprintln!("[Hawk] Failed to send event: {err}");
+                status::record_transport_error(route, format!("Failed to send event: {err}"));
             }
         }
     }
 }
+
+/**
+ * Agent configuration shared by both agents: the TLS backend selected by
+ * the cargo features.
+ */
+#[cfg(any(feature = "rustls", feature = "native-tls"))]
+fn agent_config() -> ConfigBuilder<AgentScope> {
+    let provider = if cfg!(feature = "native-tls") {
+        TlsProvider::NativeTls
+    } else {
+        TlsProvider::Rustls
+    };
+
+    Agent::config_builder().tls_config(TlsConfig::builder().provider(provider).build())
+}
+
+/**
+ * Without a TLS backend, only `http://` endpoints can be reached.
+ */
+#[cfg(not(any(feature = "rustls", feature = "native-tls")))]
+fn agent_config() -> ConfigBuilder<AgentScope> {
+    Agent::config_builder()
+}
+
+/**
+ * Checks that `endpoint` can be reached by this build: with a TLS
+ * backend, every endpoint can.
+ */
+#[cfg(any(feature = "rustls", feature = "native-tls"))]
+pub(crate) fn check_scheme(_endpoint: &str) -> Result<(), String> {
+    Ok(())
+}
+
+/**
+ * Without a TLS backend, rejects `https://` endpoints, so that `init`
+ * fails instead of every send.
+ */
+#[cfg(not(any(feature = "rustls", feature = "native-tls")))]
+pub(crate) fn check_scheme(endpoint: &str) -> Result<(), String> {
+    if endpoint
+        .get(..8)
+        .is_some_and(|scheme| scheme.eq_ignore_ascii_case("https://"))
+    {
+        return Err(format!(
+            "Hawk collector {endpoint} requires the `rustls` or `native-tls` feature"
+        ));
+    }
+    Ok(())
+}
@@ -2,12 +2,34 @@
  * Transport layer — HTTP delivery and background worker thread.
  *
  * Everything related to *how* we deliver events to the Hawk backend:
- * - `http` — reqwest-based HTTP client wrapper
+ * - `encoding` — JSON, MessagePack, and CBOR serialization of envelopes
+ * - `http` — ureq-based HTTP client wrapper
+ * - `fetch` — browser `fetch()` transport (`wasm32-unknown-unknown`)
+ * - `wasi_http` — WASI HTTP outbound transport (`wasm32-wasip2`)
  * - `worker` — background thread, bounded channel, flush signaling
  */
 
+pub mod encoding;
+
+#[cfg(not(target_arch = "wasm32"))]
 pub mod http;
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+pub mod fetch;
+
+#[cfg(all(target_arch = "wasm32", target_os = "wasi"))]
+pub mod wasi_http;
+
 pub mod worker;
 
+pub(crate) use encoding::json_size;
+pub use encoding::WireEncoding;
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+pub use fetch::Transport;
+#[cfg(not(target_arch = "wasm32"))]
 pub use http::Transport;
-pub use worker::{FlushSignal, Worker, WorkerMsg};
+#[cfg(all(target_arch = "wasm32", target_os = "wasi"))]
+pub use wasi_http::Transport;
+#[cfg(not(target_arch = "wasm32"))]
+pub use worker::FlushSignal;
+pub use worker::{QueueSlot, QueuedEvent, Route, Worker, WorkerMsg};
@@ -0,0 +1,212 @@
+/*!
+ * WASI transport (`wasm32-wasip2`) for sending events to the Hawk collector.
+ *
+ * Uses the `wasi:http/outgoing-handler` interface, which hosts such as
+ * wasmtime (`wasmtime serve`) and Spin provide to server-side components.
+ *
+ * Design decisions:
+ * - **Blocking** — WASI components have no threads; the request is sent
+ *   and its response awaited on the calling thread (see the no-thread mode
+ *   in `worker`).
+ * - **Best-effort delivery** — errors are logged to stderr (forwarded by
+ *   the host) and swallowed, as on native targets.
+ */
+
+use std::time::Duration;
+
+use wasip2::http::outgoing_handler;
+use wasip2::http::types::{Fields, Method, OutgoingBody, OutgoingRequest, Scheme};
+
+use serde::Serialize;
+
+use super::encoding::WireEncoding;
+use super::Route;
+use crate::protocol::constants::{
+    CATCHER_VERSION, FEATURES_HEADER, PROTOCOL_HEADER, PROTOCOL_VERSION, SDK_HEADER,
+};
+use crate::status;
+
+/// Largest chunk `blocking_write_and_flush` accepts in one call.
+const MAX_WRITE_CHUNK: usize = 4096;
+
+/**
+ * Delivers serialized events through `wasi:http/outgoing-handler`.
+ *
+ * The worker creates one during `Client::init()` like the native
+ * transport.
+ */
+pub struct Transport {
+    /// Encoding used with collectors that support it.
+    encoding: WireEncoding,
+}
+
+impl Transport {
+    pub fn new(encoding: WireEncoding) -> Result<Self, String> {
+        Ok(Self { encoding })
+    }
+
+    /**
+     * Connectivity checks are not run on this target. Every request
+     * already blocks until its response arrives, so an unreachable
+     * collector shows up in `status()` after the first send instead.
+     * Always succeeds.
+     */
+    pub fn check(&self, _endpoint: &str) -> Result<(), String> {
+        Ok(())
+    }
+
+    /**
+     * POSTs an envelope (`HawkEvent` or `HawkTransaction`) to the
+     * collector endpoint of `route` as JSON, or in the configured binary encoding if
+     * the endpoint advertised it, and waits for the response.
+     *
+     * Best-effort: any error is printed to stderr and swallowed.
+     */
+    pub fn send(&self, route: &Route, event: &impl Serialize) {
+        let endpoint = route.endpoint.as_str();
+        let encoding = match self.encoding.feature() {
+            Some(feature) if status::supports(endpoint, feature) => self.encoding,
+            _ => WireEncoding::Json,
+        };
+        let result = encoding
+            .encode(event)
+            .map_err(|err| format!("failed to serialize event: {err}"))
+            .and_then(|body| post(endpoint, encoding.content_type(), &body));
+
+        match result {
+            Ok(response) if (200..300).contains(&response.status) => {
+                status::record_features(endpoint, response.features.as_deref());
+            }
+            Ok(Response {
+                status: 429,
+                retry_after,
+                ..
+            }) => {
+                status::record_rate_limit(route, retry_after);
+                status::record_transport_error(
+                    route,
+                    "Collector responded with HTTP 429 (rate limited)".into(),
+                );
+            }
+            Ok(Response {
+                status,
+                retry_after,
+                ..
+            }) => {
+                eprintln!("[Hawk] Collector responded with HTTP {status}");
+                status::record_transport_error(
+                    route,
+                    format!("Collector responded with HTTP {status}"),
+                );
+                if let Some(reason) = status::dormancy_reason(status) {
+                    status::record_dormancy(route, reason, retry_after);
+                }
+            }
+            Err(err) => {
+                eprintln!("[Hawk] Failed to send event: {err}");
+                status::record_transport_error(route, format!("Failed to send event: {err}"));
+            }
+        }
+    }
+}
+
+/**
+ * The parts of a collector response the transport acts on.
+ */
+struct Response {
+    status: u16,
+    /// `X-Hawk-Features` header.
+    features: Option<String>,
+    /// `Retry-After` header, if it holds a number of seconds.
+    retry_after: Option<Duration>,
+}
+
+/**
+ * Sends `body` as a POST request to `endpoint` and returns the response.
+ */
+fn post(endpoint: &str, content_type: &str, body: &[u8]) -> Result<Response, String> {
+    let (scheme, authority, path) = split_url(endpoint)?;
+
+    let headers = Fields::from_list(&[
+        ("content-type".to_string(), content_type.as_bytes().to_vec()),
+        (
+            PROTOCOL_HEADER.to_ascii_lowercase(),
+            PROTOCOL_VERSION.to_string().into_bytes(),
+        ),
+        (
+            SDK_HEADER.to_ascii_lowercase(),
+            CATCHER_VERSION.as_bytes().to_vec(),
+        ),
+    ])
+    .map_err(|err| format!("invalid header: {err:?}"))?;
+
+    let request = OutgoingRequest::new(headers);
+    request
+        .set_method(&Method::Post)
+        .map_err(|()| "invalid method")?;
+    request
+        .set_scheme(Some(&scheme))
+        .map_err(|()| "invalid scheme")?;
+    request
+        .set_authority(Some(authority))
+        .map_err(|()| "invalid authority")?;
+    request
+        .set_path_with_query(Some(path))
+        .map_err(|()| "invalid path")?;
+
+    let outgoing_body = request.body().map_err(|()| "request body already taken")?;
+    let response = outgoing_handler::handle(request, None).map_err(|err| format!("{err:?}"))?;
+
+    {
+        let stream = outgoing_body
+            .write()
+            .map_err(|()| "body stream already taken")?;
+        for chunk in body.chunks(MAX_WRITE_CHUNK) {
+            stream
+                .blocking_write_and_flush(chunk)
+                .map_err(|err| format!("writing body: {err:?}"))?;
+        }
+    }
+    OutgoingBody::finish(outgoing_body, None).map_err(|err| format!("{err:?}"))?;
+
+    response.subscribe().block();
+    match response.get() {
+        Some(Ok(Ok(response))) => {
+            let headers = response.headers();
+            let header = |name: &str| {
+                headers
+                    .get(name)
+                    .into_iter()
+                    .next()
+                    .and_then(|value| String::from_utf8(value).ok())
+            };
+            Ok(Response {
+                status: response.status(),
+                features: header(&FEATURES_HEADER.to_ascii_lowercase()),
+                retry_after: header("retry-after")
+                    .as_deref()
+                    .and_then(status::parse_retry_after),
+            })
+        }
+        Some(Ok(Err(err))) => Err(format!("{err:?}")),
+        Some(Err(())) | None => Err("response not available".to_string()),
+    }
+}
+
+/**
+ * Splits `https://host[:port]/path` into scheme, authority, and path.
+ */
+fn split_url(url: &str) -> Result<(Scheme, &str, &str), String> {
+    let (scheme, rest) = if let Some(rest) = url.strip_prefix("https://") {
+        (Scheme::Https, rest)
+    } else if let Some(rest) = url.strip_prefix("http://") {
+        (Scheme::Http, rest)
+    } else {
+        return Err(format!("unsupported endpoint URL: {url}"));
+    };
+
+    match rest.find('/') {
+        Some(index) => Ok((scheme, &rest[..index], &rest[index..])),
+        None => Ok((scheme, rest, "/")),
+    }
+}
@@ -5,33 +5,122 @@
  * Architecture overview:
  *
  * ```text
- *  ┌─────────────┐     bounded channel     ┌────────────────┐
- *  │  User code   │ ───── WorkerMsg ──────► │  Worker thread  │
- *  │  (any thread)│                         │  (single)       │
- *  └─────────────┘                         └───────┬────────┘
- *                                                  │
- *                                           Transport::send()
- *                                                  │
- *                                           ┌──────▼──────┐
- *                                           │  Collector   │
- *                                           └─────────────┘
+ *  ┌─────────────┐     bounded channel     ┌──────────────────────┐
+ *  │  User code   │ ───── WorkerMsg ──────► │  Symbolicator thread  │
+ *  │  (any thread)│                         │  (resolves frames)    │
+ *  └─────────────┘                         └──────────┬───────────┘
+ *                                                     │ internal channel
+ *                                           ┌─────────▼─────────┐
+ *                                           │  Worker thread     │
+ *                                           │  (serializes)      │
+ *                                           └─────────┬─────────┘
+ *                                                     │ one channel per destination
+ *                                           ┌─────────▼─────────┐
+ *                                           │  Sender threads    │
+ *                                           └─────────┬─────────┘
+ *                                                     │
+ *                                              Transport::send()
+ *                                                     │
+ *                                              ┌──────▼──────┐
+ *                                              │  Collector   │
+ *                                              └─────────────┘
  * ```
  *
+ * The two stages run concurrently: while the symbolicator resolves the
+ * deep backtrace of one event, the worker keeps sending the events
+ * prepared before it, and the other way around. Messages keep their order
+ * through both stages, so a `Flush` still comes after everything enqueued
+ * before it.
+ *
  * The channel carries `WorkerMsg` variants:
- * - `Event(HawkEvent)` — a serialized event ready to be POSTed.
+ * - `Event(Box<QueuedEvent>, Option<Backtrace>, QueueSlot)` — an event
+ *   serialized on the capturing thread, plus the raw (unresolved)
+ *   backtrace captured at the call site.
+ * - `Batch(Vec<QueuedEvent>, QueueSlot)` — related events sent together.
+ * - `Transaction(Box<HawkTransaction>, QueueSlot)` — a finished
+ *   transaction.
+ * - `Metrics(Box<HawkMetrics>, QueueSlot)` — custom metrics of one flush
+ *   interval.
  * - `Flush(Arc<FlushSignal>)` — a signal requesting the worker to notify
  *   the caller once all preceding events have been drained.
  *
- * The worker loop runs until the channel disconnects (i.e., all senders
- * are dropped), which happens when the `Client` is dropped.
+ * A payload's `QueueSlot` holds its place in the queue and its size
+ * against `Options::max_queue_bytes` through both stages, until the
+ * primary destination has sent it: the byte budget bounds every event
+ * not sent yet.
+ *
+ * Every payload is delivered to each destination (`Route`): the primary
+ * project and those of `Options::destinations`, with the envelope's token
+ * replaced by theirs. Each destination, and the shadow collector
+ * (`Options::shadow`) a share of events is mirrored to, has a sending
+ * thread of its own, fed by a bounded channel: a slow destination holds
+ * up neither the worker nor the others, and drops its own payloads when
+ * it falls behind. Errors, rate limits, and dormancy are tracked per
+ * destination, so one project's quota does not silence another sharing
+ * its collector.
+ *
+ * The stages run until the channel disconnects (i.e., all senders are
+ * dropped), which happens when the `Client` is dropped. While idle, the
+ * worker wakes up every metrics flush interval to send aggregated metrics.
+ *
+ * On wasm32 there are no threads to spawn (no-thread mode): the receiver
+ * is parked in a thread-local instead and drained by `Worker::drain()`,
+ * which runs both stages in turn:
+ * - browser (`wasm32-unknown-unknown`) — each enqueued event schedules a
+ *   drain on the microtask queue (`Worker::schedule_drain`);
+ * - WASI — each enqueued event is sent right away, on the capturing call.
+ *
+ * `flush()` drains synchronously on both.
  */
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+use std::cell::Cell;
+#[cfg(target_arch = "wasm32")]
+use std::cell::RefCell;
 use std::sync::{Arc, Condvar, Mutex};
+#[cfg(not(target_arch = "wasm32"))]
 use std::thread;
 
 use crossbeam_channel::Receiver;
+#[cfg(not(target_arch = "wasm32"))]
+use crossbeam_channel::{RecvTimeoutError, Sender};
+use serde::ser::{SerializeMap, Serializer};
+use serde::Serialize;
+use serde_json::Value;
 
-use super::http::Transport;
+use super::{json_size, Transport};
+use crate::client::{self, Shadow};
+#[cfg(not(target_arch = "wasm32"))]
+use crate::metrics::FLUSH_INTERVAL_MS;
+use crate::overhead;
+use crate::protocol::metrics::HawkMetrics;
+use crate::protocol::performance::HawkTransaction;
 use crate::protocol::types::HawkEvent;
+use crate::stacktrace::{self, Backtrace};
+use crate::status;
+
+/// The worker's state in no-thread mode: channel, destinations, shadow
+/// traffic, and transport.
+#[cfg(target_arch = "wasm32")]
+type InlineWorker = (Receiver<WorkerMsg>, Vec<Lane>, Option<Mirror>, Transport);
+
+/// Payloads waiting for a destination's thread; more are dropped. The
+/// primary destination never has more, as its payloads hold their place
+/// in the queue until sent.
+#[cfg(not(target_arch = "wasm32"))]
+const LANE_CAPACITY: usize = client::QUEUE_CAPACITY;
+
+#[cfg(target_arch = "wasm32")]
+thread_local! {
+    /// The worker's state in no-thread mode, set by `Worker::spawn`.
+    static INLINE_WORKER: RefCell<Option<InlineWorker>> =
+        const { RefCell::new(None) };
+}
+
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+thread_local! {
+    /// Whether a drain is already queued on the microtask queue.
+    static DRAIN_SCHEDULED: Cell<bool> = const { Cell::new(false) };
+}
 
 // ---------------------------------------------------------------------------
 // WorkerMsg — the messages sent through the bounded channel
@@ -43,19 +132,302 @@ use crate::protocol::types::HawkEvent;
  */
 pub enum WorkerMsg {
     /**
-     * A fully assembled `HawkEvent` envelope ready to be serialized and
-     * POSTed to the collector.
+     * An event, serialized on the capturing thread, ready to be POSTed to
+     * the collector.
+     *
+     * The optional `Backtrace` holds raw frame addresses only; the worker
+     * resolves symbols and fills `payload.backtrace` before sending, so
+     * the capturing thread never pays for DWARF lookups.
+     */
+    Event(Box<QueuedEvent>, Option<Backtrace>, QueueSlot),
+
+    /**
+     * Related events enqueued together (`capture_batch()`), taking a
+     * single slot of the queue. Their backtraces, if any, are resolved
+     * already.
      */
-    Event(HawkEvent),
+    Batch(Vec<QueuedEvent>, QueueSlot),
+
+    /**
+     * A finished transaction (performance monitoring), sent as is.
+     */
+    Transaction(Box<HawkTransaction>, QueueSlot),
+
+    /**
+     * Aggregated custom metrics, sent as is.
+     */
+    Metrics(Box<HawkMetrics>, QueueSlot),
 
     /**
      * A flush request. The worker signals `FlushSignal` once all messages
      * that were in the channel *before* this `Flush` message have been
-     * processed.
+     * processed. Not sent in no-thread mode, where `flush()` drains
+     * directly.
      */
+    #[cfg_attr(target_arch = "wasm32", allow(dead_code))]
     Flush(Arc<FlushSignal>),
 }
 
+/**
+ * An event as queued: its `HawkEvent` envelope serialized once, when
+ * enqueued, so that its size is known up front and the worker only has
+ * to send it.
+ */
+pub struct QueuedEvent {
+    /// The serialized envelope.
+    body: Value,
+
+    /// The event's ID, or its title without one: picks the events
+    /// mirrored to the shadow collector.
+    key: String,
+
+    /// ID of the persisted copy deleted once delivered, if any.
+    persisted: Option<String>,
+}
+
+impl QueuedEvent {
+    /**
+     * Serializes an event. `persisted` tells whether a copy of it was
+     * written to `Options::fatal_event_dir`. Returns `None`, reported, if
+     * the event cannot be serialized.
+     */
+    pub(crate) fn new(event: &HawkEvent, persisted: bool) -> Option<Self> {
+        let id = event
+            .payload
+            .addons
+            .as_ref()
+            .and_then(|addons| addons.event_id.clone());
+        Some(Self {
+            body: to_value(event)?,
+            key: id.clone().unwrap_or_else(|| event.payload.title.clone()),
+            persisted: id.filter(|_| persisted),
+        })
+    }
+
+    /**
+     * ID of the event's persisted copy, if any.
+     */
+    pub(crate) fn persisted(&self) -> Option<&str> {
+        self.persisted.as_deref()
+    }
+
+    /**
+     * Size in bytes of the event's JSON, counted against
+     * `Options::max_queue_bytes`.
+     */
+    pub(crate) fn size(&self) -> usize {
+        json_size(&self.body)
+    }
+}
+
+/**
+ * A message's place in the queue (`pending_events()`) and its bytes of
+ * the queue's byte budget, given back when the primary destination is
+ * done with it — sent, skipped, or dropped — or when the message is
+ * dropped on the way. Shared by the events of a batch, which take a
+ * single place.
+ */
+pub struct QueueSlot {
+    bytes: usize,
+}
+
+impl QueueSlot {
+    /**
+     * Takes a place in the queue, holding `bytes` reserved already with
+     * `status::reserve_queue_bytes()`.
+     */
+    pub(crate) fn new(bytes: usize) -> Self {
+        status::record_enqueued();
+        Self { bytes }
+    }
+}
+
+impl Drop for QueueSlot {
+    fn drop(&mut self) {
+        status::record_dequeued();
+        status::release_queue_bytes(self.bytes);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Route, Lane — where payloads are delivered
+// ---------------------------------------------------------------------------
+
+/**
+ * A destination payloads are delivered to: a collector endpoint and the
+ * integration token sent to it.
+ */
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Route {
+    /// Collector URL payloads are POSTed to.
+    pub endpoint: String,
+
+    /// Integration token of the destination project.
+    pub token: Arc<str>,
+}
+
+/**
+ * A serialized payload on its way to one destination.
+ */
+struct Payload {
+    /// The envelope, shared by every destination: each sends it with its
+    /// own token.
+    body: Arc<Value>,
+
+    /// ID of the persisted copy deleted once sent (primary only).
+    persisted: Option<String>,
+
+    /// Place in the queue, for the primary destination; given back when
+    /// the payload is dropped.
+    _slot: Option<Arc<QueueSlot>>,
+}
+
+impl Payload {
+    /**
+     * Sends the payload, unless the destination rate limits the client or
+     * put it to sleep, then deletes the persisted copy of its event.
+     */
+    fn send(self, route: &Route, transport: &Transport) {
+        let client = client::get_client();
+        if !status::is_paused(route, status::now_of(client)) {
+            let envelope = Addressed {
+                token: &route.token,
+                body: &self.body,
+            };
+            transport.send(route, &envelope);
+        }
+        if let (Some(id), Some(client)) = (self.persisted, client) {
+            client.remove_persisted(&id);
+        }
+    }
+}
+
+/**
+ * An envelope with its token replaced by the destination's.
+ */
+struct Addressed<'a> {
+    token: &'a str,
+    body: &'a Value,
+}
+
+impl Serialize for Addressed<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let Value::Object(fields) = self.body else {
+            return self.body.serialize(serializer);
+        };
+        let mut map = serializer.serialize_map(Some(fields.len()))?;
+        for (key, value) in fields {
+            match key.as_str() {
+                "token" => map.serialize_entry(key, self.token)?,
+                _ => map.serialize_entry(key, value)?,
+            }
+        }
+        map.end()
+    }
+}
+
+/**
+ * What a destination's thread is handed.
+ */
+#[cfg(not(target_arch = "wasm32"))]
+enum LaneMsg {
+    Payload(Box<Payload>),
+
+    /// Counted down once the payloads before it are sent.
+    Flush(Arc<FlushCountdown>),
+}
+
+/**
+ * A destination, and how payloads reach it: the channel of the thread
+ * sending to it, or, in no-thread mode, the worker's transport. Each
+ * destination having its own thread, a slow one holds up neither the
+ * worker nor the others.
+ */
+struct Lane {
+    #[cfg(target_arch = "wasm32")]
+    route: Route,
+
+    #[cfg(not(target_arch = "wasm32"))]
+    sender: Sender<LaneMsg>,
+}
+
+impl Lane {
+    /**
+     * Spawns the thread sending to `route`. It runs until the worker
+     * thread, holding the sender, exits.
+     */
+    #[cfg(not(target_arch = "wasm32"))]
+    fn spawn(route: Route, transport: Transport) -> Result<Self, String> {
+        let (sender, receiver) = crossbeam_channel::bounded::<LaneMsg>(LANE_CAPACITY);
+        thread::Builder::new()
+            .name("hawk-sender".into())
+            .spawn(move || {
+                let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+                    for msg in receiver.iter() {
+                        match msg {
+                            LaneMsg::Payload(payload) => payload.send(&route, &transport),
+                            LaneMsg::Flush(countdown) => countdown.count_down(),
+                        }
+                    }
+                }));
+                if result.is_err() {
+                    eprintln!(
+                        "[Hawk] hawk-sender thread for {} panicked — its payloads will be dropped",
+                        route.endpoint
+                    );
+                }
+            })
+            .map_err(|e| format!("Failed to spawn hawk-sender thread: {e}"))?;
+        Ok(Self { sender })
+    }
+
+    /**
+     * Hands a payload over to the destination's thread. Never waits: with
+     * the thread behind, the payload is dropped.
+     */
+    #[cfg_attr(not(target_arch = "wasm32"), allow(unused_variables))]
+    fn send(&self, payload: Payload, transport: &Transport) {
+        #[cfg(not(target_arch = "wasm32"))]
+        let _ = self.sender.try_send(LaneMsg::Payload(Box::new(payload)));
+
+        #[cfg(target_arch = "wasm32")]
+        payload.send(&self.route, transport);
+    }
+}
+
+/**
+ * The shadow collector (`Options::shadow`): a destination with the
+ * primary token, sent a share of events only.
+ */
+struct Mirror {
+    lane: Lane,
+
+    /// Fraction of events mirrored.
+    rate: f64,
+}
+
+/**
+ * A flush waiting on several destinations: the last one done signals it.
+ */
+#[cfg(not(target_arch = "wasm32"))]
+struct FlushCountdown {
+    remaining: std::sync::atomic::AtomicUsize,
+    signal: Arc<FlushSignal>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl FlushCountdown {
+    fn count_down(&self) {
+        if self
+            .remaining
+            .fetch_sub(1, std::sync::atomic::Ordering::SeqCst)
+            == 1
+        {
+            self.signal.notify();
+        }
+    }
+}
+
 // ---------------------------------------------------------------------------
 // FlushSignal — condvar-based notification for flush completion
 // ---------------------------------------------------------------------------
@@ -78,6 +450,7 @@ pub struct FlushSignal {
     condvar: Condvar,
 }
 
+#[cfg_attr(target_arch = "wasm32", allow(dead_code))]
 impl FlushSignal {
     /**
      * Creates a new `FlushSignal` in the "not yet flushed" state.
@@ -131,71 +504,537 @@ impl FlushSignal {
 // ---------------------------------------------------------------------------
 
 /**
- * Handle to the background worker thread.
+ * Handle to the background threads.
  *
- * The worker is spawned during `Client::new()` and runs until the channel
- * disconnects (all senders dropped). It processes messages sequentially:
- * - `Event` → serialize + HTTP POST via `Transport`.
- * - `Flush` → signal the requester that all prior events are drained.
+ * They are spawned during `Client::new()` and run until the channel
+ * disconnects (all senders dropped). Messages are processed sequentially:
+ * - `Event` → resolve backtrace symbols (symbolicator), then serialize
+ *   (worker) and HTTP POST via `Transport` (one thread per destination).
+ * - `Transaction` / `Metrics` → serialize + HTTP POST via `Transport`.
+ * - `Flush` → signal the requester once every destination has sent all
+ *   prior payloads.
  */
 pub struct Worker;
 
 impl Worker {
     /**
-     * Spawns the background worker thread.
+     * Spawns the symbolicator and worker threads, connected by a channel
+     * as bounded as the queue, and a sending thread per destination. A
+     * payload keeps its place in the queue until the primary destination
+     * has sent it: when sending falls behind, the queue fills up.
      *
-     * The thread runs until the channel disconnects (all senders dropped).
-     * It is fire-and-forget — no join handle is stored because the
+     * The threads run until the channel disconnects (all senders dropped).
+     * They are fire-and-forget — no join handle is stored because the
      * `Guard::drop()` → `flush()` path ensures all events are drained
      * before the process exits.
      *
      * # Arguments
      * * `receiver` — The receiving end of the bounded channel.
-     * * `endpoint` — The collector URL to POST events to.
+     * * `routes` — The destinations to deliver to, the primary one first.
+     * * `shadow` — Where to mirror a share of events, if anywhere.
      * * `transport` — The HTTP transport used for sending.
      */
+    #[cfg(not(target_arch = "wasm32"))]
     pub fn spawn(
         receiver: Receiver<WorkerMsg>,
-        endpoint: String,
+        routes: Vec<Route>,
+        shadow: Option<Shadow>,
         transport: Transport,
     ) -> Result<(), String> {
+        let lanes = routes
+            .iter()
+            .map(|route| Lane::spawn(route.clone(), transport.clone()))
+            .collect::<Result<Vec<_>, _>>()?;
+        let mirror = shadow.and_then(|shadow| {
+            let route = Route {
+                endpoint: shadow.endpoint,
+                token: routes[0].token.clone(),
+            };
+            Lane::spawn(route, transport.clone())
+                .map_err(|e| eprintln!("[Hawk] {e} — events will not be mirrored"))
+                .ok()
+                .map(|lane| Mirror {
+                    lane,
+                    rate: shadow.rate,
+                })
+        });
+
+        status::set_worker_alive(true);
+        Self::spawn_stages(receiver, routes, lanes, mirror, transport, Self::prepare)
+    }
+
+    /**
+     * Spawns the two stages: the symbolicator, running `prepare` on each
+     * message, and the worker, handing the prepared ones to `lanes`.
+     */
+    #[cfg(not(target_arch = "wasm32"))]
+    fn spawn_stages(
+        receiver: Receiver<WorkerMsg>,
+        routes: Vec<Route>,
+        lanes: Vec<Lane>,
+        mirror: Option<Mirror>,
+        transport: Transport,
+        prepare: impl Fn(WorkerMsg, &[Route]) -> Option<WorkerMsg> + Send + 'static,
+    ) -> Result<(), String> {
+        let (prepared_sender, prepared) = crossbeam_channel::bounded(client::QUEUE_CAPACITY);
+        Self::spawn_stage("hawk-worker", move || {
+            Self::run_loop(&prepared, &lanes, mirror.as_ref(), &transport);
+        })?;
+        Self::spawn_stage("hawk-symbolicator", move || {
+            for msg in receiver.iter() {
+                let Some(msg) = prepare(msg, &routes) else {
+                    continue;
+                };
+                if prepared_sender.send(msg).is_err() {
+                    break;
+                }
+            }
+        })
+    }
+
+    /**
+     * Spawns one stage of the pipeline. A panic inside it (e.g. a bug in
+     * the HTTP client or the symbolizer) is caught and logged, and marks
+     * the worker as dead in `status()`, rather than killing the thread
+     * silently.
+     */
+    #[cfg(not(target_arch = "wasm32"))]
+    fn spawn_stage(name: &str, stage: impl FnOnce() + Send + 'static) -> Result<(), String> {
+        let thread_name = name.to_string();
         thread::Builder::new()
-            .name("hawk-worker".into())
+            .name(thread_name.clone())
             .spawn(move || {
-                /*
-                 * Wrap the event loop in catch_unwind so a panic inside
-                 * transport.send() (e.g. reqwest bug) doesn't kill the
-                 * thread silently. We log and exit instead.
-                 */
-                let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                    Self::run_loop(&receiver, &endpoint, &transport);
-                }));
+                let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(stage));
 
+                status::set_worker_alive(false);
                 if result.is_err() {
-                    eprintln!("[Hawk] Worker thread panicked — events will be dropped");
+                    eprintln!("[Hawk] {thread_name} thread panicked — events will be dropped");
                 }
             })
             .map(|_| ())
-            .map_err(|e| format!("Failed to spawn worker thread: {e}"))
+            .map_err(|e| {
+                status::set_worker_alive(false);
+                format!("Failed to spawn {name} thread: {e}")
+            })
     }
 
     /**
-     * The main event loop of the worker thread.
+     * No-thread variant of `spawn()`: stores the worker's state for
+     * `drain()` instead of starting a thread.
+     */
+    #[cfg(target_arch = "wasm32")]
+    pub fn spawn(
+        receiver: Receiver<WorkerMsg>,
+        routes: Vec<Route>,
+        shadow: Option<Shadow>,
+        transport: Transport,
+    ) -> Result<(), String> {
+        let mirror = shadow.map(|shadow| Mirror {
+            lane: Lane {
+                route: Route {
+                    endpoint: shadow.endpoint,
+                    token: routes[0].token.clone(),
+                },
+            },
+            rate: shadow.rate,
+        });
+        let lanes = routes.into_iter().map(|route| Lane { route }).collect();
+        INLINE_WORKER
+            .with(|worker| *worker.borrow_mut() = Some((receiver, lanes, mirror, transport)));
+        status::set_worker_alive(true);
+        Ok(())
+    }
+
+    /**
+     * Queues a `drain()` on the microtask queue, unless one is pending.
      *
-     * Blocks on `receiver.recv()` waiting for the next message.
-     * When the channel disconnects (all senders dropped), `recv()` returns
-     * `Err(RecvError)` and the loop exits cleanly.
-     */
-    fn run_loop(receiver: &Receiver<WorkerMsg>, endpoint: &str, transport: &Transport) {
-        while let Ok(msg) = receiver.recv() {
-            match msg {
-                WorkerMsg::Event(event) => {
-                    transport.send(endpoint, &event);
+     * Called after each enqueued event: events captured in one task are
+     * sent together, right after it yields.
+     */
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    pub fn schedule_drain() {
+        if DRAIN_SCHEDULED.with(|scheduled| scheduled.replace(true)) {
+            return;
+        }
+
+        wasm_bindgen_futures::spawn_local(async {
+            DRAIN_SCHEDULED.with(|scheduled| scheduled.set(false));
+            Self::drain();
+        });
+    }
+
+    /**
+     * Processes every message currently in the channel, without blocking.
+     * In the browser, requests are started before this returns and
+     * responses arrive later; on WASI, each request completes in turn.
+     */
+    #[cfg(target_arch = "wasm32")]
+    pub fn drain() {
+        INLINE_WORKER.with(|worker| {
+            if let Some((receiver, lanes, mirror, transport)) = worker.borrow().as_ref() {
+                let routes: Vec<Route> = lanes.iter().map(|lane| lane.route.clone()).collect();
+                while let Ok(msg) = receiver.try_recv() {
+                    if let Some(msg) = Self::prepare(msg, &routes) {
+                        Self::process(msg, lanes, mirror.as_ref(), transport);
+                    }
+                }
+            }
+        });
+    }
+
+    /**
+     * The main event loop of the worker thread, fed prepared messages by
+     * the symbolicator.
+     *
+     * Blocks on `receiver.recv_timeout()` waiting for the next message;
+     * on timeout, asks the client to enqueue metrics that are due. When
+     * the channel disconnects (all senders dropped), the loop exits
+     * cleanly.
+     */
+    #[cfg(not(target_arch = "wasm32"))]
+    fn run_loop(
+        receiver: &Receiver<WorkerMsg>,
+        lanes: &[Lane],
+        mirror: Option<&Mirror>,
+        transport: &Transport,
+    ) {
+        let interval = std::time::Duration::from_millis(FLUSH_INTERVAL_MS);
+
+        loop {
+            match receiver.recv_timeout(interval) {
+                Ok(msg) => Self::process(msg, lanes, mirror, transport),
+                Err(RecvTimeoutError::Timeout) => {
+                    if let Some(client) = client::get_client() {
+                        client.flush_metrics(false);
+                    }
+                }
+                Err(RecvTimeoutError::Disconnected) => break,
+            }
+        }
+    }
+
+    /**
+     * First stage: takes a message off the queue and resolves the
+     * backtrace of an event. Returns `None` for a payload dropped because
+     * every destination in `routes` rate limits the client or put it to
+     * sleep.
+     */
+    fn prepare(msg: WorkerMsg, routes: &[Route]) -> Option<WorkerMsg> {
+        /*
+         * While every collector rate limits the client or puts it to sleep,
+         * payloads are dropped unsent — without resolving backtraces for
+         * nothing.
+         */
+        if !matches!(msg, WorkerMsg::Flush(_)) {
+            let now = status::now_of(client::get_client());
+            if routes.iter().all(|route| status::is_paused(route, now)) {
+                return None;
+            }
+        }
+
+        match msg {
+            WorkerMsg::Event(mut event, Some(bt), slot) => {
+                let _timer = overhead::Timer::symbolication();
+                stacktrace::resolve_into_json(&mut event.body["payload"], &bt);
+                Some(WorkerMsg::Event(event, None, slot))
+            }
+            msg => Some(msg),
+        }
+    }
+
+    /**
+     * Second stage: hands a prepared event, a transaction, or metrics to
+     * every destination, or passes a flush on to them.
+     */
+    fn process(msg: WorkerMsg, lanes: &[Lane], mirror: Option<&Mirror>, transport: &Transport) {
+        match msg {
+            WorkerMsg::Event(event, _, slot) => {
+                Self::deliver_event(*event, &Arc::new(slot), lanes, mirror, transport);
+            }
+            WorkerMsg::Batch(events, slot) => {
+                let slot = Arc::new(slot);
+                for event in events {
+                    Self::deliver_event(event, &slot, lanes, mirror, transport);
+                }
+            }
+            WorkerMsg::Transaction(transaction, slot) => {
+                if let Some(body) = to_value(&transaction) {
+                    Self::deliver(Arc::new(body), None, Arc::new(slot), lanes, transport);
                 }
-                WorkerMsg::Flush(signal) => {
-                    signal.notify();
+            }
+            WorkerMsg::Metrics(metrics, slot) => {
+                if let Some(body) = to_value(&metrics) {
+                    Self::deliver(Arc::new(body), None, Arc::new(slot), lanes, transport);
                 }
             }
+            WorkerMsg::Flush(signal) => Self::flush(signal, lanes),
+        }
+    }
+
+    /**
+     * Hands an event to every destination and, if sampled, to the shadow
+     * collector. The primary destination deletes its persisted copy once
+     * sent.
+     */
+    fn deliver_event(
+        event: QueuedEvent,
+        slot: &Arc<QueueSlot>,
+        lanes: &[Lane],
+        mirror: Option<&Mirror>,
+        transport: &Transport,
+    ) {
+        let body = Arc::new(event.body);
+        if let Some(mirror) = mirror.filter(|mirror| is_mirrored(&event.key, mirror.rate)) {
+            let payload = Payload {
+                body: Arc::clone(&body),
+                persisted: None,
+                _slot: None,
+            };
+            mirror.lane.send(payload, transport);
+        }
+        Self::deliver(body, event.persisted, Arc::clone(slot), lanes, transport);
+    }
+
+    /**
+     * Hands a serialized envelope to every destination, which sends it
+     * with its own token. The primary one gets `persisted` and `slot`.
+     */
+    fn deliver(
+        body: Arc<Value>,
+        persisted: Option<String>,
+        slot: Arc<QueueSlot>,
+        lanes: &[Lane],
+        transport: &Transport,
+    ) {
+        let Some((primary, others)) = lanes.split_first() else {
+            return;
+        };
+        for lane in others {
+            let payload = Payload {
+                body: Arc::clone(&body),
+                persisted: None,
+                _slot: None,
+            };
+            lane.send(payload, transport);
+        }
+        let payload = Payload {
+            body,
+            persisted,
+            _slot: Some(slot),
+        };
+        primary.send(payload, transport);
+    }
+
+    /**
+     * Signals a flush once every destination has sent the payloads handed
+     * to it before. Waits for room in their channels, unlike payloads.
+     */
+    #[cfg(not(target_arch = "wasm32"))]
+    fn flush(signal: Arc<FlushSignal>, lanes: &[Lane]) {
+        let countdown = Arc::new(FlushCountdown {
+            remaining: std::sync::atomic::AtomicUsize::new(lanes.len()),
+            signal,
+        });
+        for lane in lanes {
+            if lane
+                .sender
+                .send(LaneMsg::Flush(Arc::clone(&countdown)))
+                .is_err()
+            {
+                countdown.count_down();
+            }
+        }
+    }
+
+    /**
+     * No-thread variant of `flush()`: everything before it was sent
+     * already.
+     */
+    #[cfg(target_arch = "wasm32")]
+    fn flush(signal: Arc<FlushSignal>, _lanes: &[Lane]) {
+        signal.notify();
+    }
+}
+
+/**
+ * Serializes an envelope for the destinations, reporting failures.
+ */
+fn to_value(envelope: &impl Serialize) -> Option<Value> {
+    serde_json::to_value(envelope)
+        .map_err(|err| eprintln!("[Hawk] Failed to serialize payload: {err}"))
+        .ok()
+}
+
+/**
+ * Whether an event is mirrored to the shadow collector: a stable hash
+ * (FNV-1a) of its ID, or of its title without one, below `rate`.
+ */
+fn is_mirrored(key: &str, rate: f64) -> bool {
+    let hash = key.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    (hash % 10_000) < (rate * 10_000.0) as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::types::{Addons, EventData};
+
+    /**
+     * Verifies that the share of mirrored events follows the rate and
+     * that the choice is stable for a given event.
+     */
+    #[test]
+    fn test_is_mirrored() {
+        let keys: Vec<String> = (0..1000)
+            .map(|index| {
+                let mut payload = EventData::new("boom");
+                payload.addons = Some(Addons {
+                    event_id: Some(format!("event-{index}")),
+                    ..Addons::default()
+                });
+                let event = HawkEvent::new("token", payload);
+                QueuedEvent::new(&event, false).unwrap().key
+            })
+            .collect();
+        assert_eq!(keys[7], "event-7");
+
+        let mirrored = keys.iter().filter(|key| is_mirrored(key, 0.25)).count();
+        assert!(
+            (150..350).contains(&mirrored),
+            "{mirrored} of 1000 mirrored"
+        );
+        assert!(keys
+            .iter()
+            .all(|key| is_mirrored(key, 0.25) == is_mirrored(key, 0.25)));
+        assert!(keys.iter().all(|key| is_mirrored(key, 1.0)));
+        assert!(!keys.iter().any(|key| is_mirrored(key, 0.0)));
+    }
+
+    /**
+     * Verifies that an event prepared already is delivered while the
+     * symbolicator is still resolving the backtrace of the next one.
+     */
+    #[test]
+    fn test_slow_symbolication() {
+        let (sender, receiver) = crossbeam_channel::bounded(10);
+        let (lane_sender, lane_receiver) = crossbeam_channel::bounded(10);
+        let route = Route {
+            endpoint: "http://127.0.0.1:9/slow-symbolication".into(),
+            token: "primary".into(),
+        };
+        let (resolve, resolved) = crossbeam_channel::bounded::<()>(0);
+        Worker::spawn_stages(
+            receiver,
+            vec![route],
+            vec![Lane {
+                sender: lane_sender,
+            }],
+            None,
+            Transport::new(Default::default()).unwrap(),
+            move |msg, routes| {
+                if matches!(msg, WorkerMsg::Event(_, Some(_), _)) {
+                    let _ = resolved.recv();
+                }
+                Worker::prepare(msg, routes)
+            },
+        )
+        .unwrap();
+
+        let event = |title: &str, backtrace: Option<Backtrace>| {
+            let event = HawkEvent::new("primary", EventData::new(title));
+            let event = QueuedEvent::new(&event, false).unwrap();
+            WorkerMsg::Event(Box::new(event), backtrace, QueueSlot::new(0))
+        };
+        let title = |msg: LaneMsg| match msg {
+            LaneMsg::Payload(payload) => payload.body["payload"]["title"].clone(),
+            LaneMsg::Flush(_) => Value::Null,
+        };
+        let timeout = std::time::Duration::from_secs(5);
+
+        sender.send(event("shallow", None)).unwrap();
+        sender
+            .send(event("deep", Some(Backtrace::new_unresolved())))
+            .unwrap();
+        assert_eq!(
+            title(lane_receiver.recv_timeout(timeout).unwrap()),
+            "shallow"
+        );
+        assert!(lane_receiver.try_recv().is_err());
+
+        resolve.send(()).unwrap();
+        assert_eq!(title(lane_receiver.recv_timeout(timeout).unwrap()), "deep");
+    }
+
+    /**
+     * Verifies that each destination sends the payloads with its own token,
+     * that one falling behind drops payloads instead of holding up the
+     * others, and that a payload keeps its place in the queue and its
+     * bytes of the byte budget through both stages, until the primary
+     * destination is done with it.
+     */
+    #[test]
+    fn test_lanes() {
+        let lane = |endpoint: &str, token: &str, capacity| {
+            let (sender, receiver) = crossbeam_channel::bounded(capacity);
+            let route = Route {
+                endpoint: endpoint.into(),
+                token: token.into(),
+            };
+            (Lane { sender }, receiver, route)
+        };
+        let (primary, primary_receiver, primary_route) = lane("http://127.0.0.1:9/", "primary", 10);
+        let (slow, slow_receiver, slow_route) = lane("http://127.0.0.1:9/", "slow", 1);
+        let lanes = [primary, slow];
+        let transport = Transport::new(Default::default()).unwrap();
+
+        let routes = [primary_route.clone(), slow_route.clone()];
+        let pending = status::pending_events();
+        let queued = status::queue_bytes();
+        let mut reserved = 0;
+        for index in 0..3 {
+            let event = HawkEvent::new("primary", EventData::new(format!("boom {index}")));
+            let event = QueuedEvent::new(&event, false).unwrap();
+            let bytes = event.size();
+            assert!(status::reserve_queue_bytes(bytes, usize::MAX));
+            reserved += bytes;
+
+            let backtrace = (index == 0).then(Backtrace::new_unresolved);
+            let msg = WorkerMsg::Event(Box::new(event), backtrace, QueueSlot::new(bytes));
+            let msg = Worker::prepare(msg, &routes).unwrap();
+            assert!(status::queue_bytes() >= queued + reserved);
+            Worker::process(msg, &lanes, None, &transport);
+        }
+
+        let token = |msg: &LaneMsg, route: &Route| match msg {
+            LaneMsg::Payload(payload) => {
+                let envelope = Addressed {
+                    token: &route.token,
+                    body: &payload.body,
+                };
+                serde_json::to_value(&envelope).unwrap()["token"].clone()
+            }
+            LaneMsg::Flush(_) => Value::Null,
+        };
+        let slow: Vec<LaneMsg> = slow_receiver.try_iter().collect();
+        assert_eq!(slow.len(), 1);
+        assert_eq!(token(&slow[0], &slow_route), "slow");
+        drop(slow);
+
+        let primary: Vec<LaneMsg> = primary_receiver.try_iter().collect();
+        assert_eq!(primary.len(), 3);
+        assert!(primary
+            .iter()
+            .all(|msg| token(msg, &primary_route) == "primary"));
+        if let LaneMsg::Payload(payload) = &primary[0] {
+            let frames = &payload.body["payload"]["backtrace"];
+            assert_eq!(frames.is_array(), cfg!(feature = "backtrace"));
         }
+        assert!(status::pending_events() >= pending + 3);
+        assert!(status::queue_bytes() >= queued + reserved);
+        drop(primary);
+        assert!(status::pending_events() < pending + 3);
+        assert!(status::queue_bytes() < queued + reserved);
     }
 }
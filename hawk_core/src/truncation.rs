@@ -0,0 +1,356 @@
+/*!
+ * String truncation — a size cap on every string of an event.
+ *
+ * A multi-megabyte response body in a context value or a huge log
+ * message in a breadcrumb would otherwise be sent as is. Applied to every
+ * event right before it is enqueued, after scrubbing (so that patterns
+ * see whole values): strings longer than `Options::max_string_length`
+ * characters are cut and end with `TRUNCATION_MARKER`.
+ *
+ * Covers the title, every string in the context (keys excluded), and
+ * breadcrumb messages, categories, and data. Backtrace frames are
+ * bounded by `max_backtrace_frames` instead.
+ *
+ * # Shape limits
+ *
+ * The context and breadcrumb data are also capped in shape as soon as
+ * the event is assembled, before `before_send`, sanitizing, and
+ * scrubbing, so that a pathological value — a recursive structure
+ * serialized a thousand levels deep, an array of a million items — costs
+ * neither those passes nor serialization much:
+ *
+ * - arrays and objects nested deeper than `Options::max_context_depth`
+ *   are replaced with `TRUNCATION_MARKER`;
+ * - past `Options::max_context_nodes` values, breadth first, the rest of
+ *   an array is replaced with one `TRUNCATION_MARKER` item, and the rest
+ *   of an object with a `TRUNCATION_MARKER` field holding the number of
+ *   fields removed.
+ *
+ * Breadcrumb data is capped when recorded, and scoped context
+ * (`with_context()`) is copied into events capped already, so that
+ * neither is ever copied whole. The walks are iterative, and removed
+ * values are freed iteratively too, so any depth is handled without
+ * recursion. `serde_json::Value` is a tree:
+ * a cyclic structure can only reach the SDK already unrolled, which the
+ * depth limit then cuts.
+ */
+
+use std::collections::VecDeque;
+
+use serde_json::{json, Map, Value};
+
+use crate::protocol::types::EventData;
+
+/// Default for `Options::max_string_length`.
+pub const DEFAULT_MAX_STRING_LENGTH: usize = 8192;
+
+/// Default for `Options::max_context_depth`.
+pub const DEFAULT_MAX_CONTEXT_DEPTH: usize = 16;
+
+/// Default for `Options::max_context_nodes`.
+pub const DEFAULT_MAX_CONTEXT_NODES: usize = 5000;
+
+/// Appended to truncated strings.
+pub const TRUNCATION_MARKER: &str = "...[truncated]";
+
+/**
+ * Truncates every string of the event to `max_length` characters, not
+ * counting the marker.
+ */
+pub(crate) fn truncate_event(event: &mut EventData, max_length: usize) {
+    truncate_string(&mut event.title, max_length);
+
+    if let Some(context) = event.context.as_mut() {
+        truncate_value(context, max_length);
+    }
+
+    for breadcrumb in event.breadcrumbs.iter_mut().flatten() {
+        for text in [&mut breadcrumb.message, &mut breadcrumb.category]
+            .into_iter()
+            .flatten()
+        {
+            truncate_string(text, max_length);
+        }
+        if let Some(data) = breadcrumb.data.as_mut() {
+            truncate_value(data, max_length);
+        }
+    }
+}
+
+/**
+ * Caps the depth and number of values of the context and of breadcrumb
+ * data, each on its own; `0` disables either limit.
+ */
+pub(crate) fn limit_event(event: &mut EventData, max_depth: usize, max_nodes: usize) {
+    if let Some(context) = event.context.as_mut() {
+        limit_value(context, max_depth, max_nodes);
+    }
+    for breadcrumb in event.breadcrumbs.iter_mut().flatten() {
+        if let Some(data) = breadcrumb.data.as_mut() {
+            limit_value(data, max_depth, max_nodes);
+        }
+    }
+}
+
+/**
+ * Caps the depth and number of values of `root`, as `limit_event()`.
+ */
+pub(crate) fn limit_value(root: &mut Value, max_depth: usize, max_nodes: usize) {
+    let (max_depth, max_nodes) = limits(max_depth, max_nodes);
+
+    let mut nodes = 0;
+    let mut removed = Vec::new();
+    let mut queue = VecDeque::from([(root, 0)]);
+
+    while let Some((value, depth)) = queue.pop_front() {
+        let children = match value {
+            Value::Array(items) => items.len(),
+            Value::Object(fields) => fields.len(),
+            _ => 0,
+        };
+        if children == 0 {
+            continue;
+        }
+        if depth >= max_depth {
+            removed.push(std::mem::replace(
+                value,
+                Value::String(TRUNCATION_MARKER.into()),
+            ));
+            continue;
+        }
+
+        let kept = children.min(max_nodes - nodes);
+        nodes += kept;
+        match value {
+            Value::Array(items) => {
+                if kept < children {
+                    removed.extend(items.drain(kept..));
+                    items.push(Value::String(TRUNCATION_MARKER.into()));
+                }
+                queue.extend(items.iter_mut().map(|item| (item, depth + 1)));
+            }
+            Value::Object(fields) => {
+                if kept < children {
+                    let extra: Vec<String> = fields.keys().skip(kept).cloned().collect();
+                    removed.extend(extra.iter().filter_map(|key| fields.remove(key)));
+                    fields.insert(TRUNCATION_MARKER.into(), json!(children - kept));
+                }
+                queue.extend(fields.values_mut().map(|field| (field, depth + 1)));
+            }
+            _ => {}
+        }
+    }
+
+    drop_iteratively(removed);
+}
+
+/**
+ * Copies `root` within the same limits, without recursing into it: deep
+ * or wide values borrowed from elsewhere, such as scoped context, are
+ * never copied whole. Values past the node budget are counted depth
+ * first, rather than breadth first as by `limit_value()`.
+ */
+pub(crate) fn clone_limited(root: &Value, max_depth: usize, max_nodes: usize) -> Value {
+    enum Frame<'a> {
+        Array(std::slice::Iter<'a, Value>, Vec<Value>),
+        Object(serde_json::map::Iter<'a>, Map<String, Value>, String),
+    }
+
+    let (max_depth, max_nodes) = limits(max_depth, max_nodes);
+    let mut nodes = 0;
+    let mut stack: Vec<Frame> = Vec::new();
+    let mut pending = root;
+
+    loop {
+        /*
+         * Copy the pending value, or open it and copy its children first.
+         */
+        let mut value = match pending {
+            Value::Array(items) if !items.is_empty() && stack.len() < max_depth => {
+                stack.push(Frame::Array(items.iter(), Vec::with_capacity(items.len())));
+                None
+            }
+            Value::Object(fields) if !fields.is_empty() && stack.len() < max_depth => {
+                stack.push(Frame::Object(fields.iter(), Map::new(), String::new()));
+                None
+            }
+            Value::Array(items) if !items.is_empty() => {
+                Some(Value::String(TRUNCATION_MARKER.into()))
+            }
+            Value::Object(fields) if !fields.is_empty() => {
+                Some(Value::String(TRUNCATION_MARKER.into()))
+            }
+            value => Some(value.clone()),
+        };
+
+        /*
+         * Store it in its parent, closing the containers it completes,
+         * until one has a child left to copy.
+         */
+        pending = loop {
+            let Some(frame) = stack.last_mut() else {
+                return value.unwrap_or_default();
+            };
+            let full = nodes >= max_nodes;
+            let child = match frame {
+                Frame::Array(items, copy) => {
+                    copy.extend(value.take());
+                    if full && items.len() > 0 {
+                        *items = [].iter();
+                        copy.push(Value::String(TRUNCATION_MARKER.into()));
+                    }
+                    items.next()
+                }
+                Frame::Object(fields, copy, key) => {
+                    if let Some(value) = value.take() {
+                        copy.insert(std::mem::take(key), value);
+                    }
+                    if full && fields.len() > 0 {
+                        let rest = fields.len();
+                        for _ in fields.by_ref() {}
+                        copy.insert(TRUNCATION_MARKER.into(), json!(rest));
+                    }
+                    fields.next().map(|(name, field)| {
+                        key.clone_from(name);
+                        field
+                    })
+                }
+            };
+            match child {
+                Some(child) => {
+                    nodes += 1;
+                    break child;
+                }
+                None => {
+                    value = stack.pop().map(|frame| match frame {
+                        Frame::Array(_, copy) => Value::Array(copy),
+                        Frame::Object(_, copy, _) => Value::Object(copy),
+                    });
+                }
+            }
+        };
+    }
+}
+
+/**
+ * The limits to apply, `0` standing for none.
+ */
+fn limits(max_depth: usize, max_nodes: usize) -> (usize, usize) {
+    let none = |limit| if limit == 0 { usize::MAX } else { limit };
+    (none(max_depth), none(max_nodes))
+}
+
+/**
+ * Frees values without recursing into them, however deep.
+ */
+fn drop_iteratively(mut values: Vec<Value>) {
+    while let Some(value) = values.pop() {
+        match value {
+            Value::Array(items) => values.extend(items),
+            Value::Object(fields) => values.extend(fields.into_iter().map(|(_, field)| field)),
+            _ => {}
+        }
+    }
+}
+
+fn truncate_value(value: &mut Value, max_length: usize) {
+    match value {
+        Value::String(text) => truncate_string(text, max_length),
+        Value::Array(items) => items
+            .iter_mut()
+            .for_each(|item| truncate_value(item, max_length)),
+        Value::Object(fields) => fields
+            .values_mut()
+            .for_each(|field| truncate_value(field, max_length)),
+        _ => {}
+    }
+}
+
+fn truncate_string(text: &mut String, max_length: usize) {
+    if let Some((cut, _)) = text.char_indices().nth(max_length) {
+        text.truncate(cut);
+        text.push_str(TRUNCATION_MARKER);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::protocol::types::Breadcrumb;
+    use serde_json::json;
+
+    /**
+     * Verifies that long strings are cut on character boundaries
+     * everywhere in the event, and that short ones are left alone.
+     */
+    #[test]
+    fn test_truncate_event() {
+        let mut event = EventData::new("déjà vu");
+        event.context = Some(json!({ "body": ["abcdef", 42], "short": "abc" }));
+        event.breadcrumbs = Some(vec![Breadcrumb {
+            data: Some(json!({ "sql": "SELECT 1" })),
+            ..Breadcrumb::at(0, "abcd")
+        }]);
+
+        truncate_event(&mut event, 4);
+
+        assert_eq!(event.title, format!("déjà{TRUNCATION_MARKER}"));
+        assert_eq!(
+            event.context.unwrap(),
+            json!({ "body": [format!("abcd{TRUNCATION_MARKER}"), 42], "short": "abc" })
+        );
+        let breadcrumb = &event.breadcrumbs.unwrap()[0];
+        assert_eq!(breadcrumb.message.as_deref(), Some("abcd"));
+        assert_eq!(
+            breadcrumb.data,
+            Some(json!({ "sql": format!("SELE{TRUNCATION_MARKER}") }))
+        );
+    }
+
+    /**
+     * Verifies that deep values are cut at the depth limit, that the rest
+     * past the node budget is replaced breadth first, and that a value
+     * too deep to recurse into is handled, capped in place or copied.
+     */
+    #[test]
+    fn test_limit_event() {
+        let mut event = EventData::new("boom");
+        event.context = Some(json!({ "a": { "b": { "c": 1 } }, "list": [1, 2, 3, 4], "x": 0 }));
+        limit_event(&mut event, 2, 0);
+        assert_eq!(
+            event.context.as_ref().unwrap(),
+            &json!({ "a": { "b": TRUNCATION_MARKER }, "list": [1, 2, 3, 4], "x": 0 })
+        );
+
+        limit_event(&mut event, 0, 5);
+        assert_eq!(
+            event.context.unwrap(),
+            json!({ "a": { "b": TRUNCATION_MARKER }, "list": [1, TRUNCATION_MARKER], "x": 0 })
+        );
+
+        let mut deep = Value::Null;
+        for _ in 0..1_000_000 {
+            deep = Value::Array(vec![deep]);
+        }
+        let mut event = EventData::new("boom");
+        event.context = Some(deep);
+        limit_event(&mut event, 3, 0);
+        assert_eq!(event.context.unwrap(), json!([[[TRUNCATION_MARKER]]]));
+
+        let mut deep = Value::Null;
+        for _ in 0..1_000_000 {
+            deep = Value::Array(vec![deep]);
+        }
+        let mut wide = json!({ "list": [1, 2, 3, 4] });
+        wide["deep"] = deep;
+        assert_eq!(
+            clone_limited(&wide, 3, 0),
+            json!({ "deep": [[TRUNCATION_MARKER]], "list": [1, 2, 3, 4] })
+        );
+        assert_eq!(
+            clone_limited(&wide, 0, 4),
+            json!({ "deep": [[[[TRUNCATION_MARKER]]]], TRUNCATION_MARKER: 1 })
+        );
+        limit_value(&mut { wide }, 1, 0);
+    }
+}
@@ -0,0 +1,118 @@
+/*!
+ * Allocation failure tracking for out-of-memory reports.
+ *
+ * When an allocation fails, std prints `memory allocation of N bytes
+ * failed` and aborts — the crash handler sees a bare SIGABRT. Hooking the
+ * alloc error path directly (`std::alloc::set_alloc_error_hook`) is
+ * nightly-only, so instead `HawkAlloc` wraps the global allocator and
+ * remembers the last failed request. The crash record then carries the
+ * requested size and the process's peak RSS, and the event is reported as
+ * an out-of-memory crash.
+ *
+ * The failure path must not allocate: it only stores into atomics and
+ * calls `getrusage`.
+ */
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Size of the last failed allocation, `0` if none failed.
+pub(crate) static FAILED_ALLOC_SIZE: AtomicUsize = AtomicUsize::new(0);
+
+/// Peak resident set size in KiB at the time of the last failure.
+pub(crate) static MAX_RSS_KB: AtomicUsize = AtomicUsize::new(0);
+
+/**
+ * Global allocator wrapper recording allocation failures for OOM reports.
+ *
+ * Adds no overhead on successful allocations.
+ *
+ * ```ignore
+ * #[global_allocator]
+ * static ALLOC: hawk::HawkAlloc = hawk::HawkAlloc::system();
+ * ```
+ *
+ * Wrap a custom allocator with `HawkAlloc::new(MyAlloc)`. Failures are only
+ * reported if the process then aborts and `catch_crashes` is enabled; a
+ * failed `try_reserve` that the program recovers from sends nothing.
+ */
+pub struct HawkAlloc<A = System> {
+    inner: A,
+}
+
+impl HawkAlloc<System> {
+    /**
+     * Wraps the system allocator.
+     */
+    pub const fn system() -> Self {
+        Self { inner: System }
+    }
+}
+
+impl<A> HawkAlloc<A> {
+    /**
+     * Wraps a custom global allocator.
+     */
+    pub const fn new(inner: A) -> Self {
+        Self { inner }
+    }
+}
+
+unsafe impl<A: GlobalAlloc> GlobalAlloc for HawkAlloc<A> {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        check(self.inner.alloc(layout), layout.size())
+    }
+
+    unsafe fn alloc_zeroed(&self, layout: Layout) -> *mut u8 {
+        check(self.inner.alloc_zeroed(layout), layout.size())
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        check(self.inner.realloc(ptr, layout, new_size), new_size)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        self.inner.dealloc(ptr, layout)
+    }
+}
+
+/**
+ * Passes `ptr` through, recording a failure if it is null.
+ */
+#[inline]
+fn check(ptr: *mut u8, size: usize) -> *mut u8 {
+    if ptr.is_null() {
+        record_failure(size);
+    }
+    ptr
+}
+
+#[cold]
+fn record_failure(size: usize) {
+    FAILED_ALLOC_SIZE.store(size.max(1), Ordering::Relaxed);
+    MAX_RSS_KB.store(max_rss_kb(), Ordering::Relaxed);
+}
+
+/**
+ * Peak RSS of the process in KiB, via `getrusage`. `0` if unavailable.
+ */
+#[cfg(unix)]
+fn max_rss_kb() -> usize {
+    let mut usage: libc::rusage = unsafe { std::mem::zeroed() };
+    if unsafe { libc::getrusage(libc::RUSAGE_SELF, &mut usage) } != 0 {
+        return 0;
+    }
+
+    // Linux reports KiB, macOS bytes.
+    let max_rss = usage.ru_maxrss as usize;
+    if cfg!(target_vendor = "apple") {
+        max_rss / 1024
+    } else {
+        max_rss
+    }
+}
+
+#[cfg(not(unix))]
+fn max_rss_kb() -> usize {
+    0
+}
@@ -0,0 +1,56 @@
+/*!
+ * Flush on `std::process::exit()`.
+ *
+ * `process::exit()` runs no destructors, so a `Guard` held by `main` never
+ * flushes and events queued right before the exit are lost. It does run
+ * the C `atexit` handlers, so one is registered here that flushes the
+ * queue and records the exit as clean in `Options::state_file` — unless a
+ * panic was reported: after a panic in `main`, the runtime also exits
+ * through `exit(101)`. The worker thread is still alive at that point —
+ * threads are only torn down once the handlers have run.
+ *
+ * Returning from `main` also runs the handler, after the `Guard` has
+ * already flushed; the second flush finds the queue empty and returns
+ * immediately.
+ */
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Ensures `install_exit_flush()` is idempotent — `atexit` handlers can't
+/// be unregistered.
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/// Names of the `hawk_core` background threads, which must never wait on
+/// themselves.
+const WORKER_THREAD_NAMES: [&str; 3] = ["hawk-symbolicator", "hawk-worker", "hawk-sender"];
+
+/**
+ * Registers an `atexit` handler flushing pending events when the process
+ * exits via `std::process::exit()` (or `libc::exit()`).
+ *
+ * Idempotent — subsequent calls are silent no-ops.
+ */
+pub fn install_exit_flush() {
+    if INSTALLED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    if unsafe { libc::atexit(flush_at_exit) } != 0 {
+        eprintln!("[Hawk] Failed to register exit handler");
+    }
+}
+
+/**
+ * The `atexit` handler. Must not unwind into libc.
+ */
+extern "C" fn flush_at_exit() {
+    let _ = std::panic::catch_unwind(|| {
+        if !std::thread::current()
+            .name()
+            .is_some_and(|name| WORKER_THREAD_NAMES.contains(&name))
+        {
+            hawk_core::flush();
+            hawk_core::mark_clean_exit_unless_panicked();
+        }
+    });
+}
@@ -0,0 +1,239 @@
+/*!
+ * Hawk Crash Handler — capture of native crashes (fatal signals and
+ * unhandled Windows exceptions).
+ *
+ * Segfaults, bus errors, illegal instructions, and `abort()` bypass the
+ * Rust panic hook entirely: the process is killed by a signal. This crate
+ * installs signal handlers for them — on Windows, an unhandled exception
+ * filter catching access violations, stack overflows, and the like.
+ *
+ * Nothing in a signal handler may allocate, lock, or do network I/O, so
+ * reporting happens in two phases:
+ *
+ * 1. **Crash** — the handler writes a tiny crash record (signal number or
+ *    exception code, fault address, pid, timestamp, executable build ID,
+ *    and the raw frame addresses of the crashing thread) with raw
+ *    `open` / `write` syscalls to a file path prepared at install time,
+ *    then hands the signal over to the previously installed handler (or
+ *    the default action).
+ * 2. **Next start** — `install()` picks up crash records left by previous
+ *    runs, sends each one as a `fatal` event, and deletes it. Frames carry
+ *    only addresses and module offsets; the backend symbolicates them with
+ *    the build ID.
+ *
+ * Out-of-memory aborts are recognized when the global allocator is wrapped
+ * in `HawkAlloc`, which records the failed request size and peak RSS.
+ *
+ * `install_exit_flush()` registers an `atexit` handler flushing pending
+ * events on `std::process::exit()`, which skips the `Guard`'s destructor.
+ *
+ * With the `shutdown-signals` feature, `install_shutdown_flush()` also
+ * flushes pending events on SIGINT / SIGTERM before the process exits.
+ *
+ * Runs that crash again right after launch are detected by `hawk_core`
+ * as a startup crash loop (`hawk_core::is_crash_looping()`); their crash
+ * events then carry `startup_crash: true` and are delivered before
+ * `install()` returns.
+ *
+ * Unless turned off with `set_minidumps(false)` or by a spool key, the
+ * handler also writes a minidump of the crash next to the record —
+ * registers, stack memory, and loaded modules, for the cases frame
+ * addresses do not explain — sent as an attachment of the crash event.
+ * Minidumps are written on Linux and Android (x86_64, aarch64; crashing
+ * thread only, see `minidump`) and on Windows (every thread, with
+ * `MiniDumpWriteDump`).
+ *
+ * # Data at rest
+ *
+ * Crash records hold no user data: signal number or exception code,
+ * fault address, pid, timestamp, build ID, allocation size, and frame
+ * addresses. Minidumps are another matter: the stack memory they hold
+ * may contain anything the crashing thread was working on, user data
+ * included, and it is sent as is — scrubbing only applies to event
+ * fields. Turn them off with `set_minidumps(false)` where that is not
+ * acceptable.
+ *
+ * Event payloads — messages, context, breadcrumbs — only live in memory
+ * until they are sent, unless `fatal_event_dir` is set: fatal events are
+ * then written there, scrubbed, until delivered — in a versioned format
+ * that later SDK versions migrate when reading it.
+ *
+ * Both are written in plaintext unless `Options::spool_key` is set: crash
+ * records and persisted events are then encrypted with it
+ * (ChaCha20-Poly1305), and no minidumps are written, as they cannot be.
+ * Either way, keep both directories private to the user running the app.
+ *
+ * # Platform support
+ *
+ * Unix (Linux, macOS, BSDs) and Windows. On Windows, fail-fast
+ * terminations — `std::process::abort()`, and so out-of-memory aborts and
+ * panics under `panic = "abort"` — skip exception filters and leave no
+ * record; panics are still reported by the panic hook before aborting.
+ *
+ * On macOS and iOS, Mach exceptions reach the handler as the signals the
+ * kernel turns them into: `EXC_BAD_ACCESS` as SIGSEGV / SIGBUS,
+ * `EXC_BAD_INSTRUCTION` as SIGILL, `EXC_ARITHMETIC` as SIGFPE, and
+ * `EXC_BREAKPOINT` — the trap instruction of Rust intrinsics and Swift
+ * runtime checks on Apple silicon — as SIGTRAP. No Mach exception port is
+ * claimed, so ReportCrash and debuggers keep working as before.
+ *
+ * On other targets `install()` still delivers pending records but
+ * installs no handlers.
+ */
+
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+mod alloc;
+#[cfg(unix)]
+mod exit;
+#[cfg(all(
+    any(target_os = "linux", target_os = "android"),
+    any(target_arch = "x86_64", target_arch = "aarch64")
+))]
+mod minidump;
+#[cfg(any(unix, windows))]
+mod record;
+mod report;
+#[cfg(all(unix, feature = "shutdown-signals"))]
+mod shutdown;
+mod startup;
+#[cfg(unix)]
+mod unix;
+#[cfg(windows)]
+mod windows;
+
+pub use alloc::HawkAlloc;
+#[cfg(unix)]
+pub use exit::install_exit_flush;
+#[cfg(all(unix, feature = "shutdown-signals"))]
+pub use shutdown::install_shutdown_flush;
+
+/// File extension of crash records inside the crash directory.
+pub(crate) const RECORD_EXTENSION: &str = "crash";
+
+/// File extension of minidumps inside the crash directory.
+pub(crate) const DUMP_EXTENSION: &str = "dmp";
+
+/// Ensures `install()` is idempotent.
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/// Whether crashes also leave a minidump.
+static MINIDUMPS: AtomicBool = AtomicBool::new(true);
+
+// ---------------------------------------------------------------------------
+// Public API
+// ---------------------------------------------------------------------------
+
+/**
+ * Default directory for crash records:
+ * `<temp dir>/hawk-crashes/<integration>-<exe>`, so that apps sharing a
+ * temp dir don't pick up (and report) each other's crashes. Call after
+ * `hawk_core::init()`; before it, the integration part is left out.
+ */
+pub fn default_crash_dir() -> PathBuf {
+    let exe = std::env::current_exe()
+        .ok()
+        .and_then(|path| {
+            path.file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+        })
+        .unwrap_or_else(|| "unknown".into());
+    let name = match hawk_core::integration_id() {
+        Some(integration) => format!("{}-{}", path_component(&integration), path_component(&exe)),
+        None => path_component(&exe),
+    };
+    std::env::temp_dir().join("hawk-crashes").join(name)
+}
+
+/**
+ * `name` with everything but ASCII letters, digits, `-` and `_` replaced by
+ * `_`, safe to use as a directory name.
+ */
+fn path_component(name: &str) -> String {
+    name.chars()
+        .map(|c| {
+            if c.is_ascii_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect()
+}
+
+/**
+ * Sets whether crashes also leave a minidump, sent with the crash event.
+ * On by default. Call before `install()`.
+ */
+pub fn set_minidumps(enabled: bool) {
+    MINIDUMPS.store(enabled, Ordering::Relaxed);
+}
+
+/**
+ * Installs the crash handler, storing crash records in `crash_dir`.
+ *
+ * Also sends (and deletes) records left in `crash_dir` by previous runs
+ * that crashed. Idempotent — subsequent calls are silent no-ops.
+ *
+ * Must be called AFTER `hawk_core::init()` — otherwise pending records
+ * have nowhere to go and are kept for the next run.
+ */
+pub fn install(crash_dir: &Path) {
+    if INSTALLED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    if let Err(e) = std::fs::create_dir_all(crash_dir) {
+        eprintln!(
+            "[Hawk] Failed to create crash directory {}: {e}",
+            crash_dir.display()
+        );
+        return;
+    }
+
+    startup::mark_started();
+    report::send_pending(crash_dir);
+
+    // Minidumps are written as is, and cannot be sealed.
+    if hawk_core::spool_cipher().is_some() {
+        MINIDUMPS.store(false, Ordering::Relaxed);
+    }
+    #[cfg(any(unix, windows))]
+    record::prepare();
+
+    #[cfg(all(
+        any(target_os = "linux", target_os = "android"),
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ))]
+    if MINIDUMPS.load(Ordering::Relaxed) {
+        minidump::prepare(crash_dir);
+    }
+
+    #[cfg(unix)]
+    unix::install(crash_dir);
+
+    #[cfg(windows)]
+    windows::install(crash_dir, MINIDUMPS.load(Ordering::Relaxed));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * Verifies that the default crash directory is namespaced by the
+     * executable, and that names are made safe for a path.
+     */
+    #[test]
+    fn test_default_crash_dir() {
+        let dir = default_crash_dir();
+        assert!(dir.starts_with(std::env::temp_dir().join("hawk-crashes")));
+        let exe = std::env::current_exe().unwrap();
+        let exe = path_component(&exe.file_stem().unwrap().to_string_lossy());
+        assert!(dir.file_name().unwrap().to_string_lossy().ends_with(&exe));
+
+        assert_eq!(path_component("my app/../x.y"), "my_app____x_y");
+        assert_eq!(path_component("a1-b_2"), "a1-b_2");
+    }
+}
@@ -0,0 +1,761 @@
+/*!
+ * Minidumps of native crashes on Linux and Android (x86_64, aarch64).
+ *
+ * Frame addresses alone rarely explain memory corruption; the registers
+ * and stack contents at the time of the crash do. Next to the crash
+ * record, the signal handler writes `<crash_dir>/<pid>.dmp` in the
+ * minidump format of Breakpad and Windows Error Reporting, which
+ * debuggers and minidump processors read. It holds:
+ *
+ * - the crashing thread: its registers, from the signal context, and up
+ *   to `MAX_STACK_BYTES` of its stack;
+ * - the exception: signal number, `si_code`, and fault address;
+ * - the system: CPU architecture, processor count, and kernel version;
+ * - the modules loaded when `install()` ran, with their build IDs.
+ *
+ * Other threads are not dumped, and libraries loaded after `install()`
+ * are missing from the module list.
+ *
+ * Everything that does not depend on the crash is serialized by
+ * `prepare()` into a template. The handler writes the template, then the
+ * exception, thread, and register sections from stack buffers, with plain
+ * `write(2)` calls. The stack is written straight from memory: `write(2)`
+ * fails with `EFAULT` on an unmapped page instead of faulting, so a stack
+ * pointer gone wild only costs the stack bytes.
+ */
+
+use std::ffi::{CStr, CString};
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::sync::OnceLock;
+
+use libc::{c_int, c_void, siginfo_t};
+
+/// Stack bytes dumped, from the stack pointer up.
+const MAX_STACK_BYTES: usize = 64 * 1024;
+
+/// Smallest page size of the supported targets.
+const PAGE_SIZE: u64 = 4096;
+
+/// `MINIDUMP_HEADER` signature (`"MDMP"`) and format version.
+const SIGNATURE: u32 = 0x504d_444d;
+const VERSION: u32 = 0xa793;
+
+/// Stream types, in directory order.
+const THREAD_LIST_STREAM: u32 = 3;
+const MODULE_LIST_STREAM: u32 = 4;
+const EXCEPTION_STREAM: u32 = 6;
+const SYSTEM_INFO_STREAM: u32 = 7;
+const STREAM_COUNT: usize = 4;
+
+/// Sizes of the fixed-size structures written.
+const HEADER_SIZE: usize = 32;
+const DIRECTORY_ENTRY_SIZE: usize = 12;
+const SYSTEM_INFO_SIZE: usize = 56;
+const MODULE_SIZE: usize = 108;
+const EXCEPTION_STREAM_SIZE: usize = 168;
+const THREAD_LIST_SIZE: usize = 4 + 48;
+
+/// CodeView record signature of ELF build IDs, as written by Breakpad.
+const CV_SIGNATURE_ELF: u32 = 0x4270_454c;
+
+/// Operating system of `MINIDUMP_SYSTEM_INFO`, as numbered by Breakpad.
+#[cfg(target_os = "android")]
+const PLATFORM_ID: u32 = 0x8203;
+#[cfg(not(target_os = "android"))]
+const PLATFORM_ID: u32 = 0x8201;
+
+/// `NT_GNU_BUILD_ID` note type.
+const NT_GNU_BUILD_ID: u32 = 3;
+
+/// Path and template prepared by `prepare()`.
+static PREPARED: OnceLock<Prepared> = OnceLock::new();
+
+/**
+ * What the handler needs to write a dump, built before any crash.
+ */
+struct Prepared {
+    /// Path of this process's dump, NUL-terminated for `open(2)`.
+    path: CString,
+
+    /// Header, stream directory, system information, and module list.
+    template: Vec<u8>,
+}
+
+/**
+ * A module loaded in the process.
+ */
+struct Module {
+    base: u64,
+    size: u32,
+    name: String,
+    build_id: Vec<u8>,
+}
+
+/**
+ * File offsets of the sections written by the handler, after the
+ * template.
+ */
+struct Layout {
+    exception: usize,
+    thread_list: usize,
+    context: usize,
+    stack: usize,
+}
+
+impl Layout {
+    fn new(template_len: usize) -> Self {
+        let exception = template_len;
+        let thread_list = exception + EXCEPTION_STREAM_SIZE;
+        let context = align(thread_list + THREAD_LIST_SIZE, 16);
+        Self {
+            exception,
+            thread_list,
+            context,
+            stack: context + arch::CONTEXT_SIZE,
+        }
+    }
+}
+
+/**
+ * Prepares the dump of this process: `<crash_dir>/<pid>.dmp`, with the
+ * modules loaded now.
+ */
+pub(crate) fn prepare(crash_dir: &Path) {
+    let path = crash_dir.join(format!("{}.{}", std::process::id(), crate::DUMP_EXTENSION));
+    let Ok(path) = CString::new(path.as_os_str().as_bytes()) else {
+        return;
+    };
+    let template = template(&system_version(), processor_count(), &loaded_modules());
+    let _ = PREPARED.set(Prepared { path, template });
+}
+
+/**
+ * Writes the dump of a crash, from the signal handler. No-op unless
+ * `prepare()` ran.
+ */
+pub(crate) fn write(signal: c_int, info: *mut siginfo_t, context: *mut c_void) {
+    if let Some(prepared) = PREPARED.get() {
+        write_dump(prepared, signal, info, context);
+    }
+}
+
+fn write_dump(prepared: &Prepared, signal: c_int, info: *mut siginfo_t, context: *mut c_void) {
+    if context.is_null() {
+        return;
+    }
+    let context = unsafe { &*(context as *const libc::ucontext_t) };
+
+    let fd = unsafe {
+        libc::open(
+            prepared.path.as_ptr(),
+            libc::O_WRONLY | libc::O_CREAT | libc::O_TRUNC,
+            0o600 as libc::c_uint,
+        )
+    };
+    if fd < 0 {
+        return;
+    }
+
+    let layout = Layout::new(prepared.template.len());
+    let thread_id = unsafe { libc::syscall(libc::SYS_gettid) } as u32;
+    let (pc, sp) = arch::pc_and_sp(context);
+    let stack_start = sp.saturating_sub(arch::RED_ZONE);
+
+    let code = if info.is_null() {
+        0
+    } else {
+        unsafe { (*info).si_code as u32 }
+    };
+    let address = if signal == libc::SIGSEGV || signal == libc::SIGBUS {
+        crate::unix::fault_address(signal, info) as u64
+    } else {
+        pc
+    };
+
+    write_all(fd, &prepared.template);
+
+    let mut sections = [0u8; EXCEPTION_STREAM_SIZE + THREAD_LIST_SIZE];
+    let mut cursor = Cursor::new(&mut sections);
+
+    // MINIDUMP_EXCEPTION_STREAM
+    cursor.u32(thread_id);
+    cursor.skip(4);
+    cursor.u32(signal as u32);
+    cursor.u32(code);
+    cursor.u64(0);
+    cursor.u64(address);
+    cursor.skip(8 + 15 * 8);
+    cursor.u32(arch::CONTEXT_SIZE as u32);
+    cursor.u32(layout.context as u32);
+
+    // MINIDUMP_THREAD_LIST with the crashing thread
+    cursor.u32(1);
+    cursor.u32(thread_id);
+    cursor.skip(12 + 8);
+    cursor.skip(12); // stack start and size, written once known
+    cursor.u32(layout.stack as u32);
+    cursor.u32(arch::CONTEXT_SIZE as u32);
+    cursor.u32(layout.context as u32);
+
+    write_all(fd, &sections);
+    write_all(
+        fd,
+        &[0; 16][..layout.context - layout.thread_list - THREAD_LIST_SIZE],
+    );
+
+    let mut registers = [0u8; arch::CONTEXT_SIZE];
+    arch::write_context(&mut Cursor::new(&mut registers), context);
+    write_all(fd, &registers);
+
+    /*
+     * After a stack overflow the stack pointer is in the guard page: dump
+     * from the page above it instead.
+     */
+    let mut stack_start = stack_start;
+    let mut stack_size = write_memory(fd, stack_start as usize, MAX_STACK_BYTES);
+    if stack_size == 0 {
+        stack_start = (sp | (PAGE_SIZE - 1)) + 1;
+        stack_size = write_memory(fd, stack_start as usize, MAX_STACK_BYTES);
+    }
+
+    let mut stack = [0u8; 12];
+    let mut cursor = Cursor::new(&mut stack);
+    cursor.u64(stack_start);
+    cursor.u32(stack_size as u32);
+    unsafe {
+        libc::pwrite(
+            fd,
+            stack.as_ptr() as *const c_void,
+            stack.len(),
+            (layout.thread_list + 4 + 24) as libc::off_t,
+        );
+        libc::close(fd);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Template
+// ---------------------------------------------------------------------------
+
+/**
+ * Serializes the header, the stream directory, and the streams known
+ * before the crash: system information and module list.
+ */
+fn template(version: &str, processors: u8, modules: &[Module]) -> Vec<u8> {
+    let system_info = HEADER_SIZE + DIRECTORY_ENTRY_SIZE * STREAM_COUNT;
+    let version_string = system_info + SYSTEM_INFO_SIZE;
+    let module_list = align(version_string + string_size(version), 4);
+    let module_list_size = 4 + MODULE_SIZE * modules.len();
+
+    // Names and CodeView records follow the module list.
+    let mut offset = module_list + module_list_size;
+    let mut module_data = Vec::with_capacity(modules.len());
+    for module in modules {
+        let name = offset;
+        let cv = align(name + string_size(&module.name), 4);
+        offset = align(cv + 4 + module.build_id.len(), 4);
+        module_data.push((name, cv));
+    }
+    let layout = Layout::new(align(offset, 16));
+
+    let mut buf = vec![0u8; layout.exception];
+    let mut cursor = Cursor::new(&mut buf);
+
+    // MINIDUMP_HEADER
+    cursor.u32(SIGNATURE);
+    cursor.u32(VERSION);
+    cursor.u32(STREAM_COUNT as u32);
+    cursor.u32(HEADER_SIZE as u32);
+    cursor.u32(0);
+    cursor.u32(0);
+    cursor.u64(0);
+
+    // Stream directory
+    let streams = [
+        (SYSTEM_INFO_STREAM, SYSTEM_INFO_SIZE, system_info),
+        (MODULE_LIST_STREAM, module_list_size, module_list),
+        (EXCEPTION_STREAM, EXCEPTION_STREAM_SIZE, layout.exception),
+        (THREAD_LIST_STREAM, THREAD_LIST_SIZE, layout.thread_list),
+    ];
+    for (stream_type, size, rva) in streams {
+        cursor.u32(stream_type);
+        cursor.u32(size as u32);
+        cursor.u32(rva as u32);
+    }
+
+    // MINIDUMP_SYSTEM_INFO
+    cursor.u16(arch::PROCESSOR_ARCHITECTURE);
+    cursor.skip(4);
+    cursor.u8(processors);
+    cursor.u8(0);
+    let (major, minor, build) = kernel_version(version);
+    cursor.u32(major);
+    cursor.u32(minor);
+    cursor.u32(build);
+    cursor.u32(PLATFORM_ID);
+    cursor.u32(version_string as u32);
+    cursor.skip(4 + 24);
+    cursor.string(version);
+
+    // MINIDUMP_MODULE_LIST
+    cursor.seek(module_list);
+    cursor.u32(modules.len() as u32);
+    for (module, (name, cv)) in modules.iter().zip(&module_data) {
+        cursor.u64(module.base);
+        cursor.u32(module.size);
+        cursor.skip(8);
+        cursor.u32(*name as u32);
+        cursor.skip(52);
+        cursor.u32(4 + module.build_id.len() as u32);
+        cursor.u32(*cv as u32);
+        cursor.skip(8 + 16);
+    }
+    for (module, (name, cv)) in modules.iter().zip(&module_data) {
+        cursor.seek(*name);
+        cursor.string(&module.name);
+        cursor.seek(*cv);
+        cursor.u32(CV_SIGNATURE_ELF);
+        cursor.bytes(&module.build_id);
+    }
+
+    buf
+}
+
+/**
+ * Size of a `MINIDUMP_STRING`: length, UTF-16 code units, terminator.
+ */
+fn string_size(text: &str) -> usize {
+    4 + text.encode_utf16().count() * 2 + 2
+}
+
+fn align(offset: usize, to: usize) -> usize {
+    offset.div_ceil(to) * to
+}
+
+/**
+ * `uname` fields describing the kernel, e.g.
+ * `"Linux 6.8.0-45-generic #45-Ubuntu SMP ... x86_64"`.
+ */
+fn system_version() -> String {
+    let mut name: libc::utsname = unsafe { std::mem::zeroed() };
+    if unsafe { libc::uname(&mut name) } != 0 {
+        return String::new();
+    }
+    let field = |field: &[libc::c_char]| {
+        unsafe { CStr::from_ptr(field.as_ptr()) }
+            .to_string_lossy()
+            .into_owned()
+    };
+    format!(
+        "{} {} {} {}",
+        field(&name.sysname),
+        field(&name.release),
+        field(&name.version),
+        field(&name.machine)
+    )
+}
+
+/**
+ * Major, minor, and patch numbers of the kernel release in `version`.
+ */
+fn kernel_version(version: &str) -> (u32, u32, u32) {
+    let release = version.split(' ').nth(1).unwrap_or_default();
+    let mut numbers = release
+        .split(|c: char| !c.is_ascii_digit())
+        .map(|number| number.parse().unwrap_or(0));
+    (
+        numbers.next().unwrap_or(0),
+        numbers.next().unwrap_or(0),
+        numbers.next().unwrap_or(0),
+    )
+}
+
+fn processor_count() -> u8 {
+    let count = unsafe { libc::sysconf(libc::_SC_NPROCESSORS_ONLN) };
+    count.clamp(0, u8::MAX as libc::c_long) as u8
+}
+
+/**
+ * The modules loaded in the process, the main executable first.
+ */
+fn loaded_modules() -> Vec<Module> {
+    unsafe extern "C" fn collect(
+        info: *mut libc::dl_phdr_info,
+        _size: libc::size_t,
+        data: *mut c_void,
+    ) -> c_int {
+        let info = &*info;
+        let modules = &mut *(data as *mut Vec<Module>);
+        if info.dlpi_phdr.is_null() {
+            return 0;
+        }
+        let bias = info.dlpi_addr as usize;
+        let headers = std::slice::from_raw_parts(info.dlpi_phdr, info.dlpi_phnum as usize);
+
+        let loads = headers.iter().filter(|phdr| phdr.p_type == libc::PT_LOAD);
+        let start = loads.clone().map(|phdr| phdr.p_vaddr as usize).min();
+        let end = loads
+            .map(|phdr| (phdr.p_vaddr + phdr.p_memsz) as usize)
+            .max();
+        let (Some(start), Some(end)) = (start, end) else {
+            return 0;
+        };
+        let start = start & !(PAGE_SIZE as usize - 1);
+
+        let name = if info.dlpi_name.is_null() || *info.dlpi_name == 0 {
+            if !modules.is_empty() {
+                return 0;
+            }
+            std::env::current_exe()
+                .map(|path| path.to_string_lossy().into_owned())
+                .unwrap_or_default()
+        } else {
+            CStr::from_ptr(info.dlpi_name)
+                .to_string_lossy()
+                .into_owned()
+        };
+
+        let build_id = headers
+            .iter()
+            .filter(|phdr| phdr.p_type == libc::PT_NOTE)
+            .find_map(|phdr| {
+                let notes = std::slice::from_raw_parts(
+                    (bias + phdr.p_vaddr as usize) as *const u8,
+                    phdr.p_memsz as usize,
+                );
+                gnu_build_id(notes, if phdr.p_align == 8 { 8 } else { 4 })
+            })
+            .unwrap_or_default();
+
+        modules.push(Module {
+            base: (bias + start) as u64,
+            size: (end - start) as u32,
+            name,
+            build_id,
+        });
+        0
+    }
+
+    let mut modules: Vec<Module> = Vec::new();
+    unsafe {
+        libc::dl_iterate_phdr(Some(collect), &mut modules as *mut _ as *mut c_void);
+    }
+    modules
+}
+
+/**
+ * The `NT_GNU_BUILD_ID` note in an ELF note segment, if any.
+ */
+fn gnu_build_id(mut notes: &[u8], alignment: usize) -> Option<Vec<u8>> {
+    let word = |bytes: &[u8], at: usize| {
+        bytes
+            .get(at..at + 4)
+            .map(|word| u32::from_ne_bytes(word.try_into().unwrap()) as usize)
+    };
+
+    while notes.len() >= 12 {
+        let (name_size, desc_size, note_type) = (word(notes, 0)?, word(notes, 4)?, word(notes, 8)?);
+        let desc_start = 12 + align(name_size, alignment);
+        let desc = notes.get(desc_start..desc_start + desc_size)?;
+
+        if note_type == NT_GNU_BUILD_ID as usize && notes.get(12..12 + name_size)? == b"GNU\0" {
+            return Some(desc.to_vec());
+        }
+        notes = notes.get(align(desc_start + desc_size, alignment)..)?;
+    }
+    None
+}
+
+// ---------------------------------------------------------------------------
+// Writing
+// ---------------------------------------------------------------------------
+
+/**
+ * Little-endian writer over a zeroed buffer; skipped bytes stay zero.
+ */
+struct Cursor<'a> {
+    buf: &'a mut [u8],
+    pos: usize,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(buf: &'a mut [u8]) -> Self {
+        Self { buf, pos: 0 }
+    }
+
+    fn bytes(&mut self, bytes: &[u8]) {
+        self.buf[self.pos..self.pos + bytes.len()].copy_from_slice(bytes);
+        self.pos += bytes.len();
+    }
+
+    fn u8(&mut self, value: u8) {
+        self.bytes(&[value]);
+    }
+
+    fn u16(&mut self, value: u16) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn u32(&mut self, value: u32) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn u64(&mut self, value: u64) {
+        self.bytes(&value.to_le_bytes());
+    }
+
+    fn skip(&mut self, count: usize) {
+        self.pos += count;
+    }
+
+    fn seek(&mut self, pos: usize) {
+        self.pos = pos;
+    }
+
+    /**
+     * Writes a `MINIDUMP_STRING`: byte length, then NUL-terminated UTF-16.
+     */
+    fn string(&mut self, text: &str) {
+        self.u32((text.encode_utf16().count() * 2) as u32);
+        for unit in text.encode_utf16() {
+            self.u16(unit);
+        }
+        self.u16(0);
+    }
+}
+
+/**
+ * `write(2)` loop handling short writes. Errors are ignored — there is
+ * nothing useful to do about them inside a crashing process.
+ */
+fn write_all(fd: c_int, mut bytes: &[u8]) {
+    while !bytes.is_empty() {
+        let written = unsafe { libc::write(fd, bytes.as_ptr() as *const c_void, bytes.len()) };
+        if written <= 0 {
+            return;
+        }
+        bytes = &bytes[written as usize..];
+    }
+}
+
+/**
+ * Writes up to `len` bytes of memory from `start`, stopping at the first
+ * unreadable page. Returns the number of bytes written.
+ */
+fn write_memory(fd: c_int, start: usize, len: usize) -> usize {
+    let mut done = 0;
+    while done < len {
+        let written = unsafe { libc::write(fd, (start + done) as *const c_void, len - done) };
+        if written <= 0 {
+            break;
+        }
+        done += written as usize;
+    }
+    done
+}
+
+// ---------------------------------------------------------------------------
+// Registers
+// ---------------------------------------------------------------------------
+
+#[cfg(target_arch = "x86_64")]
+mod arch {
+    use super::Cursor;
+
+    /// `PROCESSOR_ARCHITECTURE_AMD64`.
+    pub const PROCESSOR_ARCHITECTURE: u16 = 9;
+
+    /// Size of the AMD64 `CONTEXT`.
+    pub const CONTEXT_SIZE: usize = 1232;
+
+    /// `CONTEXT_AMD64 | CONTROL | INTEGER`, and `FLOATING_POINT` when the
+    /// FPU state is available.
+    const CONTEXT_FLAGS: u32 = 0x0010_0003;
+    const CONTEXT_FLOATING_POINT: u32 = 0x0010_0008;
+
+    /// Bytes below the stack pointer that leaf functions may use.
+    pub const RED_ZONE: u64 = 128;
+
+    pub fn pc_and_sp(context: &libc::ucontext_t) -> (u64, u64) {
+        let registers = &context.uc_mcontext.gregs;
+        (
+            registers[libc::REG_RIP as usize] as u64,
+            registers[libc::REG_RSP as usize] as u64,
+        )
+    }
+
+    /**
+     * Writes the AMD64 `CONTEXT`: control and integer registers, and the
+     * `FXSAVE` area the kernel saved for the signal.
+     */
+    pub fn write_context(cursor: &mut Cursor, context: &libc::ucontext_t) {
+        let registers = &context.uc_mcontext.gregs;
+        let register = |index: libc::c_int| registers[index as usize] as u64;
+        let fpu = context.uc_mcontext.fpregs;
+
+        cursor.skip(6 * 8);
+        let flags = if fpu.is_null() {
+            CONTEXT_FLAGS
+        } else {
+            CONTEXT_FLAGS | CONTEXT_FLOATING_POINT
+        };
+        cursor.u32(flags);
+        cursor.u32(if fpu.is_null() {
+            0
+        } else {
+            unsafe { (*fpu).mxcsr }
+        });
+        cursor.u16(register(libc::REG_CSGSFS) as u16);
+        cursor.skip(10);
+        cursor.u32(register(libc::REG_EFL) as u32);
+        cursor.skip(6 * 8);
+        for index in [
+            libc::REG_RAX,
+            libc::REG_RCX,
+            libc::REG_RDX,
+            libc::REG_RBX,
+            libc::REG_RSP,
+            libc::REG_RBP,
+            libc::REG_RSI,
+            libc::REG_RDI,
+            libc::REG_R8,
+            libc::REG_R9,
+            libc::REG_R10,
+            libc::REG_R11,
+            libc::REG_R12,
+            libc::REG_R13,
+            libc::REG_R14,
+            libc::REG_R15,
+            libc::REG_RIP,
+        ] {
+            cursor.u64(register(index));
+        }
+        if !fpu.is_null() {
+            let area = unsafe {
+                std::slice::from_raw_parts(
+                    fpu as *const u8,
+                    std::mem::size_of::<libc::_libc_fpstate>(),
+                )
+            };
+            cursor.bytes(area);
+        }
+    }
+}
+
+#[cfg(target_arch = "aarch64")]
+mod arch {
+    use super::Cursor;
+
+    /// `PROCESSOR_ARCHITECTURE_ARM64`.
+    pub const PROCESSOR_ARCHITECTURE: u16 = 12;
+
+    /// Size of the ARM64 `CONTEXT`.
+    pub const CONTEXT_SIZE: usize = 912;
+
+    /// `CONTEXT_ARM64 | CONTROL | INTEGER`.
+    const CONTEXT_FLAGS: u32 = 0x0040_0003;
+
+    /// No red zone on AArch64.
+    pub const RED_ZONE: u64 = 0;
+
+    pub fn pc_and_sp(context: &libc::ucontext_t) -> (u64, u64) {
+        (context.uc_mcontext.pc, context.uc_mcontext.sp)
+    }
+
+    /**
+     * Writes the ARM64 `CONTEXT`: control and integer registers.
+     */
+    pub fn write_context(cursor: &mut Cursor, context: &libc::ucontext_t) {
+        let registers = &context.uc_mcontext;
+        cursor.u32(CONTEXT_FLAGS);
+        cursor.u32(registers.pstate as u32);
+        for register in registers.regs {
+            cursor.u64(register);
+        }
+        cursor.u64(registers.sp);
+        cursor.u64(registers.pc);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn u32_at(dump: &[u8], at: usize) -> u32 {
+        u32::from_le_bytes(dump[at..at + 4].try_into().unwrap())
+    }
+
+    /**
+     * Verifies that a dump holds the exception, the crashing thread's
+     * stack, and the main executable.
+     */
+    #[test]
+    fn test_write_dump() {
+        let dir = std::env::temp_dir().join(format!("hawk-minidump-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("test.dmp");
+        let prepared = Prepared {
+            path: CString::new(path.as_os_str().as_bytes()).unwrap(),
+            template: template(&system_version(), processor_count(), &loaded_modules()),
+        };
+
+        let stack = [0x5au8; 256];
+        let stack_start = stack.as_ptr() as u64;
+        let mut context: libc::ucontext_t = unsafe { std::mem::zeroed() };
+        #[cfg(target_arch = "x86_64")]
+        {
+            context.uc_mcontext.gregs[libc::REG_RSP as usize] =
+                (stack_start + arch::RED_ZONE) as i64;
+            context.uc_mcontext.gregs[libc::REG_RIP as usize] = 0x1234;
+        }
+        #[cfg(target_arch = "aarch64")]
+        {
+            context.uc_mcontext.sp = stack_start;
+            context.uc_mcontext.pc = 0x1234;
+        }
+
+        write_dump(
+            &prepared,
+            libc::SIGILL,
+            std::ptr::null_mut(),
+            &mut context as *mut _ as *mut c_void,
+        );
+        let dump = std::fs::read(&path).unwrap();
+        let _ = std::fs::remove_dir_all(&dir);
+
+        assert_eq!(u32_at(&dump, 0), SIGNATURE);
+        assert_eq!(u32_at(&dump, 8), STREAM_COUNT as u32);
+
+        let stream = |stream_type: u32| {
+            (0..STREAM_COUNT)
+                .map(|index| HEADER_SIZE + index * DIRECTORY_ENTRY_SIZE)
+                .find(|&entry| u32_at(&dump, entry) == stream_type)
+                .map(|entry| u32_at(&dump, entry + 8) as usize)
+                .expect("stream")
+        };
+
+        let exception = stream(EXCEPTION_STREAM);
+        assert_eq!(u32_at(&dump, exception + 8), libc::SIGILL as u32);
+        assert_eq!(u32_at(&dump, exception + 24), 0x1234);
+
+        let thread = stream(THREAD_LIST_STREAM) + 4;
+        assert_eq!(u32_at(&dump, thread + 24), stack_start as u32);
+        let stack_size = u32_at(&dump, thread + 32) as usize;
+        let stack_rva = u32_at(&dump, thread + 36) as usize;
+        assert!(stack_size >= stack.len());
+        assert_eq!(&dump[stack_rva..stack_rva + stack.len()], &stack[..]);
+
+        let modules = stream(MODULE_LIST_STREAM);
+        assert!(u32_at(&dump, modules) >= 1);
+        let name = u32_at(&dump, modules + 4 + 20) as usize;
+        let name: Vec<u16> = dump[name + 4..name + 4 + u32_at(&dump, name) as usize]
+            .chunks(2)
+            .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+            .collect();
+        assert_eq!(
+            String::from_utf16(&name).unwrap(),
+            std::env::current_exe().unwrap().to_string_lossy()
+        );
+    }
+}
@@ -0,0 +1,207 @@
+/*!
+ * Crash record output, sealed with the spool key (`Options::spool_key`)
+ * when one is set.
+ *
+ * The handlers write their lines through `RecordWriter`. Without a key,
+ * lines go straight to the file. With one, the file starts with
+ * `spool::MAGIC` and lines are collected in a static buffer, sealed into a
+ * chunk by `flush()` — after the header, and after the frames — or when
+ * the buffer is full. Chunks are prepared in `install()` (see
+ * `spool::PreparedChunk`), so sealing neither allocates nor needs more
+ * stack than the alternate signal stack has; the buffer is static for
+ * the same reason, and only the first crashing thread writes a record.
+ */
+
+use std::cell::UnsafeCell;
+use std::sync::OnceLock;
+
+use hawk_core::spool::{PreparedChunk, CHUNK_HEADER, CHUNK_OVERHEAD, MAGIC, PREPARED_CHUNK_LEN};
+
+/// Chunks prepared per record: the header, the frames, and spare room.
+const RECORD_CHUNKS: usize = 4;
+
+/// Chunks sealing this process's record, prepared in `install()`.
+static CHUNKS: OnceLock<Vec<PreparedChunk>> = OnceLock::new();
+
+/// Chunk being collected: header, plaintext, and tag.
+struct ChunkBuffer(UnsafeCell<[u8; PREPARED_CHUNK_LEN + CHUNK_OVERHEAD]>);
+
+// Only used by the one thread writing a record (see `RecordWriter::new`).
+unsafe impl Sync for ChunkBuffer {}
+
+static BUFFER: ChunkBuffer = ChunkBuffer(UnsafeCell::new([0; PREPARED_CHUNK_LEN + CHUNK_OVERHEAD]));
+
+/**
+ * Seals records with the client's spool key, if it has one. Call from
+ * `install()`, before the handlers are installed.
+ */
+pub(crate) fn prepare() {
+    let Some(cipher) = hawk_core::spool_cipher() else {
+        return;
+    };
+    let chunks = (0..RECORD_CHUNKS)
+        .map(|_| cipher.prepare_chunk())
+        .collect::<Result<_, _>>()
+        .unwrap_or_else(|e| {
+            // No chunks: records are left empty rather than in plaintext.
+            eprintln!("[Hawk] Failed to prepare crash record encryption: {e}");
+            Vec::new()
+        });
+    let _ = CHUNKS.set(chunks);
+}
+
+/**
+ * Writes the lines of a record to `output`, sealed if a key is set. Lines
+ * past the prepared chunks are dropped.
+ */
+pub(crate) struct RecordWriter<F: FnMut(&[u8])> {
+    output: F,
+    /// Chunks left; `None` if records are not sealed.
+    chunks: Option<&'static [PreparedChunk]>,
+    len: usize,
+}
+
+impl<F: FnMut(&[u8])> RecordWriter<F> {
+    /**
+     * Starts a record.
+     *
+     * # Safety
+     *
+     * No other `RecordWriter` may be alive: they share one buffer.
+     */
+    pub(crate) unsafe fn new(output: F) -> Self {
+        Self::with_chunks(output, CHUNKS.get().map(Vec::as_slice))
+    }
+
+    fn with_chunks(mut output: F, chunks: Option<&'static [PreparedChunk]>) -> Self {
+        if chunks.is_some() {
+            output(MAGIC);
+        }
+        Self {
+            output,
+            chunks,
+            len: 0,
+        }
+    }
+
+    /**
+     * Writes `<key><value>\n`, formatting the number into a stack buffer.
+     */
+    pub(crate) fn field(&mut self, key: &[u8], value: u64) {
+        let mut buf = [0u8; 20];
+        let mut pos = buf.len();
+        let mut rest = value;
+
+        loop {
+            pos -= 1;
+            buf[pos] = b'0' + (rest % 10) as u8;
+            rest /= 10;
+            if rest == 0 {
+                break;
+            }
+        }
+
+        self.bytes(key);
+        self.bytes(&buf[pos..]);
+        self.bytes(b"\n");
+    }
+
+    /**
+     * Writes raw bytes.
+     */
+    pub(crate) fn bytes(&mut self, mut bytes: &[u8]) {
+        if self.chunks.is_none() {
+            (self.output)(bytes);
+            return;
+        }
+        while !bytes.is_empty() {
+            if self.len == PREPARED_CHUNK_LEN {
+                self.flush();
+            }
+            let taken = bytes.len().min(PREPARED_CHUNK_LEN - self.len);
+            let start = CHUNK_HEADER + self.len;
+            // SAFETY: this writer is the only one alive (`new`).
+            let buffer = unsafe { &mut *BUFFER.0.get() };
+            buffer[start..start + taken].copy_from_slice(&bytes[..taken]);
+            self.len += taken;
+            bytes = &bytes[taken..];
+        }
+    }
+
+    /**
+     * Seals and writes the lines collected so far. No-op without a key.
+     */
+    pub(crate) fn flush(&mut self) {
+        let Some(chunks) = self.chunks else {
+            return;
+        };
+        if self.len == 0 {
+            return;
+        }
+        if let Some((chunk, rest)) = chunks.split_first() {
+            // SAFETY: this writer is the only one alive (`new`).
+            let buffer = unsafe { &mut *BUFFER.0.get() };
+            let sealed = chunk.seal(buffer, self.len);
+            (self.output)(&buffer[..sealed]);
+            self.chunks = Some(rest);
+        }
+        self.len = 0;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hawk_core::spool::SpoolCipher;
+
+    /**
+     * Verifies that a sealed record opens to its lines, whether they fit
+     * one chunk or spill over several, that lines past the prepared
+     * chunks are dropped, and that records without a key are written as
+     * is.
+     */
+    #[test]
+    fn test_sealed_record() {
+        let cipher = SpoolCipher::new(&[3; 32]).unwrap();
+        let chunks: Vec<PreparedChunk> = (0..RECORD_CHUNKS)
+            .map(|_| cipher.prepare_chunk().unwrap())
+            .collect();
+        let chunks: &'static [PreparedChunk] = chunks.leak();
+
+        let mut file = Vec::new();
+        let mut record =
+            RecordWriter::with_chunks(|bytes: &[u8]| file.extend_from_slice(bytes), Some(chunks));
+        record.field(b"signal=", 11);
+        record.flush();
+        for ip in 0..100u64 {
+            record.field(b"frame=", 93824992301894 + ip);
+        }
+        record.flush();
+
+        let mut expected = b"signal=11\n".to_vec();
+        for ip in 0..100u64 {
+            expected.extend_from_slice(format!("frame={}\n", 93824992301894 + ip).as_bytes());
+        }
+        assert!(expected.len() > PREPARED_CHUNK_LEN);
+        assert!(!file.windows(6).any(|window| window == b"frame="));
+        assert_eq!(cipher.open(&file).unwrap(), expected);
+
+        let mut file = Vec::new();
+        let mut record = RecordWriter::with_chunks(
+            |bytes: &[u8]| file.extend_from_slice(bytes),
+            Some(vec![cipher.prepare_chunk().unwrap()].leak()),
+        );
+        record.field(b"signal=", 11);
+        record.flush();
+        record.field(b"frame=", 1);
+        record.flush();
+        assert_eq!(cipher.open(&file).unwrap(), b"signal=11\n");
+
+        let mut file = Vec::new();
+        let mut record =
+            RecordWriter::with_chunks(|bytes: &[u8]| file.extend_from_slice(bytes), None);
+        record.field(b"signal=", 11);
+        record.flush();
+        assert_eq!(file, b"signal=11\n");
+    }
+}
@@ -0,0 +1,433 @@
+/*!
+ * Crash records left by previous runs: parsing and delivery.
+ */
+
+use std::path::Path;
+
+use hawk_core::spool;
+use hawk_core::{Addons, Attachment, BacktraceFrame, EventData, CATCHER_VERSION};
+use serde_json::json;
+
+/// Minidumps larger than this are not sent.
+const MAX_MINIDUMP_BYTES: u64 = 4 * 1024 * 1024;
+
+/**
+ * A parsed crash record (see `unix::write_record` for the format).
+ */
+#[derive(Clone, Debug, Default, PartialEq)]
+pub(crate) struct CrashRecord {
+    /// Fatal signal, `0` for a Windows exception.
+    pub signal: i32,
+
+    /// Windows exception code (`NTSTATUS`), `0` for a signal.
+    pub exception_code: u32,
+
+    pub address: u64,
+    pub pid: u64,
+
+    /// When the crash happened, in seconds since the Unix epoch.
+    pub time: u64,
+
+    /// When the crashed run installed the handler, in milliseconds since
+    /// the Unix epoch; `0` if unknown.
+    pub started: u64,
+
+    /// Load bias and end address of the crashed executable (`0` if unknown).
+    pub image_base: u64,
+    pub image_end: u64,
+
+    /// Build ID of the crashed executable.
+    pub build_id: Option<String>,
+
+    /// Size of the allocation that failed before the crash, `0` if none.
+    pub alloc_failed: u64,
+
+    /// Peak RSS in KiB when the allocation failed.
+    pub max_rss_kb: u64,
+
+    /// Raw instruction addresses of the crashing thread, innermost first.
+    pub frames: Vec<u64>,
+
+    /// Minidump written next to the record, if any. Not part of the
+    /// record's text.
+    pub minidump: Option<Vec<u8>>,
+}
+
+impl CrashRecord {
+    /**
+     * Parses a record. Unknown keys are ignored; `signal` or
+     * `exception_code` is required.
+     */
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut signal = None;
+        let mut exception_code = None;
+        let mut record = CrashRecord::default();
+
+        for line in text.lines() {
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+
+            match key {
+                "signal" => signal = value.parse().ok(),
+                "exception_code" => exception_code = value.parse().ok(),
+                "address" => record.address = value.parse().unwrap_or(0),
+                "pid" => record.pid = value.parse().unwrap_or(0),
+                "time" => record.time = value.parse().unwrap_or(0),
+                "started" => record.started = value.parse().unwrap_or(0),
+                "image_base" => record.image_base = value.parse().unwrap_or(0),
+                "image_end" => record.image_end = value.parse().unwrap_or(0),
+                "build_id" => record.build_id = Some(value.to_string()),
+                "alloc_failed" => record.alloc_failed = value.parse().unwrap_or(0),
+                "max_rss_kb" => record.max_rss_kb = value.parse().unwrap_or(0),
+                "frame" => record.frames.extend(value.parse::<u64>().ok()),
+                _ => {}
+            }
+        }
+
+        match (signal, exception_code) {
+            (_, Some(code)) => record.exception_code = code,
+            (Some(signal), None) => record.signal = signal,
+            (None, None) => return None,
+        }
+        Some(record)
+    }
+
+    /**
+     * Builds the `fatal` event reported for this crash.
+     */
+    pub fn to_event(&self) -> EventData {
+        let mut context = json!({
+            "crash": {
+                "faultAddress": format!("{:#x}", self.address),
+                "pid": self.pid,
+                "crashedAt": self.time,
+            }
+        });
+        if self.started != 0 {
+            context["crash"]["uptimeMs"] = json!(self.uptime_ms());
+        }
+
+        let title = if self.is_out_of_memory() {
+            format!(
+                "Out of memory: allocation of {} bytes failed",
+                self.alloc_failed
+            )
+        } else if self.exception_code != 0 {
+            let (name, description) = exception_name(self.exception_code);
+            context["crash"]["exceptionCode"] = json!(format!("{:#010x}", self.exception_code));
+            context["crash"]["exceptionName"] = json!(name);
+            format!("Unhandled exception: {name} ({description})")
+        } else {
+            let (name, description) = signal_name(self.signal);
+            context["crash"]["signal"] = json!(self.signal);
+            context["crash"]["signalName"] = json!(name);
+            format!("Fatal signal: {name} ({description})")
+        };
+
+        if self.is_out_of_memory() {
+            context["outOfMemory"] = json!({
+                "allocationSize": self.alloc_failed,
+                "maxRssKb": self.max_rss_kb,
+            });
+        }
+
+        EventData {
+            title,
+            event_type: Some("fatal".to_string()),
+            backtrace: (!self.frames.is_empty()).then(|| self.backtrace()),
+            context: Some(context),
+            // Breadcrumbs of the current run say nothing about the crash.
+            breadcrumbs: Some(Vec::new()),
+            addons: (self.build_id.is_some() || self.minidump.is_some()).then(|| Addons {
+                build_id: self.build_id.clone(),
+                attachments: self
+                    .minidump
+                    .iter()
+                    .map(|dump| {
+                        let filename = format!("{}.{}", self.pid, crate::DUMP_EXTENSION);
+                        Attachment::new(filename, "application/x-dmp", dump)
+                    })
+                    .collect(),
+                ..Addons::default()
+            }),
+            fingerprint: None,
+            user: None,
+            catcher_version: CATCHER_VERSION.into(),
+        }
+    }
+
+    /**
+     * Time from handler installation to the crash, to the second: `time`
+     * has no finer resolution.
+     */
+    pub fn uptime_ms(&self) -> u64 {
+        self.time.saturating_mul(1000).saturating_sub(self.started)
+    }
+
+    /**
+     * `true` if the process aborted after a failed allocation — std's
+     * default alloc error handler calls `abort()`.
+     */
+    fn is_out_of_memory(&self) -> bool {
+        self.alloc_failed != 0 && self.signal == SIGABRT
+    }
+
+    /**
+     * Address-only frames, to be symbolicated on the backend with the
+     * build ID. The symbols of the crashed binary are not available here:
+     * it may have been replaced since the crash.
+     *
+     * Module offsets are only known for frames inside the main executable.
+     */
+    fn backtrace(&self) -> Vec<BacktraceFrame> {
+        let in_image =
+            |ip: u64| self.image_end != 0 && (self.image_base..self.image_end).contains(&ip);
+
+        self.frames
+            .iter()
+            .map(|&ip| BacktraceFrame {
+                file: None,
+                line: None,
+                column: None,
+                function: None,
+                module: None,
+                instruction_addr: Some(format!("{ip:#x}")),
+                module_offset: in_image(ip).then(|| format!("{:#x}", ip - self.image_base)),
+                inlined: false,
+                in_app: false,
+            })
+            .collect()
+    }
+}
+
+/**
+ * Sends every crash record found in `dir` and deletes it.
+ *
+ * Records are left in place if the SDK is not initialized, so that they
+ * are picked up by the next run instead of being lost. Crashes of a
+ * startup crash loop are flagged, and waited for (see
+ * `hawk_core::is_crash_looping()`).
+ */
+pub(crate) fn send_pending(dir: &Path) {
+    if !hawk_core::is_initialized() {
+        return;
+    }
+
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return;
+    };
+
+    let mut records = Vec::new();
+    for entry in entries.flatten() {
+        let path = entry.path();
+        if path.extension().and_then(|e| e.to_str()) != Some(crate::RECORD_EXTENSION) {
+            continue;
+        }
+
+        let dump = path.with_extension(crate::DUMP_EXTENSION);
+        let minidump = read_minidump(&dump);
+        let _ = std::fs::remove_file(&dump);
+
+        records.extend(
+            read_record(&path)
+                .and_then(|text| CrashRecord::parse(&text))
+                .map(|record| CrashRecord { minidump, ..record }),
+        );
+
+        let _ = std::fs::remove_file(&path);
+    }
+    records.sort_by_key(|record| record.time);
+
+    for record in &records {
+        let mut event = record.to_event();
+        if let Some(context) = event.context.as_mut() {
+            context["previous_run"] = json!(true);
+        }
+        hawk_core::mark_startup_crash(&mut event);
+        hawk_core::capture_event(event);
+    }
+
+    if hawk_core::is_crash_looping() && !records.is_empty() {
+        hawk_core::flush();
+    }
+}
+
+/**
+ * Reads the crash record at `path`, opening it with the spool key if it
+ * was sealed. Records sealed with another key are reported.
+ */
+fn read_record(path: &Path) -> Option<String> {
+    let contents = std::fs::read(path).ok()?;
+    match spool::read(contents, hawk_core::spool_cipher()) {
+        Ok(text) => String::from_utf8(text).ok(),
+        Err(e) => {
+            eprintln!(
+                "[Hawk] Discarding unreadable crash record {}: {e}",
+                path.display()
+            );
+            None
+        }
+    }
+}
+
+/**
+ * Reads the minidump at `path`, if there is one small enough to send.
+ */
+fn read_minidump(path: &Path) -> Option<Vec<u8>> {
+    let size = std::fs::metadata(path).ok()?.len();
+    if size > MAX_MINIDUMP_BYTES {
+        eprintln!(
+            "[Hawk] Minidump {} is too large to send ({size} bytes)",
+            path.display()
+        );
+        return None;
+    }
+    std::fs::read(path).ok()
+}
+
+const SIGABRT: i32 = 6;
+
+/// SIGBUS is the one fatal signal whose number differs between platforms.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+const SIGBUS: i32 = 7;
+#[cfg(not(any(target_os = "linux", target_os = "android")))]
+const SIGBUS: i32 = 10;
+
+/**
+ * Conventional name and short description of a fatal signal.
+ */
+fn signal_name(signal: i32) -> (&'static str, &'static str) {
+    match signal {
+        4 => ("SIGILL", "illegal instruction"),
+        5 => ("SIGTRAP", "trap"),
+        SIGABRT => ("SIGABRT", "abort"),
+        8 => ("SIGFPE", "arithmetic exception"),
+        11 => ("SIGSEGV", "segmentation fault"),
+        SIGBUS => ("SIGBUS", "bus error"),
+        _ => ("signal", "unknown fatal signal"),
+    }
+}
+
+/**
+ * Name and short description of a Windows exception code.
+ */
+fn exception_name(code: u32) -> (&'static str, &'static str) {
+    match code {
+        0xC000_0005 => ("EXCEPTION_ACCESS_VIOLATION", "access violation"),
+        0xC000_0006 => ("EXCEPTION_IN_PAGE_ERROR", "in-page I/O error"),
+        0xC000_001D => ("EXCEPTION_ILLEGAL_INSTRUCTION", "illegal instruction"),
+        0xC000_008C => ("EXCEPTION_ARRAY_BOUNDS_EXCEEDED", "array bounds exceeded"),
+        0xC000_0094 => ("EXCEPTION_INT_DIVIDE_BY_ZERO", "integer division by zero"),
+        0xC000_0095 => ("EXCEPTION_INT_OVERFLOW", "integer overflow"),
+        0xC000_0096 => ("EXCEPTION_PRIV_INSTRUCTION", "privileged instruction"),
+        0xC000_00FD => ("EXCEPTION_STACK_OVERFLOW", "stack overflow"),
+        0xC000_0374 => ("STATUS_HEAP_CORRUPTION", "heap corruption"),
+        0x8000_0002 => ("EXCEPTION_DATATYPE_MISALIGNMENT", "misaligned access"),
+        0x8000_0003 => ("EXCEPTION_BREAKPOINT", "breakpoint"),
+        0xE06D_7363 => ("CPP_EXCEPTION", "unhandled C++ exception"),
+        _ => ("exception", "unknown exception"),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * Verifies parsing of a record as written by the signal handler.
+     */
+    #[test]
+    fn test_parse_record() {
+        let record = CrashRecord::parse("signal=11\naddress=16\npid=42\ntime=1760000000\n")
+            .expect("should parse");
+
+        assert_eq!(
+            record,
+            CrashRecord {
+                signal: 11,
+                address: 16,
+                pid: 42,
+                time: 1_760_000_000,
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            record.to_event().title,
+            "Fatal signal: SIGSEGV (segmentation fault)"
+        );
+    }
+
+    /**
+     * Verifies that frames inside the executable get module offsets, and
+     * that the minidump is attached.
+     */
+    #[test]
+    fn test_record_frames() {
+        let mut record = CrashRecord::parse(
+            "signal=11\npid=42\nimage_base=4096\nimage_end=8192\nbuild_id=abc\nframe=4352\nframe=65536\n",
+        )
+        .expect("should parse");
+        record.minidump = Some(b"MDMP".to_vec());
+
+        let event = record.to_event();
+        let frames = event.backtrace.expect("frames");
+        assert_eq!(frames[0].module_offset.as_deref(), Some("0x100"));
+        assert_eq!(frames[1].instruction_addr.as_deref(), Some("0x10000"));
+        assert_eq!(frames[1].module_offset, None);
+        let addons = event.addons.expect("addons");
+        assert_eq!(addons.build_id.as_deref(), Some("abc"));
+        assert_eq!(
+            addons.attachments,
+            [Attachment::new("42.dmp", "application/x-dmp", b"MDMP")]
+        );
+        assert_eq!(addons.attachments[0].data, "TURNUA==");
+    }
+
+    /**
+     * Verifies that an abort after a failed allocation is reported as OOM.
+     */
+    #[test]
+    fn test_out_of_memory_record() {
+        let record = CrashRecord::parse("signal=6\nalloc_failed=1024\nmax_rss_kb=2048\n")
+            .expect("should parse");
+
+        let event = record.to_event();
+        assert_eq!(
+            event.title,
+            "Out of memory: allocation of 1024 bytes failed"
+        );
+        assert_eq!(
+            event.context.expect("context")["outOfMemory"],
+            json!({ "allocationSize": 1024, "maxRssKb": 2048 })
+        );
+    }
+
+    /**
+     * Verifies that a Windows record is reported by its exception code.
+     */
+    #[test]
+    fn test_windows_exception_record() {
+        let record = CrashRecord::parse("exception_code=3221225477\naddress=0\npid=42\n")
+            .expect("should parse");
+        assert_eq!(record.signal, 0);
+
+        let event = record.to_event();
+        assert_eq!(
+            event.title,
+            "Unhandled exception: EXCEPTION_ACCESS_VIOLATION (access violation)"
+        );
+        let crash = &event.context.expect("context")["crash"];
+        assert_eq!(crash["exceptionCode"], "0xc0000005");
+        assert_eq!(crash["faultAddress"], "0x0");
+        assert_eq!(crash.get("signal"), None);
+    }
+
+    /**
+     * Verifies that a truncated record without a signal is rejected.
+     */
+    #[test]
+    fn test_parse_record_requires_signal() {
+        assert_eq!(CrashRecord::parse("pid=42\n"), None);
+    }
+}
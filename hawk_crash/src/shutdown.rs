@@ -0,0 +1,63 @@
+/*!
+ * Flush on SIGINT / SIGTERM.
+ *
+ * Services usually stop via Ctrl-C or `kill`: the default action of these
+ * signals terminates the process immediately, so the `Guard` never drops
+ * and events still queued for the worker are lost.
+ *
+ * `install_shutdown_flush()` registers handlers (through `signal-hook`)
+ * and waits for the signals on a dedicated thread. On the first one it
+ * flushes the queue (bounded by the flush timeout), then emulates the
+ * default action — the process still terminates by that signal, with the
+ * usual exit status.
+ *
+ * Only for applications that don't handle these signals themselves: a
+ * graceful-shutdown handler would be cut short by the emulated default
+ * action. Such applications should call `hawk::flush()` on their own
+ * shutdown path instead.
+ */
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use signal_hook::consts::{SIGINT, SIGTERM};
+use signal_hook::iterator::Signals;
+
+/// Ensures `install_shutdown_flush()` is idempotent.
+static INSTALLED: AtomicBool = AtomicBool::new(false);
+
+/**
+ * Flushes pending events when the process receives SIGINT or SIGTERM,
+ * then terminates it as the signal's default action would.
+ *
+ * Idempotent — subsequent calls are silent no-ops.
+ */
+pub fn install_shutdown_flush() {
+    if INSTALLED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+
+    let mut signals = match Signals::new([SIGINT, SIGTERM]) {
+        Ok(signals) => signals,
+        Err(e) => {
+            eprintln!("[Hawk] Failed to register shutdown signal handlers: {e}");
+            return;
+        }
+    };
+
+    let spawned = std::thread::Builder::new()
+        .name("hawk-signals".into())
+        .spawn(move || {
+            if let Some(signal) = signals.forever().next() {
+                hawk_core::flush();
+
+                if let Err(e) = signal_hook::low_level::emulate_default_handler(signal) {
+                    eprintln!("[Hawk] Failed to terminate after signal {signal}: {e}");
+                    std::process::exit(128 + signal);
+                }
+            }
+        });
+
+    if let Err(e) = spawned {
+        eprintln!("[Hawk] Failed to spawn signal thread: {e}");
+    }
+}
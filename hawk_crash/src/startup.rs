@@ -0,0 +1,25 @@
+/*!
+ * Launch time of the process, written into crash records (`started=`) so
+ * that crash events carry the uptime at the crash.
+ *
+ * Startup crash loops are detected by `hawk_core` from the run history in
+ * `Options::state_file`, which native crashes and panics alike leave
+ * unclean; see `hawk_core::is_crash_looping()`.
+ */
+
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// When `install()` ran, in milliseconds since the Unix epoch. Written
+/// once, read by the crash handlers.
+pub(crate) static STARTED_MS: AtomicU64 = AtomicU64::new(0);
+
+/**
+ * Records the launch time written into crash records.
+ */
+pub(crate) fn mark_started() {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default();
+    STARTED_MS.store(now.as_millis() as u64, Ordering::Relaxed);
+}
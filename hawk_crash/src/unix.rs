@@ -0,0 +1,450 @@
+/*!
+ * Unix signal handler writing crash records.
+ *
+ * Everything the handler touches is prepared in `install()`: the record
+ * path is a pre-built `CString`, the previous `sigaction`s are stored in a
+ * `OnceLock` (reading it is a plain atomic load). Inside the handler only
+ * async-signal-safe calls are made: `open`, `write`, `pwrite`, `close`,
+ * `clock_gettime`, `getpid`, `gettid`, `sigaction`, `raise`. The minidump
+ * (see `minidump`) is written between the record header and the frames.
+ * With a spool key, lines are sealed in a static buffer (see `record`).
+ *
+ * The one exception is the stack walk (`backtrace::trace_unsynchronized`),
+ * which goes through the platform unwinder. It does not allocate and works
+ * from signal handlers in practice, but it is done last — after the record
+ * header is safely on disk — so a failing walk only loses the frames.
+ */
+
+use std::ffi::CString;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::OnceLock;
+use std::{mem, ptr};
+
+use libc::{c_int, c_void, siginfo_t};
+
+use crate::record::RecordWriter;
+
+/// Fatal signals that bypass the panic hook.
+#[cfg(not(target_vendor = "apple"))]
+const SIGNALS: [c_int; 5] = [
+    libc::SIGSEGV,
+    libc::SIGBUS,
+    libc::SIGILL,
+    libc::SIGFPE,
+    libc::SIGABRT,
+];
+
+/// On Apple platforms, also SIGTRAP: `EXC_BREAKPOINT` from trap instructions
+/// (`brk` on Apple silicon). Elsewhere it belongs to debuggers.
+#[cfg(target_vendor = "apple")]
+const SIGNALS: [c_int; 6] = [
+    libc::SIGSEGV,
+    libc::SIGBUS,
+    libc::SIGILL,
+    libc::SIGFPE,
+    libc::SIGABRT,
+    libc::SIGTRAP,
+];
+
+/// Path of this process's crash record, NUL-terminated for `open(2)`.
+static RECORD_PATH: OnceLock<CString> = OnceLock::new();
+
+/// Handlers that were installed before ours, restored when a signal hits.
+static PREVIOUS: OnceLock<[(c_int, libc::sigaction); SIGNALS.len()]> = OnceLock::new();
+
+/// Set by the first crashing thread; later signals skip the record write.
+static HANDLING: AtomicBool = AtomicBool::new(false);
+
+/// Pre-formatted `build_id=<id>\n` line, empty if there is no build ID.
+static BUILD_ID_LINE: OnceLock<Vec<u8>> = OnceLock::new();
+
+/// Maximum number of raw frame addresses written to the record.
+#[cfg(feature = "backtrace")]
+const MAX_FRAMES: usize = 64;
+
+/// Extra frames walked to skip the handler and the signal trampoline.
+#[cfg(feature = "backtrace")]
+const HANDLER_FRAMES: usize = 8;
+
+/// Load bias and end address of the main executable, `0` if unknown.
+/// Written once in `install()`, only read by the handler.
+static IMAGE_BASE: AtomicUsize = AtomicUsize::new(0);
+static IMAGE_END: AtomicUsize = AtomicUsize::new(0);
+
+/**
+ * Installs the handler for all `SIGNALS`.
+ *
+ * The record path is `<crash_dir>/<pid>.crash`.
+ */
+pub(crate) fn install(crash_dir: &Path) {
+    let path = crash_dir.join(format!(
+        "{}.{}",
+        std::process::id(),
+        crate::RECORD_EXTENSION
+    ));
+    let Ok(path) = CString::new(path.as_os_str().as_bytes()) else {
+        return;
+    };
+    let _ = RECORD_PATH.set(path);
+
+    let build_id_line = hawk_core::build_id()
+        .map(|id| format!("build_id={id}\n").into_bytes())
+        .unwrap_or_default();
+    let _ = BUILD_ID_LINE.set(build_id_line);
+
+    if let Some((base, end)) = image_range() {
+        IMAGE_BASE.store(base, Ordering::Relaxed);
+        IMAGE_END.store(end, Ordering::Relaxed);
+    }
+
+    let mut previous = [(0, unsafe { mem::zeroed::<libc::sigaction>() }); SIGNALS.len()];
+
+    for (slot, &signal) in previous.iter_mut().zip(SIGNALS.iter()) {
+        /*
+         * SA_ONSTACK: run on the alternate signal stack that std sets up
+         * for its threads, so stack overflows can still be recorded.
+         */
+        unsafe {
+            let mut action: libc::sigaction = mem::zeroed();
+            action.sa_sigaction = handle_signal as *const () as usize;
+            action.sa_flags = libc::SA_SIGINFO | libc::SA_ONSTACK;
+            libc::sigemptyset(&mut action.sa_mask);
+
+            *slot = (signal, mem::zeroed());
+            libc::sigaction(signal, &action, &mut slot.1);
+        }
+    }
+
+    let _ = PREVIOUS.set(previous);
+}
+
+/**
+ * The signal handler: write the record, then defer to the previous handler.
+ *
+ * Fault signals raised by the CPU (SIGSEGV from a bad access, …) re-trigger
+ * when the handler returns, now reaching the restored previous handler with
+ * the original `siginfo` — this keeps std's stack overflow message intact.
+ * Signals sent explicitly (`abort()`, `kill`) are re-raised instead.
+ */
+extern "C" fn handle_signal(signal: c_int, info: *mut siginfo_t, context: *mut c_void) {
+    if !HANDLING.swap(true, Ordering::SeqCst) {
+        write_record(signal, info, context);
+    }
+
+    restore_previous(signal);
+
+    let sent_explicitly = info.is_null() || unsafe { (*info).si_code } <= 0;
+    if signal == libc::SIGABRT || sent_explicitly {
+        unsafe {
+            libc::raise(signal);
+        }
+    }
+}
+
+/**
+ * Restores the handler that was installed before ours for `signal`.
+ */
+fn restore_previous(signal: c_int) {
+    if let Some(previous) = PREVIOUS.get() {
+        for (sig, action) in previous.iter() {
+            if *sig == signal {
+                unsafe {
+                    libc::sigaction(signal, action, ptr::null_mut());
+                }
+            }
+        }
+    }
+}
+
+/**
+ * Writes the crash record:
+ *
+ * ```text
+ * signal=11
+ * address=140737488346112
+ * pid=4242
+ * time=1760000000
+ * started=1759999998500
+ * image_base=93824992231424
+ * image_end=93824993439744
+ * build_id=8f1c2b...
+ * alloc_failed=68719476736
+ * max_rss_kb=1048576
+ * frame=93824992301894
+ * frame=...
+ * ```
+ *
+ * `frame` lines hold raw instruction addresses of the crashing thread,
+ * innermost first; `image_*` and `build_id` describe the main executable so
+ * the report can turn them into module offsets for server-side
+ * symbolication, even if the binary was replaced before the next start.
+ * `alloc_*` / `max_rss_kb` are only present after a failed allocation
+ * (see `HawkAlloc`).
+ */
+fn write_record(signal: c_int, info: *mut siginfo_t, context: *mut c_void) {
+    let Some(path) = RECORD_PATH.get() else {
+        return;
+    };
+
+    let fd = unsafe {
+        libc::open(
+            path.as_ptr(),
+            libc::O_WRONLY | libc::O_CREAT | libc::O_TRUNC,
+            0o600 as libc::c_uint,
+        )
+    };
+    if fd < 0 {
+        return;
+    }
+
+    let mut now: libc::timespec = unsafe { mem::zeroed() };
+    unsafe {
+        libc::clock_gettime(libc::CLOCK_REALTIME, &mut now);
+    }
+
+    // SAFETY: only the first crashing thread gets here (`HANDLING`).
+    let mut record = unsafe { RecordWriter::new(|bytes: &[u8]| write_all(fd, bytes)) };
+
+    record.field(b"signal=", signal as u64);
+    record.field(b"address=", fault_address(signal, info) as u64);
+    record.field(b"pid=", unsafe { libc::getpid() } as u64);
+    record.field(b"time=", now.tv_sec as u64);
+    record.field(
+        b"started=",
+        crate::startup::STARTED_MS.load(Ordering::Relaxed),
+    );
+    record.field(b"image_base=", IMAGE_BASE.load(Ordering::Relaxed) as u64);
+    record.field(b"image_end=", IMAGE_END.load(Ordering::Relaxed) as u64);
+    if let Some(line) = BUILD_ID_LINE.get() {
+        record.bytes(line);
+    }
+
+    let failed_alloc = crate::alloc::FAILED_ALLOC_SIZE.load(Ordering::Relaxed);
+    if failed_alloc != 0 {
+        record.field(b"alloc_failed=", failed_alloc as u64);
+        record.field(
+            b"max_rss_kb=",
+            crate::alloc::MAX_RSS_KB.load(Ordering::Relaxed) as u64,
+        );
+    }
+
+    #[cfg(all(
+        any(target_os = "linux", target_os = "android"),
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    ))]
+    crate::minidump::write(signal, info, context);
+
+    record.flush();
+    write_frames(&mut record, context);
+    record.flush();
+
+    unsafe {
+        libc::close(fd);
+    }
+}
+
+/**
+ * Walks the stack and writes one `frame=` line per return address.
+ *
+ * The walk starts inside this handler; frames up to the interrupted
+ * instruction (taken from the signal context, where available) are
+ * skipped. If that instruction is not found, all frames are kept.
+ */
+#[cfg(feature = "backtrace")]
+fn write_frames(record: &mut RecordWriter<impl FnMut(&[u8])>, context: *mut c_void) {
+    let mut frames = [0usize; MAX_FRAMES + HANDLER_FRAMES];
+    let mut count = 0;
+
+    unsafe {
+        backtrace::trace_unsynchronized(|frame| {
+            frames[count] = frame.ip() as usize;
+            count += 1;
+            count < frames.len()
+        });
+    }
+
+    let walked = &frames[..count];
+    let start = interrupted_pc(context)
+        .and_then(|pc| walked.iter().position(|&ip| ip == pc))
+        .unwrap_or(0);
+
+    for &ip in walked[start..].iter().take(MAX_FRAMES) {
+        record.field(b"frame=", ip as u64);
+    }
+}
+
+/**
+ * Without the `backtrace` feature: the report carries no frames.
+ */
+#[cfg(not(feature = "backtrace"))]
+fn write_frames(_record: &mut RecordWriter<impl FnMut(&[u8])>, _context: *mut c_void) {}
+
+/**
+ * Program counter of the interrupted instruction, from the `ucontext_t`
+ * passed to the handler. Only decoded on Linux and Apple platforms,
+ * x86_64 / aarch64.
+ */
+#[cfg(feature = "backtrace")]
+#[allow(unused_variables)]
+fn interrupted_pc(context: *mut c_void) -> Option<usize> {
+    if context.is_null() {
+        return None;
+    }
+
+    #[cfg(all(target_os = "linux", target_arch = "x86_64"))]
+    {
+        let context = unsafe { &*(context as *const libc::ucontext_t) };
+        Some(context.uc_mcontext.gregs[libc::REG_RIP as usize] as usize)
+    }
+
+    #[cfg(all(target_os = "linux", target_arch = "aarch64"))]
+    {
+        let context = unsafe { &*(context as *const libc::ucontext_t) };
+        Some(context.uc_mcontext.pc as usize)
+    }
+
+    #[cfg(all(target_vendor = "apple", target_arch = "x86_64"))]
+    {
+        let context = unsafe { &*(context as *const libc::ucontext_t) };
+        (!context.uc_mcontext.is_null())
+            .then(|| unsafe { (*context.uc_mcontext).__ss.__rip as usize })
+    }
+
+    #[cfg(all(target_vendor = "apple", target_arch = "aarch64"))]
+    {
+        let context = unsafe { &*(context as *const libc::ucontext_t) };
+        (!context.uc_mcontext.is_null())
+            .then(|| unsafe { (*context.uc_mcontext).__ss.__pc as usize })
+    }
+
+    #[cfg(not(all(
+        any(target_os = "linux", target_vendor = "apple"),
+        any(target_arch = "x86_64", target_arch = "aarch64")
+    )))]
+    {
+        None
+    }
+}
+
+/**
+ * Load bias and end address of the main executable.
+ *
+ * `dl_iterate_phdr` reports the main program first; its end is the highest
+ * end of its `PT_LOAD` segments. Linux / Android only.
+ */
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn image_range() -> Option<(usize, usize)> {
+    unsafe extern "C" fn first_image(
+        info: *mut libc::dl_phdr_info,
+        _size: libc::size_t,
+        data: *mut c_void,
+    ) -> c_int {
+        let info = &*info;
+        let range = &mut *(data as *mut Option<(usize, usize)>);
+        let base = info.dlpi_addr as usize;
+
+        let end = (0..info.dlpi_phnum as usize)
+            .map(|i| &*info.dlpi_phdr.add(i))
+            .filter(|phdr| phdr.p_type == libc::PT_LOAD)
+            .map(|phdr| base + (phdr.p_vaddr + phdr.p_memsz) as usize)
+            .max();
+
+        *range = end.map(|end| (base, end));
+        1 // stop after the main program
+    }
+
+    let mut range: Option<(usize, usize)> = None;
+    unsafe {
+        libc::dl_iterate_phdr(Some(first_image), &mut range as *mut _ as *mut c_void);
+    }
+    range
+}
+
+/**
+ * Load bias and end address of the main executable.
+ *
+ * dyld lists the main program first; its end is the highest end of its
+ * `LC_SEGMENT_64` commands, `__PAGEZERO` included, which starts at 0.
+ */
+#[cfg(target_vendor = "apple")]
+fn image_range() -> Option<(usize, usize)> {
+    /// `mach_header_64`, after which the load commands start.
+    #[repr(C)]
+    struct MachHeader64 {
+        magic: u32,
+        cputype: i32,
+        cpusubtype: i32,
+        filetype: u32,
+        ncmds: u32,
+        sizeofcmds: u32,
+        flags: u32,
+        reserved: u32,
+    }
+
+    extern "C" {
+        fn _dyld_get_image_header(image_index: u32) -> *const MachHeader64;
+        fn _dyld_get_image_vmaddr_slide(image_index: u32) -> isize;
+    }
+
+    unsafe {
+        let header = _dyld_get_image_header(0);
+        if header.is_null() {
+            return None;
+        }
+        let slide = _dyld_get_image_vmaddr_slide(0) as usize;
+
+        let mut command = header.add(1) as *const u8;
+        let mut end = None;
+        for _ in 0..(*header).ncmds {
+            let load = &*(command as *const libc::load_command);
+            if load.cmd == libc::LC_SEGMENT_64 {
+                let segment = &*(command as *const libc::segment_command_64);
+                let segment_end = slide + (segment.vmaddr + segment.vmsize) as usize;
+                end = end.max(Some(segment_end));
+            }
+            command = command.add(load.cmdsize as usize);
+        }
+        end.map(|end| (slide, end))
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "android", target_vendor = "apple")))]
+fn image_range() -> Option<(usize, usize)> {
+    None
+}
+
+/**
+ * `write(2)` loop handling short writes. Errors are ignored — there is
+ * nothing useful to do about them inside a crashing process.
+ */
+fn write_all(fd: c_int, mut bytes: &[u8]) {
+    while !bytes.is_empty() {
+        let written = unsafe { libc::write(fd, bytes.as_ptr() as *const c_void, bytes.len()) };
+        if written <= 0 {
+            return;
+        }
+        bytes = &bytes[written as usize..];
+    }
+}
+
+/**
+ * Faulting memory address for SIGSEGV / SIGBUS, `0` otherwise — for other
+ * signals `si_addr` overlaps unrelated `siginfo` fields (e.g. sender pid).
+ */
+pub(crate) fn fault_address(signal: c_int, info: *mut siginfo_t) -> usize {
+    if info.is_null() || (signal != libc::SIGSEGV && signal != libc::SIGBUS) {
+        return 0;
+    }
+
+    #[cfg(any(target_os = "linux", target_os = "android"))]
+    {
+        unsafe { (*info).si_addr() as usize }
+    }
+
+    #[cfg(not(any(target_os = "linux", target_os = "android")))]
+    {
+        unsafe { (*info).si_addr as usize }
+    }
+}
@@ -0,0 +1,347 @@
+/*!
+ * Windows unhandled exception filter writing crash records.
+ *
+ * Access violations, illegal instructions, stack overflows, and other
+ * structured exceptions never reach the panic hook. A filter installed
+ * with `SetUnhandledExceptionFilter` runs on the faulting thread once no
+ * handler claimed the exception, right before Windows Error Reporting
+ * terminates the process.
+ *
+ * As with the Unix handler, everything the filter touches is prepared in
+ * `install()`: the UTF-16 record path, the build ID line, and the image
+ * range. The filter itself only calls `CreateFileW`, `WriteFile`,
+ * `CloseHandle`, `GetSystemTimeAsFileTime`, and `GetCurrentProcessId` —
+ * the heap may be what is corrupted. The stack walk comes after the
+ * record header is on disk. With a spool key, lines are sealed in a static
+ * buffer (see `record`).
+ *
+ * The minidump comes last: `MiniDumpWriteDump` allocates and takes the
+ * loader lock, so it may hang or fail in a badly broken process — by then
+ * the record is complete.
+ *
+ * Fail-fast terminations (`std::process::abort()`, `__fastfail`, security
+ * check failures) skip exception filters entirely and are not recorded.
+ * A panic that aborts is still reported by the panic hook first.
+ */
+
+use std::os::windows::ffi::OsStrExt;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::OnceLock;
+use std::{mem, ptr};
+
+use windows_sys::Win32::Foundation::{
+    CloseHandle, EXCEPTION_ACCESS_VIOLATION, EXCEPTION_IN_PAGE_ERROR, FILETIME, GENERIC_WRITE,
+    HANDLE, INVALID_HANDLE_VALUE,
+};
+use windows_sys::Win32::Storage::FileSystem::{
+    CreateFileW, WriteFile, CREATE_ALWAYS, FILE_ATTRIBUTE_NORMAL,
+};
+use windows_sys::Win32::System::Diagnostics::Debug::{
+    MiniDumpNormal, MiniDumpWithThreadInfo, MiniDumpWriteDump, SetUnhandledExceptionFilter,
+    EXCEPTION_CONTINUE_SEARCH, EXCEPTION_POINTERS, LPTOP_LEVEL_EXCEPTION_FILTER,
+    MINIDUMP_EXCEPTION_INFORMATION,
+};
+use windows_sys::Win32::System::LibraryLoader::GetModuleHandleW;
+use windows_sys::Win32::System::ProcessStatus::{K32GetModuleInformation, MODULEINFO};
+use windows_sys::Win32::System::SystemInformation::GetSystemTimeAsFileTime;
+use windows_sys::Win32::System::Threading::{
+    GetCurrentProcess, GetCurrentProcessId, GetCurrentThreadId,
+};
+
+use crate::record::RecordWriter;
+
+/// Path of this process's crash record, UTF-16 and NUL-terminated.
+static RECORD_PATH: OnceLock<Vec<u16>> = OnceLock::new();
+
+/// Path of this process's minidump, if minidumps are on.
+static DUMP_PATH: OnceLock<Vec<u16>> = OnceLock::new();
+
+/// Filter that was installed before ours, called after the record is written.
+static PREVIOUS: OnceLock<LPTOP_LEVEL_EXCEPTION_FILTER> = OnceLock::new();
+
+/// Set by the first crashing thread; later exceptions skip the record write.
+static HANDLING: AtomicBool = AtomicBool::new(false);
+
+/// Pre-formatted `build_id=<id>\n` line, empty if there is no build ID.
+static BUILD_ID_LINE: OnceLock<Vec<u8>> = OnceLock::new();
+
+/// Maximum number of raw frame addresses written to the record.
+#[cfg(feature = "backtrace")]
+const MAX_FRAMES: usize = 64;
+
+/// Extra frames walked to skip the filter and the exception dispatcher.
+#[cfg(feature = "backtrace")]
+const HANDLER_FRAMES: usize = 8;
+
+/// Base and end address of the main executable, `0` if unknown.
+/// Written once in `install()`, only read by the filter.
+static IMAGE_BASE: AtomicUsize = AtomicUsize::new(0);
+static IMAGE_END: AtomicUsize = AtomicUsize::new(0);
+
+/// 100-nanosecond intervals between 1601-01-01 and the Unix epoch.
+const FILETIME_UNIX_EPOCH: u64 = 116_444_736_000_000_000;
+
+/**
+ * Installs the unhandled exception filter.
+ *
+ * The record path is `<crash_dir>/<pid>.crash`, and the minidump's
+ * `<crash_dir>/<pid>.dmp`.
+ */
+pub(crate) fn install(crash_dir: &Path, minidumps: bool) {
+    let path = |extension: &str| -> Vec<u16> {
+        let path = crash_dir.join(format!("{}.{extension}", std::process::id()));
+        path.as_os_str().encode_wide().chain(Some(0)).collect()
+    };
+    let _ = RECORD_PATH.set(path(crate::RECORD_EXTENSION));
+    if minidumps {
+        let _ = DUMP_PATH.set(path(crate::DUMP_EXTENSION));
+    }
+
+    let build_id_line = hawk_core::build_id()
+        .map(|id| format!("build_id={id}\n").into_bytes())
+        .unwrap_or_default();
+    let _ = BUILD_ID_LINE.set(build_id_line);
+
+    if let Some((base, end)) = image_range() {
+        IMAGE_BASE.store(base, Ordering::Relaxed);
+        IMAGE_END.store(end, Ordering::Relaxed);
+    }
+
+    let previous = unsafe { SetUnhandledExceptionFilter(Some(handle_exception)) };
+    let _ = PREVIOUS.set(previous);
+}
+
+/**
+ * The filter: write the record, then defer to the previous filter, or let
+ * Windows terminate the process as it would have without us.
+ */
+unsafe extern "system" fn handle_exception(pointers: *const EXCEPTION_POINTERS) -> i32 {
+    if !HANDLING.swap(true, Ordering::SeqCst) {
+        write_record(pointers);
+        write_minidump(pointers);
+    }
+
+    match PREVIOUS.get() {
+        Some(Some(previous)) => previous(pointers),
+        _ => EXCEPTION_CONTINUE_SEARCH,
+    }
+}
+
+/**
+ * Writes the crash record as `unix::write_record` does, with
+ * `exception_code` in place of `signal`:
+ *
+ * ```text
+ * exception_code=3221225477
+ * address=0
+ * pid=4242
+ * time=1760000000
+ * started=1759999998500
+ * image_base=140696549949440
+ * image_end=140696551112704
+ * build_id=8f1c2b...
+ * frame=140696549973126
+ * frame=...
+ * ```
+ *
+ * `address` is the inaccessible data address for access violations and
+ * in-page errors, and the faulting instruction otherwise.
+ */
+fn write_record(pointers: *const EXCEPTION_POINTERS) {
+    let Some(path) = RECORD_PATH.get() else {
+        return;
+    };
+    if pointers.is_null() || unsafe { (*pointers).ExceptionRecord.is_null() } {
+        return;
+    }
+    let exception = unsafe { &*(*pointers).ExceptionRecord };
+
+    let file = create_file(path);
+    if file == INVALID_HANDLE_VALUE {
+        return;
+    }
+
+    let mut now: FILETIME = unsafe { mem::zeroed() };
+    unsafe {
+        GetSystemTimeAsFileTime(&mut now);
+    }
+    let ticks = (u64::from(now.dwHighDateTime) << 32) | u64::from(now.dwLowDateTime);
+
+    // SAFETY: only the first crashing thread gets here (`HANDLING`).
+    let mut record = unsafe { RecordWriter::new(|bytes: &[u8]| write_all(file, bytes)) };
+
+    let code = exception.ExceptionCode;
+    let address = if (code == EXCEPTION_ACCESS_VIOLATION || code == EXCEPTION_IN_PAGE_ERROR)
+        && exception.NumberParameters >= 2
+    {
+        exception.ExceptionInformation[1]
+    } else {
+        exception.ExceptionAddress as usize
+    };
+
+    record.field(b"exception_code=", u64::from(code as u32));
+    record.field(b"address=", address as u64);
+    record.field(b"pid=", u64::from(unsafe { GetCurrentProcessId() }));
+    record.field(
+        b"time=",
+        ticks.saturating_sub(FILETIME_UNIX_EPOCH) / 10_000_000,
+    );
+    record.field(
+        b"started=",
+        crate::startup::STARTED_MS.load(Ordering::Relaxed),
+    );
+    record.field(b"image_base=", IMAGE_BASE.load(Ordering::Relaxed) as u64);
+    record.field(b"image_end=", IMAGE_END.load(Ordering::Relaxed) as u64);
+    if let Some(line) = BUILD_ID_LINE.get() {
+        record.bytes(line);
+    }
+
+    let failed_alloc = crate::alloc::FAILED_ALLOC_SIZE.load(Ordering::Relaxed);
+    if failed_alloc != 0 {
+        record.field(b"alloc_failed=", failed_alloc as u64);
+        record.field(
+            b"max_rss_kb=",
+            crate::alloc::MAX_RSS_KB.load(Ordering::Relaxed) as u64,
+        );
+    }
+
+    record.flush();
+    write_frames(&mut record, exception.ExceptionAddress as usize);
+    record.flush();
+
+    unsafe {
+        CloseHandle(file);
+    }
+}
+
+/**
+ * Writes the minidump of every thread with `MiniDumpWriteDump`, if
+ * minidumps are on.
+ */
+fn write_minidump(pointers: *const EXCEPTION_POINTERS) {
+    let Some(path) = DUMP_PATH.get() else {
+        return;
+    };
+    let file = create_file(path);
+    if file == INVALID_HANDLE_VALUE {
+        return;
+    }
+
+    let exception = MINIDUMP_EXCEPTION_INFORMATION {
+        ThreadId: unsafe { GetCurrentThreadId() },
+        ExceptionPointers: pointers as *mut EXCEPTION_POINTERS,
+        ClientPointers: 0,
+    };
+    unsafe {
+        MiniDumpWriteDump(
+            GetCurrentProcess(),
+            GetCurrentProcessId(),
+            file,
+            MiniDumpNormal | MiniDumpWithThreadInfo,
+            &exception,
+            ptr::null(),
+            ptr::null(),
+        );
+        CloseHandle(file);
+    }
+}
+
+/**
+ * Opens `path` (UTF-16, NUL-terminated) for writing, truncating it.
+ */
+fn create_file(path: &[u16]) -> HANDLE {
+    unsafe {
+        CreateFileW(
+            path.as_ptr(),
+            GENERIC_WRITE,
+            0,
+            ptr::null(),
+            CREATE_ALWAYS,
+            FILE_ATTRIBUTE_NORMAL,
+            ptr::null_mut(),
+        )
+    }
+}
+
+/**
+ * Walks the stack and writes one `frame=` line per return address.
+ *
+ * The walk starts inside this filter; frames up to the faulting
+ * instruction are skipped. If it is not found, all frames are kept.
+ */
+#[cfg(feature = "backtrace")]
+fn write_frames(record: &mut RecordWriter<impl FnMut(&[u8])>, faulting_ip: usize) {
+    let mut frames = [0usize; MAX_FRAMES + HANDLER_FRAMES];
+    let mut count = 0;
+
+    unsafe {
+        backtrace::trace_unsynchronized(|frame| {
+            frames[count] = frame.ip() as usize;
+            count += 1;
+            count < frames.len()
+        });
+    }
+
+    let walked = &frames[..count];
+    let start = walked.iter().position(|&ip| ip == faulting_ip).unwrap_or(0);
+
+    for &ip in walked[start..].iter().take(MAX_FRAMES) {
+        record.field(b"frame=", ip as u64);
+    }
+}
+
+/**
+ * Without the `backtrace` feature: the report carries no frames.
+ */
+#[cfg(not(feature = "backtrace"))]
+fn write_frames(_record: &mut RecordWriter<impl FnMut(&[u8])>, _faulting_ip: usize) {}
+
+/**
+ * Base and end address of the main executable's image.
+ */
+fn image_range() -> Option<(usize, usize)> {
+    unsafe {
+        let module = GetModuleHandleW(ptr::null());
+        if module.is_null() {
+            return None;
+        }
+
+        let mut info: MODULEINFO = mem::zeroed();
+        if K32GetModuleInformation(
+            GetCurrentProcess(),
+            module,
+            &mut info,
+            mem::size_of::<MODULEINFO>() as u32,
+        ) == 0
+        {
+            return None;
+        }
+
+        let base = info.lpBaseOfDll as usize;
+        Some((base, base + info.SizeOfImage as usize))
+    }
+}
+
+/**
+ * `WriteFile` loop handling short writes. Errors are ignored — there is
+ * nothing useful to do about them inside a crashing process.
+ */
+fn write_all(file: HANDLE, mut bytes: &[u8]) {
+    while !bytes.is_empty() {
+        let mut written = 0u32;
+        let ok = unsafe {
+            WriteFile(
+                file,
+                bytes.as_ptr(),
+                bytes.len() as u32,
+                &mut written,
+                ptr::null_mut(),
+            )
+        };
+        if ok == 0 || written == 0 {
+            return;
+        }
+        bytes = &bytes[written as usize..];
+    }
+}
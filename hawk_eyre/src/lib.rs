@@ -0,0 +1,234 @@
+/*!
+ * Hawk eyre integration — an `EyreHandler` reporting `eyre::Report`s.
+ *
+ * `HawkHandler` wraps the handler that would otherwise format reports
+ * (eyre's default one, or color-eyre's) and sends a report to Hawk the
+ * first time it is printed with `{:?}`. That covers the common CLI setup
+ * where `main` returns `eyre::Result<()>` and the runtime prints the error
+ * on exit.
+ *
+ * The event carries:
+ *
+ * - the chain as the title, outermost context first;
+ * - the event `type` derived from the root cause by `hawk_core::error_type()`;
+ * - the fully rendered report under `eyre.report` — with color-eyre, its
+ *   sections, suggestions, notes, span trace, and backtrace — with color
+ *   codes stripped;
+ * - the location where the report was created, as the only frame. The
+ *   stack at print time is the runtime's exit path, so none is captured.
+ *
+ * Returning from `main` drops the `Guard` before the error is printed; the
+ * event is delivered by the `flush_on_exit` handler (Unix) in that case.
+ *
+ * # Example
+ *
+ * ```ignore
+ * // eyre's default formatting
+ * hawk_eyre::install()?;
+ *
+ * // color-eyre: install its panic hook BEFORE hawk::init(), which chains it
+ * let (panic_hook, eyre_hook) = color_eyre::config::HookBuilder::default().into_hooks();
+ * panic_hook.install();
+ * hawk_eyre::install_with(eyre_hook.into_eyre_hook())?;
+ * ```
+ */
+
+use std::error::Error;
+use std::fmt;
+use std::panic::Location;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use eyre::{DefaultHandler, EyreHandler, InstallError};
+use hawk_core::{EventData, CATCHER_VERSION};
+use serde_json::json;
+
+/// Event type used when the root cause's type cannot be determined,
+/// e.g. for `eyre!("message")` errors.
+const DEFAULT_ERROR_TYPE: &str = "Error";
+
+// ---------------------------------------------------------------------------
+// Public API
+// ---------------------------------------------------------------------------
+
+/**
+ * Installs `HawkHandler` around eyre's default handler.
+ *
+ * Fails if an eyre hook is already set, or a `Report` has already been
+ * created (which installs the default hook implicitly).
+ */
+pub fn install() -> Result<(), InstallError> {
+    install_with(DefaultHandler::default_with)
+}
+
+/**
+ * Installs `HawkHandler` around the handlers created by `hook` — e.g.
+ * color-eyre's `EyreHook::into_eyre_hook()`.
+ */
+pub fn install_with(
+    hook: impl Fn(&(dyn Error + 'static)) -> Box<dyn EyreHandler> + Send + Sync + 'static,
+) -> Result<(), InstallError> {
+    eyre::set_hook(Box::new(move |error| {
+        Box::new(HawkHandler::new(hook(error)))
+    }))
+}
+
+// ---------------------------------------------------------------------------
+// HawkHandler
+// ---------------------------------------------------------------------------
+
+/**
+ * `EyreHandler` that formats through an inner handler and reports the
+ * rendered report to Hawk.
+ */
+pub struct HawkHandler {
+    inner: Box<dyn EyreHandler>,
+
+    /// Where the report was created, if eyre tracked it.
+    location: Option<&'static Location<'static>>,
+
+    /// Set once the report has been sent: a report may be printed many
+    /// times, but is one error.
+    reported: AtomicBool,
+}
+
+impl HawkHandler {
+    pub fn new(inner: Box<dyn EyreHandler>) -> Self {
+        Self {
+            inner,
+            location: None,
+            reported: AtomicBool::new(false),
+        }
+    }
+}
+
+impl EyreHandler for HawkHandler {
+    fn debug(&self, error: &(dyn Error + 'static), f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.reported.swap(true, Ordering::Relaxed) {
+            let rendered = InnerDebug {
+                handler: &*self.inner,
+                error,
+            }
+            .to_string();
+            hawk_core::capture_event(build_event(error, &strip_ansi(&rendered), self.location));
+        }
+
+        self.inner.debug(error, f)
+    }
+
+    fn display(&self, error: &(dyn Error + 'static), f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.inner.display(error, f)
+    }
+
+    fn track_caller(&mut self, location: &'static Location<'static>) {
+        self.location = Some(location);
+        self.inner.track_caller(location);
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Internal helpers
+// ---------------------------------------------------------------------------
+
+/**
+ * Renders a report through the inner handler's `debug`.
+ */
+struct InnerDebug<'a> {
+    handler: &'a dyn EyreHandler,
+    error: &'a (dyn Error + 'static),
+}
+
+impl fmt::Display for InnerDebug<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.handler.debug(self.error, f)
+    }
+}
+
+/**
+ * Builds the event for a report rendered as `rendered`.
+ */
+fn build_event(
+    error: &(dyn Error + 'static),
+    rendered: &str,
+    location: Option<&'static Location<'static>>,
+) -> EventData {
+    let chain: Vec<String> = eyre::Chain::new(error).map(ToString::to_string).collect();
+    let root_type = eyre::Chain::new(error)
+        .last()
+        .and_then(hawk_core::error_type);
+
+    EventData {
+        title: chain.join(": "),
+        event_type: Some(root_type.unwrap_or_else(|| DEFAULT_ERROR_TYPE.into())),
+        backtrace: location.map(|location| vec![hawk_core::frame_from_location(location)]),
+        context: Some(json!({ "eyre": { "chain": chain, "report": rendered } })),
+        breadcrumbs: None,
+        addons: None,
+        fingerprint: None,
+        user: None,
+        catcher_version: CATCHER_VERSION.into(),
+    }
+}
+
+/**
+ * Removes ANSI escape sequences (`ESC [ ... letter`), which color-eyre
+ * emits for colored terminal output.
+ */
+fn strip_ansi(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c == '\u{1b}' {
+            if chars.next() == Some('[') {
+                chars.by_ref().find(|c| c.is_ascii_alphabetic());
+            }
+        } else {
+            stripped.push(c);
+        }
+    }
+
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use eyre::WrapErr;
+
+    /**
+     * Verifies that the handler prints the inner handler's output unchanged
+     * and that the event carries the chain and the stripped report text,
+     * typed `DEFAULT_ERROR_TYPE` for a plain message.
+     */
+    #[test]
+    fn test_handler_and_event() {
+        let report = "x1".parse::<u32>().wrap_err("loading config").unwrap_err();
+        let error: &(dyn Error + 'static) = report.as_ref();
+
+        let handler = HawkHandler::new(DefaultHandler::default_with(error));
+        let printed = InnerDebug {
+            handler: &handler,
+            error,
+        }
+        .to_string();
+        let expected = InnerDebug {
+            handler: &*handler.inner,
+            error,
+        }
+        .to_string();
+        assert_eq!(printed, expected);
+
+        let event = build_event(
+            error,
+            &strip_ansi("\u{1b}[31mloading config\u{1b}[0m"),
+            None,
+        );
+        assert_eq!(event.title, "loading config: invalid digit found in string");
+        assert_eq!(event.event_type.as_deref(), Some("ParseIntError"));
+        assert_eq!(event.context.unwrap()["eyre"]["report"], "loading config");
+
+        let message = eyre::eyre!("no route for {}", "/admin");
+        let event = build_event(message.as_ref(), "", None);
+        assert_eq!(event.event_type.as_deref(), Some(DEFAULT_ERROR_TYPE));
+    }
+}
@@ -0,0 +1,255 @@
+/*!
+ * Hawk log backend — `log` crate integration.
+ *
+ * `HawkLogger` implements `log::Log`:
+ *
+ * - Records at or above `LoggerOptions::event_level` (default `Error`) are
+ *   sent as Hawk events, with a backtrace from the logging call site and
+ *   the record's target / module / location as context.
+ * - Records at or above `LoggerOptions::breadcrumb_level` (default `Info`)
+ *   are recorded as breadcrumbs, so the next event shows what led up to it.
+ * - Forwarded records are mirrored into the log tail attached to fatal
+ *   events, if `Options::log_tail_bytes` is set.
+ * - Records from `LoggerOptions::ignore_targets` are never forwarded to
+ *   Hawk — by default the SDK's own HTTP stack, whose error logs about a
+ *   failed delivery must not produce more deliveries.
+ *
+ * It can run on its own (`init()`) or wrap another logger such as
+ * `env_logger`, which keeps receiving every record it has enabled.
+ *
+ * # Example
+ *
+ * ```ignore
+ * // Standalone
+ * hawk_log::init(hawk_log::LoggerOptions::default())?;
+ *
+ * // Alongside env_logger
+ * let env_logger = env_logger::Builder::from_default_env().build();
+ * let max_level = env_logger.filter().max(log::LevelFilter::Info);
+ * log::set_boxed_logger(Box::new(hawk_log::HawkLogger::wrap(env_logger, Default::default())))?;
+ * log::set_max_level(max_level);
+ * ```
+ */
+
+use hawk_core::forwarding::{forward_once, matches_target};
+use hawk_core::{Breadcrumb, EventData, CATCHER_VERSION};
+use log::{Level, LevelFilter, Log, Metadata, Record, SetLoggerError};
+use serde_json::json;
+
+// ---------------------------------------------------------------------------
+// Options
+// ---------------------------------------------------------------------------
+
+/**
+ * Configuration for `HawkLogger`.
+ */
+pub struct LoggerOptions {
+    /// Records at or above this level are sent as events.
+    /// Defaults to `Error`; `Off` disables events.
+    pub event_level: LevelFilter,
+
+    /// Records at or above this level (and below `event_level`) are
+    /// recorded as breadcrumbs. Defaults to `Info`; `Off` disables them.
+    pub breadcrumb_level: LevelFilter,
+
+    /// Target prefixes (`"hyper"`, `"my_app::noisy"`) whose records are not
+    /// forwarded to Hawk. Defaults to the SDK's HTTP stack: `ureq`, `rustls`.
+    pub ignore_targets: Vec<String>,
+}
+
+impl Default for LoggerOptions {
+    fn default() -> Self {
+        Self {
+            event_level: LevelFilter::Error,
+            breadcrumb_level: LevelFilter::Info,
+            ignore_targets: vec!["ureq".to_string(), "rustls".to_string()],
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// HawkLogger
+// ---------------------------------------------------------------------------
+
+/**
+ * `log::Log` implementation forwarding records to Hawk, optionally
+ * wrapping another logger.
+ */
+pub struct HawkLogger {
+    inner: Option<Box<dyn Log>>,
+    options: LoggerOptions,
+}
+
+impl HawkLogger {
+    /**
+     * Creates a logger that only forwards to Hawk.
+     */
+    pub fn new(options: LoggerOptions) -> Self {
+        Self {
+            inner: None,
+            options,
+        }
+    }
+
+    /**
+     * Creates a logger that passes every record to `inner` (subject to
+     * `inner`'s own filtering) and forwards to Hawk as well.
+     */
+    pub fn wrap(inner: impl Log + 'static, options: LoggerOptions) -> Self {
+        Self {
+            inner: Some(Box::new(inner)),
+            options,
+        }
+    }
+
+    /**
+     * Most verbose level Hawk itself is interested in — the minimum to
+     * pass to `log::set_max_level()`.
+     */
+    pub fn max_level(&self) -> LevelFilter {
+        self.options.event_level.max(self.options.breadcrumb_level)
+    }
+
+    /**
+     * Whether a record with this metadata is forwarded to Hawk.
+     */
+    fn forwards(&self, metadata: &Metadata<'_>) -> bool {
+        metadata.level() <= self.max_level()
+            && !self
+                .options
+                .ignore_targets
+                .iter()
+                .any(|prefix| matches_target(metadata.target(), prefix))
+    }
+
+    /**
+     * Sends the record as an event or records it as a breadcrumb.
+     */
+    fn forward(&self, record: &Record<'_>) {
+        let message = record.args().to_string();
+        if hawk_core::log_tail_enabled() {
+            hawk_core::record_log_line(&format!(
+                "{} {}: {message}",
+                record.level(),
+                record.target()
+            ));
+        }
+
+        if record.level() <= self.options.event_level && hawk_core::accepts_events() {
+            let event = EventData {
+                title: message.clone(),
+                event_type: Some(level(record.level()).as_str().to_string()),
+                backtrace: None,
+                context: Some(json!({
+                    "log": {
+                        "target": record.target(),
+                        "module": record.module_path(),
+                        "file": record.file(),
+                        "line": record.line(),
+                    }
+                })),
+                breadcrumbs: None,
+                addons: None,
+                fingerprint: None,
+                user: None,
+                catcher_version: CATCHER_VERSION.into(),
+            };
+
+            hawk_core::capture_event_with_backtrace(event, hawk_core::Backtrace::new_unresolved());
+        }
+
+        /*
+         * Events are recorded as breadcrumbs too, so that a later event
+         * shows the errors logged before it.
+         */
+        hawk_core::add_breadcrumb(Breadcrumb {
+            category: Some(record.target().to_string()),
+            level: Some(level(record.level()).as_str().to_string()),
+            ..Breadcrumb::at(hawk_core::now_millis(), message)
+        });
+    }
+}
+
+impl Log for HawkLogger {
+    fn enabled(&self, metadata: &Metadata<'_>) -> bool {
+        self.forwards(metadata)
+            || self
+                .inner
+                .as_ref()
+                .is_some_and(|inner| inner.enabled(metadata))
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        if let Some(inner) = &self.inner {
+            if inner.enabled(record.metadata()) {
+                inner.log(record);
+            }
+        }
+
+        if self.forwards(record.metadata()) {
+            forward_once(|| self.forward(record));
+        }
+    }
+
+    fn flush(&self) {
+        if let Some(inner) = &self.inner {
+            inner.flush();
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Public API
+// ---------------------------------------------------------------------------
+
+/**
+ * Installs a standalone `HawkLogger` as the global logger and sets the
+ * max log level accordingly.
+ *
+ * Fails if a global logger is already set. To keep another logger, build
+ * it, `HawkLogger::wrap()` it, and install the result yourself.
+ */
+pub fn init(options: LoggerOptions) -> Result<(), SetLoggerError> {
+    let logger = HawkLogger::new(options);
+    let max_level = logger.max_level();
+
+    log::set_boxed_logger(Box::new(logger))?;
+    log::set_max_level(max_level);
+    Ok(())
+}
+
+// ---------------------------------------------------------------------------
+// Internal helpers
+// ---------------------------------------------------------------------------
+
+/**
+ * Hawk severity of a log level.
+ */
+fn level(level: Level) -> hawk_core::Level {
+    match level {
+        Level::Error => hawk_core::Level::Error,
+        Level::Warn => hawk_core::Level::Warn,
+        Level::Info => hawk_core::Level::Info,
+        Level::Debug | Level::Trace => hawk_core::Level::Debug,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * Verifies level and target filtering of forwarded records.
+     */
+    #[test]
+    fn test_forwards_filters_level_and_target() {
+        let logger = HawkLogger::new(LoggerOptions::default());
+        let metadata = |level, target| Metadata::builder().level(level).target(target).build();
+
+        assert!(logger.forwards(&metadata(Level::Error, "my_app")));
+        assert!(logger.forwards(&metadata(Level::Info, "my_app::db")));
+        assert!(!logger.forwards(&metadata(Level::Debug, "my_app")));
+        assert!(!logger.forwards(&metadata(Level::Error, "ureq::unversioned")));
+        assert!(logger.forwards(&metadata(Level::Error, "ureq_like")));
+    }
+}
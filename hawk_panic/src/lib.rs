@@ -5,10 +5,68 @@
  * custom `std::panic::set_hook` handler. When a panic occurs, it:
  *
  * 1. Extracts the panic message, source location, and thread name.
- * 2. Captures a backtrace at the panic site.
- * 3. Builds an `EventData` with `type = "fatal"` and sends it via
- *    `hawk_core::capture_event()`.
- * 4. Calls the previous panic hook (so the default stderr output is preserved).
+ * 2. Captures an unresolved backtrace at the panic site, topped by a
+ *    synthetic frame for the exact panic location.
+ * 3. Builds an `EventData` typed by `hawk_core::panic_type()` — e.g.
+ *    `UnwrapNone`, `IndexOutOfBounds`, `AssertionFailed`, otherwise
+ *    `"fatal"` — and sends it via
+ *    `hawk_core::capture_event_with_backtrace()` — symbols are resolved
+ *    on the worker thread.
+ * 4. Flushes the queue synchronously if the binary is built with
+ *    `panic = "abort"` (or `set_flush_on_panic(true)` was called).
+ * 5. Calls the previous panic hook (so the default stderr output is preserved).
+ *
+ * # panic = "abort"
+ *
+ * With `panic = "abort"` the process aborts right after the hook returns:
+ * no unwinding, so the `Guard` is never dropped and queued events would be
+ * lost. The hook detects this at compile time (`cfg!(panic = "abort")`)
+ * and blocks until the worker has delivered the event (bounded by the
+ * flush timeout).
+ *
+ * # Custom hooks
+ *
+ * Applications that must own the panic hook call `capture()` from it
+ * instead of installing this one.
+ *
+ * # Scoped context
+ *
+ * `with_context()` attaches extra context to any panic raised on the
+ * current thread while a closure runs — used by integrations such as
+ * `hawk_tokio` to tag panics with the task they happened in.
+ *
+ * # Custom payloads
+ *
+ * `panic!("...")` payloads are `&str` / `String`. Frameworks that call
+ * `std::panic::panic_any()` with their own types can register an
+ * extractor via `register_payload_extractor()` so their panics get a
+ * meaningful title instead of `<unknown panic>`.
+ *
+ * # Suppression
+ *
+ * `suppress()` disables capture on the current thread while a closure
+ * runs — for tests that panic on purpose or `catch_unwind`-based control
+ * flow. `set_ignore_modules()` ignores panics raised by given crates or
+ * modules everywhere. `uninstall()` removes the hook entirely.
+ *
+ * # Double panics
+ *
+ * A panic in `Drop` while unwinding (or any panic escaping an
+ * `extern "C"` function) cannot unwind: std invokes the hook once more
+ * with a fixed message and aborts the process. That last invocation is
+ * reported as a separate `abort` event carrying the messages of the panics
+ * that led to it, and the queue is flushed before the abort.
+ *
+ * # Thread information
+ *
+ * Panic events carry the panicking thread's name and IDs under
+ * `context.thread`. With `set_thread_inventory(true)`, all threads of the
+ * process are listed under `context.threads` (Linux only).
+ *
+ * # Browser (wasm32)
+ *
+ * On `wasm32-unknown-unknown` panics are also printed to the browser
+ * console, and events carry the JavaScript stack under `context.jsStack`.
  *
  * # Recursion safety
  *
@@ -16,12 +74,23 @@
  * if `hawk_core::capture_event` itself were to panic.
  */
 
-use std::cell::Cell;
+#![deny(unsafe_code)]
+
+mod threads;
+/* `#[wasm_bindgen]` extern blocks expand to unsafe glue code. */
+#[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+#[allow(unsafe_code)]
+mod wasm;
+
+use std::any::Any;
+use std::cell::{Cell, RefCell};
 use std::panic;
 use std::panic::PanicHookInfo;
 use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
 
 use hawk_core::{EventData, CATCHER_VERSION};
+use serde_json::{Map, Value};
 
 // ---------------------------------------------------------------------------
 // Guards
@@ -31,14 +100,85 @@ use hawk_core::{EventData, CATCHER_VERSION};
 /// won't stack hooks and produce duplicate events per panic.
 static INSTALLED: AtomicBool = AtomicBool::new(false);
 
+/// Signature of a panic hook, as returned by `panic::take_hook()`.
+type PanicHook = Box<dyn Fn(&PanicHookInfo<'_>) + Send + Sync + 'static>;
+
+/// The hook that was active before `install()`. Called after ours, and
+/// restored by `uninstall()`.
+static PREVIOUS_HOOK: Mutex<Option<Arc<PanicHook>>> = Mutex::new(None);
+
+/// Turns a custom panic payload into a message, `None` if the payload is
+/// not of the extractor's type.
+type PayloadExtractor = Box<dyn Fn(&(dyn Any + Send)) -> Option<String> + Send + Sync>;
+
+/// Extractors added via `register_payload_extractor()`, tried in order.
+static PAYLOAD_EXTRACTORS: RwLock<Vec<PayloadExtractor>> = RwLock::new(Vec::new());
+
+/// Set via `set_flush_on_panic()`; implied by `panic = "abort"`.
+static FLUSH_ON_PANIC: AtomicBool = AtomicBool::new(cfg!(panic = "abort"));
+
+/// Set via `set_thread_inventory()`.
+static THREAD_INVENTORY: AtomicBool = AtomicBool::new(false);
+
+/// Module prefixes set via `set_ignore_modules()`.
+static IGNORE_MODULES: RwLock<Vec<String>> = RwLock::new(Vec::new());
+
+/// Crates of the standard library, whose frames sit above the code that
+/// panicked.
+const STD_CRATES: [&str; 5] = ["std", "core", "alloc", "panic_unwind", "__rustc"];
+
+/// Function std's panic machinery runs the hook from; the frames below it
+/// are those of the panic itself (as in std's short backtraces).
+const SHORT_BACKTRACE_END: &str = "__rust_end_short_backtrace";
+
+/// Names of the `hawk_core` background threads, which must never wait on
+/// themselves.
+const WORKER_THREAD_NAMES: [&str; 3] = ["hawk-symbolicator", "hawk-worker", "hawk-sender"];
+
 thread_local! {
     /**
      * Per-thread flag that prevents re-entrancy into the panic hook.
      * Breaks recursion if `hawk_core::capture_event` itself panics.
      */
     static IN_HOOK: Cell<bool> = const { Cell::new(false) };
+
+    /**
+     * Nesting depth of `suppress()` calls on this thread. Panics are not
+     * captured while it is non-zero.
+     */
+    static SUPPRESS_DEPTH: Cell<usize> = const { Cell::new(0) };
+
+    /**
+     * Context objects pushed by `with_context()`, outermost first.
+     * Merged into the `context` of panic events raised on this thread.
+     */
+    static CONTEXT_STACK: RefCell<Vec<Arc<Value>>> = const { RefCell::new(Vec::new()) };
+
+    /**
+     * Messages of the most recent panics on this thread, oldest first,
+     * capped at `RECENT_PANICS_CAPACITY`. Lets the final non-unwinding
+     * panic of a double panic report the panics that caused it.
+     */
+    static RECENT_PANICS: RefCell<Vec<String>> = const { RefCell::new(Vec::new()) };
 }
 
+/// Number of messages kept in `RECENT_PANICS` — a double panic involves
+/// the original panic and the one raised by `Drop` during cleanup.
+const RECENT_PANICS_CAPACITY: usize = 2;
+
+/**
+ * Messages std uses for the final panic before aborting, with the title
+ * prefix and the number of preceding panics that caused it.
+ */
+const NON_UNWINDING_PANICS: [(&str, &str, usize); 2] = [
+    ("panic in a destructor during cleanup", "double panic", 2),
+    (
+        "panic in a function that cannot unwind",
+        "panic in a function that cannot unwind",
+        1,
+    ),
+];
+
 // ---------------------------------------------------------------------------
 // Public API
 // ---------------------------------------------------------------------------
@@ -60,10 +200,50 @@ pub fn install() {
         return;
     }
 
-    let previous_hook = panic::take_hook();
+    let previous_hook = Arc::new(panic::take_hook());
+    *lock_previous_hook() = Some(previous_hook);
+
+    panic::set_hook(Box::new(|info| {
+        capture(info, None);
+
+        #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+        wasm::console_error(info);
+
+        /*
+         * Clone out of the lock: the previous hook may itself panic-abort
+         * or run for a while (printing a backtrace).
+         */
+        let previous_hook = lock_previous_hook().clone();
+        if let Some(previous_hook) = previous_hook {
+            previous_hook(info);
+        }
+    }));
+}
+
+/**
+ * Captures a panic from a hook the application owns.
+ *
+ * For frameworks that insist on installing their own panic hook: call
+ * this from that hook instead of `install()`ing Hawk's. Events get the
+ * same title, backtrace, context, double panic detection, and flushing as
+ * with the Hawk hook. `extra_context` (a JSON object) is merged over the
+ * usual context.
+ *
+ * Respects `suppress()`; recursive panics raised while capturing are
+ * ignored.
+ *
+ * ```ignore
+ * std::panic::set_hook(Box::new(|info| {
+ *     hawk_panic::capture(info, Some(json!({ "request": current_request_id() })));
+ *     framework_report(info);
+ * }));
+ * ```
+ */
+pub fn capture(info: &PanicHookInfo<'_>, extra_context: Option<Value>) {
+    let is_suppressed = SUPPRESS_DEPTH.with(|depth| depth.get() > 0);
 
-    panic::set_hook(Box::new(move |info| {
-        let is_recursive = IN_HOOK.with(|flag| {
+    let skip_capture = is_suppressed
+        || IN_HOOK.with(|flag| {
             if flag.get() {
                 true
             } else {
@@ -72,43 +252,255 @@ pub fn install() {
             }
         });
 
-        if !is_recursive {
-            let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
-                handle_panic(info);
-            }));
+    if skip_capture {
+        return;
+    }
 
-            IN_HOOK.with(|flag| flag.set(false));
-        }
+    let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let aborting = handle_panic(info, extra_context);
 
-        previous_hook(info);
+        if (aborting || FLUSH_ON_PANIC.load(Ordering::Relaxed))
+            && !std::thread::current()
+                .name()
+                .is_some_and(|name| WORKER_THREAD_NAMES.contains(&name))
+        {
+            hawk_core::flush();
+        }
     }));
+
+    IN_HOOK.with(|flag| flag.set(false));
+}
+
+/**
+ * Removes the Hawk panic hook, restoring the hook that was active before
+ * `install()`.
+ *
+ * Whatever hook is current gets replaced — if another library installed
+ * its own hook on top of Hawk's, that one is removed as well.
+ * No-op if the hook is not installed.
+ *
+ * # Panics
+ * Panics if called from a panicking thread (like `std::panic::set_hook`).
+ */
+pub fn uninstall() {
+    if !INSTALLED.load(Ordering::SeqCst) {
+        return;
+    }
+
+    let previous_hook = lock_previous_hook().take();
+    match previous_hook {
+        Some(previous_hook) => panic::set_hook(Box::new(move |info| previous_hook(info))),
+        None => drop(panic::take_hook()),
+    }
+
+    INSTALLED.store(false, Ordering::SeqCst);
+}
+
+/**
+ * Lists all threads of the process (OS ID, name, scheduler state) in the
+ * context of panic events. Off by default: listing hundreds of threads
+ * adds noticeably to the payload. Linux only; no-op elsewhere.
+ */
+pub fn set_thread_inventory(enabled: bool) {
+    THREAD_INVENTORY.store(enabled, Ordering::Relaxed);
+}
+
+/**
+ * Ignores panics raised in the given crates or modules, e.g.
+ * `["vendored_parser"]` for a dependency with a known benign panic in a
+ * thread of its own. A prefix matches the module path and its submodules
+ * (`"a::b"` matches `a::b` and `a::b::c`, not `a::bc`).
+ *
+ * Matched against the topmost frame of the panic's backtrace outside the
+ * standard library — the code that called `panic!`, `unwrap()`, etc. The
+ * backtrace is then resolved in the hook, so the list costs a symbol
+ * lookup per panic; an empty list (the default) costs nothing.
+ *
+ * Non-unwinding panics (double panics) are always reported.
+ */
+pub fn set_ignore_modules(modules: Vec<String>) {
+    *IGNORE_MODULES.write().unwrap_or_else(|e| e.into_inner()) = modules;
+}
+
+/**
+ * Registers a message extractor for panic payloads of type `T`.
+ *
+ * Used for the event title when a panic's payload is a `T` — e.g. raised
+ * via `std::panic::panic_any(MyError { .. })`. `&str` and `String`
+ * payloads are always handled; extractors are tried in registration order
+ * after them.
+ *
+ * ```ignore
+ * hawk_panic::register_payload_extractor::<HttpAbort>(|abort| {
+ *     format!("request aborted with status {}", abort.status)
+ * });
+ * ```
+ */
+pub fn register_payload_extractor<T: Any>(extract: impl Fn(&T) -> String + Send + Sync + 'static) {
+    let extractor: PayloadExtractor =
+        Box::new(move |payload| payload.downcast_ref::<T>().map(&extract));
+
+    PAYLOAD_EXTRACTORS
+        .write()
+        .unwrap_or_else(|e| e.into_inner())
+        .push(extractor);
+}
+
+/**
+ * Returns the message of a panic payload: the `&str` / `String` passed to
+ * `panic!`, the output of a registered extractor, or `"<unknown panic>"`.
+ *
+ * Integrations that catch panics themselves (`hawk_tokio`, `hawk_rayon`)
+ * use this to title their events consistently with the hook.
+ */
+pub fn payload_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(s) = payload.downcast_ref::<&str>() {
+        return (*s).to_string();
+    }
+    if let Some(s) = payload.downcast_ref::<String>() {
+        return s.clone();
+    }
+
+    PAYLOAD_EXTRACTORS
+        .read()
+        .unwrap_or_else(|e| e.into_inner())
+        .iter()
+        .find_map(|extract| extract(payload))
+        .unwrap_or_else(|| "<unknown panic>".to_string())
+}
+
+/**
+ * Runs `f` with panic capture disabled on the current thread.
+ *
+ * Panics raised inside `f` still reach the previous hook (so they are
+ * printed as usual) but are not sent to Hawk. Capture is re-enabled when
+ * `f` returns or unwinds. Calls may nest. Other threads are unaffected.
+ *
+ * ```ignore
+ * let result = hawk_panic::suppress(|| std::panic::catch_unwind(|| parse(input)));
+ * ```
+ */
+pub fn suppress<R>(f: impl FnOnce() -> R) -> R {
+    struct Restore;
+
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            SUPPRESS_DEPTH.with(|depth| depth.set(depth.get() - 1));
+        }
+    }
+
+    SUPPRESS_DEPTH.with(|depth| depth.set(depth.get() + 1));
+    let _restore = Restore;
+
+    f()
+}
+
+/**
+ * Returns `true` if the Hawk panic hook has been installed.
+ *
+ * Integrations that catch unwinding panics themselves use this to avoid
+ * reporting a panic the hook has already captured.
+ */
+pub fn is_installed() -> bool {
+    INSTALLED.load(Ordering::SeqCst)
+}
+
+/**
+ * Makes the panic hook block until the panic event is delivered.
+ *
+ * Always on when built with `panic = "abort"`. Useful with unwinding too
+ * when panics usually end the process some other way (e.g. a supervisor
+ * killing it) before the `Guard` gets to flush. Cannot be turned off
+ * under `panic = "abort"`.
+ */
+pub fn set_flush_on_panic(enabled: bool) {
+    FLUSH_ON_PANIC.store(enabled || cfg!(panic = "abort"), Ordering::Relaxed);
+}
+
+/**
+ * Runs `f` with `context` attached to any panic raised on this thread
+ * while it runs.
+ *
+ * Calls nest: inner contexts are merged over outer ones, key by key.
+ * `context` should be a JSON object; other values are ignored. It can be
+ * passed as an `Arc` so that hot paths (e.g. every poll of a future)
+ * don't rebuild the JSON value.
+ *
+ * The panic hook runs before unwinding starts, so the context is still
+ * in place when the event is built.
+ */
+pub fn with_context<R>(context: impl Into<Arc<Value>>, f: impl FnOnce() -> R) -> R {
+    struct PopOnDrop;
+
+    impl Drop for PopOnDrop {
+        fn drop(&mut self) {
+            CONTEXT_STACK.with(|stack| {
+                stack.borrow_mut().pop();
+            });
+        }
+    }
+
+    CONTEXT_STACK.with(|stack| stack.borrow_mut().push(context.into()));
+    let _pop = PopOnDrop;
+
+    f()
 }
 
 // ---------------------------------------------------------------------------
 // Internal: build and send the panic event
 // ---------------------------------------------------------------------------
 
-fn handle_panic(info: &PanicHookInfo) {
-    let message = match info.payload().downcast_ref::<&str>() {
-        Some(s) => (*s).to_string(),
-        None => match info.payload().downcast_ref::<String>() {
-            Some(s) => s.clone(),
-            None => "<unknown panic>".to_string(),
-        },
-    };
+/**
+ * Builds and sends the event for one hook invocation.
+ *
+ * Returns `true` if the process is about to abort (non-unwinding panic).
+ */
+fn handle_panic(info: &PanicHookInfo, extra_context: Option<Value>) -> bool {
+    let message = payload_message(info.payload());
+
+    let non_unwinding = NON_UNWINDING_PANICS
+        .iter()
+        .find(|(marker, _, _)| message == *marker);
+
+    if let Some(&(_, kind, causes)) = non_unwinding {
+        send_abort_event(&message, kind, causes, extra_context);
+        return true;
+    }
+
+    RECENT_PANICS.with(|recent| {
+        let mut recent = recent.borrow_mut();
+        if recent.len() == RECENT_PANICS_CAPACITY {
+            recent.remove(0);
+        }
+        recent.push(message.clone());
+    });
 
     let (file, line) = match info.location() {
         Some(loc) => (Some(loc.file().to_string()), Some(loc.line())),
         None => (None, None),
     };
 
+    /*
+     * The hook's location is exact even when the binary is stripped or
+     * the panicking function was inlined — put it on top of the trace.
+     */
+    let location_frame = info.location().map(hawk_core::frame_from_location);
+
     let thread_name = std::thread::current()
         .name()
         .unwrap_or("<unnamed>")
         .to_string();
 
-    let bt = backtrace::Backtrace::new();
-    let frames = hawk_core::convert_backtrace(&bt);
+    let bt = hawk_core::Backtrace::new_unresolved();
+
+    {
+        let ignore_modules = IGNORE_MODULES.read().unwrap_or_else(|e| e.into_inner());
+        if !ignore_modules.is_empty()
+            && is_ignored(&hawk_core::convert_backtrace(&bt), &ignore_modules)
+        {
+            return false;
+        }
+    }
 
     let location_str = match (&file, line) {
         (Some(f), Some(l)) => format!(" at {f}:{l}"),
@@ -118,10 +510,216 @@ fn handle_panic(info: &PanicHookInfo) {
 
     let event = EventData {
         title,
-        event_type: Some("fatal".to_string()),
-        backtrace: if frames.is_empty() { None } else { Some(frames) },
-        catcher_version: CATCHER_VERSION.to_string(),
+        event_type: Some(hawk_core::panic_type(&message).to_string()),
+        backtrace: location_frame.map(|frame| vec![frame]),
+        context: Some(Value::Object(panic_context(extra_context))),
+        breadcrumbs: None,
+        addons: None,
+        fingerprint: None,
+        user: None,
+        catcher_version: CATCHER_VERSION.into(),
     };
 
-    hawk_core::capture_event(event);
+    hawk_core::mark_panicked();
+    hawk_core::capture_event_with_backtrace(event, bt);
+    false
+}
+
+/**
+ * Whether the topmost frame of the panic outside the standard library
+ * belongs to one of the `ignore_modules`. Frames of the hook itself, above
+ * std's panic machinery, are skipped.
+ */
+fn is_ignored(frames: &[hawk_core::BacktraceFrame], ignore_modules: &[String]) -> bool {
+    let panic_start = frames
+        .iter()
+        .position(|frame| {
+            frame
+                .function
+                .as_deref()
+                .is_some_and(|function| function.ends_with(SHORT_BACKTRACE_END))
+        })
+        .map_or(0, |index| index + 1);
+
+    let panicking_module = frames[panic_start..]
+        .iter()
+        .filter_map(|frame| frame.module.as_deref())
+        .find(|module| {
+            let krate = module.split("::").next().unwrap_or(module);
+            !STD_CRATES.contains(&krate)
+        });
+
+    panicking_module.is_some_and(|module| {
+        ignore_modules.iter().any(|prefix| {
+            module
+                .strip_prefix(prefix.as_str())
+                .is_some_and(|rest| rest.is_empty() || rest.starts_with("::"))
+        })
+    })
+}
+
+/**
+ * Sends the `abort` event for a non-unwinding panic.
+ *
+ * The panic's own location points into std (the cleanup / unwind guard),
+ * so the title is built from the messages of the `causes` most recent
+ * panics on this thread instead:
+ * `double panic: <in Drop> (while panicking: <original>) [thread: main]`.
+ */
+fn send_abort_event(reason: &str, kind: &str, causes: usize, extra_context: Option<Value>) {
+    let messages = RECENT_PANICS.with(|recent| {
+        let recent = recent.borrow();
+        recent[recent.len().saturating_sub(causes)..].to_vec()
+    });
+
+    let thread_name = std::thread::current()
+        .name()
+        .unwrap_or("<unnamed>")
+        .to_string();
+
+    let title = match messages.as_slice() {
+        [first, last] => {
+            format!("{kind}: {last} (while panicking: {first}) [thread: {thread_name}]")
+        }
+        [last] => format!("{kind}: {last} [thread: {thread_name}]"),
+        _ => format!("{kind} [thread: {thread_name}]"),
+    };
+
+    let mut context = panic_context(extra_context);
+    context.insert(
+        "abort".to_string(),
+        serde_json::json!({ "reason": reason, "panicMessages": messages }),
+    );
+
+    let event = EventData {
+        title,
+        event_type: Some("abort".to_string()),
+        backtrace: None,
+        context: Some(Value::Object(context)),
+        breadcrumbs: None,
+        addons: None,
+        fingerprint: None,
+        user: None,
+        catcher_version: CATCHER_VERSION.into(),
+    };
+
+    hawk_core::capture_event_with_backtrace(event, hawk_core::Backtrace::new_unresolved());
+}
+
+/**
+ * Locks `PREVIOUS_HOOK`, ignoring poisoning — the slot holds no invariant
+ * a panicking holder could break.
+ */
+fn lock_previous_hook() -> std::sync::MutexGuard<'static, Option<Arc<PanicHook>>> {
+    PREVIOUS_HOOK.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/**
+ * Context of a panic event: scoped contexts from `with_context()`, plus
+ * `thread` (and `threads` if the inventory is enabled), plus the caller's
+ * `extra` object from `capture()`.
+ */
+fn panic_context(extra: Option<Value>) -> Map<String, Value> {
+    let mut context = match scoped_context() {
+        Some(Value::Object(fields)) => fields,
+        _ => Map::new(),
+    };
+
+    context.insert("thread".to_string(), threads::current_thread());
+
+    #[cfg(all(target_arch = "wasm32", target_os = "unknown"))]
+    context.insert("jsStack".to_string(), Value::String(wasm::js_stack()));
+
+    if THREAD_INVENTORY.load(Ordering::Relaxed) {
+        if let Some(inventory) = threads::thread_inventory() {
+            context.insert("threads".to_string(), inventory);
+        }
+    }
+
+    if let Some(Value::Object(extra)) = extra {
+        context.extend(extra);
+    }
+
+    context
+}
+
+/**
+ * Merges the contexts pushed via `with_context()` on this thread into a
+ * single JSON object. Returns `None` if there are none.
+ */
+fn scoped_context() -> Option<Value> {
+    CONTEXT_STACK.with(|stack| {
+        let stack = stack.borrow();
+        if stack.is_empty() {
+            return None;
+        }
+
+        let mut merged = Map::new();
+        for context in stack.iter() {
+            if let Value::Object(fields) = context.as_ref() {
+                merged.extend(fields.clone());
+            }
+        }
+
+        Some(Value::Object(merged))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct CustomPayload {
+        code: u32,
+    }
+
+    /**
+     * Verifies that registered extractors are used for custom payloads
+     * and that unknown payloads still get a placeholder.
+     */
+    #[test]
+    fn test_payload_message() {
+        register_payload_extractor::<CustomPayload>(|p| format!("custom error {}", p.code));
+
+        let custom: Box<dyn Any + Send> = Box::new(CustomPayload { code: 7 });
+        let unknown: Box<dyn Any + Send> = Box::new(42_u8);
+
+        assert_eq!(payload_message(custom.as_ref()), "custom error 7");
+        assert_eq!(payload_message(unknown.as_ref()), "<unknown panic>");
+        assert_eq!(payload_message(&"plain"), "plain");
+    }
+
+    /**
+     * Verifies that the topmost frame outside std decides whether a panic
+     * is ignored, on module path boundaries.
+     */
+    #[test]
+    fn test_is_ignored() {
+        let frame = |module: &str| hawk_core::BacktraceFrame {
+            file: None,
+            line: None,
+            column: None,
+            function: Some(format!("{module}::f")),
+            module: Some(module.to_string()),
+            instruction_addr: None,
+            module_offset: None,
+            inlined: false,
+            in_app: true,
+        };
+        let mut hook = frame("my_app::hooks");
+        hook.function = Some(format!("std::sys::backtrace::{SHORT_BACKTRACE_END}"));
+        hook.module = Some("std::sys::backtrace".to_string());
+        let frames = [
+            frame("my_app::hooks"),
+            hook,
+            frame("core::panicking"),
+            frame("vendored::parse"),
+            frame("my_app"),
+        ];
+
+        assert!(is_ignored(&frames, &["vendored".to_string()]));
+        assert!(is_ignored(&frames, &["vendored::parse".to_string()]));
+        assert!(!is_ignored(&frames, &["vendor".to_string()]));
+        assert!(!is_ignored(&frames, &["my_app".to_string()]));
+    }
 }
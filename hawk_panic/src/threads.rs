@@ -0,0 +1,116 @@
+/*!
+ * Thread information attached to panic events.
+ *
+ * - `current_thread()` — the panicking thread: name, Rust `ThreadId`, and
+ *   the OS thread ID where available. Always included.
+ * - `thread_inventory()` — every thread of the process with its OS ID,
+ *   name, and scheduler state, so that deadlocked or starving siblings
+ *   are visible during triage. Opt-in (`set_thread_inventory(true)`);
+ *   Linux only, read from `/proc/self/task`.
+ */
+
+use serde_json::{json, Value};
+
+/// Upper bound on threads listed in the inventory, to keep payloads small.
+#[cfg(target_os = "linux")]
+const MAX_INVENTORY_THREADS: usize = 256;
+
+/**
+ * `{"name": ..., "id": ..., "osId": ...}` for the current thread.
+ */
+pub(crate) fn current_thread() -> Value {
+    let thread = std::thread::current();
+
+    json!({
+        "name": thread.name(),
+        "id": rust_thread_id(thread.id()),
+        "osId": os_thread_id(),
+    })
+}
+
+/**
+ * Lists all threads of the process, `None` where unsupported.
+ */
+#[cfg(target_os = "linux")]
+pub(crate) fn thread_inventory() -> Option<Value> {
+    let tasks = std::fs::read_dir("/proc/self/task").ok()?;
+
+    let threads: Vec<Value> = tasks
+        .flatten()
+        .filter_map(|task| task.file_name().to_str()?.parse::<u64>().ok())
+        .take(MAX_INVENTORY_THREADS)
+        .map(|tid| {
+            let dir = format!("/proc/self/task/{tid}");
+            let name = std::fs::read_to_string(format!("{dir}/comm"))
+                .ok()
+                .map(|comm| comm.trim_end().to_string());
+            let state = std::fs::read_to_string(format!("{dir}/stat"))
+                .ok()
+                .and_then(|stat| parse_state(&stat));
+
+            json!({ "osId": tid, "name": name, "state": state })
+        })
+        .collect();
+
+    Some(Value::Array(threads))
+}
+
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn thread_inventory() -> Option<Value> {
+    None
+}
+
+/**
+ * Numeric part of a `ThreadId` — its `Debug` output is `ThreadId(N)`
+ * (`ThreadId::as_u64` is unstable).
+ */
+fn rust_thread_id(id: std::thread::ThreadId) -> Option<u64> {
+    format!("{id:?}")
+        .strip_prefix("ThreadId(")?
+        .strip_suffix(')')?
+        .parse()
+        .ok()
+}
+
+/**
+ * Kernel thread ID of the current thread (Linux: `/proc/thread-self`
+ * links to `<pid>/task/<tid>`).
+ */
+#[cfg(target_os = "linux")]
+fn os_thread_id() -> Option<u64> {
+    let link = std::fs::read_link("/proc/thread-self").ok()?;
+    link.file_name()?.to_str()?.parse().ok()
+}
+
+#[cfg(not(target_os = "linux"))]
+fn os_thread_id() -> Option<u64> {
+    None
+}
+
+/**
+ * Extracts the state letter (`R`, `S`, `D`, ...) from a `/proc/<pid>/stat`
+ * line. The thread name in parentheses may contain spaces and `)`, so the
+ * state is located after the LAST closing parenthesis.
+ */
+#[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+fn parse_state(stat: &str) -> Option<String> {
+    let (_, rest) = stat.rsplit_once(')')?;
+    rest.split_whitespace().next().map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * Verifies state parsing with a thread name containing `) `.
+     */
+    #[test]
+    fn test_parse_state() {
+        assert_eq!(
+            parse_state("1234 (tokio) x) S 1 1234 0").as_deref(),
+            Some("S")
+        );
+        assert_eq!(parse_state("garbage"), None);
+    }
+}
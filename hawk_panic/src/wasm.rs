@@ -0,0 +1,40 @@
+/*!
+ * Browser support (`wasm32-unknown-unknown`).
+ *
+ * std's default panic hook writes to stderr, which goes nowhere in the
+ * browser, and `std::backtrace` is unsupported. Like
+ * `console_error_panic_hook`, the Hawk hook prints panics to the console
+ * with the JavaScript stack, and attaches that stack to the event — the
+ * only trace available on this target.
+ */
+
+use std::panic::PanicHookInfo;
+
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+extern "C" {
+    type Error;
+
+    #[wasm_bindgen(constructor)]
+    fn new() -> Error;
+
+    #[wasm_bindgen(structural, method, getter)]
+    fn stack(error: &Error) -> String;
+}
+
+/**
+ * Stack of the current JavaScript call, including the wasm frames
+ * (`new Error().stack`).
+ */
+pub(crate) fn js_stack() -> String {
+    Error::new().stack()
+}
+
+/**
+ * Prints the panic and the JavaScript stack with `console.error`.
+ */
+pub(crate) fn console_error(info: &PanicHookInfo<'_>) {
+    let message = format!("{info}\n\nStack:\n\n{}\n\n", js_stack());
+    web_sys::console::error_1(&JsValue::from_str(&message));
+}
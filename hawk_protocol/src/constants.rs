@@ -0,0 +1,35 @@
+/*!
+ * SDK-wide constants.
+ *
+ * These values are baked into every event envelope and identify
+ * the catcher type and version to the Hawk backend.
+ */
+
+/// The catcher type identifier sent in every `HawkEvent` envelope.
+/// Tells the backend which SDK family produced this event.
+pub const CATCHER_TYPE: &str = "errors/rust";
+
+/// The catcher type identifier sent in every `HawkTransaction` envelope.
+pub const PERFORMANCE_CATCHER_TYPE: &str = "performance/rust";
+
+/// The catcher type identifier sent in every `HawkMetrics` envelope.
+pub const METRICS_CATCHER_TYPE: &str = "metrics/rust";
+
+/// SDK version string included in every event payload.
+/// Derived at compile time from the `hawk_protocol` package version (shared by the workspace) in `Cargo.toml`.
+pub const CATCHER_VERSION: &str = concat!("hawk-rust/", env!("CARGO_PKG_VERSION"));
+
+/// Version of the wire protocol spoken by this SDK, sent in the
+/// `PROTOCOL_HEADER` of every request.
+pub const PROTOCOL_VERSION: u32 = 1;
+
+/// Request header carrying `PROTOCOL_VERSION`.
+pub const PROTOCOL_HEADER: &str = "X-Hawk-Protocol";
+
+/// Request header carrying `CATCHER_VERSION`.
+pub const SDK_HEADER: &str = "X-Hawk-Sdk";
+
+/// Response header in which a collector advertises the optional wire
+/// features it supports, comma-separated (e.g. `gzip, batch`). Collectors
+/// that do not send it get the baseline protocol.
+pub const FEATURES_HEADER: &str = "X-Hawk-Features";
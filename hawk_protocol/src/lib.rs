@@ -0,0 +1,132 @@
+/*!
+ * Hawk protocol — the event wire format, usable without `std`.
+ *
+ * Everything related to *what* is sent to the Hawk backend, split out of
+ * `hawk_core` so that it builds for `no_std + alloc` targets:
+ * - `types` — HawkEvent envelope, EventData payload, BacktraceFrame, …
+ * - `performance` — HawkTransaction envelope, TransactionData, SpanData
+ * - `metrics` — HawkMetrics envelope, MetricsData, MetricData
+ * - `constants` — CATCHER_TYPE, CATCHER_VERSION, protocol headers
+ * - `token` — base64 token decoding and endpoint derivation
+ * - `Sink` — delivery of serialized events over any transport
+ *
+ * `hawk_core` builds on it for hosted targets. Embedded / RTOS firmware
+ * uses it directly: build an `EventData`, wrap it with `HawkEvent::new()`,
+ * and hand it to a `Sink` writing to UART, MQTT, or a gateway — the bytes
+ * are exactly what `hawk_core` would POST.
+ *
+ * # Features
+ *
+ * - `std` (default) — `Breadcrumb::new()`, timestamped from the system
+ *   clock. Without it, use `Breadcrumb::at()`.
+ *
+ * # Example
+ *
+ * ```ignore
+ * struct Uart(/* ... */);
+ *
+ * impl hawk_protocol::Sink for Uart {
+ *     type Error = UartError;
+ *
+ *     fn send(&mut self, event: &[u8]) -> Result<(), UartError> {
+ *         self.write_frame(event)
+ *     }
+ * }
+ *
+ * let event = HawkEvent::new(TOKEN, EventData::new("sensor offline"));
+ * event.send_to(&mut uart)?;
+ * ```
+ */
+
+#![cfg_attr(not(feature = "std"), no_std)]
+#![forbid(unsafe_code)]
+
+extern crate alloc;
+
+pub mod constants;
+pub mod metrics;
+pub mod performance;
+pub mod token;
+pub mod types;
+
+pub use constants::{
+    CATCHER_TYPE, CATCHER_VERSION, METRICS_CATCHER_TYPE, PERFORMANCE_CATCHER_TYPE,
+};
+pub use metrics::{HawkMetrics, MetricData, MetricValue, MetricsData};
+pub use performance::{HawkTransaction, SpanData, TransactionData};
+pub use types::{Addons, Attachment, BacktraceFrame, Breadcrumb, EventData, HawkEvent, User};
+
+use alloc::vec::Vec;
+
+// ---------------------------------------------------------------------------
+// Sink
+// ---------------------------------------------------------------------------
+
+/**
+ * Destination for serialized events — the pluggable end of the pipeline.
+ *
+ * Receives one `HawkEvent` encoded as JSON per call. Implementations
+ * decide how the bytes reach the collector: directly over HTTP, or via a
+ * gateway forwarding them as the body of a POST to the collector endpoint
+ * (`token::default_endpoint()`).
+ */
+pub trait Sink {
+    /// Error reported by the underlying transport.
+    type Error;
+
+    /**
+     * Delivers one serialized event.
+     */
+    fn send(&mut self, event: &[u8]) -> Result<(), Self::Error>;
+}
+
+impl HawkEvent {
+    /**
+     * Serializes the envelope to the JSON bytes the collector expects.
+     */
+    pub fn to_json(&self) -> Vec<u8> {
+        /* Serializing these types cannot fail: all map keys are strings. */
+        serde_json::to_vec(self).unwrap_or_default()
+    }
+
+    /**
+     * Serializes the envelope and hands it to `sink`.
+     */
+    pub fn send_to<S: Sink>(&self, sink: &mut S) -> Result<(), S::Error> {
+        sink.send(&self.to_json())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Collects sent events in memory.
+    struct VecSink(Vec<Vec<u8>>);
+
+    impl Sink for VecSink {
+        type Error = ();
+
+        fn send(&mut self, event: &[u8]) -> Result<(), ()> {
+            self.0.push(event.to_vec());
+            Ok(())
+        }
+    }
+
+    /**
+     * Verifies that a sink receives the JSON envelope.
+     */
+    #[test]
+    fn test_send_to_sink() {
+        let mut sink = VecSink(Vec::new());
+        HawkEvent::new("token", EventData::new("sensor offline"))
+            .send_to(&mut sink)
+            .unwrap();
+
+        let json: serde_json::Value = serde_json::from_slice(&sink.0[0]).unwrap();
+        assert_eq!(json["catcherType"], CATCHER_TYPE);
+        assert_eq!(json["payload"]["title"], "sensor offline");
+        assert_eq!(json["payload"]["catcherVersion"], CATCHER_VERSION);
+        assert_eq!(json["payload"].get("fingerprint"), None);
+    }
+}
@@ -0,0 +1,212 @@
+/*!
+ * Metric payloads: counters, gauges, and distributions.
+ *
+ * Metrics are aggregated by the sender over a flush interval and delivered
+ * as one `HawkMetrics` envelope per interval, with the `metrics/rust`
+ * catcher type, to the same collector endpoint as error events. Each
+ * metric is identified by its name, type, and tags.
+ *
+ * Timestamps are milliseconds since the Unix epoch.
+ */
+use alloc::borrow::Cow;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use serde::Serialize;
+
+use crate::constants::{CATCHER_VERSION, METRICS_CATCHER_TYPE};
+
+// ---------------------------------------------------------------------------
+// Envelope
+// ---------------------------------------------------------------------------
+
+/**
+ * The envelope of one flush interval's metrics:
+ * `{ "token", "catcherType": "metrics/rust", "payload": { ... } }`.
+ */
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HawkMetrics {
+    /// The raw base64-encoded integration token provided by the user.
+    pub token: Arc<str>,
+
+    /// Always `"metrics/rust"`.
+    pub catcher_type: Cow<'static, str>,
+
+    /// The aggregated metrics.
+    pub payload: MetricsData,
+}
+
+impl HawkMetrics {
+    /**
+     * Wraps aggregated metrics in the envelope for the given integration
+     * token.
+     */
+    pub fn new(token: impl Into<Arc<str>>, payload: MetricsData) -> Self {
+        Self {
+            token: token.into(),
+            catcher_type: METRICS_CATCHER_TYPE.into(),
+            payload,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// MetricsData
+// ---------------------------------------------------------------------------
+
+/**
+ * Metrics aggregated between `start_timestamp` and `end_timestamp`.
+ */
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MetricsData {
+    /// Start of the interval, in milliseconds since the Unix epoch.
+    pub start_timestamp: u64,
+
+    /// End of the interval, in milliseconds since the Unix epoch.
+    pub end_timestamp: u64,
+
+    /// One entry per name, type, and tag set recorded in the interval.
+    pub metrics: Vec<MetricData>,
+
+    /// SDK version string, e.g. `"hawk-rust/0.1.0"`.
+    pub catcher_version: Cow<'static, str>,
+}
+
+impl MetricsData {
+    /**
+     * Creates the payload for the interval between the two timestamps.
+     */
+    pub fn new(start_timestamp: u64, end_timestamp: u64, metrics: Vec<MetricData>) -> Self {
+        Self {
+            start_timestamp,
+            end_timestamp,
+            metrics,
+            catcher_version: CATCHER_VERSION.into(),
+        }
+    }
+}
+
+/**
+ * One aggregated metric.
+ */
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct MetricData {
+    /// Metric name, e.g. `"orders.failed"`.
+    pub name: String,
+
+    /// Tags, sorted by key.
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub tags: BTreeMap<String, String>,
+
+    /// The type (`"type"` key) and aggregated values.
+    #[serde(flatten)]
+    pub value: MetricValue,
+}
+
+/**
+ * Aggregated values of a metric, by type.
+ */
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+pub enum MetricValue {
+    /// Sum of all increments.
+    Counter { value: f64 },
+
+    /// Last value set, with the range, sum, and count of all values set.
+    Gauge {
+        last: f64,
+        min: f64,
+        max: f64,
+        sum: f64,
+        count: u64,
+    },
+
+    /// A uniform sample of at most `MAX_DISTRIBUTION_VALUES` of the values
+    /// recorded, with the range, sum, and count of all of them.
+    Distribution {
+        values: Vec<f64>,
+        min: f64,
+        max: f64,
+        sum: f64,
+        count: u64,
+    },
+}
+
+/**
+ * Values kept per distribution and flush interval. Past it, each new value
+ * replaces a kept one at random (reservoir sampling), so that memory stays
+ * bounded however hot the distribution is.
+ */
+pub const MAX_DISTRIBUTION_VALUES: usize = 1000;
+
+impl MetricValue {
+    /**
+     * Aggregate of a distribution's first value.
+     */
+    pub fn distribution(value: f64) -> Self {
+        Self::Distribution {
+            values: alloc::vec![value],
+            min: value,
+            max: value,
+            sum: value,
+            count: 1,
+        }
+    }
+
+    /**
+     * Folds another recorded value into the aggregate.
+     */
+    pub fn add(&mut self, value: f64) {
+        match self {
+            Self::Counter { value: total } => *total += value,
+            Self::Gauge {
+                last,
+                min,
+                max,
+                sum,
+                count,
+            } => {
+                *last = value;
+                *min = min.min(value);
+                *max = max.max(value);
+                *sum += value;
+                *count += 1;
+            }
+            Self::Distribution {
+                values,
+                min,
+                max,
+                sum,
+                count,
+            } => {
+                *min = min.min(value);
+                *max = max.max(value);
+                *sum += value;
+                *count += 1;
+                if values.len() < MAX_DISTRIBUTION_VALUES {
+                    values.push(value);
+                } else {
+                    let slot = (mix(*count) % *count) as usize;
+                    if let Some(kept) = values.get_mut(slot) {
+                        *kept = value;
+                    }
+                }
+            }
+        }
+    }
+}
+
+/**
+ * SplitMix64 finalizer: spreads the value count over the reservoir
+ * without a random number generator, which `no_std` lacks.
+ */
+fn mix(mut x: u64) -> u64 {
+    x = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    x ^ (x >> 31)
+}
@@ -0,0 +1,149 @@
+/*!
+ * Performance payloads: transactions and their spans.
+ *
+ * A transaction is one timed unit of work — a request, a job run — split
+ * into nested spans (a query, an outgoing call, ...). It is delivered as
+ * its own envelope, `HawkTransaction`, with the `performance/rust`
+ * catcher type, to the same collector endpoint as error events.
+ *
+ * Timestamps are milliseconds since the Unix epoch; durations are
+ * milliseconds, fractional.
+ */
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use serde::Serialize;
+
+use crate::constants::{CATCHER_VERSION, PERFORMANCE_CATCHER_TYPE};
+
+// ---------------------------------------------------------------------------
+// Envelope
+// ---------------------------------------------------------------------------
+
+/**
+ * The envelope of a finished transaction:
+ * `{ "token", "catcherType": "performance/rust", "payload": { ... } }`.
+ */
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HawkTransaction {
+    /// The raw base64-encoded integration token provided by the user.
+    pub token: Arc<str>,
+
+    /// Always `"performance/rust"`.
+    pub catcher_type: Cow<'static, str>,
+
+    /// The finished transaction.
+    pub payload: TransactionData,
+}
+
+impl HawkTransaction {
+    /**
+     * Wraps a transaction in the envelope for the given integration token.
+     */
+    pub fn new(token: impl Into<Arc<str>>, payload: TransactionData) -> Self {
+        Self {
+            token: token.into(),
+            catcher_type: PERFORMANCE_CATCHER_TYPE.into(),
+            payload,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// TransactionData
+// ---------------------------------------------------------------------------
+
+/**
+ * A finished transaction with its finished spans.
+ */
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransactionData {
+    /// Random ID, 16 hex digits. Spans reference it as their `parentId`.
+    pub id: String,
+
+    /// What was measured, e.g. `"GET /orders"` or `"nightly-import"`.
+    pub name: String,
+
+    /// Kind of operation, e.g. `"http.server"` or `"job"`.
+    pub op: String,
+
+    /// Outcome: `"ok"` unless set otherwise.
+    pub status: String,
+
+    /// Start, in milliseconds since the Unix epoch.
+    pub start_timestamp: u64,
+
+    /// Duration in milliseconds.
+    pub duration: f64,
+
+    /// Finished child spans, in the order they finished.
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub spans: Vec<SpanData>,
+
+    /// Arbitrary structured data.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+
+    /// SDK version string, e.g. `"hawk-rust/0.1.0"`.
+    pub catcher_version: Cow<'static, str>,
+}
+
+impl TransactionData {
+    /**
+     * Creates a transaction with status `"ok"`, no spans, and no data.
+     */
+    pub fn new(id: impl Into<String>, name: impl Into<String>, op: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            name: name.into(),
+            op: op.into(),
+            status: "ok".to_string(),
+            start_timestamp: 0,
+            duration: 0.0,
+            spans: Vec::new(),
+            data: None,
+            catcher_version: CATCHER_VERSION.into(),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// SpanData
+// ---------------------------------------------------------------------------
+
+/**
+ * A finished span: a timed operation inside a transaction or another span.
+ */
+#[derive(Clone, Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpanData {
+    /// Random ID, 16 hex digits.
+    pub id: String,
+
+    /// ID of the enclosing span, or of the transaction for top-level spans.
+    pub parent_id: String,
+
+    /// Kind of operation, e.g. `"db.query"`.
+    pub op: String,
+
+    /// What the span did, e.g. the normalized SQL statement.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    /// Outcome: `"ok"` unless set otherwise.
+    pub status: String,
+
+    /// Start, in milliseconds since the Unix epoch.
+    pub start_timestamp: u64,
+
+    /// Duration in milliseconds.
+    pub duration: f64,
+
+    /// Arbitrary structured data.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+}
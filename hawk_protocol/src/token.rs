@@ -14,6 +14,9 @@
  * If the user provides a custom `collector_endpoint`, this decoding is still
  * performed for validation — but the custom endpoint takes precedence.
  */
+use alloc::format;
+use alloc::string::String;
+
 use base64::Engine as _;
 use serde::Deserialize;
 
@@ -167,9 +170,6 @@ mod tests {
      */
     #[test]
     fn test_default_endpoint() {
-        assert_eq!(
-            default_endpoint("abc123"),
-            "https://abc123.k1.hawk.so/"
-        );
+        assert_eq!(default_endpoint("abc123"), "https://abc123.k1.hawk.so/");
     }
 }
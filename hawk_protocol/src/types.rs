@@ -0,0 +1,435 @@
+/**
+ * Core type definitions for the Hawk Rust SDK.
+ *
+ * These structures mirror the protocol expected by the Hawk backend,
+ * matching the Node.js catcher's JSON format 1:1.
+ *
+ * The outermost envelope is `HawkEvent`, which wraps an `EventData` payload.
+ * The backend receives: { token, catcherType, payload: EventData }.
+ */
+use alloc::borrow::Cow;
+use alloc::string::{String, ToString};
+use alloc::sync::Arc;
+use alloc::vec::Vec;
+
+use base64::Engine as _;
+use serde::{Deserialize, Serialize};
+
+use crate::constants::{CATCHER_TYPE, CATCHER_VERSION};
+
+// ---------------------------------------------------------------------------
+// Envelope — the top-level structure POSTed to the collector
+// ---------------------------------------------------------------------------
+
+/**
+ * The outer envelope sent to the Hawk collector via HTTP POST.
+ *
+ * This matches the Node.js `HawkEvent` interface exactly:
+ * ```json
+ * {
+ *   "token": "<base64-encoded-integration-token>",
+ *   "catcherType": "errors/rust",
+ *   "payload": { ...EventData... }
+ * }
+ * ```
+ *
+ * `token` is the raw base64-encoded integration token (passed through as-is).
+ * `catcherType` identifies the SDK family — we use `"errors/rust"`.
+ * `payload` carries the actual event data.
+ */
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct HawkEvent {
+    /// The raw base64-encoded integration token provided by the user,
+    /// shared by the client's envelopes rather than copied into each.
+    pub token: Arc<str>,
+
+    /// Identifies the catcher family. Always `"errors/rust"` for this SDK.
+    pub catcher_type: Cow<'static, str>,
+
+    /// The event payload conforming to the `EventData` schema.
+    pub payload: EventData,
+}
+
+impl HawkEvent {
+    /**
+     * Wraps a payload in the envelope for the given integration token.
+     */
+    pub fn new(token: impl Into<Arc<str>>, payload: EventData) -> Self {
+        Self {
+            token: token.into(),
+            catcher_type: CATCHER_TYPE.into(),
+            payload,
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// EventData — the actual error / message payload
+// ---------------------------------------------------------------------------
+
+/**
+ * Core event payload matching the backend's `EventData<Addons>` interface.
+ *
+ * MVP sends only `title`, `type`, `backtrace`, `context`, `addons`, and
+ * `catcherVersion`. Fields like `release` and `user` are omitted for now
+ * and will be added in future iterations.
+ */
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EventData {
+    /// Human-readable title, e.g. `"Error: something broke"` or `"panic: index out of bounds"`.
+    pub title: String,
+
+    /// Error type name — equivalent to `error.name` in Node.js.
+    /// Examples: `"Error"`, `"TypeError"`, `"panic"`, `"message"`.
+    #[serde(rename = "type")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_type: Option<String>,
+
+    /// Stack trace frames, from the most recent call to the earliest.
+    /// `None` when no backtrace is available.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub backtrace: Option<Vec<BacktraceFrame>>,
+
+    /// Free-form per-event context, e.g. `{"task": {"name": "sync", "id": "7"}}`.
+    /// Should be a JSON object so that context from several sources merges.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub context: Option<serde_json::Value>,
+
+    /// Trail of events leading up to this one, oldest first. Filled in by
+    /// the client from `add_breadcrumb()` calls when left as `None`; set
+    /// `Some(vec![])` to send none.
+    #[serde(default, skip_serializing_if = "is_none_or_empty")]
+    pub breadcrumbs: Option<Vec<Breadcrumb>>,
+
+    /// Rust-specific data (build ID, …). Filled in by the client when
+    /// left as `None`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub addons: Option<Addons>,
+
+    /// Grouping key overriding the default grouping: events with equal
+    /// fingerprints are grouped together, e.g. `["db-timeout", "orders"]`.
+    /// Set per event, or computed by `Options::group_by`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fingerprint: Option<Vec<String>>,
+
+    /// The user affected by this event, e.g. the one who sent the request
+    /// that failed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<User>,
+
+    /// SDK version string, e.g. `"hawk-rust/0.1.0"`. Borrows
+    /// `CATCHER_VERSION` rather than copying it into every event; set it
+    /// with `.into()`, from a `&'static str` or a `String`.
+    pub catcher_version: Cow<'static, str>,
+}
+
+impl EventData {
+    /**
+     * Creates an event with the given title and every optional field
+     * unset.
+     */
+    pub fn new(title: impl Into<String>) -> Self {
+        Self {
+            title: title.into(),
+            event_type: None,
+            backtrace: None,
+            context: None,
+            breadcrumbs: None,
+            addons: None,
+            fingerprint: None,
+            user: None,
+            catcher_version: CATCHER_VERSION.into(),
+        }
+    }
+}
+
+fn is_none_or_empty<T>(value: &Option<Vec<T>>) -> bool {
+    value.as_ref().is_none_or(Vec::is_empty)
+}
+
+// ---------------------------------------------------------------------------
+// User — the affected user
+// ---------------------------------------------------------------------------
+
+/**
+ * A user affected by an event, matching the backend's `EventUser`.
+ */
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct User {
+    /// Identifier of the user in the application.
+    pub id: String,
+
+    /// Display name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Link to the user's page, e.g. in an admin panel.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
+    /// URL of the user's picture.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub photo: Option<String>,
+}
+
+impl User {
+    /**
+     * Creates a user with only an identifier.
+     */
+    pub fn new(id: impl Into<String>) -> Self {
+        Self {
+            id: id.into(),
+            ..Self::default()
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Addons — language-specific payload section
+// ---------------------------------------------------------------------------
+
+/**
+ * Rust-specific event data, sent as the backend's `addons` field.
+ *
+ * All fields are optional and omitted from the JSON when unset.
+ */
+#[derive(Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Addons {
+    /// Build ID of the executable that produced the event (GNU build ID,
+    /// Mach-O UUID, or PE debug ID). Lets the backend symbolicate frames
+    /// of stripped binaries from separately uploaded debug files.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build_id: Option<String>,
+
+    /// Identifier of the event: a random UUID, or one made by
+    /// `Options::event_id_generator`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event_id: Option<String>,
+
+    /// Position of the event among those enqueued by the SDK client since
+    /// `init()`, starting at 0. A gap means events were lost (e.g. the
+    /// queue was full); sorting by it restores the order in which events
+    /// were reported.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<u64>,
+
+    /// Unix timestamp in milliseconds at which the event was enqueued.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queued_at: Option<u64>,
+
+    /// Identifier shared by related events sent together with
+    /// `capture_batch()`, e.g. all validation failures of one request, so
+    /// that the backend can present them together.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub group_correlation_id: Option<String>,
+
+    /// Files sent along with the event, e.g. the minidump of a native
+    /// crash.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub attachments: Vec<Attachment>,
+}
+
+/**
+ * A file sent along with an event, base64-encoded into the payload.
+ */
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Attachment {
+    /// File name shown by the backend, e.g. `"crash.dmp"`.
+    pub filename: String,
+
+    /// MIME type, e.g. `"application/x-dmp"`.
+    pub content_type: String,
+
+    /// File contents, base64-encoded (standard alphabet, padded).
+    pub data: String,
+}
+
+impl Attachment {
+    /**
+     * Creates an attachment from raw file contents.
+     */
+    pub fn new(
+        filename: impl Into<String>,
+        content_type: impl Into<String>,
+        contents: &[u8],
+    ) -> Self {
+        Self {
+            filename: filename.into(),
+            content_type: content_type.into(),
+            data: base64::engine::general_purpose::STANDARD.encode(contents),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Breadcrumb
+// ---------------------------------------------------------------------------
+
+/**
+ * A single entry of the trail of events leading up to an error — a log
+ * line, an HTTP request, a state change — matching the backend's
+ * `Breadcrumb`.
+ *
+ * Build one with `Breadcrumb::new()` and override fields as needed:
+ *
+ * ```ignore
+ * hawk::add_breadcrumb(hawk::Breadcrumb {
+ *     category: Some("db".into()),
+ *     ..hawk::Breadcrumb::new("loaded 42 orders")
+ * });
+ * ```
+ */
+#[derive(Clone, Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Breadcrumb {
+    /// When it happened, in milliseconds since the Unix epoch.
+    pub timestamp: u64,
+
+    /// Kind of breadcrumb: `"default"`, `"request"`, `"logic"`, `"error"`, …
+    #[serde(rename = "type")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub breadcrumb_type: Option<String>,
+
+    /// Free-form category, e.g. a log target or `"http"`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub category: Option<String>,
+
+    /// Human-readable description.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+
+    /// Severity: `"fatal"`, `"error"`, `"warning"`, `"info"`, or `"debug"`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<String>,
+
+    /// Arbitrary structured data.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<serde_json::Value>,
+}
+
+impl Breadcrumb {
+    /**
+     * Creates an `info` breadcrumb with the given message, timestamped now
+     * by the system clock. With a clock injected into the SDK, use
+     * `Breadcrumb::at(hawk::now_millis(), ...)` instead.
+     */
+    #[cfg(feature = "std")]
+    pub fn new(message: impl Into<String>) -> Self {
+        Self::at(now_millis(), message)
+    }
+
+    /**
+     * Creates an `info` breadcrumb with the given message and timestamp
+     * (milliseconds since the Unix epoch) — for targets without a system
+     * clock.
+     */
+    pub fn at(timestamp: u64, message: impl Into<String>) -> Self {
+        Self {
+            timestamp,
+            breadcrumb_type: None,
+            category: None,
+            message: Some(message.into()),
+            level: Some("info".to_string()),
+            data: None,
+        }
+    }
+}
+
+/**
+ * Current time in milliseconds since the Unix epoch, by the system clock.
+ * Public for the SDK crates only; applications read the SDK's clock with
+ * `hawk::now_millis()`.
+ *
+ * `SystemTime::now()` panics on `wasm32-unknown-unknown`; the browser
+ * clock is read through `Date.now()` there.
+ */
+#[cfg(all(
+    feature = "std",
+    not(all(target_arch = "wasm32", target_os = "unknown"))
+))]
+#[doc(hidden)]
+pub fn now_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+#[cfg(all(feature = "std", target_arch = "wasm32", target_os = "unknown"))]
+#[doc(hidden)]
+pub fn now_millis() -> u64 {
+    js_sys::Date::now() as u64
+}
+
+// ---------------------------------------------------------------------------
+// BacktraceFrame
+// ---------------------------------------------------------------------------
+
+/**
+ * A single frame in the backtrace, matching the backend's `BacktraceFrame`.
+ *
+ * In the MVP we populate what we can from `backtrace::BacktraceFrame`:
+ * - `file` — source file path (if resolved)
+ * - `line` — line number
+ * - `column` — column number (often unavailable)
+ * - `function` — demangled function name
+ * - `module` — owning crate / module path, parsed from `function`
+ * - `in_app` — application code vs std / third-party crates
+ * - `instruction_addr` / `module_offset` — raw addresses (opt-in)
+ * - `inlined` — frame shares a physical stack frame with the next one
+ *
+ * The `sourceCode` field from the Node.js version is omitted in the MVP
+ * because Rust binaries typically don't ship source alongside.
+ */
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BacktraceFrame {
+    /// Source file path, if debug info is available.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub file: Option<String>,
+
+    /// Line number within the source file.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u32>,
+
+    /// Column number within the source line.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<u32>,
+
+    /// Demangled function / symbol name.
+    #[serde(rename = "function")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub function: Option<String>,
+
+    /// Module path owning the function, e.g. `"my_app::db"` for
+    /// `my_app::db::query`. The first segment is the crate name.
+    /// Enables module-based filtering in `before_send` and grouping.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub module: Option<String>,
+
+    /// Absolute instruction pointer, as hex (`"0x55d0c3a1b2c4"`).
+    /// Only set when `Options::include_addresses` is enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub instruction_addr: Option<String>,
+
+    /// Instruction pointer relative to the base address of the binary /
+    /// shared library it belongs to, as hex. Stable across ASLR, so together
+    /// with the build ID it lets an offline tool re-symbolize the frame.
+    /// Only set when `Options::include_addresses` is enabled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub module_offset: Option<String>,
+
+    /// `true` if the function was inlined into its caller — the next frame
+    /// in the list — and both share one physical stack frame.
+    #[serde(default, skip_serializing_if = "core::ops::Not::not")]
+    pub inlined: bool,
+
+    /// Whether the frame belongs to the application rather than to std or
+    /// a third-party crate. Used by the dashboard to highlight user code
+    /// and by the backend for grouping.
+    pub in_app: bool,
+}
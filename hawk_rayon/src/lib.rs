@@ -0,0 +1,172 @@
+/*!
+ * Hawk Rayon integration — panic capture for rayon thread pools.
+ *
+ * Panics in jobs started with `rayon::spawn` / `ThreadPool::spawn` have no
+ * caller to propagate to: rayon catches them and hands the payload to the
+ * pool's `panic_handler`, aborting the process if none is set. The global
+ * panic hook (`hawk_panic`) sees them only as panics on an anonymous worker
+ * thread.
+ *
+ * `build_pool()` / `build_global()` finish a `ThreadPoolBuilder` so that:
+ *
+ * 1. Every worker thread runs inside `hawk_panic::with_context()`, tagging
+ *    panics captured by the hook with `{"pool": {"name": ..., "threadIndex": ...}}`.
+ * 2. The pool's `panic_handler` reports the payload as a fatal event
+ *    with the same context if the panic hook is NOT installed.
+ * 3. A panicking `spawn` job no longer aborts the process: the handler
+ *    reports it and the worker moves on to the next job.
+ *
+ * Panics in `join` / `scope` / parallel iterators are unaffected — rayon
+ * propagates them to the caller as usual, and the hook still tags them
+ * with the pool context.
+ *
+ * # Example
+ *
+ * ```ignore
+ * let pool = hawk_rayon::build_pool("image-resize", rayon::ThreadPoolBuilder::new())?;
+ * pool.spawn(|| resize_all());
+ * ```
+ */
+
+use std::any::Any;
+use std::sync::Arc;
+
+use hawk_core::{EventData, CATCHER_VERSION};
+use rayon_core::{ThreadBuilder, ThreadPool, ThreadPoolBuildError, ThreadPoolBuilder};
+use serde_json::{json, Value};
+
+// ---------------------------------------------------------------------------
+// Public API
+// ---------------------------------------------------------------------------
+
+/**
+ * Builds a thread pool whose panics are reported to Hawk with the pool
+ * name and worker index as context.
+ *
+ * Replaces `builder.build()`. The builder's own `panic_handler` and
+ * `spawn_handler` are overridden; thread names and stack sizes are kept.
+ * Unnamed worker threads are called `<name>-<index>`.
+ */
+pub fn build_pool(
+    name: &str,
+    builder: ThreadPoolBuilder,
+) -> Result<ThreadPool, ThreadPoolBuildError> {
+    build(name, builder, false).map(|pool| pool.expect("local pool is returned"))
+}
+
+/**
+ * Same as `build_pool()`, but initializes rayon's global pool.
+ *
+ * Replaces `builder.build_global()`. Fails if the global pool has already
+ * been initialized — including implicitly, by any earlier rayon call.
+ */
+pub fn build_global(name: &str, builder: ThreadPoolBuilder) -> Result<(), ThreadPoolBuildError> {
+    build(name, builder, true).map(|_| ())
+}
+
+// ---------------------------------------------------------------------------
+// Internal helpers
+// ---------------------------------------------------------------------------
+
+/**
+ * Instruments `builder` and builds either a local or the global pool.
+ *
+ * Both paths live in one function because the instrumented builder type
+ * (`ThreadPoolBuilder<CustomSpawn<_>>`) cannot be named outside rayon.
+ */
+fn build(
+    name: &str,
+    builder: ThreadPoolBuilder,
+    global: bool,
+) -> Result<Option<ThreadPool>, ThreadPoolBuildError> {
+    let pool_name: Arc<str> = Arc::from(name);
+    let handler_pool_name = pool_name.clone();
+
+    let builder = builder
+        .panic_handler(move |payload| {
+            if !hawk_panic::is_installed() {
+                let index = rayon_core::current_thread_index();
+                report_panic(payload.as_ref(), &pool_context(&handler_pool_name, index));
+            }
+        })
+        .spawn_handler(move |thread: ThreadBuilder| {
+            let index = thread.index();
+            let context = pool_context(&pool_name, Some(index));
+
+            let thread_name = thread
+                .name()
+                .map(str::to_string)
+                .unwrap_or_else(|| format!("{pool_name}-{index}"));
+
+            let mut std_builder = std::thread::Builder::new().name(thread_name);
+            if let Some(stack_size) = thread.stack_size() {
+                std_builder = std_builder.stack_size(stack_size);
+            }
+
+            std_builder.spawn(move || hawk_panic::with_context(context, || thread.run()))?;
+            Ok(())
+        });
+
+    if global {
+        builder.build_global().map(|()| None)
+    } else {
+        builder.build().map(Some)
+    }
+}
+
+/**
+ * Builds the `{"pool": {"name": ..., "threadIndex": ...}}` context object.
+ */
+fn pool_context(name: &str, index: Option<usize>) -> Value {
+    json!({
+        "pool": {
+            "name": name,
+            "threadIndex": index,
+        }
+    })
+}
+
+/**
+ * Reports a panic handed to the pool's `panic_handler` when the global
+ * panic hook is not installed.
+ *
+ * The stack at this point is rayon's job loop, not the panic site, so no
+ * backtrace is attached.
+ */
+fn report_panic(payload: &(dyn Any + Send), context: &Value) {
+    let message = hawk_panic::payload_message(payload);
+
+    let pool_name = context["pool"]["name"].as_str().unwrap_or_default();
+
+    hawk_core::capture_event(EventData {
+        title: format!("panic: {message} [pool: {pool_name}]"),
+        event_type: Some(hawk_core::panic_type(&message).to_string()),
+        backtrace: None,
+        context: Some(context.clone()),
+        breadcrumbs: None,
+        addons: None,
+        fingerprint: None,
+        user: None,
+        catcher_version: CATCHER_VERSION.into(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * Verifies that a panicking `spawn` job no longer aborts the process
+     * and that workers keep their names.
+     */
+    #[test]
+    fn test_pool_survives_spawn_panic() {
+        let pool = build_pool("test-pool", ThreadPoolBuilder::new().num_threads(1))
+            .expect("pool should build");
+
+        pool.spawn(|| panic!("job failed"));
+
+        let name = pool.install(|| std::thread::current().name().map(str::to_string));
+        assert_eq!(name.as_deref(), Some("test-pool-0"));
+    }
+}
@@ -0,0 +1,271 @@
+/*!
+ * Hawk slog integration — a `slog::Drain`.
+ *
+ * `HawkDrain` mirrors the `log` and `tracing` integrations:
+ *
+ * - Records at or above `DrainOptions::event_level` (default `Error`) are
+ *   sent as Hawk events, with a backtrace from the logging call site.
+ * - Records at or above `DrainOptions::breadcrumb_level` (default `Info`)
+ *   are recorded as breadcrumbs.
+ * - Records from `DrainOptions::ignore_modules` are never forwarded.
+ *
+ * The key-value pairs of the record and of the logger it was logged on are
+ * mapped into the top level of the event context (the record's win over
+ * the logger's), next to a `slog` key with the record's module, location,
+ * and tag. Breadcrumbs carry them as data.
+ *
+ * # Example
+ *
+ * ```ignore
+ * use slog::Drain;
+ *
+ * let term = slog_term::FullFormat::new(slog_term::TermDecorator::new().build()).build();
+ * let drain = slog::Duplicate::new(term, hawk_slog::HawkDrain::default()).fuse();
+ * let logger = slog::Logger::root(slog_async::Async::new(drain).build().fuse(), slog::o!());
+ * ```
+ */
+
+use std::fmt;
+
+use hawk_core::forwarding::{forward_once, matches_target};
+use hawk_core::{Breadcrumb, EventData, CATCHER_VERSION};
+use serde_json::{json, Map, Value};
+use slog::{Drain, FilterLevel, Key, Level, Never, OwnedKVList, Record, Serializer, KV};
+
+// ---------------------------------------------------------------------------
+// Options
+// ---------------------------------------------------------------------------
+
+/**
+ * Configuration for `HawkDrain`.
+ */
+pub struct DrainOptions {
+    /// Records at or above this level are sent as events.
+    /// Defaults to `Error`; `Off` disables events.
+    pub event_level: FilterLevel,
+
+    /// Records at or above this level (and below `event_level`) are
+    /// recorded as breadcrumbs. Defaults to `Info`; `Off` disables them.
+    pub breadcrumb_level: FilterLevel,
+
+    /// Module prefixes (`"hyper"`, `"my_app::noisy"`) whose records are
+    /// not forwarded to Hawk. Empty by default.
+    pub ignore_modules: Vec<String>,
+}
+
+impl Default for DrainOptions {
+    fn default() -> Self {
+        Self {
+            event_level: FilterLevel::Error,
+            breadcrumb_level: FilterLevel::Info,
+            ignore_modules: Vec::new(),
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// HawkDrain
+// ---------------------------------------------------------------------------
+
+/**
+ * `slog::Drain` forwarding records to Hawk. Never fails; combine it with
+ * other drains through `slog::Duplicate`.
+ */
+pub struct HawkDrain {
+    options: DrainOptions,
+}
+
+impl HawkDrain {
+    pub fn new(options: DrainOptions) -> Self {
+        Self { options }
+    }
+
+    /**
+     * Whether a record with this level and module is forwarded to Hawk.
+     */
+    fn forwards(&self, level: Level, module: &str) -> bool {
+        (self.options.event_level.accepts(level) || self.options.breadcrumb_level.accepts(level))
+            && !self
+                .options
+                .ignore_modules
+                .iter()
+                .any(|prefix| matches_target(module, prefix))
+    }
+
+    /**
+     * Sends the record as an event or records it as a breadcrumb.
+     */
+    fn forward(&self, record: &Record<'_>, values: &OwnedKVList) {
+        let message = record.msg().to_string();
+
+        /* Logger values first, so that the record's own pairs override them. */
+        let mut fields = FieldSerializer(Map::new());
+        let _ = values.serialize(record, &mut fields);
+        let _ = record.kv().serialize(record, &mut fields);
+        let FieldSerializer(fields) = fields;
+
+        if self.options.event_level.accepts(record.level()) && hawk_core::accepts_events() {
+            /*
+             * Fields go first so that they can never shadow the `slog` key.
+             */
+            let mut context = fields.clone();
+            context.insert(
+                "slog".to_string(),
+                json!({
+                    "module": record.module(),
+                    "file": record.file(),
+                    "line": record.line(),
+                    "tag": (!record.tag().is_empty()).then(|| record.tag()),
+                }),
+            );
+
+            let event = EventData {
+                title: message.clone(),
+                event_type: Some(level(record.level()).as_str().to_string()),
+                backtrace: None,
+                context: Some(Value::Object(context)),
+                breadcrumbs: None,
+                addons: None,
+                fingerprint: None,
+                user: None,
+                catcher_version: CATCHER_VERSION.into(),
+            };
+
+            hawk_core::capture_event_with_backtrace(event, hawk_core::Backtrace::new_unresolved());
+        }
+
+        /*
+         * Events are recorded as breadcrumbs too, so that a later event
+         * shows the errors logged before it.
+         */
+        hawk_core::add_breadcrumb(Breadcrumb {
+            category: Some(record.module().to_string()),
+            level: Some(level(record.level()).as_str().to_string()),
+            data: (!fields.is_empty()).then_some(Value::Object(fields)),
+            ..Breadcrumb::at(hawk_core::now_millis(), message)
+        });
+    }
+}
+
+impl Default for HawkDrain {
+    fn default() -> Self {
+        Self::new(DrainOptions::default())
+    }
+}
+
+impl Drain for HawkDrain {
+    type Ok = ();
+    type Err = Never;
+
+    fn log(&self, record: &Record<'_>, values: &OwnedKVList) -> Result<(), Never> {
+        if self.forwards(record.level(), record.module()) {
+            forward_once(|| self.forward(record, values));
+        }
+        Ok(())
+    }
+
+    fn is_enabled(&self, level: Level) -> bool {
+        self.options.event_level.accepts(level) || self.options.breadcrumb_level.accepts(level)
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Internal helpers
+// ---------------------------------------------------------------------------
+
+/**
+ * Collects key-value pairs into a JSON object, keeping numbers and
+ * booleans typed and formatting everything else.
+ */
+struct FieldSerializer(Map<String, Value>);
+
+impl FieldSerializer {
+    fn insert(&mut self, key: Key, value: Value) -> slog::Result {
+        self.0.insert(key.to_string(), value);
+        Ok(())
+    }
+}
+
+impl Serializer for FieldSerializer {
+    fn emit_arguments(&mut self, key: Key, val: &fmt::Arguments<'_>) -> slog::Result {
+        self.insert(key, json!(val.to_string()))
+    }
+
+    fn emit_bool(&mut self, key: Key, val: bool) -> slog::Result {
+        self.insert(key, json!(val))
+    }
+
+    fn emit_i64(&mut self, key: Key, val: i64) -> slog::Result {
+        self.insert(key, json!(val))
+    }
+
+    fn emit_u64(&mut self, key: Key, val: u64) -> slog::Result {
+        self.insert(key, json!(val))
+    }
+
+    fn emit_i32(&mut self, key: Key, val: i32) -> slog::Result {
+        self.insert(key, json!(val))
+    }
+
+    fn emit_u32(&mut self, key: Key, val: u32) -> slog::Result {
+        self.insert(key, json!(val))
+    }
+
+    fn emit_f64(&mut self, key: Key, val: f64) -> slog::Result {
+        self.insert(key, json!(val))
+    }
+
+    fn emit_str(&mut self, key: Key, val: &str) -> slog::Result {
+        self.insert(key, json!(val))
+    }
+
+    fn emit_unit(&mut self, key: Key) -> slog::Result {
+        self.insert(key, Value::Null)
+    }
+
+    fn emit_none(&mut self, key: Key) -> slog::Result {
+        self.insert(key, Value::Null)
+    }
+}
+
+/**
+ * Hawk severity of a slog level.
+ */
+fn level(level: Level) -> hawk_core::Level {
+    match level {
+        Level::Critical => hawk_core::Level::Fatal,
+        Level::Error => hawk_core::Level::Error,
+        Level::Warning => hawk_core::Level::Warn,
+        Level::Info => hawk_core::Level::Info,
+        Level::Debug | Level::Trace => hawk_core::Level::Debug,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * Verifies that record pairs override logger pairs and keep their
+     * types.
+     */
+    #[test]
+    fn test_field_serializer() {
+        let values = slog::o!("user_id" => "anonymous", "request_id" => 7);
+        let values = OwnedKVList::from(values);
+
+        let rs = slog::record_static!(Level::Error, "");
+        let args = format_args!("failed");
+        let kv = slog::b!("user_id" => "u1", "retry" => true, "ratio" => 0.5);
+        let record = Record::new(&rs, &args, kv);
+
+        let mut fields = FieldSerializer(Map::new());
+        values.serialize(&record, &mut fields).unwrap();
+        record.kv().serialize(&record, &mut fields).unwrap();
+
+        assert_eq!(
+            Value::Object(fields.0),
+            json!({ "user_id": "u1", "request_id": 7, "retry": true, "ratio": 0.5 })
+        );
+    }
+}
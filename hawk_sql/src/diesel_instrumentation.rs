@@ -0,0 +1,77 @@
+/*!
+ * diesel integration — a connection `Instrumentation` (diesel 2.2+).
+ *
+ * diesel reports the start and end of every query to the connection's
+ * instrumentation, with the error if the query failed. Row counts are not
+ * reported, so breadcrumbs carry only the duration.
+ */
+
+use std::time::Instant;
+
+use diesel::connection::{Instrumentation, InstrumentationEvent};
+use diesel::result::Error;
+
+/**
+ * Records the queries of one connection as breadcrumbs and captures failed
+ * ones as events. `Error::NotFound` — an empty result, usually handled with
+ * `.optional()` — is recorded but not captured.
+ *
+ * Set it on a connection with `Connection::set_instrumentation()`, or on
+ * every new connection with `install_diesel()`.
+ */
+#[derive(Debug, Default)]
+pub struct DieselInstrumentation {
+    /// When the running query started.
+    started: Option<Instant>,
+}
+
+impl Instrumentation for DieselInstrumentation {
+    fn on_connection_event(&mut self, event: InstrumentationEvent<'_>) {
+        match event {
+            InstrumentationEvent::StartQuery { .. } => self.started = Some(Instant::now()),
+            InstrumentationEvent::FinishQuery { query, error, .. } => {
+                let duration = self
+                    .started
+                    .take()
+                    .map(|started| started.elapsed())
+                    .unwrap_or_default();
+                let sql = strip_binds(&query.to_string());
+
+                let error = error.filter(|error| !matches!(error, Error::NotFound));
+                let message = error.map(ToString::to_string);
+                hawk_core::add_breadcrumb(crate::query_breadcrumb(
+                    &sql,
+                    duration,
+                    None,
+                    message.as_deref(),
+                ));
+
+                if let Some(error) = error {
+                    crate::capture_query_error(&sql, error);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+/**
+ * Makes `DieselInstrumentation` the default instrumentation of connections
+ * established from now on.
+ */
+pub fn install_diesel() -> diesel::QueryResult<()> {
+    diesel::connection::set_default_instrumentation(|| {
+        Some(Box::new(DieselInstrumentation::default()))
+    })
+}
+
+/**
+ * Removes the ` -- binds: [...]` suffix diesel appends to a query's
+ * `Display` output, which holds the parameter values.
+ */
+fn strip_binds(query: &str) -> String {
+    match query.rsplit_once(" -- binds: ") {
+        Some((sql, _)) => sql.to_string(),
+        None => query.to_string(),
+    }
+}
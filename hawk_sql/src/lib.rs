@@ -0,0 +1,320 @@
+/*!
+ * Hawk database integration — SQL query breadcrumbs and query errors.
+ *
+ * Every executed statement becomes a breadcrumb, so an error shows the
+ * queries that ran before it:
+ *
+ * - The statement is normalized: literals and bind placeholders are
+ *   replaced with `?`, comments dropped, whitespace collapsed, and
+ *   `IN (?, ?, ?)` lists shortened to `IN (?)`. Parameter values never
+ *   reach Hawk.
+ * - The breadcrumb data holds the duration, the row count when the driver
+ *   reports one, and the statement's fingerprint — a hash of the
+ *   normalized statement that is the same for every execution of a query.
+ * - Failed queries are captured as events carrying the normalized
+ *   statement and its fingerprint under `query`.
+ *
+ * Drivers are hooked up by feature:
+ *
+ * - `sqlx` — `SqlxLayer`, a `tracing-subscriber` layer reading the
+ *   `sqlx::query` events sqlx emits for every statement. sqlx does not
+ *   report failed statements there; wrap query results with
+ *   `CaptureQueryError`.
+ * - `diesel` — `DieselInstrumentation` (diesel 2.2+), which records
+ *   queries and captures their errors; `install_diesel()` enables it for
+ *   every new connection.
+ *
+ * `record_query()` and `capture_query_error()` cover other drivers.
+ *
+ * # Example
+ *
+ * ```ignore
+ * // sqlx: statements are logged at DEBUG
+ * tracing_subscriber::registry()
+ *     .with(hawk_sql::SqlxLayer)
+ *     .init();
+ *
+ * let user = sqlx::query_as::<_, User>(SQL)
+ *     .fetch_one(&pool)
+ *     .await
+ *     .capture_query_error(SQL)?;
+ *
+ * // diesel
+ * hawk_sql::install_diesel()?;
+ * ```
+ */
+
+use std::fmt::Display;
+use std::time::Duration;
+
+use hawk_core::{Breadcrumb, EventData, CATCHER_VERSION};
+use serde_json::{json, Map, Value};
+
+#[cfg(feature = "diesel")]
+mod diesel_instrumentation;
+#[cfg(feature = "sqlx")]
+mod sqlx_layer;
+
+#[cfg(feature = "diesel")]
+pub use diesel_instrumentation::{install_diesel, DieselInstrumentation};
+#[cfg(feature = "sqlx")]
+pub use sqlx_layer::SqlxLayer;
+
+/// Breadcrumb category of recorded queries.
+const QUERY_CATEGORY: &str = "query";
+
+/// Event type of captured query errors.
+const QUERY_ERROR_TYPE: &str = "QueryError";
+
+// ---------------------------------------------------------------------------
+// Public API
+// ---------------------------------------------------------------------------
+
+/**
+ * Records an executed statement as a breadcrumb.
+ *
+ * `rows` is the number of rows returned or affected, if known.
+ * Silent no-op if the SDK is not initialized.
+ */
+pub fn record_query(sql: &str, duration: Duration, rows: Option<u64>) {
+    hawk_core::add_breadcrumb(query_breadcrumb(sql, duration, rows, None));
+}
+
+/**
+ * Sends a failed query to Hawk, with the normalized statement and its
+ * fingerprint. The backtrace is taken here.
+ *
+ * Silent no-op if the SDK is not initialized.
+ */
+pub fn capture_query_error(sql: &str, error: &(impl Display + ?Sized)) {
+    hawk_core::capture_event_with_backtrace(
+        build_error_event(sql, &error.to_string()),
+        hawk_core::Backtrace::new_unresolved(),
+    );
+}
+
+/**
+ * Reports the error of a query's `Result` to Hawk and passes the result on
+ * unchanged — for drivers that do not report failures themselves.
+ */
+pub trait CaptureQueryError: Sized {
+    /**
+     * Sends the error, if any, as a failure of the statement `sql`.
+     */
+    fn capture_query_error(self, sql: &str) -> Self;
+}
+
+impl<T, E: Display> CaptureQueryError for Result<T, E> {
+    fn capture_query_error(self, sql: &str) -> Self {
+        if let Err(error) = &self {
+            capture_query_error(sql, error);
+        }
+        self
+    }
+}
+
+/**
+ * Normalizes a statement: string and numeric literals and bind
+ * placeholders (`$1`, `?1`, `:name`, `@p1`) become `?`, comments are
+ * dropped, whitespace is collapsed, and `?` lists are shortened —
+ * `IN (?, ?, ?)` becomes `IN (?)`.
+ */
+pub fn normalize_sql(sql: &str) -> String {
+    let mut normalized = String::with_capacity(sql.len());
+    let mut chars = sql.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        let previous = normalized.chars().last();
+        let after_word = previous.is_some_and(|p| p.is_alphanumeric() || p == '_');
+
+        match c {
+            '\'' => {
+                /* A doubled quote is an escaped quote inside the literal. */
+                while let Some(c) = chars.next() {
+                    if c == '\'' && chars.next_if_eq(&'\'').is_none() {
+                        break;
+                    }
+                }
+                normalized.push('?');
+            }
+            '"' | '`' => {
+                normalized.push(c);
+                for inner in chars.by_ref() {
+                    normalized.push(inner);
+                    if inner == c {
+                        break;
+                    }
+                }
+            }
+            '-' if chars.peek() == Some(&'-') => {
+                chars.by_ref().find(|&c| c == '\n');
+                push_space(&mut normalized);
+            }
+            '/' if chars.peek() == Some(&'*') => {
+                chars.next();
+                let mut last = '\0';
+                for c in chars.by_ref() {
+                    if last == '*' && c == '/' {
+                        break;
+                    }
+                    last = c;
+                }
+                push_space(&mut normalized);
+            }
+            c if c.is_whitespace() => push_space(&mut normalized),
+            c if c.is_ascii_digit() && !after_word => {
+                while chars
+                    .next_if(|c| c.is_ascii_alphanumeric() || *c == '.')
+                    .is_some()
+                {}
+                normalized.push('?');
+            }
+            '$' | '?' | ':' | '@'
+                if chars
+                    .peek()
+                    .is_some_and(|c| c.is_alphanumeric() || *c == '_') =>
+            {
+                /* `::type` casts are not placeholders. */
+                if c == ':' && previous == Some(':') {
+                    normalized.push(c);
+                    continue;
+                }
+                while chars
+                    .next_if(|c| c.is_alphanumeric() || *c == '_')
+                    .is_some()
+                {}
+                normalized.push('?');
+            }
+            c => normalized.push(c),
+        }
+    }
+
+    let mut normalized = normalized.trim().to_string();
+    while normalized.contains("?, ?") || normalized.contains("?,?") {
+        normalized = normalized.replace("?, ?", "?").replace("?,?", "?");
+    }
+    normalized
+}
+
+/**
+ * Fingerprint of a statement: a hash of its normalized form, as 16 hex
+ * digits. Executions of the same query with different parameters share
+ * it.
+ */
+pub fn fingerprint(sql: &str) -> String {
+    fingerprint_normalized(&normalize_sql(sql))
+}
+
+// ---------------------------------------------------------------------------
+// Internal helpers
+// ---------------------------------------------------------------------------
+
+/**
+ * Builds the breadcrumb for an executed statement; `error` marks a failed
+ * one.
+ */
+fn query_breadcrumb(
+    sql: &str,
+    duration: Duration,
+    rows: Option<u64>,
+    error: Option<&str>,
+) -> Breadcrumb {
+    let statement = normalize_sql(sql);
+
+    let mut data = Map::new();
+    data.insert(
+        "fingerprint".to_string(),
+        json!(fingerprint_normalized(&statement)),
+    );
+    data.insert(
+        "duration_ms".to_string(),
+        json!(duration.as_secs_f64() * 1000.0),
+    );
+    if let Some(rows) = rows {
+        data.insert("rows".to_string(), json!(rows));
+    }
+    if let Some(error) = error {
+        data.insert("error".to_string(), json!(error));
+    }
+
+    Breadcrumb {
+        category: Some(QUERY_CATEGORY.to_string()),
+        level: Some(if error.is_some() { "error" } else { "info" }.to_string()),
+        data: Some(Value::Object(data)),
+        ..Breadcrumb::at(hawk_core::now_millis(), statement)
+    }
+}
+
+/**
+ * Builds the event for a failed statement, without a backtrace.
+ */
+fn build_error_event(sql: &str, error: &str) -> EventData {
+    let statement = normalize_sql(sql);
+
+    EventData {
+        title: error.to_string(),
+        event_type: Some(QUERY_ERROR_TYPE.to_string()),
+        backtrace: None,
+        context: Some(json!({
+            "query": {
+                "fingerprint": fingerprint_normalized(&statement),
+                "statement": statement,
+            }
+        })),
+        breadcrumbs: None,
+        addons: None,
+        fingerprint: None,
+        user: None,
+        catcher_version: CATCHER_VERSION.into(),
+    }
+}
+
+/**
+ * FNV-1a hash of an already normalized statement. Stable across builds
+ * and platforms, unlike `DefaultHasher`.
+ */
+fn fingerprint_normalized(statement: &str) -> String {
+    let hash = statement
+        .bytes()
+        .fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    format!("{hash:016x}")
+}
+
+/**
+ * Appends a single space, unless the output is empty or already ends with
+ * one.
+ */
+fn push_space(normalized: &mut String) {
+    if !normalized.is_empty() && !normalized.ends_with(' ') {
+        normalized.push(' ');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * Verifies that literals, placeholders, and comments are stripped and
+     * that executions with different parameters share a fingerprint.
+     */
+    #[test]
+    fn test_normalize_sql() {
+        assert_eq!(
+            normalize_sql(
+                "SELECT *\n  FROM users -- by name\n WHERE name = 'O''Brien' AND id IN (1, 2, 3)"
+            ),
+            "SELECT * FROM users WHERE name = ? AND id IN (?)"
+        );
+        assert_eq!(
+            normalize_sql("UPDATE t2 SET \"col1\" = $1::text /* hint */ WHERE id = :id LIMIT 10"),
+            "UPDATE t2 SET \"col1\" = ?::text WHERE id = ? LIMIT ?"
+        );
+        assert_eq!(
+            fingerprint("SELECT * FROM users WHERE id = 1"),
+            fingerprint("SELECT  *  FROM users WHERE id = 42")
+        );
+    }
+}
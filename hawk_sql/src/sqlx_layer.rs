@@ -0,0 +1,99 @@
+/*!
+ * sqlx integration — a `tracing-subscriber` layer reading sqlx's query log.
+ *
+ * sqlx emits a `sqlx::query` event for every statement it executes, at
+ * `DEBUG` by default (`ConnectOptions::log_statements` changes the level).
+ * Each carries the statement, its duration, and the rows it returned and
+ * affected; `SqlxLayer` turns those into breadcrumbs. With the `log`
+ * bridge instead of `tracing`, sqlx's events are not seen.
+ */
+
+use std::time::Duration;
+
+use tracing_core::field::{Field, Visit};
+use tracing_core::{Event, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+
+/// Target of sqlx's per-statement events.
+const SQLX_QUERY_TARGET: &str = "sqlx::query";
+
+/**
+ * `tracing_subscriber::Layer` recording sqlx statements as breadcrumbs.
+ * Ignores every other event.
+ */
+#[derive(Clone, Copy, Debug, Default)]
+pub struct SqlxLayer;
+
+impl<S: Subscriber> Layer<S> for SqlxLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if event.metadata().target() != SQLX_QUERY_TARGET {
+            return;
+        }
+
+        let mut query = QueryVisitor::default();
+        event.record(&mut query);
+
+        /*
+         * sqlx leaves `db.statement` empty when the statement is short
+         * enough to be its own summary.
+         */
+        let statement = query.statement.trim();
+        let sql = if statement.is_empty() {
+            &query.summary
+        } else {
+            statement
+        };
+        if sql.is_empty() {
+            return;
+        }
+
+        let rows = match (query.rows_returned, query.rows_affected) {
+            (None, None) => None,
+            (returned, affected) => Some(returned.unwrap_or(0) + affected.unwrap_or(0)),
+        };
+
+        crate::record_query(
+            sql,
+            Duration::try_from_secs_f64(query.elapsed_secs).unwrap_or_default(),
+            rows,
+        );
+    }
+}
+
+/**
+ * Collects the fields of a `sqlx::query` event.
+ */
+#[derive(Default)]
+struct QueryVisitor {
+    summary: String,
+    statement: String,
+    elapsed_secs: f64,
+    rows_returned: Option<u64>,
+    rows_affected: Option<u64>,
+}
+
+impl Visit for QueryVisitor {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        match field.name() {
+            "summary" => self.summary = value.to_string(),
+            "db.statement" => self.statement = value.to_string(),
+            _ => {}
+        }
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        match field.name() {
+            "rows_returned" => self.rows_returned = Some(value),
+            "rows_affected" => self.rows_affected = Some(value),
+            _ => {}
+        }
+    }
+
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        if field.name() == "elapsed_secs" {
+            self.elapsed_secs = value;
+        }
+    }
+
+    fn record_debug(&mut self, _field: &Field, _value: &dyn std::fmt::Debug) {}
+}
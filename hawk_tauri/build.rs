@@ -0,0 +1,5 @@
+const COMMANDS: &[&str] = &["capture_frontend_error"];
+
+fn main() {
+    tauri_plugin::Builder::new(COMMANDS).build();
+}
@@ -0,0 +1,275 @@
+/*!
+ * Hawk Tauri integration — one token, one pipeline for both halves of a
+ * Tauri app.
+ *
+ * The plugin:
+ *
+ * - installs the Hawk panic hook in the Rust backend when the app starts;
+ * - exposes the `capture_frontend_error` command, so that errors thrown in
+ *   the webview are sent by the Rust client — with the backend's token,
+ *   breadcrumbs, and `before_send` — instead of by a separate JS SDK;
+ * - provides `CaptureCommandError` to report the errors command handlers
+ *   return to the frontend.
+ *
+ * `hawk::init()` must be called before the app is built, as usual.
+ *
+ * # Example
+ *
+ * ```ignore
+ * let _guard = hawk::init(token, hawk::Options::default())?;
+ *
+ * tauri::Builder::default()
+ *     .plugin(tauri_plugin_hawk::init())
+ *     .invoke_handler(tauri::generate_handler![save_file])
+ *     .run(tauri::generate_context!())?;
+ *
+ * #[tauri::command]
+ * fn save_file(path: String) -> Result<(), String> {
+ *     std::fs::write(&path, "...")
+ *         .map_err(|err| err.to_string())
+ *         .capture_command_error("save_file")
+ * }
+ * ```
+ *
+ * The frontend needs the `hawk:default` permission in its capability, and
+ * forwards its errors with:
+ *
+ * ```js
+ * import { invoke } from "@tauri-apps/api/core";
+ *
+ * window.addEventListener("error", (e) =>
+ *   invoke("plugin:hawk|capture_frontend_error", {
+ *     error: { message: e.message, name: e.error?.name, stack: e.error?.stack,
+ *              url: e.filename, line: e.lineno, column: e.colno },
+ *   }));
+ * window.addEventListener("unhandledrejection", (e) =>
+ *   invoke("plugin:hawk|capture_frontend_error", {
+ *     error: { message: String(e.reason?.message ?? e.reason), name: e.reason?.name,
+ *              stack: e.reason?.stack },
+ *   }));
+ * ```
+ */
+
+use std::fmt::Display;
+
+use hawk_core::{BacktraceFrame, EventData, CATCHER_VERSION};
+use serde::Deserialize;
+use serde_json::{json, Value};
+use tauri::plugin::{Builder, TauriPlugin};
+use tauri::Runtime;
+
+/// Event type of frontend errors that carry no `name`.
+const DEFAULT_ERROR_TYPE: &str = "Error";
+
+// ---------------------------------------------------------------------------
+// Plugin
+// ---------------------------------------------------------------------------
+
+/**
+ * Creates the plugin. Register it with `tauri::Builder::plugin()`.
+ */
+pub fn init<R: Runtime>() -> TauriPlugin<R> {
+    Builder::new("hawk")
+        .invoke_handler(tauri::generate_handler![capture_frontend_error])
+        .setup(|_app, _api| {
+            hawk_panic::install();
+            Ok(())
+        })
+        .build()
+}
+
+/**
+ * An error reported by the frontend, shaped after a JS `Error` and the
+ * `error` event. Only `message` is required.
+ */
+#[derive(Debug, Deserialize)]
+pub struct FrontendError {
+    /// `Error.message`.
+    pub message: String,
+
+    /// `Error.name` (`TypeError`, ...), used as the event type.
+    #[serde(default)]
+    pub name: Option<String>,
+
+    /// `Error.stack`, in the V8 (WebView2) or WebKit format.
+    #[serde(default)]
+    pub stack: Option<String>,
+
+    /// Script the error was thrown in (`ErrorEvent.filename`).
+    #[serde(default)]
+    pub url: Option<String>,
+
+    /// `ErrorEvent.lineno`.
+    #[serde(default)]
+    pub line: Option<u32>,
+
+    /// `ErrorEvent.colno`.
+    #[serde(default)]
+    pub column: Option<u32>,
+
+    /// Arbitrary JSON added to the event context under `frontend.extra`.
+    #[serde(default)]
+    pub extra: Option<Value>,
+}
+
+/**
+ * Sends a frontend error through the backend's Hawk client.
+ */
+#[tauri::command]
+fn capture_frontend_error(error: FrontendError) {
+    hawk_core::capture_event(build_frontend_event(error));
+}
+
+// ---------------------------------------------------------------------------
+// Command errors
+// ---------------------------------------------------------------------------
+
+/**
+ * Reports the error of a command handler's `Result` to Hawk and passes the
+ * result on unchanged.
+ */
+pub trait CaptureCommandError: Sized {
+    /**
+     * Sends the error, if any, as an event for the command `command`.
+     * The backtrace is taken at the call site.
+     */
+    fn capture_command_error(self, command: &str) -> Self;
+}
+
+impl<T, E: Display> CaptureCommandError for Result<T, E> {
+    fn capture_command_error(self, command: &str) -> Self {
+        if let Err(error) = &self {
+            let event = EventData {
+                title: error.to_string(),
+                event_type: Some(type_path::<E>().to_string()),
+                backtrace: None,
+                context: Some(json!({ "tauri": { "command": command } })),
+                breadcrumbs: None,
+                addons: None,
+                fingerprint: None,
+                user: None,
+                catcher_version: CATCHER_VERSION.into(),
+            };
+            hawk_core::capture_event_with_backtrace(event, hawk_core::Backtrace::new_unresolved());
+        }
+        self
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Internal helpers
+// ---------------------------------------------------------------------------
+
+/**
+ * Builds the event for a frontend error. The stack is parsed into frames;
+ * without one, the error location becomes the only frame.
+ */
+fn build_frontend_event(error: FrontendError) -> EventData {
+    let mut frames = error.stack.as_deref().map(parse_js_stack).unwrap_or_default();
+    if frames.is_empty() {
+        if let Some(url) = &error.url {
+            frames.push(js_frame(None, url, error.line, error.column));
+        }
+    }
+
+    EventData {
+        title: error.message,
+        event_type: Some(error.name.unwrap_or_else(|| DEFAULT_ERROR_TYPE.to_string())),
+        backtrace: (!frames.is_empty()).then_some(frames),
+        context: Some(json!({
+            "frontend": {
+                "url": error.url,
+                "stack": error.stack,
+                "extra": error.extra,
+            }
+        })),
+        breadcrumbs: None,
+        addons: None,
+        fingerprint: None,
+        user: None,
+        catcher_version: CATCHER_VERSION.into(),
+    }
+}
+
+/**
+ * Parses a JS `Error.stack` into frames, innermost first. Understands V8
+ * lines (`at fn (url:line:col)`, `at url:line:col`) and WebKit / Gecko
+ * lines (`fn@url:line:col`); anything else — the message line, `[native
+ * code]` — is skipped.
+ */
+fn parse_js_stack(stack: &str) -> Vec<BacktraceFrame> {
+    stack.lines().filter_map(parse_js_line).collect()
+}
+
+fn parse_js_line(line: &str) -> Option<BacktraceFrame> {
+    let line = line.trim();
+
+    let (function, location) = if let Some(rest) = line.strip_prefix("at ") {
+        match rest.strip_suffix(')').and_then(|rest| rest.rsplit_once(" (")) {
+            Some((function, location)) => (Some(function), location),
+            None => (None, rest),
+        }
+    } else {
+        let (function, location) = line.split_once('@')?;
+        ((!function.is_empty()).then_some(function), location)
+    };
+
+    let (rest, column) = location.rsplit_once(':')?;
+    let (file, line) = rest.rsplit_once(':')?;
+
+    Some(js_frame(function, file, line.parse().ok(), column.parse().ok()))
+}
+
+/**
+ * A frame of frontend code. Bundled dependencies are not in-app.
+ */
+fn js_frame(function: Option<&str>, file: &str, line: Option<u32>, column: Option<u32>) -> BacktraceFrame {
+    BacktraceFrame {
+        file: Some(file.to_string()),
+        line,
+        column,
+        function: function.map(ToString::to_string),
+        module: None,
+        instruction_addr: None,
+        module_offset: None,
+        inlined: false,
+        in_app: !file.contains("node_modules"),
+    }
+}
+
+/**
+ * Path of a type, without generic arguments, as `hawk::capture_error`
+ * types its events.
+ */
+fn type_path<E>() -> &'static str {
+    let name = std::any::type_name::<E>();
+    name.split('<').next().unwrap_or(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * Verifies that V8 and WebKit stack lines are parsed and other lines
+     * skipped.
+     */
+    #[test]
+    fn test_parse_js_stack() {
+        let v8 = "TypeError: x is undefined\n    at render (http://localhost:1420/src/App.tsx:12:7)\n    at http://localhost:1420/node_modules/.vite/deps/react.js:40:3";
+        let frames = parse_js_stack(v8);
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].function.as_deref(), Some("render"));
+        assert_eq!(frames[0].file.as_deref(), Some("http://localhost:1420/src/App.tsx"));
+        assert_eq!((frames[0].line, frames[0].column), (Some(12), Some(7)));
+        assert!(frames[0].in_app);
+        assert!(!frames[1].in_app);
+
+        let webkit = "render@tauri://localhost/assets/index.js:3:15\n@tauri://localhost/assets/index.js:9:1\n[native code]";
+        let frames = parse_js_stack(webkit);
+        assert_eq!(frames.len(), 2);
+        assert_eq!(frames[0].function.as_deref(), Some("render"));
+        assert_eq!(frames[1].function, None);
+        assert_eq!(frames[1].line, Some(9));
+    }
+}
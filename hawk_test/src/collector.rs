@@ -0,0 +1,262 @@
+/*!
+ * Mock collector — an in-process HTTP server standing in for Hawk.
+ *
+ * Point `collector_endpoint` at `MockCollector::endpoint()` to run the
+ * real pipeline end to end — worker thread, HTTP transport, JSON envelopes
+ * — without network access. Call `flush()` before inspecting what was
+ * received: it returns once the worker has delivered every pending
+ * envelope.
+ *
+ * ```ignore
+ * let collector = hawk_test::MockCollector::start().unwrap();
+ * let _guard = hawk::init(hawk::Options {
+ *     token: TOKEN.into(),
+ *     collector_endpoint: Some(collector.endpoint()),
+ *     ..Default::default()
+ * });
+ *
+ * hawk::send("boom");
+ * hawk::flush();
+ * assert_eq!(collector.events()[0]["title"], "boom");
+ * ```
+ *
+ * Like the real collector, it answers `400` to bodies that are not JSON
+ * envelopes with a `token` and a `catcherType`; those are not recorded.
+ * Requests must carry a `Content-Length`.
+ */
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use hawk_core::CATCHER_TYPE;
+use serde_json::Value;
+
+/**
+ * A running mock collector. Stops accepting connections when dropped.
+ */
+pub struct MockCollector {
+    addr: SocketAddr,
+    received: Arc<Mutex<Vec<Value>>>,
+    stopped: Arc<AtomicBool>,
+}
+
+impl MockCollector {
+    /**
+     * Starts a collector on a free port of the loopback interface.
+     */
+    pub fn start() -> io::Result<Self> {
+        let listener = TcpListener::bind("127.0.0.1:0")?;
+        let addr = listener.local_addr()?;
+        let received = Arc::new(Mutex::new(Vec::new()));
+        let stopped = Arc::new(AtomicBool::new(false));
+
+        let (thread_received, thread_stopped) = (received.clone(), stopped.clone());
+        std::thread::Builder::new()
+            .name("hawk-mock-collector".into())
+            .spawn(move || {
+                for stream in listener.incoming() {
+                    if thread_stopped.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    let Ok(stream) = stream else { continue };
+
+                    /*
+                     * The transport keeps connections alive, so each one is
+                     * served on its own thread until the client closes it.
+                     */
+                    let received = thread_received.clone();
+                    std::thread::spawn(move || {
+                        if let Err(e) = serve(stream, &received) {
+                            eprintln!("[Hawk] Mock collector connection failed: {e}");
+                        }
+                    });
+                }
+            })?;
+
+        Ok(Self {
+            addr,
+            received,
+            stopped,
+        })
+    }
+
+    /**
+     * URL to use as `collector_endpoint`.
+     */
+    pub fn endpoint(&self) -> String {
+        format!("http://{}/", self.addr)
+    }
+
+    /**
+     * Every envelope received so far — events, transactions, metrics —
+     * in order of arrival.
+     */
+    pub fn envelopes(&self) -> Vec<Value> {
+        self.lock_received().clone()
+    }
+
+    /**
+     * Payloads of the error event envelopes received so far.
+     */
+    pub fn events(&self) -> Vec<Value> {
+        self.lock_received()
+            .iter()
+            .filter(|envelope| envelope["catcherType"] == CATCHER_TYPE)
+            .map(|envelope| envelope["payload"].clone())
+            .collect()
+    }
+
+    /**
+     * Forgets everything received so far.
+     */
+    pub fn clear(&self) {
+        self.lock_received().clear();
+    }
+
+    fn lock_received(&self) -> MutexGuard<'_, Vec<Value>> {
+        self.received
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Drop for MockCollector {
+    fn drop(&mut self) {
+        self.stopped.store(true, Ordering::SeqCst);
+
+        /*
+         * Wake the accept loop up so that it sees the flag.
+         */
+        let _ = TcpStream::connect(self.addr);
+    }
+}
+
+/**
+ * Serves the requests of one connection until it is closed.
+ */
+fn serve(stream: TcpStream, received: &Mutex<Vec<Value>>) -> io::Result<()> {
+    let mut writer = stream.try_clone()?;
+    let mut reader = BufReader::new(stream);
+
+    loop {
+        let mut request_line = String::new();
+        if reader.read_line(&mut request_line)? == 0 {
+            return Ok(());
+        }
+
+        let mut content_length = None;
+        loop {
+            let mut line = String::new();
+            if reader.read_line(&mut line)? == 0 {
+                return Ok(());
+            }
+            let line = line.trim_end();
+            if line.is_empty() {
+                break;
+            }
+            if let Some((name, value)) = line.split_once(':') {
+                if name.eq_ignore_ascii_case("content-length") {
+                    content_length = value.trim().parse::<usize>().ok();
+                }
+            }
+        }
+
+        let Some(content_length) = content_length else {
+            respond(
+                &mut writer,
+                "411 Length Required",
+                r#"{"error":true,"message":"Content-Length required"}"#,
+            )?;
+            return Ok(());
+        };
+
+        let mut body = vec![0; content_length];
+        reader.read_exact(&mut body)?;
+
+        match serde_json::from_slice::<Value>(&body) {
+            Ok(envelope)
+                if envelope["token"].is_string() && envelope["catcherType"].is_string() =>
+            {
+                received
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner())
+                    .push(envelope);
+                respond(&mut writer, "200 OK", r#"{"error":false}"#)?;
+            }
+            _ => respond(
+                &mut writer,
+                "400 Bad Request",
+                r#"{"error":true,"message":"Invalid envelope"}"#,
+            )?,
+        }
+    }
+}
+
+fn respond(writer: &mut TcpStream, status: &str, body: &str) -> io::Result<()> {
+    write!(
+        writer,
+        "HTTP/1.1 {status}\r\nContent-Type: application/json\r\nContent-Length: {}\r\n\r\n{body}",
+        body.len()
+    )?;
+    writer.flush()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * POSTs `body` over `stream` and returns the response status line,
+     * consuming the response.
+     */
+    fn post(stream: &mut TcpStream, reader: &mut impl BufRead, body: &str) -> String {
+        write!(
+            stream,
+            "POST / HTTP/1.1\r\nContent-Length: {}\r\n\r\n{body}",
+            body.len()
+        )
+        .unwrap();
+
+        let mut status = String::new();
+        reader.read_line(&mut status).unwrap();
+        let mut content_length = 0;
+        loop {
+            let mut line = String::new();
+            reader.read_line(&mut line).unwrap();
+            match line.trim_end().split_once(": ") {
+                Some(("Content-Length", value)) => content_length = value.parse().unwrap(),
+                Some(_) => {}
+                None => break,
+            }
+        }
+        reader.read_exact(&mut vec![0; content_length]).unwrap();
+        status
+    }
+
+    /**
+     * Verifies that envelopes are recorded and answered over a kept-alive
+     * connection, and that invalid bodies are rejected.
+     */
+    #[test]
+    fn test_mock_collector() {
+        let collector = MockCollector::start().unwrap();
+        let mut stream = TcpStream::connect(collector.addr).unwrap();
+        let mut reader = BufReader::new(stream.try_clone().unwrap());
+
+        let event = format!(
+            r#"{{"token":"t","catcherType":"{CATCHER_TYPE}","payload":{{"title":"boom"}}}}"#
+        );
+        assert!(post(&mut stream, &mut reader, &event).contains("200"));
+        let metrics = r#"{"token":"t","catcherType":"metrics/rust","payload":{}}"#;
+        assert!(post(&mut stream, &mut reader, metrics).contains("200"));
+        assert!(post(&mut stream, &mut reader, "not json").contains("400"));
+
+        assert_eq!(collector.envelopes().len(), 2);
+        assert_eq!(
+            collector.events(),
+            vec![serde_json::json!({ "title": "boom" })]
+        );
+    }
+}
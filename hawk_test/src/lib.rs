@@ -0,0 +1,223 @@
+/*!
+ * Hawk test helpers — asserting on the events your code reports.
+ *
+ * `with_captured_events()` runs a closure and returns the events sent from
+ * the calling thread meanwhile — by `hawk::send()`, panics, or any
+ * integration — exactly as they would have been sent: scoped context,
+ * breadcrumbs, `before_send`, and scrubbing applied, backtrace resolved.
+ * Nothing is sent over the network.
+ *
+ * If the SDK was not initialized, the first call initializes it with a
+ * test client and catches panics. The test client sends nothing: its
+ * transport keeps the events being captured and drops everything else.
+ * If the code under test initialized the SDK already, that client is used
+ * as is.
+ *
+ * The client is shared by every test of the process: `reset()` clears
+ * what earlier tests left in it — breadcrumbs, dedupe history, log tail.
+ *
+ * Capture is per thread, so tests running in parallel do not see each
+ * other's events; events sent from threads spawned by the closure are not
+ * captured. Transactions and metrics are not captured.
+ *
+ * `capture_panic()` runs a closure on a thread of its own and returns the
+ * event the panic hook reported for its panic — to test how panics are
+ * reported (title, type, context) without aborting the test.
+ *
+ * With the `collector` feature, `MockCollector` is an in-process HTTP
+ * server to point `collector_endpoint` at, for end-to-end tests of the
+ * real worker and transport.
+ *
+ * # Example
+ *
+ * ```ignore
+ * #[test]
+ * fn reports_invalid_input() {
+ *     let events = hawk_test::with_captured_events(|| {
+ *         let _ = parse_config("not toml");
+ *     });
+ *     hawk_test::assert_event_titled(&events, "invalid config");
+ * }
+ * ```
+ */
+
+#![forbid(unsafe_code)]
+
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::Once;
+
+use hawk_core::Options;
+
+#[cfg(feature = "collector")]
+mod collector;
+
+pub use hawk_core::EventData;
+
+#[cfg(feature = "collector")]
+pub use collector::MockCollector;
+
+/// Token of the test client: integration ID `test`, never contacted.
+const TEST_TOKEN: &str = "eyJpbnRlZ3JhdGlvbklkIjoidGVzdCIsInNlY3JldCI6InRlc3QifQ==";
+
+static INIT: Once = Once::new();
+
+/**
+ * Runs `f` and returns the events sent from this thread meanwhile, in
+ * order.
+ */
+pub fn with_captured_events(f: impl FnOnce()) -> Vec<EventData> {
+    ensure_client();
+    hawk_core::capture_events(f).1
+}
+
+/**
+ * Runs `f` on a new thread and returns the event reported for its panic.
+ *
+ * The panic is caught: it only ends the thread. Returns `None` if `f`
+ * returned without panicking, or if the panic was not reported — e.g. the
+ * code under test initialized the SDK with `catch_panics` off, or the
+ * panic comes from an ignored module.
+ */
+pub fn capture_panic(f: impl FnOnce() + Send + 'static) -> Option<EventData> {
+    ensure_client();
+    let thread = std::thread::Builder::new()
+        .name("hawk-test-panic".into())
+        .spawn(|| {
+            let (outcome, mut events) =
+                hawk_core::capture_events(|| panic::catch_unwind(AssertUnwindSafe(f)));
+            outcome.err().and_then(|_| events.pop())
+        })
+        .expect("failed to spawn the panicking thread");
+    thread.join().unwrap_or(None)
+}
+
+/**
+ * Clears the breadcrumbs, dedupe history, log tail, and pending metrics
+ * of the client, for a test to start from a clean slate. This affects
+ * tests running in parallel too: call it from tests that do not share
+ * breadcrumbs with others, or run them serially.
+ */
+pub fn reset() {
+    ensure_client();
+    hawk_core::reset_client();
+}
+
+/**
+ * Panics unless one of `events` has exactly the title `title`.
+ */
+#[track_caller]
+pub fn assert_event_titled(events: &[EventData], title: &str) {
+    if !events.iter().any(|event| event.title == title) {
+        panic!("no event titled {title:?}; captured: {:?}", titles(events));
+    }
+}
+
+/**
+ * Panics if any event was captured.
+ */
+#[track_caller]
+pub fn assert_no_events(events: &[EventData]) {
+    if !events.is_empty() {
+        panic!("expected no events; captured: {:?}", titles(events));
+    }
+}
+
+fn titles(events: &[EventData]) -> Vec<&str> {
+    events.iter().map(|event| event.title.as_str()).collect()
+}
+
+/**
+ * Initializes the test client unless a client exists already.
+ */
+fn ensure_client() {
+    INIT.call_once(|| {
+        if hawk_core::is_initialized() {
+            return;
+        }
+
+        /*
+         * The collector is never contacted, so a plain `http://` endpoint
+         * keeps the client working without a TLS backend. The guard would
+         * flush on drop; the test client lives until the process exits.
+         */
+        let options = Options {
+            collector_endpoint: Some("http://127.0.0.1:9/".into()),
+            ..Options::default()
+        };
+        match hawk_core::init(TEST_TOKEN, options) {
+            Ok(guard) => {
+                std::mem::forget(guard);
+                hawk_core::use_test_transport();
+                hawk_panic::install();
+            }
+            /* Another thread initialized the SDK first: just as good. */
+            Err(_) if hawk_core::is_initialized() => {}
+            Err(err) => panic!("[Hawk] Failed to initialize the test client: {err}"),
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * Verifies that events and panics from the closure are captured, and
+     * that events from other threads are not.
+     */
+    #[test]
+    fn test_with_captured_events() {
+        let events = with_captured_events(|| {
+            hawk_core::send("first");
+            std::thread::spawn(|| hawk_core::send("elsewhere"))
+                .join()
+                .unwrap();
+            let _ = std::panic::catch_unwind(|| panic!("boom"));
+        });
+
+        assert_eq!(events.len(), 2);
+        assert_event_titled(&events, "first");
+        assert!(events[1].title.contains("boom"));
+        assert!(events[0].backtrace.is_some());
+
+        assert_no_events(&with_captured_events(|| {}));
+    }
+
+    /**
+     * Verifies that `reset()` clears the breadcrumbs left by earlier code.
+     */
+    #[test]
+    fn test_reset() {
+        let events = with_captured_events(|| {
+            hawk_core::add_breadcrumb(hawk_core::Breadcrumb::new("earlier"));
+            hawk_core::send("before");
+            reset();
+            hawk_core::send("clean");
+        });
+
+        let breadcrumbs = events[0].breadcrumbs.as_ref().unwrap();
+        assert_eq!(breadcrumbs[0].message.as_deref(), Some("earlier"));
+        assert!(events[1].breadcrumbs.as_ref().is_none_or(Vec::is_empty));
+    }
+
+    /**
+     * Verifies that the panic of the closure is reported, typed, from its
+     * own thread, and that a closure that returns reports nothing.
+     */
+    #[test]
+    fn test_capture_panic() {
+        let event = capture_panic(|| {
+            std::env::var_os("HAWK_TEST_UNSET_VARIABLE").unwrap();
+        })
+        .expect("no panic event");
+
+        assert!(event
+            .title
+            .starts_with("panic: called `Option::unwrap()` on a `None` value"));
+        assert!(event.title.ends_with("[thread: hawk-test-panic]"));
+        assert_eq!(event.event_type.as_deref(), Some("UnwrapNone"));
+        assert_eq!(event.context.unwrap()["thread"]["name"], "hawk-test-panic");
+
+        assert!(capture_panic(|| {}).is_none());
+    }
+}
@@ -0,0 +1,205 @@
+/*!
+ * Hawk Tokio integration — panic capture for spawned tasks.
+ *
+ * A panic inside a `tokio::spawn`ed task is caught by the runtime and
+ * stored in the task's `JoinHandle`. The global panic hook (`hawk_panic`)
+ * still sees it, but only knows the runtime worker thread it happened on —
+ * not which task panicked — and nothing at all is reported if the hook is
+ * not installed and the `JoinHandle` is never awaited.
+ *
+ * `spawn()` / `spawn_named()` wrap the future so that:
+ *
+ * 1. Every poll runs inside `hawk_panic::with_context()`, tagging panics
+ *    captured by the hook with `{"task": {"name": ..., "id": ...}}`.
+ * 2. If the panic hook is NOT installed, the unwinding panic is caught and
+ *    reported here as a fatal event with the same task context.
+ * 3. The panic is then resumed, so the `JoinHandle` still yields a
+ *    `JoinError` with `is_panic() == true` — runtime semantics are unchanged.
+ *
+ * # Example
+ *
+ * ```ignore
+ * let handle = hawk_tokio::spawn_named("sync-orders", async {
+ *     sync_orders().await
+ * });
+ * ```
+ */
+
+use std::future::Future;
+use std::panic::{self, AssertUnwindSafe};
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll};
+
+use hawk_core::{EventData, CATCHER_VERSION};
+use serde_json::{json, Value};
+use tokio::task::JoinHandle;
+
+// ---------------------------------------------------------------------------
+// Public API
+// ---------------------------------------------------------------------------
+
+/**
+ * Spawns a task on the current Tokio runtime, reporting its panics to Hawk
+ * with the task ID as context.
+ *
+ * Drop-in replacement for `tokio::spawn`.
+ *
+ * # Panics
+ * Panics if called outside of a Tokio runtime, like `tokio::spawn`.
+ */
+pub fn spawn<F>(future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    tokio::spawn(HawkTask::new(None, future))
+}
+
+/**
+ * Same as `spawn()`, additionally tagging panic events with a task name.
+ *
+ * Tokio's own task names require `tokio_unstable`; this name is only
+ * used by Hawk.
+ */
+pub fn spawn_named<F>(name: &str, future: F) -> JoinHandle<F::Output>
+where
+    F: Future + Send + 'static,
+    F::Output: Send + 'static,
+{
+    tokio::spawn(HawkTask::new(Some(name.to_string()), future))
+}
+
+// ---------------------------------------------------------------------------
+// HawkTask — the future wrapper
+// ---------------------------------------------------------------------------
+
+/**
+ * Future wrapper installing the task's panic context around every poll.
+ *
+ * The inner future is boxed to keep pin projection trivial (no `unsafe`,
+ * no extra dependency); `tokio::spawn` allocates per task anyway.
+ */
+struct HawkTask<F> {
+    inner: Pin<Box<F>>,
+
+    /// Task name given to `spawn_named()`.
+    name: Option<String>,
+
+    /// `{"task": {...}}` context, built on first poll — the task ID is
+    /// only available from inside the running task.
+    context: Option<Arc<Value>>,
+}
+
+impl<F: Future> HawkTask<F> {
+    fn new(name: Option<String>, future: F) -> Self {
+        Self {
+            inner: Box::pin(future),
+            name,
+            context: None,
+        }
+    }
+}
+
+impl<F: Future> Future for HawkTask<F> {
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.get_mut();
+
+        let context = this
+            .context
+            .get_or_insert_with(|| Arc::new(task_context(this.name.as_deref())))
+            .clone();
+
+        let inner = &mut this.inner;
+        let result = hawk_panic::with_context(Arc::clone(&context), || {
+            panic::catch_unwind(AssertUnwindSafe(|| inner.as_mut().poll(cx)))
+        });
+
+        match result {
+            Ok(poll) => poll,
+            Err(payload) => {
+                if !hawk_panic::is_installed() {
+                    report_panic(payload.as_ref(), this.name.as_deref(), &context);
+                }
+                panic::resume_unwind(payload)
+            }
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Internal helpers
+// ---------------------------------------------------------------------------
+
+/**
+ * Builds the `{"task": {"name": ..., "id": ...}}` context object.
+ */
+fn task_context(name: Option<&str>) -> Value {
+    let id = tokio::task::try_id().map(|id| id.to_string());
+
+    json!({
+        "task": {
+            "name": name,
+            "id": id,
+        }
+    })
+}
+
+/**
+ * Reports a caught task panic when the global panic hook is not installed.
+ *
+ * The stack at this point is the runtime's poll stack, not the panic site,
+ * so no backtrace is attached.
+ */
+fn report_panic(payload: &(dyn std::any::Any + Send), name: Option<&str>, context: &Value) {
+    let message = hawk_panic::payload_message(payload);
+
+    let title = match name {
+        Some(name) => format!("panic: {message} [task: {name}]"),
+        None => format!("panic: {message} [task]"),
+    };
+
+    hawk_core::capture_event(EventData {
+        title,
+        event_type: Some(hawk_core::panic_type(&message).to_string()),
+        backtrace: None,
+        context: Some(context.clone()),
+        breadcrumbs: None,
+        addons: None,
+        fingerprint: None,
+        user: None,
+        catcher_version: CATCHER_VERSION.into(),
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /**
+     * Verifies that the wrapper is transparent: outputs pass through and
+     * panics still surface as `JoinError::is_panic()`.
+     */
+    #[tokio::test]
+    async fn test_spawn_preserves_join_semantics() {
+        assert_eq!(spawn(async { 42 }).await.unwrap(), 42);
+
+        let err = spawn_named("boom", async { panic!("task failed") })
+            .await
+            .unwrap_err();
+        assert!(err.is_panic());
+    }
+
+    /**
+     * Verifies the shape of the task context.
+     */
+    #[test]
+    fn test_task_context_outside_runtime() {
+        assert_eq!(
+            task_context(Some("sync")),
+            json!({ "task": { "name": "sync", "id": null } })
+        );
+    }
+}
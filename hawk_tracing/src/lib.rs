@@ -0,0 +1,390 @@
+/*!
+ * Hawk tracing integration — a `tracing-subscriber` layer.
+ *
+ * `HawkLayer` plugs into any subscriber built on `tracing_subscriber`'s
+ * `Registry`:
+ *
+ * - Events at or above `LayerOptions::event_level` (default `ERROR`) are
+ *   sent as Hawk events. The context holds the event's fields and the span
+ *   hierarchy it happened in, root first, with each span's fields.
+ * - The fields of all enclosing spans are also merged into the top level
+ *   of the context, so an error deep inside a request handler carries the
+ *   `request_id` / `user_id` recorded on the request span. When spans
+ *   share a field name, the innermost span wins.
+ * - Events at or above `LayerOptions::breadcrumb_level` (default `INFO`)
+ *   are recorded as breadcrumbs, with the event's fields as data.
+ * - Forwarded events are mirrored, with their fields, into the log tail
+ *   attached to fatal events, if `Options::log_tail_bytes` is set.
+ * - Events from `LayerOptions::ignore_targets` are never forwarded.
+ *
+ * Span fields are collected when spans are created and updated by
+ * `Span::record`, so fields filled in late (`tracing::field::Empty`) show
+ * up too.
+ *
+ * # Example
+ *
+ * ```ignore
+ * use tracing_subscriber::prelude::*;
+ *
+ * tracing_subscriber::registry()
+ *     .with(tracing_subscriber::fmt::layer())
+ *     .with(hawk_tracing::HawkLayer::default())
+ *     .init();
+ * ```
+ */
+
+use std::fmt::{self, Write as _};
+
+use hawk_core::forwarding::{forward_once, matches_target};
+use hawk_core::{Breadcrumb, EventData, CATCHER_VERSION};
+use serde_json::{json, Map, Value};
+use tracing_core::field::{Field, Visit};
+use tracing_core::span::{Attributes, Id, Record};
+use tracing_core::{Event, Level, LevelFilter, Subscriber};
+use tracing_subscriber::layer::{Context, Layer};
+use tracing_subscriber::registry::{LookupSpan, Scope};
+
+// ---------------------------------------------------------------------------
+// Options
+// ---------------------------------------------------------------------------
+
+/**
+ * Configuration for `HawkLayer`.
+ */
+pub struct LayerOptions {
+    /// Events at or above this level are sent as Hawk events.
+    /// Defaults to `ERROR`; `OFF` disables them.
+    pub event_level: LevelFilter,
+
+    /// Events at or above this level (and below `event_level`) are
+    /// recorded as breadcrumbs. Defaults to `INFO`; `OFF` disables them.
+    pub breadcrumb_level: LevelFilter,
+
+    /// Target prefixes whose events are not forwarded to Hawk.
+    /// Defaults to the SDK's HTTP stack: `ureq`, `rustls`.
+    pub ignore_targets: Vec<String>,
+}
+
+impl Default for LayerOptions {
+    fn default() -> Self {
+        Self {
+            event_level: LevelFilter::ERROR,
+            breadcrumb_level: LevelFilter::INFO,
+            ignore_targets: vec!["ureq".to_string(), "rustls".to_string()],
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// HawkLayer
+// ---------------------------------------------------------------------------
+
+/**
+ * `tracing_subscriber::Layer` forwarding events to Hawk.
+ */
+pub struct HawkLayer {
+    options: LayerOptions,
+}
+
+impl HawkLayer {
+    pub fn new(options: LayerOptions) -> Self {
+        Self { options }
+    }
+
+    /**
+     * Whether an event with this level and target is forwarded to Hawk.
+     */
+    fn forwards(&self, level: &Level, target: &str) -> bool {
+        (*level <= self.options.event_level || *level <= self.options.breadcrumb_level)
+            && !self
+                .options
+                .ignore_targets
+                .iter()
+                .any(|prefix| matches_target(target, prefix))
+    }
+
+    /**
+     * Sends the event to Hawk or records it as a breadcrumb.
+     */
+    fn forward<S>(&self, event: &Event<'_>, ctx: &Context<'_, S>)
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        let metadata = event.metadata();
+
+        let mut fields = Map::new();
+        event.record(&mut FieldVisitor(&mut fields));
+        let message = match fields.remove("message") {
+            Some(Value::String(message)) => message,
+            Some(other) => other.to_string(),
+            None => metadata.name().to_string(),
+        };
+
+        if hawk_core::log_tail_enabled() {
+            let mut line = format!("{} {}: {message}", metadata.level(), metadata.target());
+            for (name, value) in &fields {
+                let _ = write!(line, " {name}={value}");
+            }
+            hawk_core::record_log_line(&line);
+        }
+
+        if *metadata.level() <= self.options.event_level && hawk_core::accepts_events() {
+            let spans = span_stack(ctx.event_scope(event));
+
+            /*
+             * Span fields go first so that they can never shadow the
+             * `tracing` / `spans` keys.
+             */
+            let mut context = merge_span_fields(&spans);
+            context.insert(
+                "tracing".to_string(),
+                json!({
+                    "target": metadata.target(),
+                    "module": metadata.module_path(),
+                    "file": metadata.file(),
+                    "line": metadata.line(),
+                    "fields": fields,
+                }),
+            );
+            context.insert("spans".to_string(), Value::Array(spans));
+
+            let event = EventData {
+                title: message.clone(),
+                event_type: Some(level(metadata.level()).as_str().to_string()),
+                backtrace: None,
+                context: Some(Value::Object(context)),
+                breadcrumbs: None,
+                addons: None,
+                fingerprint: None,
+                user: None,
+                catcher_version: CATCHER_VERSION.into(),
+            };
+
+            hawk_core::capture_event_with_backtrace(event, hawk_core::Backtrace::new_unresolved());
+        }
+
+        /*
+         * Events are recorded as breadcrumbs too, so that a later event
+         * shows the errors traced before it.
+         */
+        hawk_core::add_breadcrumb(Breadcrumb {
+            category: Some(metadata.target().to_string()),
+            level: Some(level(metadata.level()).as_str().to_string()),
+            data: (!fields.is_empty()).then_some(Value::Object(fields)),
+            ..Breadcrumb::at(hawk_core::now_millis(), message)
+        });
+    }
+}
+
+impl Default for HawkLayer {
+    fn default() -> Self {
+        Self::new(LayerOptions::default())
+    }
+}
+
+impl<S> Layer<S> for HawkLayer
+where
+    S: Subscriber + for<'a> LookupSpan<'a>,
+{
+    fn on_new_span(&self, attrs: &Attributes<'_>, id: &Id, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+
+        let mut fields = Map::new();
+        attrs.record(&mut FieldVisitor(&mut fields));
+        span.extensions_mut().insert(SpanFields(fields));
+    }
+
+    fn on_record(&self, id: &Id, values: &Record<'_>, ctx: Context<'_, S>) {
+        let Some(span) = ctx.span(id) else { return };
+
+        let mut extensions = span.extensions_mut();
+        if let Some(SpanFields(fields)) = extensions.get_mut::<SpanFields>() {
+            values.record(&mut FieldVisitor(fields));
+        }
+    }
+
+    fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+        let metadata = event.metadata();
+        if self.forwards(metadata.level(), metadata.target()) {
+            forward_once(|| self.forward(event, &ctx));
+        }
+    }
+}
+
+// ---------------------------------------------------------------------------
+// Internal helpers
+// ---------------------------------------------------------------------------
+
+/**
+ * Fields of a span, stored in its registry extensions.
+ */
+struct SpanFields(Map<String, Value>);
+
+/**
+ * Collects recorded fields into a JSON object, keeping numbers and
+ * booleans typed and formatting everything else.
+ */
+struct FieldVisitor<'a>(&'a mut Map<String, Value>);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_f64(&mut self, field: &Field, value: f64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_i64(&mut self, field: &Field, value: i64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_u64(&mut self, field: &Field, value: u64) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_bool(&mut self, field: &Field, value: bool) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), json!(value));
+    }
+
+    fn record_error(&mut self, field: &Field, value: &(dyn std::error::Error + 'static)) {
+        self.0
+            .insert(field.name().to_string(), json!(value.to_string()));
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), json!(format!("{value:?}")));
+    }
+}
+
+/**
+ * Describes the spans an event happened in, root first:
+ * `[{"name": ..., "target": ..., "fields": {...}}, ...]`.
+ */
+fn span_stack<S>(scope: Option<Scope<'_, S>>) -> Vec<Value>
+where
+    S: for<'a> LookupSpan<'a>,
+{
+    let Some(scope) = scope else {
+        return Vec::new();
+    };
+
+    scope
+        .from_root()
+        .map(|span| {
+            let extensions = span.extensions();
+            let fields = extensions
+                .get::<SpanFields>()
+                .map(|SpanFields(fields)| fields.clone())
+                .unwrap_or_default();
+
+            json!({
+                "name": span.name(),
+                "target": span.metadata().target(),
+                "fields": fields,
+            })
+        })
+        .collect()
+}
+
+/**
+ * Flattens the fields of a `span_stack()` into one object, inner spans
+ * overriding outer ones.
+ */
+fn merge_span_fields(spans: &[Value]) -> Map<String, Value> {
+    let mut merged = Map::new();
+    for span in spans {
+        if let Some(fields) = span["fields"].as_object() {
+            merged.extend(fields.clone());
+        }
+    }
+    merged
+}
+
+/**
+ * Hawk severity of a tracing level.
+ */
+fn level(level: &Level) -> hawk_core::Level {
+    match *level {
+        Level::ERROR => hawk_core::Level::Error,
+        Level::WARN => hawk_core::Level::Warn,
+        Level::INFO => hawk_core::Level::Info,
+        _ => hawk_core::Level::Debug,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+    use tracing_subscriber::layer::SubscriberExt;
+
+    /// Records the span stack of the last event it sees.
+    struct Probe(Arc<Mutex<Vec<Value>>>);
+
+    impl<S> Layer<S> for Probe
+    where
+        S: Subscriber + for<'a> LookupSpan<'a>,
+    {
+        fn on_event(&self, event: &Event<'_>, ctx: Context<'_, S>) {
+            *self.0.lock().unwrap() = span_stack(ctx.event_scope(event));
+        }
+    }
+
+    /**
+     * Verifies that span fields — including ones recorded after creation —
+     * are collected root first.
+     */
+    #[test]
+    fn test_span_stack() {
+        let captured = Arc::new(Mutex::new(Vec::new()));
+        let subscriber = tracing_subscriber::registry()
+            .with(HawkLayer::default())
+            .with(Probe(captured.clone()));
+
+        tracing::subscriber::with_default(subscriber, || {
+            let request = tracing::info_span!("request", request_id = 42);
+            let _request = request.enter();
+
+            let query = tracing::info_span!("query", user_id = tracing::field::Empty);
+            query.record("user_id", "u1");
+            let _query = query.enter();
+
+            tracing::debug!("probe");
+        });
+
+        let names: Vec<_> = captured
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|span| span["name"].clone())
+            .collect();
+        assert_eq!(names, [json!("request"), json!("query")]);
+        assert_eq!(
+            captured.lock().unwrap()[0]["fields"],
+            json!({ "request_id": 42 })
+        );
+        assert_eq!(
+            captured.lock().unwrap()[1]["fields"],
+            json!({ "user_id": "u1" })
+        );
+    }
+
+    /**
+     * Verifies that inner span fields override outer ones when merged.
+     */
+    #[test]
+    fn test_merge_span_fields() {
+        let spans = [
+            json!({ "name": "request", "fields": { "request_id": 1, "user_id": "anonymous" } }),
+            json!({ "name": "auth", "fields": { "user_id": "u1" } }),
+        ];
+
+        let merged = merge_span_fields(&spans);
+        assert_eq!(
+            Value::Object(merged),
+            json!({ "request_id": 1, "user_id": "u1" })
+        );
+    }
+}